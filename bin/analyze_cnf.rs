@@ -0,0 +1,40 @@
+use std::{fs, process};
+
+use clap::Parser;
+use rsdd::repr::{Cnf, OrderHeuristic};
+
+/// Reports width measures and interaction-graph statistics of a CNF, to
+/// estimate the cost of compiling it
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// input CNF in DIMACS form
+    #[clap(short, long, value_parser)]
+    file: String,
+
+    /// elimination order to analyze. one of: `linear`, `minfill`,
+    /// `weighted_minfill`, `mindegree`, `force`, `mince`, `hypergraph`,
+    /// `anneal_cutwidth`, `anneal_bandwidth`
+    #[clap(long, value_parser, default_value_t = String::from("minfill"))]
+    order: String,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let file = fs::read_to_string(&args.file).unwrap();
+
+    let cnf = Cnf::from_dimacs(&file).unwrap_or_else(|e| {
+        eprintln!("Error parsing {} as DIMACS: {}", args.file, e);
+        process::exit(1)
+    });
+
+    let heuristic = args.order.parse::<OrderHeuristic>().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1)
+    });
+
+    let analysis = cnf.analyze(&cnf.order_by(heuristic));
+
+    println!("{}", serde_json::to_string_pretty(&analysis).unwrap());
+}
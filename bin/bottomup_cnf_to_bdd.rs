@@ -1,13 +1,74 @@
-use std::{fs, time::Instant};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    process,
+    time::Instant,
+};
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use rsdd::{
     builder::{bdd::RobddBuilder, cache::LruIteTable, BottomUpBuilder},
     plan::BottomUpPlan,
-    repr::{BddPtr, Cnf, DTree},
-    serialize::BDDSerializer,
+    repr::{BddPtr, Cnf, DTree, OrderHeuristic, VarLabel, VarSet},
+    serialize::{
+        bdd_to_binary, bdd_to_dddmp, bdd_to_graph, bdd_to_nnf, BDDSerializer, GraphFormat,
+        NnfFormat,
+    },
+    util::hypergraph::PartitionConfig,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Strategy {
+    /// a variable order-based decision tree decomposition
+    Dtree,
+    /// a dtree found by min-cut bisection
+    Hypergraph,
+    /// bucket elimination along the variable order, quantifying variables
+    /// outside a `c p show` projection early
+    Bucket,
+    /// clauses grouped by scope
+    Clustered,
+    /// a balanced conjunction
+    Balanced,
+}
+
+/// parses an `auto_`-prefixed heuristic name
+fn parse_order(order: &str) -> Result<OrderHeuristic, String> {
+    order
+        .strip_prefix("auto_")
+        .ok_or_else(|| format!("order `{}` must start with `auto_`", order))
+        .and_then(|name| name.parse::<OrderHeuristic>())
+}
+
+/// the first label in `plan` (including quantified, conditioned and composed
+/// labels) that is not one of the CNF's `num_vars` variables
+fn unknown_label(plan: &BottomUpPlan, num_vars: usize) -> Option<VarLabel> {
+    let unknown = |v: &VarLabel| v.value_usize() >= num_vars;
+    match plan {
+        BottomUpPlan::Literal(v, _) => Some(*v).filter(unknown),
+        BottomUpPlan::ConstTrue | BottomUpPlan::ConstFalse => None,
+        BottomUpPlan::Not(f) => unknown_label(f, num_vars),
+        BottomUpPlan::And(l, r) | BottomUpPlan::Or(l, r) | BottomUpPlan::Iff(l, r) => {
+            unknown_label(l, num_vars).or_else(|| unknown_label(r, num_vars))
+        }
+        BottomUpPlan::Ite(f, g, h) => unknown_label(f, num_vars)
+            .or_else(|| unknown_label(g, num_vars))
+            .or_else(|| unknown_label(h, num_vars)),
+        BottomUpPlan::Exists(vars, f) | BottomUpPlan::Forall(vars, f) => vars
+            .iter()
+            .find(unknown)
+            .or_else(|| unknown_label(f, num_vars)),
+        BottomUpPlan::Condition(v, _, f) => Some(*v)
+            .filter(unknown)
+            .or_else(|| unknown_label(f, num_vars)),
+        BottomUpPlan::Compose(v, f, g) => Some(*v)
+            .filter(unknown)
+            .or_else(|| unknown_label(f, num_vars))
+            .or_else(|| unknown_label(g, num_vars)),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -17,14 +78,33 @@ struct Args {
 
     /// variable order for BDD.
     /// defaults to `auto_minfill`, which uses a min-fill heuristic.
-    /// allowed: `auto_minfill`, `auto_force`
-    #[clap(long, value_parser, default_value_t = String::from("auto_minfill"))]
-    order: String,
-
-    /// compilation order (a tree describing sequence of clause-conjunctions).
-    /// defaults to `dtree`, a variable order-based decision tree decomposition
-    #[clap(long, value_parser, default_value_t = String::from("dtree"))]
-    strategy: String,
+    /// allowed: `auto_` followed by any heuristic name, i.e. `auto_linear`,
+    /// `auto_minfill`, `auto_weighted_minfill`, `auto_mindegree`,
+    /// `auto_force`, `auto_mince`, `auto_hypergraph`, `auto_anneal_cutwidth`,
+    /// `auto_anneal_bandwidth`
+    #[clap(long, value_parser = parse_order, default_value = "auto_minfill")]
+    order: OrderHeuristic,
+
+    /// compilation order (a tree describing sequence of clause-conjunctions)
+    #[clap(long, value_enum, default_value_t = Strategy::Dtree)]
+    strategy: Strategy,
+
+    /// maximum number of variables in a cluster for the `clustered` strategy
+    #[clap(long, value_parser, default_value_t = 16)]
+    cluster_size: usize,
+
+    /// (optional) execute a serialized plan instead of generating one with
+    /// `--strategy`. plans are read as s-expressions if the file ends in
+    /// `.sexpr`, and as JSON otherwise
+    #[clap(long, value_parser)]
+    plan: Option<String>,
+
+    /// output format for the compiled BDD.
+    /// allowed: `json` (the serialized BDD), `dddmp` (CUDD's DDDMP format),
+    /// `nnf` (a c2d d-DNNF), `d4` (a d4 d-DNNF), `binary` (rsdd's binary
+    /// format), `dot` (a Graphviz drawing), `mermaid` (a Mermaid drawing)
+    #[clap(long, value_parser, default_value_t = String::from("json"))]
+    output_format: String,
 
     /// show verbose output (including timing information, cache profiling, etc.)
     #[clap(short, long, value_parser)]
@@ -34,35 +114,77 @@ struct Args {
 fn main() {
     let args = Args::parse();
 
-    let file = fs::read_to_string(args.file).unwrap();
+    let file = fs::read_to_string(&args.file).unwrap();
 
-    let cnf = Cnf::from_dimacs(&file);
+    let weighted = Cnf::from_weighted_dimacs(&file).unwrap_or_else(|e| {
+        eprintln!("Error parsing {} as DIMACS: {}", args.file, e);
+        process::exit(1)
+    });
+    let cnf = weighted.cnf;
+    let projection = weighted.projection;
 
     let start = Instant::now();
 
-    let order = match args.order.as_str() {
-        "auto_minfill" => cnf.min_fill_order(),
-        "auto_force" => cnf.force_order(),
-        _ => panic!(
-            "Unknown order {} provided, expected one of: `auto_minfill`, `auto_force`",
-            args.order
-        ),
-    };
-
-    let plan = match args.strategy.as_str() {
-        "dtree" => {
+    let order = cnf.order_by(args.order);
+
+    let plan = match (&args.plan, args.strategy) {
+        (Some(path), _) => {
+            let text = fs::read_to_string(path).unwrap();
+            let plan = if path.ends_with(".sexpr") {
+                BottomUpPlan::from_sexpr(&text).map_err(|e| e.to_string())
+            } else {
+                serde_json::from_str(&text).map_err(|e| e.to_string())
+            };
+            // plans from an external scheduler may mention variables the CNF
+            // does not have
+            let plan = plan.and_then(|plan| match unknown_label(&plan, cnf.num_vars()) {
+                Some(v) => Err(format!(
+                    "variable {} is out of range, the CNF has {} variables",
+                    v.value(),
+                    cnf.num_vars()
+                )),
+                None => Ok(plan),
+            });
+            plan.unwrap_or_else(|e| {
+                eprintln!("Error parsing plan {}: {}", path, e);
+                process::exit(1)
+            })
+        }
+        (None, Strategy::Dtree) => {
             let dtree = DTree::from_cnf(&cnf, &order);
             BottomUpPlan::from_dtree(&dtree)
         }
-        _ => panic!(
-            "Unknown strategy {} provided, expected one of: `dtree`",
-            args.order
-        ),
+        // there is nothing to bisect without clauses
+        (None, Strategy::Hypergraph) if cnf.clauses().is_empty() => BottomUpPlan::ConstTrue,
+        (None, Strategy::Hypergraph) => {
+            let dtree = DTree::from_cnf_hypergraph(&cnf, &PartitionConfig::default());
+            BottomUpPlan::from_dtree(&dtree)
+        }
+        (None, Strategy::Bucket) => {
+            BottomUpPlan::from_bucket_elimination(&cnf, &order, projection.as_ref())
+        }
+        (None, Strategy::Clustered) => BottomUpPlan::from_clusters(&cnf, &order, args.cluster_size),
+        (None, Strategy::Balanced) => BottomUpPlan::from_cnf_balanced(&cnf),
     };
 
     let builder = RobddBuilder::<LruIteTable<BddPtr>>::new(order);
     let bdd = builder.compile_plan(&plan);
 
+    // existentially quantify everything outside of a `c p show` projection
+    // (a no-op if the plan already did so)
+    let bdd = match projection {
+        None => bdd,
+        Some(projection) => {
+            let mut unprojected = VarSet::new_with_num_vars(cnf.num_vars());
+            for v in (0..cnf.num_vars()).map(VarLabel::new_usize) {
+                if !projection.contains(v) {
+                    unprojected.insert(v);
+                }
+            }
+            builder.exists_set(bdd, &unprojected)
+        }
+    };
+
     let elapsed = start.elapsed();
 
     if args.verbose {
@@ -73,7 +195,28 @@ fn main() {
         eprintln!("recursive calls: {}", stats.num_recursive_calls);
     }
 
-    let serialized = BDDSerializer::from_bdd(bdd);
-
-    println!("{}", serde_json::to_string(&serialized).unwrap());
+    match args.output_format.as_str() {
+        "json" => {
+            let serialized = BDDSerializer::from_bdd(bdd);
+            println!("{}", serde_json::to_string(&serialized).unwrap());
+        }
+        "dddmp" => print!("{}", bdd_to_dddmp(&[bdd], builder.order())),
+        "nnf" => print!("{}", bdd_to_nnf(bdd, cnf.num_vars(), NnfFormat::C2d)),
+        "d4" => print!("{}", bdd_to_nnf(bdd, cnf.num_vars(), NnfFormat::D4)),
+        "dot" => print!(
+            "{}",
+            bdd_to_graph(&[bdd], &HashMap::new(), GraphFormat::Dot)
+        ),
+        "mermaid" => print!(
+            "{}",
+            bdd_to_graph(&[bdd], &HashMap::new(), GraphFormat::Mermaid)
+        ),
+        "binary" => io::stdout()
+            .write_all(&bdd_to_binary(&[bdd], builder.order()))
+            .unwrap(),
+        other => {
+            eprintln!("Unknown output format: {}", other);
+            process::exit(1)
+        }
+    }
 }
@@ -1,10 +1,11 @@
-use std::{fs, time::Instant};
+use std::{collections::HashMap, fs, process, time::Instant};
 
 use clap::Parser;
 use rsdd::{
     builder::{bdd::RobddBuilder, cache::LruIteTable, BottomUpBuilder},
-    repr::{BddPtr, LogicalExpr, VarLabel, VarOrder},
-    serialize::{BDDSerializer, LogicalSExpr},
+    plan::DagPlan,
+    repr::{BddPtr, LogicalExpr, OrderHeuristic, VarLabel, VarOrder},
+    serialize::{bdd_to_graph, BDDSerializer, GraphFormat, LogicalSExpr},
 };
 use serde::{Deserialize, Serialize};
 
@@ -21,7 +22,9 @@ struct Args {
     file: String,
 
     /// variable order for BDD.
-    /// options: `linear`, `manual` (requires config in `-c`)
+    /// options: `manual` (requires config in `-c`), or a heuristic: `linear`,
+    /// `minfill`, `weighted_minfill`, `mindegree`, `force`, `mince`,
+    /// `hypergraph`, `anneal_cutwidth`, `anneal_bandwidth`
     #[clap(long, value_parser, default_value_t = String::from("linear"))]
     ordering: String,
 
@@ -29,6 +32,17 @@ struct Args {
     #[clap(short, long, value_parser)]
     config: Option<String>,
 
+    /// compile through a hash-consed plan, so that repeated subexpressions
+    /// are compiled only once
+    #[clap(long, value_parser)]
+    dag: bool,
+
+    /// output format for the compiled BDD.
+    /// allowed: `json` (the serialized BDD), `dot` (a Graphviz drawing),
+    /// `mermaid` (a Mermaid drawing); drawings use the variable names
+    #[clap(long, value_parser, default_value_t = String::from("json"))]
+    output_format: String,
+
     /// show verbose output (including timing information, cache profiling, etc.)
     #[clap(short, long, value_parser)]
     verbose: bool,
@@ -37,7 +51,7 @@ struct Args {
 fn main() {
     let args = Args::parse();
 
-    let file = fs::read_to_string(args.file).unwrap();
+    let file = fs::read_to_string(&args.file).unwrap();
 
     let config: Option<Config> = if let Some(path_to_config) = args.config {
         let config = fs::read_to_string(path_to_config).unwrap();
@@ -46,13 +60,15 @@ fn main() {
         None
     };
 
-    let sexpr = serde_sexpr::from_str::<LogicalSExpr>(&file).unwrap();
+    let sexpr = LogicalSExpr::parse(&file).unwrap_or_else(|e| {
+        eprintln!("Error parsing {} as logical s-expression: {}", args.file, e);
+        process::exit(1)
+    });
     let expr = LogicalExpr::from_sexpr(&sexpr);
 
     let start = Instant::now();
 
     let order = match args.ordering.as_str() {
-        "linear" => VarOrder::linear_order(sexpr.unique_variables().len()),
         "manual" => {
             let mapping = sexpr.variable_mapping();
             let config = config.unwrap();
@@ -64,11 +80,26 @@ fn main() {
                     .collect::<Vec<_>>(),
             )
         }
-        _ => todo!(),
+        name => {
+            let heuristic = name.parse::<OrderHeuristic>().unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(1)
+            });
+            expr.scope_cnf().order_by(heuristic)
+        }
     };
 
     let builder = RobddBuilder::<LruIteTable<BddPtr>>::new(order.clone());
-    let bdd = builder.compile_logical_expr(&expr);
+    let bdd = if args.dag {
+        let mut plan = DagPlan::new();
+        let root = plan.add_logical_expr(&expr);
+        if args.verbose {
+            eprintln!("plan nodes after sharing: {}", plan.len());
+        }
+        builder.compile_dag_plan(&plan, &[root])[0]
+    } else {
+        builder.compile_logical_expr(&expr)
+    };
 
     let elapsed = start.elapsed();
 
@@ -83,7 +114,21 @@ fn main() {
         eprintln!("recursive calls: {}", stats.num_recursive_calls);
     }
 
-    let serialized = BDDSerializer::from_bdd(bdd);
-
-    println!("{}", serde_json::to_string(&serialized).unwrap());
+    let names: HashMap<VarLabel, String> = sexpr
+        .variable_mapping()
+        .into_iter()
+        .map(|(name, index)| (VarLabel::new_usize(index), name.clone()))
+        .collect();
+    match args.output_format.as_str() {
+        "json" => {
+            let serialized = BDDSerializer::from_bdd(bdd);
+            println!("{}", serde_json::to_string(&serialized).unwrap());
+        }
+        "dot" => print!("{}", bdd_to_graph(&[bdd], &names, GraphFormat::Dot)),
+        "mermaid" => print!("{}", bdd_to_graph(&[bdd], &names, GraphFormat::Mermaid)),
+        other => {
+            eprintln!("Unknown output format: {}", other);
+            process::exit(1)
+        }
+    }
 }
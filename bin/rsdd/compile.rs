@@ -0,0 +1,358 @@
+//! Compiling problems into BDDs, SDDs or decision-DNNFs
+
+use std::{collections::HashSet, time::Instant};
+
+use clap::{Args, ValueEnum};
+use rsdd::{
+    builder::{
+        bdd::RobddBuilder,
+        cache::AllIteTable,
+        decision_nnf::{DecisionNNFBuilder, StandardDecisionNNFBuilder},
+        sdd::{CompressionSddBuilder, SddBuilder},
+        BottomUpBuilder, TopDownBuilder,
+    },
+    repr::{BddPtr, DDNNFPtr, DTree, OrderHeuristic, SddPtr, VTree, VarLabel, VarOrder},
+    serialize::{
+        bdd_to_binary, bdd_to_dddmp, bdd_to_graph, bdd_to_nnf, sdd_to_binary, sdd_to_graph,
+        sdd_to_nnf, sdd_to_ucla, BDDSerializer, GraphFormat, NnfFormat, SDDSerializer,
+    },
+    util::hypergraph::PartitionConfig,
+};
+
+use crate::input::{Formula, Problem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiagramKind {
+    /// a reduced ordered BDD, compiled bottom-up
+    Bdd,
+    /// an SDD, compiled bottom-up
+    Sdd,
+    /// a decision-DNNF, compiled top-down (CNF inputs only)
+    Dnnf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VTreeStrategy {
+    LeftLinear,
+    RightLinear,
+    /// the variable order split in half `--vtree-splits` times, then right
+    /// linear
+    EvenSplit,
+    /// from a dtree built along the variable order
+    Dtree,
+    /// from a dtree found by min-cut bisection
+    Hypergraph,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// rsdd's JSON serialization
+    Json,
+    /// CUDD's DDDMP format (BDDs only)
+    Dddmp,
+    /// a c2d d-DNNF
+    Nnf,
+    /// a d4 d-DNNF
+    D4,
+    /// rsdd's binary format (BDDs and SDDs)
+    Binary,
+    /// a Graphviz drawing
+    Dot,
+    /// a Mermaid drawing
+    Mermaid,
+    /// the UCLA SDD package's format (SDDs only)
+    Ucla,
+}
+
+#[derive(Args, Debug)]
+pub struct CompileArgs {
+    /// the kind of diagram to compile into
+    #[clap(long, value_enum, default_value_t = DiagramKind::Bdd)]
+    diagram: DiagramKind,
+
+    /// variable ordering heuristic, for BDDs, decision-DNNFs and vtrees.
+    /// one of: `linear`, `minfill`, `weighted_minfill`, `mindegree`,
+    /// `force`, `mince`, `hypergraph`, `anneal_cutwidth`, `anneal_bandwidth`
+    #[clap(long, value_parser = str::parse::<OrderHeuristic>, default_value = "minfill")]
+    order: OrderHeuristic,
+
+    /// vtree for SDDs
+    #[clap(long, value_enum, default_value_t = VTreeStrategy::Dtree)]
+    vtree: VTreeStrategy,
+
+    /// number of halvings for the `even-split` vtree
+    #[clap(long, default_value_t = 2)]
+    vtree_splits: usize,
+}
+
+/// One node of a compiled diagram, whose children are diagrams again
+pub enum Node<P> {
+    True,
+    False,
+    Lit(VarLabel, bool),
+    /// a decision on a label, to its low and high children
+    Decision(VarLabel, P, P),
+    /// a disjunction of conjunctions of primes and subs
+    Elements(Vec<(P, P)>),
+}
+
+/// A compiled diagram, with what is needed to query and print it
+pub struct Compiled<'b, P> {
+    pub kind: DiagramKind,
+    pub ptr: P,
+    pub condition: &'b dyn Fn(P, VarLabel, bool) -> P,
+    /// the root node of a diagram, for walks that need more than
+    /// [`DDNNFPtr::fold`]'s semiring values
+    pub expand: &'b dyn Fn(P) -> Node<P>,
+    pub export: &'b dyn Fn(P, ExportFormat) -> Result<Vec<u8>, String>,
+    /// seconds spent compiling
+    pub seconds: f64,
+}
+
+/// Something to do with a compiled diagram, whatever its kind
+pub trait Task {
+    /// returns what to write to standard output
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String>;
+}
+
+fn unsupported(format: ExportFormat, kind: DiagramKind) -> String {
+    format!(
+        "cannot export {} diagrams as {}",
+        kind.to_possible_value().unwrap().get_name(),
+        format.to_possible_value().unwrap().get_name()
+    )
+}
+
+/// an order over every label of the problem; labels without clauses go last
+fn order(problem: &Problem, heuristic: OrderHeuristic) -> VarOrder {
+    let order = problem.scopes().order_by(heuristic);
+    let labels: Vec<VarLabel> = order
+        .in_order_iter()
+        .chain((order.num_vars()..problem.num_vars).map(VarLabel::new_usize))
+        .collect();
+    VarOrder::new(&labels)
+}
+
+/// `vtree` extended with right-linear leaves for the labels it is missing
+fn cover(vtree: Option<VTree>, num_vars: usize) -> VTree {
+    let present = vtree.as_ref().map_or(HashSet::new(), |v| v.all_vars());
+    let missing: Vec<VarLabel> = (0..num_vars)
+        .filter(|v| !present.contains(v))
+        .map(VarLabel::new_usize)
+        .collect();
+    match vtree {
+        Some(v) if missing.is_empty() => v,
+        Some(v) => VTree::new_node(Box::new(v), Box::new(VTree::right_linear(&missing))),
+        None => VTree::right_linear(&missing),
+    }
+}
+
+fn vtree(problem: &Problem, args: &CompileArgs) -> Result<VTree, String> {
+    if problem.num_vars == 0 {
+        return Err(String::from("an SDD needs at least one variable"));
+    }
+    let labels: Vec<VarLabel> = order(problem, args.order).in_order_iter().collect();
+    let scopes = problem.scopes();
+    Ok(match args.vtree {
+        VTreeStrategy::LeftLinear => VTree::left_linear(&labels),
+        VTreeStrategy::RightLinear => VTree::right_linear(&labels),
+        VTreeStrategy::EvenSplit => {
+            // deeper splits would leave empty halves
+            let splits = usize::min(args.vtree_splits, labels.len().ilog2() as usize);
+            VTree::even_split(&labels, splits)
+        }
+        VTreeStrategy::Dtree if scopes.clauses().is_empty() => cover(None, problem.num_vars),
+        VTreeStrategy::Dtree => {
+            let dtree = DTree::from_cnf(&scopes, &scopes.order_by(args.order));
+            cover(VTree::from_dtree(&dtree), problem.num_vars)
+        }
+        VTreeStrategy::Hypergraph if scopes.num_vars() == 0 => cover(None, problem.num_vars),
+        VTreeStrategy::Hypergraph => cover(
+            Some(VTree::from_cnf_hypergraph(
+                &scopes,
+                &PartitionConfig::default(),
+            )),
+            problem.num_vars,
+        ),
+    })
+}
+
+fn bottom_up<'a, P, B: BottomUpBuilder<'a, P>>(builder: &'a B, problem: &Problem) -> P {
+    match (&problem.formula, &problem.projection) {
+        (Formula::Cnf(cnf), None) => builder.compile_cnf(cnf),
+        (Formula::Cnf(cnf), Some(projection)) => builder.compile_cnf_projected(cnf, projection),
+        (Formula::Expr(expr), _) => builder.compile_logical_expr(expr),
+    }
+}
+
+fn expand_bdd(f: BddPtr) -> Node<BddPtr> {
+    if f.is_true() {
+        Node::True
+    } else if f.is_false() {
+        Node::False
+    } else {
+        Node::Decision(f.var_safe().unwrap(), f.low(), f.high())
+    }
+}
+
+fn expand_sdd(f: SddPtr) -> Node<SddPtr> {
+    match f {
+        SddPtr::PtrTrue => Node::True,
+        SddPtr::PtrFalse => Node::False,
+        SddPtr::Var(v, polarity) => Node::Lit(v, polarity),
+        _ => Node::Elements(
+            f.node_iter()
+                .map(|and| {
+                    let sub = if f.is_neg() {
+                        and.sub().neg()
+                    } else {
+                        and.sub()
+                    };
+                    (and.prime(), sub)
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn graph_format(format: ExportFormat) -> GraphFormat {
+    if format == ExportFormat::Dot {
+        GraphFormat::Dot
+    } else {
+        GraphFormat::Mermaid
+    }
+}
+
+fn nnf_format(format: ExportFormat) -> NnfFormat {
+    if format == ExportFormat::Nnf {
+        NnfFormat::C2d
+    } else {
+        NnfFormat::D4
+    }
+}
+
+/// compiles `problem` as `args` asks and runs `task` on the result
+pub fn compile<T: Task>(problem: &Problem, args: &CompileArgs, task: T) -> Result<Vec<u8>, String> {
+    let names = problem.label_names();
+    let num_vars = problem.num_vars;
+    let kind = args.diagram;
+    let start = Instant::now();
+    match kind {
+        DiagramKind::Bdd => {
+            let builder = RobddBuilder::<AllIteTable<BddPtr>>::new(order(problem, args.order));
+            let ptr = bottom_up(&builder, problem);
+            let seconds = start.elapsed().as_secs_f64();
+            let export = |f: BddPtr, format| {
+                Ok(match format {
+                    ExportFormat::Json => serde_json::to_vec(&BDDSerializer::from_bdd(f)).unwrap(),
+                    ExportFormat::Dddmp => bdd_to_dddmp(&[f], builder.order()).into_bytes(),
+                    ExportFormat::Nnf | ExportFormat::D4 => {
+                        bdd_to_nnf(f, num_vars, nnf_format(format)).into_bytes()
+                    }
+                    ExportFormat::Binary => bdd_to_binary(&[f], builder.order()),
+                    ExportFormat::Dot | ExportFormat::Mermaid => {
+                        bdd_to_graph(&[f], &names, graph_format(format)).into_bytes()
+                    }
+                    ExportFormat::Ucla => return Err(unsupported(format, kind)),
+                })
+            };
+            let condition = |f, v, value| builder.condition(f, v, value);
+            task.run(
+                problem,
+                Compiled {
+                    kind,
+                    ptr,
+                    condition: &condition,
+                    expand: &expand_bdd,
+                    export: &export,
+                    seconds,
+                },
+            )
+        }
+        DiagramKind::Sdd => {
+            let builder = CompressionSddBuilder::new(vtree(problem, args)?);
+            let ptr = bottom_up(&builder, problem);
+            let seconds = start.elapsed().as_secs_f64();
+            let manager = builder.vtree_manager();
+            let vtree = manager.vtree_root();
+            let export = |f, format| {
+                Ok(match format {
+                    ExportFormat::Json => serde_json::to_vec(&SDDSerializer::from_sdd(f)).unwrap(),
+                    ExportFormat::Nnf | ExportFormat::D4 => {
+                        sdd_to_nnf(f, num_vars, nnf_format(format)).into_bytes()
+                    }
+                    ExportFormat::Binary => sdd_to_binary(&[f], vtree),
+                    ExportFormat::Dot | ExportFormat::Mermaid => {
+                        sdd_to_graph(&[f], vtree, &names, graph_format(format)).into_bytes()
+                    }
+                    ExportFormat::Ucla => sdd_to_ucla(f, vtree).into_bytes(),
+                    ExportFormat::Dddmp => return Err(unsupported(format, kind)),
+                })
+            };
+            let condition = |f, v, value| builder.condition(f, v, value);
+            task.run(
+                problem,
+                Compiled {
+                    kind,
+                    ptr,
+                    condition: &condition,
+                    expand: &expand_sdd,
+                    export: &export,
+                    seconds,
+                },
+            )
+        }
+        DiagramKind::Dnnf => {
+            let cnf = match &problem.formula {
+                Formula::Cnf(cnf) => cnf,
+                Formula::Expr(_) => {
+                    return Err(String::from(
+                        "decision-DNNFs can only be compiled from CNFs",
+                    ))
+                }
+            };
+            let order = order(problem, args.order);
+            // projected compilation decides the shown variables first
+            let order = match &problem.projection {
+                None => order,
+                Some(projection) => order.project_first(projection),
+            };
+            let builder = StandardDecisionNNFBuilder::new(order);
+            let ptr = match &problem.projection {
+                None => builder.compile_cnf_topdown(cnf),
+                Some(projection) => builder.compile_cnf_topdown_projected(cnf, projection),
+            };
+            let seconds = start.elapsed().as_secs_f64();
+            let export = |f: BddPtr, format| {
+                Ok(match format {
+                    ExportFormat::Json => serde_json::to_vec(&BDDSerializer::from_bdd(f)).unwrap(),
+                    ExportFormat::Nnf | ExportFormat::D4 => {
+                        bdd_to_nnf(f, num_vars, nnf_format(format)).into_bytes()
+                    }
+                    ExportFormat::Dot | ExportFormat::Mermaid => {
+                        bdd_to_graph(&[f], &names, graph_format(format)).into_bytes()
+                    }
+                    ExportFormat::Dddmp | ExportFormat::Binary | ExportFormat::Ucla => {
+                        return Err(unsupported(format, kind))
+                    }
+                })
+            };
+            let condition = |f, v, value| builder.condition(f, v, value);
+            task.run(
+                problem,
+                Compiled {
+                    kind,
+                    ptr,
+                    condition: &condition,
+                    expand: &expand_bdd,
+                    export: &export,
+                    seconds,
+                },
+            )
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Reading problems: a formula, its weights, and the variables that can be
+//! queried or observed
+
+use std::{collections::HashMap, fs, path::Path};
+
+use clap::{Args, ValueEnum};
+use rsdd::{
+    repr::{BayesianNetwork, Cnf, Literal, LogicalExpr, VarLabel, VarSet, WmcParams},
+    serialize::{LogicalSExpr, VariableWeight},
+    util::semirings::RealSemiring,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    /// pick by extension: `.cnf` and `.dimacs` are weighted DIMACS, `.sexpr`
+    /// is an s-expression and `.json` is a Bayesian network
+    Auto,
+    /// DIMACS clauses; weight and projection lines are ignored
+    Dimacs,
+    /// DIMACS with Model Counting Competition weights and projections
+    Wdimacs,
+    /// a logical formula as an s-expression
+    Sexpr,
+    /// a Bayesian network in the JSON format of `bayesian_networks/`
+    Bn,
+}
+
+#[derive(Args, Debug)]
+pub struct InputArgs {
+    /// input file
+    file: String,
+
+    /// format of the input file
+    #[clap(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+
+    /// (optional) JSON file of weights, `{"name": {"low": 0.4, "high": 0.6}}`,
+    /// overriding those of the input. DIMACS variables are named by their
+    /// (1-indexed) number, and Bayesian network indicators by `variable=state`
+    #[clap(short, long)]
+    weights: Option<String>,
+
+    /// evidence as `NAME=VALUE`; may be repeated. boolean variables take
+    /// `true` or `false`, and network variables one of their states
+    #[clap(short, long)]
+    evidence: Vec<String>,
+}
+
+pub enum Formula {
+    Cnf(Cnf),
+    Expr(LogicalExpr),
+}
+
+/// A variable that can be queried or observed. Each state is the
+/// conjunction of some literals.
+pub struct Variable {
+    pub name: String,
+    pub states: Vec<(String, Vec<Literal>)>,
+}
+
+impl Variable {
+    fn boolean(name: String, label: VarLabel) -> Variable {
+        Variable {
+            name,
+            states: vec![
+                (String::from("false"), vec![Literal::new(label, false)]),
+                (String::from("true"), vec![Literal::new(label, true)]),
+            ],
+        }
+    }
+
+    /// the index of the state called `value`
+    pub fn state(&self, value: &str) -> Result<usize, String> {
+        self.states
+            .iter()
+            .position(|(name, _)| name == value)
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.states.iter().map(|(n, _)| n.as_str()).collect();
+                format!(
+                    "`{}` has no state `{}`, expected one of: {}",
+                    self.name,
+                    value,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+pub struct Problem {
+    pub formula: Formula,
+    pub num_vars: usize,
+    /// a name for every label
+    pub names: Vec<String>,
+    pub weights: WmcParams<RealSemiring>,
+    /// if given, every other label is existentially quantified
+    pub projection: Option<VarSet>,
+    pub variables: Vec<Variable>,
+    /// the literals that the evidence asserts
+    pub evidence: Vec<Literal>,
+}
+
+impl Problem {
+    fn new(
+        formula: Formula,
+        num_vars: usize,
+        names: Vec<String>,
+        weights: WmcParams<RealSemiring>,
+    ) -> Problem {
+        let variables = names
+            .iter()
+            .enumerate()
+            .map(|(v, name)| Variable::boolean(name.clone(), VarLabel::new_usize(v)))
+            .collect();
+        Problem {
+            formula,
+            num_vars,
+            names,
+            weights,
+            projection: None,
+            variables,
+            evidence: Vec::new(),
+        }
+    }
+
+    /// a CNF with the variable interactions of the formula, to build orders
+    /// and vtrees from
+    pub fn scopes(&self) -> Cnf {
+        match &self.formula {
+            Formula::Cnf(cnf) => cnf.clone(),
+            Formula::Expr(expr) => expr.scope_cnf(),
+        }
+    }
+
+    pub fn is_projected_out(&self, label: VarLabel) -> bool {
+        self.projection
+            .as_ref()
+            .is_some_and(|projection| !projection.contains(label))
+    }
+
+    pub fn variable(&self, name: &str) -> Result<&Variable, String> {
+        self.variables
+            .iter()
+            .find(|v| v.name == name)
+            .ok_or_else(|| format!("unknown variable `{}`", name))
+    }
+
+    /// the literals asserting that the variable `name` is in state `value`
+    pub fn observe(&self, name: &str, value: &str) -> Result<Vec<Literal>, String> {
+        let variable = self.variable(name)?;
+        let state = variable.state(value)?;
+        Ok(variable.states[state].1.clone())
+    }
+
+    /// the problem's weights, with those of the named labels replaced
+    pub fn reweighted(
+        &self,
+        weights: &HashMap<String, VariableWeight<f64>>,
+    ) -> Result<WmcParams<RealSemiring>, String> {
+        let mut params = self.weights.clone();
+        for (name, weight) in weights {
+            let v = self.names.iter().position(|n| n == name).ok_or_else(|| {
+                match self.variables.iter().find(|v| v.name == *name) {
+                    // a network variable, whose states are indicator labels
+                    Some(variable) => {
+                        let indicators: Vec<String> = variable
+                            .states
+                            .iter()
+                            .map(|(state, _)| format!("`{}={}`", name, state))
+                            .collect();
+                        format!(
+                            "cannot weigh `{}` as a whole; weigh its indicators instead: {}",
+                            name,
+                            indicators.join(", ")
+                        )
+                    }
+                    None => format!("cannot weigh unknown variable `{}`", name),
+                }
+            })?;
+            params.set_weight(
+                VarLabel::new_usize(v),
+                RealSemiring(weight.low),
+                RealSemiring(weight.high),
+            );
+        }
+        Ok(params)
+    }
+
+    pub fn label_names(&self) -> HashMap<VarLabel, String> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(v, name)| (VarLabel::new_usize(v), name.clone()))
+            .collect()
+    }
+}
+
+/// the number of variables in the `p` line of a DIMACS file
+fn declared_num_vars(text: &str) -> usize {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find_map(|words| match words.as_slice() {
+            ["p", _, num_vars, ..] => num_vars.parse().ok(),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+fn read_dimacs(text: &str, weighted: bool) -> Result<Problem, String> {
+    let parsed = Cnf::from_weighted_dimacs(text).map_err(|e| e.to_string())?;
+    let num_vars = usize::max(declared_num_vars(text), parsed.cnf.num_vars());
+    let names = (1..=num_vars).map(|v| v.to_string()).collect();
+    let weights = if weighted {
+        parsed.weights
+    } else {
+        let mut weights = WmcParams::default();
+        for v in (0..num_vars).map(VarLabel::new_usize) {
+            weights.set_weight(v, RealSemiring(1.0), RealSemiring(1.0));
+        }
+        weights
+    };
+    let mut problem = Problem::new(Formula::Cnf(parsed.cnf), num_vars, names, weights);
+    if let (true, Some(projection)) = (weighted, parsed.projection) {
+        // only the shown variables can be queried, and the others, which
+        // are quantified away, must not weigh the count
+        problem
+            .variables
+            .retain(|v| projection.contains(v.states[1].1[0].label()));
+        for v in (0..num_vars).map(VarLabel::new_usize) {
+            if !projection.contains(v) {
+                problem
+                    .weights
+                    .set_weight(v, RealSemiring(1.0), RealSemiring(0.0));
+            }
+        }
+        problem.projection = Some(projection);
+    }
+    Ok(problem)
+}
+
+fn read_sexpr(text: &str) -> Result<Problem, String> {
+    let sexpr = LogicalSExpr::parse(text).map_err(|e| e.to_string())?;
+    let expr = LogicalExpr::from_sexpr(&sexpr);
+    let mapping = sexpr.variable_mapping();
+    let mut names = vec![String::new(); mapping.len()];
+    for (name, v) in mapping {
+        names[v] = name.clone();
+    }
+    let mut weights = WmcParams::default();
+    for v in (0..names.len()).map(VarLabel::new_usize) {
+        weights.set_weight(v, RealSemiring(1.0), RealSemiring(1.0));
+    }
+    Ok(Problem::new(
+        Formula::Expr(expr),
+        names.len(),
+        names,
+        weights,
+    ))
+}
+
+fn read_bn(text: &str) -> Result<Problem, String> {
+    let bn: BayesianNetwork = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let encoding = bn.encode();
+    let num_vars = encoding.names.len();
+    let mut problem = Problem::new(
+        Formula::Cnf(encoding.cnf),
+        num_vars,
+        encoding.names,
+        encoding.weights,
+    );
+    problem.variables = bn
+        .variables()
+        .iter()
+        .zip(encoding.indicators.iter())
+        .map(|(v, indicators)| Variable {
+            name: v.name.clone(),
+            states: v
+                .states
+                .iter()
+                .zip(indicators.iter())
+                .map(|(state, l)| (state.clone(), vec![Literal::new(*l, true)]))
+                .collect(),
+        })
+        .collect();
+    Ok(problem)
+}
+
+impl InputArgs {
+    pub fn load(&self) -> Result<Problem, String> {
+        let text = fs::read_to_string(&self.file)
+            .map_err(|e| format!("could not read {}: {}", self.file, e))?;
+        let format = match self.input_format {
+            InputFormat::Auto => match Path::new(&self.file).extension().and_then(|e| e.to_str()) {
+                Some("cnf" | "dimacs") => InputFormat::Wdimacs,
+                Some("sexpr") => InputFormat::Sexpr,
+                Some("json") => InputFormat::Bn,
+                _ => {
+                    return Err(format!(
+                        "cannot tell the format of {}; pass --input-format",
+                        self.file
+                    ))
+                }
+            },
+            format => format,
+        };
+        let mut problem = match format {
+            InputFormat::Dimacs => read_dimacs(&text, false),
+            InputFormat::Wdimacs | InputFormat::Auto => read_dimacs(&text, true),
+            InputFormat::Sexpr => read_sexpr(&text),
+            InputFormat::Bn => read_bn(&text),
+        }
+        .map_err(|e| format!("could not parse {}: {}", self.file, e))?;
+
+        if let Some(path) = &self.weights {
+            let text =
+                fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+            let weights: HashMap<String, VariableWeight<f64>> = serde_json::from_str(&text)
+                .map_err(|e| format!("could not parse {} as weights: {}", path, e))?;
+            problem.weights = problem
+                .reweighted(&weights)
+                .map_err(|e| format!("{}: {}", path, e))?;
+        }
+
+        for observation in self.evidence.iter() {
+            let (name, value) = observation
+                .split_once('=')
+                .ok_or_else(|| format!("evidence `{}` is not NAME=VALUE", observation))?;
+            let literals = problem.observe(name, value)?;
+            problem.evidence.extend(literals);
+        }
+        Ok(problem)
+    }
+}
@@ -0,0 +1,163 @@
+//! `rsdd`: compile and query formulas and Bayesian networks from the command
+//! line. Every subcommand reads a problem the same way (see `--help`), and
+//! results are printed as JSON.
+//!
+//! ```text
+//! rsdd count cnf/rand-3-25-75-1.cnf --diagram sdd --vtree hypergraph
+//! rsdd marginals bayesian_networks/cancer.json -e Xray=positive
+//! rsdd map bayesian_networks/cancer.json --vars Smoker,Pollution -e Dyspnoea=True
+//! rsdd export cnf/rand-3-25-75-1.cnf --format dot
+//! rsdd serve bayesian_networks/alarm.json --socket /tmp/alarm.sock
+//! ```
+mod compile;
+mod input;
+mod query;
+mod serve;
+
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process,
+};
+
+use clap::{Parser, Subcommand};
+use rsdd::repr::OrderHeuristic;
+
+use crate::{
+    compile::{compile, CompileArgs, ExportFormat},
+    input::InputArgs,
+    query::{Count, Export, Map, Marginals, Summarize, Wmc},
+    serve::Serve,
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// compile, then report the diagram's size, compilation time and
+    /// serialization
+    Compile {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+    },
+    /// count the models consistent with the evidence
+    Count {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+    },
+    /// weighted model count of the evidence
+    Wmc {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+    },
+    /// the distribution of each variable given the evidence
+    Marginals {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+    },
+    /// the most likely state of every variable given the evidence
+    Mpe {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+    },
+    /// the most likely state of some variables given the evidence, summing
+    /// out the rest
+    Map {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+        /// comma-separated variables to maximize over
+        #[clap(long, value_delimiter = ',', required = true)]
+        vars: Vec<String>,
+    },
+    /// print the diagram, conditioned on the evidence
+    Export {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+        #[clap(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+    /// compile once, then answer JSON-RPC requests (`condition`, `wmc`,
+    /// `marginals` and `map`), one per line, on standard input or a socket.
+    /// requests name variables like `--evidence`, and weigh them like
+    /// `--weights`, i.e. Bayesian networks by `variable=state` indicators
+    Serve {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        compile: CompileArgs,
+        /// (optional) Unix socket to listen on instead of standard input
+        #[clap(long)]
+        socket: Option<PathBuf>,
+    },
+    /// width measures and interaction-graph statistics of the formula, to
+    /// estimate the cost of compiling it
+    Stats {
+        #[clap(flatten)]
+        input: InputArgs,
+        /// elimination order to analyze
+        #[clap(long, value_parser = str::parse::<OrderHeuristic>, default_value = "minfill")]
+        order: OrderHeuristic,
+    },
+}
+
+fn run(command: Command) -> Result<Vec<u8>, String> {
+    match command {
+        Command::Compile { input, compile: c } => compile(&input.load()?, &c, Summarize),
+        Command::Count { input, compile: c } => compile(&input.load()?, &c, Count),
+        Command::Wmc { input, compile: c } => compile(&input.load()?, &c, Wmc),
+        Command::Marginals { input, compile: c } => compile(&input.load()?, &c, Marginals),
+        Command::Mpe { input, compile: c } => compile(&input.load()?, &c, Map(None)),
+        Command::Map {
+            input,
+            compile: c,
+            vars,
+        } => compile(&input.load()?, &c, Map(Some(vars))),
+        Command::Export {
+            input,
+            compile: c,
+            format,
+        } => compile(&input.load()?, &c, Export(format)),
+        Command::Serve {
+            input,
+            compile: c,
+            socket,
+        } => compile(&input.load()?, &c, Serve { socket }),
+        Command::Stats { input, order } => {
+            let scopes = input.load()?.scopes();
+            let analysis = scopes.analyze(&scopes.order_by(order));
+            let mut bytes = serde_json::to_vec_pretty(&analysis).unwrap();
+            bytes.push(b'\n');
+            Ok(bytes)
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(output) => io::stdout().write_all(&output).unwrap(),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1)
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! Queries on compiled diagrams
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use num_bigint::BigUint;
+use rsdd::{
+    repr::{DDNNFPtr, Literal, VarLabel, WmcParams},
+    util::semirings::RealSemiring,
+};
+use serde::{Serialize, Serializer};
+use serde_json::{value::RawValue, Value};
+
+use crate::{
+    compile::{Compiled, DiagramKind, ExportFormat, Node, Task},
+    input::{Problem, Variable},
+};
+
+/// A diagram conditioned on some literals
+#[derive(Clone)]
+pub struct Conditioned<P> {
+    ptr: P,
+    /// the product of the conditioned literals' weights
+    weight: f64,
+    assigned: HashMap<VarLabel, bool>,
+}
+
+/// Weighted model counts of a diagram that may skip variables.
+///
+/// An unsmoothed count charges nothing for a variable the diagram skips,
+/// which is only right if the variable's weights sum to one. Each label's
+/// weights are therefore rescaled to sum to one, and counts are multiplied
+/// back by the product of the original sums.
+pub struct Counter<'b, P> {
+    condition: &'b dyn Fn(P, VarLabel, bool) -> P,
+    params: WmcParams<RealSemiring>,
+    scale: f64,
+}
+
+impl<P> Conditioned<P> {
+    /// the value of every conditioned label
+    pub fn assigned(&self) -> &HashMap<VarLabel, bool> {
+        &self.assigned
+    }
+}
+
+impl<'a, 'b, P: DDNNFPtr<'a>> Counter<'b, P> {
+    pub fn new(
+        condition: &'b dyn Fn(P, VarLabel, bool) -> P,
+        weights: &WmcParams<RealSemiring>,
+        num_vars: usize,
+    ) -> Counter<'b, P> {
+        let mut params = WmcParams::default();
+        let mut scale = 1.0;
+        for v in (0..num_vars).map(VarLabel::new_usize) {
+            let (RealSemiring(low), RealSemiring(high)) = *weights.var_weight(v);
+            let sum = low + high;
+            // a label whose weights sum to zero zeroes every count
+            let (low, high) = if sum == 0.0 {
+                (0.5, 0.5)
+            } else {
+                (low / sum, high / sum)
+            };
+            params.set_weight(v, RealSemiring(low), RealSemiring(high));
+            scale *= sum;
+        }
+        Counter {
+            condition,
+            params,
+            scale,
+        }
+    }
+
+    pub fn root(&self, ptr: P) -> Conditioned<P> {
+        Conditioned {
+            ptr,
+            weight: 1.0,
+            assigned: HashMap::new(),
+        }
+    }
+
+    /// `f` further conditioned on `literals`
+    pub fn condition(&self, f: &Conditioned<P>, literals: &[Literal]) -> Conditioned<P> {
+        let mut f = f.clone();
+        for lit in literals {
+            match f.assigned.get(&lit.label()) {
+                Some(value) if *value == lit.polarity() => continue,
+                Some(_) => f.weight = 0.0,
+                None => {
+                    let (low, high) = self.params.var_weight(lit.label());
+                    f.weight *= if lit.polarity() { high.0 } else { low.0 };
+                    f.ptr = (self.condition)(f.ptr, lit.label(), lit.polarity());
+                    f.assigned.insert(lit.label(), lit.polarity());
+                }
+            }
+        }
+        f
+    }
+
+    /// the weighted model count of `f` conjoined with its conditions
+    pub fn wmc(&self, f: &Conditioned<P>) -> f64 {
+        if f.weight == 0.0 {
+            return 0.0;
+        }
+        self.scale * f.weight * f.ptr.unsmoothed_wmc(&self.params).0
+    }
+
+    /// the states of `variables` that maximize the weighted model count of
+    /// `f`, found by branch and bound with weighted counts as upper bounds
+    /// (so weights must be nonnegative); `None` if every count is zero
+    fn map(&self, f: &Conditioned<P>, variables: &[&Variable]) -> Option<(f64, Vec<usize>)> {
+        let mut best = None;
+        self.map_h(f, self.wmc(f), variables, &mut Vec::new(), &mut best);
+        best
+    }
+
+    fn map_h(
+        &self,
+        f: &Conditioned<P>,
+        bound: f64,
+        variables: &[&Variable],
+        states: &mut Vec<usize>,
+        best: &mut Option<(f64, Vec<usize>)>,
+    ) {
+        if bound <= 0.0 || best.as_ref().is_some_and(|(wmc, _)| bound <= *wmc) {
+            return;
+        }
+        match variables.split_first() {
+            None => *best = Some((bound, states.clone())),
+            Some((v, rest)) => {
+                let mut children: Vec<(f64, usize, Conditioned<P>)> = v
+                    .states
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, literals))| {
+                        let child = self.condition(f, literals);
+                        (self.wmc(&child), i, child)
+                    })
+                    .collect();
+                // the most promising states first
+                children.sort_by(|a, b| b.0.total_cmp(&a.0));
+                for (bound, i, child) in children {
+                    states.push(i);
+                    self.map_h(&child, bound, rest, states, best);
+                    states.pop();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CompileOutput {
+    diagram: &'static str,
+    num_vars: usize,
+    num_nodes: usize,
+    compile_seconds: f64,
+    serialized: Value,
+}
+
+#[derive(Serialize)]
+pub struct MarginalsOutput {
+    /// the weighted model count of the evidence
+    wmc: f64,
+    marginals: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+#[derive(Serialize)]
+pub struct MapOutput {
+    /// the weighted model count of the evidence and the assignment
+    wmc: f64,
+    assignment: Option<BTreeMap<String, String>>,
+}
+
+fn to_json<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = serde_json::to_vec_pretty(value).unwrap();
+    bytes.push(b'\n');
+    bytes
+}
+
+fn conditioned_on_evidence<'a, P: DDNNFPtr<'a>>(problem: &Problem, diagram: &Compiled<'_, P>) -> P {
+    problem.evidence.iter().fold(diagram.ptr, |f, lit| {
+        (diagram.condition)(f, lit.label(), lit.polarity())
+    })
+}
+
+/// Reports the size of the diagram, conditioned on the evidence, and its
+/// JSON serialization
+pub struct Summarize;
+
+impl Task for Summarize {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let ptr = conditioned_on_evidence(problem, &diagram);
+        let serialized = (diagram.export)(ptr, ExportFormat::Json)?;
+        Ok(to_json(&CompileOutput {
+            diagram: match diagram.kind {
+                DiagramKind::Bdd => "bdd",
+                DiagramKind::Sdd => "sdd",
+                DiagramKind::Dnnf => "dnnf",
+            },
+            num_vars: problem.num_vars,
+            num_nodes: ptr.count_nodes(),
+            compile_seconds: diagram.seconds,
+            serialized: serde_json::from_slice(&serialized).unwrap(),
+        }))
+    }
+}
+
+/// Prints the diagram, conditioned on the evidence
+pub struct Export(pub ExportFormat);
+
+impl Task for Export {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let ptr = conditioned_on_evidence(problem, &diagram);
+        (diagram.export)(ptr, self.0)
+    }
+}
+
+/// The (projected) number of models consistent with the evidence
+pub struct Count;
+
+impl Task for Count {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let count = model_count(problem, &diagram, &problem.evidence);
+        Ok(to_json(&BTreeMap::from([("count", ExactCount(count))])))
+    }
+}
+
+/// A model count, which serializes as a JSON number however large it is
+pub struct ExactCount(pub BigUint);
+
+impl Serialize for ExactCount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // serde_json writes raw values verbatim, where a number this large
+        // would be rounded to a double
+        RawValue::from_string(self.0.to_string())
+            .unwrap()
+            .serialize(serializer)
+    }
+}
+
+/// The exact (projected) number of models of the diagram conjoined with
+/// `literals`.
+///
+/// This is the count [`Counter`] computes when counted labels weigh one
+/// either way and projected-out labels only count as false, without
+/// rounding: there, every counted label is rescaled to weigh one half, so
+/// every intermediate count is a multiple of `2^-k` for the `k` counted
+/// labels. Here each such count `c` is kept as the integer `c * 2^k`
+/// instead.
+pub fn model_count<'a, P: DDNNFPtr<'a>>(
+    problem: &Problem,
+    diagram: &Compiled<'_, P>,
+    literals: &[Literal],
+) -> BigUint {
+    let counted = |v: VarLabel| !problem.is_projected_out(v);
+    let k = (0..problem.num_vars)
+        .filter(|v| counted(VarLabel::new_usize(*v)))
+        .count();
+
+    let mut ptr = diagram.ptr;
+    let mut assigned = HashMap::new();
+    // the number of counted labels that `literals` fix
+    let mut fixed = 0;
+    for lit in literals {
+        match assigned.insert(lit.label(), lit.polarity()) {
+            Some(value) if value == lit.polarity() => continue,
+            Some(_) => return BigUint::ZERO,
+            None if counted(lit.label()) => fixed += 1,
+            // a label that is projected out only counts as false
+            None if lit.polarity() => return BigUint::ZERO,
+            None => (),
+        }
+        ptr = (diagram.condition)(ptr, lit.label(), lit.polarity());
+    }
+
+    let one = BigUint::from(1u8) << k;
+    let lit = |v: VarLabel, polarity: bool| match (counted(v), polarity) {
+        (true, _) => &one >> 1,
+        (false, true) => BigUint::ZERO,
+        (false, false) => one.clone(),
+    };
+    // `BigUint`s are not `Copy`, so this cannot be a `DDNNFPtr::fold`
+    fn walk<P: Copy + Eq + Hash>(
+        f: P,
+        expand: &dyn Fn(P) -> Node<P>,
+        lit: &dyn Fn(VarLabel, bool) -> BigUint,
+        one: &BigUint,
+        k: usize,
+        memo: &mut HashMap<P, BigUint>,
+    ) -> BigUint {
+        if let Some(count) = memo.get(&f) {
+            return count.clone();
+        }
+        let mut walk = |f| walk(f, expand, lit, one, k, memo);
+        let count = match expand(f) {
+            Node::True => one.clone(),
+            Node::False => BigUint::ZERO,
+            Node::Lit(v, polarity) => lit(v, polarity),
+            Node::Decision(v, low, high) => {
+                ((lit(v, false) * walk(low)) >> k) + ((lit(v, true) * walk(high)) >> k)
+            }
+            Node::Elements(elements) => elements
+                .into_iter()
+                .map(|(prime, sub)| (walk(prime) * walk(sub)) >> k)
+                .sum(),
+        };
+        memo.insert(f, count.clone());
+        count
+    }
+    walk(ptr, diagram.expand, &lit, &one, k, &mut HashMap::new()) >> fixed
+}
+
+/// The weighted model count of the evidence
+pub struct Wmc;
+
+impl Task for Wmc {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let counter = Counter::new(diagram.condition, &problem.weights, problem.num_vars);
+        let f = counter.condition(&counter.root(diagram.ptr), &problem.evidence);
+        Ok(to_json(&BTreeMap::from([("wmc", counter.wmc(&f))])))
+    }
+}
+
+/// The distribution of every variable's state given the evidence
+pub struct Marginals;
+
+impl Task for Marginals {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let counter = Counter::new(diagram.condition, &problem.weights, problem.num_vars);
+        let f = counter.condition(&counter.root(diagram.ptr), &problem.evidence);
+        Ok(to_json(&marginals(&counter, &f, &problem.variables)))
+    }
+}
+
+/// The most likely states of some variables given the evidence, summing
+/// out the rest; every variable if `None`
+pub struct Map(pub Option<Vec<String>>);
+
+impl Task for Map {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let counter = Counter::new(diagram.condition, &problem.weights, problem.num_vars);
+        let f = counter.condition(&counter.root(diagram.ptr), &problem.evidence);
+        Ok(to_json(&map(&counter, &f, problem, self.0.as_deref())?))
+    }
+}
+
+/// the distribution of each of `variables` in the models of `f`
+pub fn marginals<'a, P: DDNNFPtr<'a>>(
+    counter: &Counter<'_, P>,
+    f: &Conditioned<P>,
+    variables: &[Variable],
+) -> MarginalsOutput {
+    let wmc = counter.wmc(f);
+    let marginals = variables
+        .iter()
+        .map(|v| {
+            let states = v
+                .states
+                .iter()
+                .map(|(state, literals)| {
+                    let p = counter.wmc(&counter.condition(f, literals)) / wmc;
+                    (state.clone(), p)
+                })
+                .collect();
+            (v.name.clone(), states)
+        })
+        .collect();
+    MarginalsOutput { wmc, marginals }
+}
+
+/// the most likely states of the variables called `names` (or of every
+/// variable) in the models of `f`
+pub fn map<'a, P: DDNNFPtr<'a>>(
+    counter: &Counter<'_, P>,
+    f: &Conditioned<P>,
+    problem: &Problem,
+    names: Option<&[String]>,
+) -> Result<MapOutput, String> {
+    let variables: Vec<&Variable> = match names {
+        None => problem.variables.iter().collect(),
+        Some(names) => names
+            .iter()
+            .map(|name| problem.variable(name))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(match counter.map(f, &variables) {
+        None => MapOutput {
+            wmc: 0.0,
+            assignment: None,
+        },
+        Some((wmc, states)) => MapOutput {
+            wmc,
+            assignment: Some(
+                variables
+                    .iter()
+                    .zip(states)
+                    .map(|(v, state)| (v.name.clone(), v.states[state].0.clone()))
+                    .collect(),
+            ),
+        },
+    })
+}
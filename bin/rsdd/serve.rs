@@ -0,0 +1,264 @@
+//! A long-running server that compiles a problem once and then answers
+//! JSON-RPC 2.0 requests about it, one JSON object per line, over standard
+//! input and output or a Unix socket.
+//!
+//! Methods, whose parameters are all optional:
+//! - `condition {evidence}` replaces the connection's evidence, which later
+//!   requests add their own to, and answers like `wmc`
+//! - `wmc {evidence, weights}` answers a [`PartialWmcResult`], whose `mc` is
+//!   the exact number of models of the conditions
+//! - `marginals {evidence, weights}` answers like `rsdd marginals`
+//! - `map {evidence, weights, vars}` answers like `rsdd map`, or like
+//!   `rsdd mpe` without `vars`
+//!
+//! where `evidence` is `{"NAME": "VALUE"}`, as for `--evidence`, and
+//! `weights` is `{"LABEL": {"low": 0.4, "high": 0.6}}`, as in weight files.
+//! Weights are keyed by label rather than by variable: DIMACS variables by
+//! their number, and a Bayesian network variable's states by their
+//! `variable=state` indicators, which weigh 1 unless reweighted:
+//! ```text
+//! > {"jsonrpc": "2.0", "id": 1, "method": "marginals", "params": {"evidence": {"Xray": "positive"}}}
+//! < {"jsonrpc":"2.0","id":1,"result":{"wmc":0.208141,"marginals":{...}}}
+//! > {"jsonrpc": "2.0", "id": 2, "method": "wmc", "params": {"weights": {"Smoker=True": {"low": 1, "high": 0.5}}}}
+//! < {"jsonrpc":"2.0","id":2,"result":{"partial_model":{},"wmc":0.85,"mc":...}}
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use rsdd::{
+    repr::{DDNNFPtr, Literal},
+    serialize::{PartialWmcResult, VariableWeight},
+    util::semirings::RealSemiring,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{
+    value::{to_raw_value, RawValue},
+    Value,
+};
+
+use crate::{
+    compile::{Compiled, Task},
+    input::Problem,
+    query::{map, marginals, model_count, Conditioned, Counter, ExactCount},
+};
+
+// error codes from the JSON-RPC 2.0 specification
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Params {
+    evidence: HashMap<String, String>,
+    weights: HashMap<String, VariableWeight<f64>>,
+    vars: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    /// raw, so that exact counts are not rounded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Box<RawValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+/// Answers requests until standard input or the socket's listener closes
+pub struct Serve {
+    pub socket: Option<PathBuf>,
+}
+
+impl Task for Serve {
+    fn run<'a, P: DDNNFPtr<'a>>(
+        self,
+        problem: &Problem,
+        diagram: Compiled<'_, P>,
+    ) -> Result<Vec<u8>, String> {
+        let server = Server { problem, diagram };
+        match &self.socket {
+            None => server
+                .serve(io::stdin().lock(), io::stdout().lock())
+                .map_err(|e| e.to_string())?,
+            Some(path) => server.listen(path)?,
+        }
+        Ok(Vec::new())
+    }
+}
+
+struct Server<'p, 'b, P> {
+    problem: &'p Problem,
+    diagram: Compiled<'b, P>,
+}
+
+impl<'a, 'p, 'b, P: DDNNFPtr<'a>> Server<'p, 'b, P> {
+    #[cfg(unix)]
+    fn listen(&self, path: &PathBuf) -> Result<(), String> {
+        use std::os::unix::net::UnixListener;
+
+        let listener = UnixListener::bind(path)
+            .map_err(|e| format!("could not listen on {}: {}", path.display(), e))?;
+        eprintln!("listening on {}", path.display());
+        // diagrams cannot be shared across threads, so clients are served
+        // one at a time
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| {
+                let reader = BufReader::new(stream.try_clone()?);
+                self.serve(reader, stream)
+            });
+            if let Err(e) = result {
+                eprintln!("connection failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn listen(&self, _path: &PathBuf) -> Result<(), String> {
+        Err(String::from("sockets are only supported on Unix"))
+    }
+
+    /// answers each line of `reader` on `writer`; the evidence set by
+    /// `condition` lasts until `reader` closes
+    fn serve(&self, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        let mut evidence = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.respond(&line, &mut evidence) {
+                serde_json::to_writer(&mut writer, &response)?;
+                writeln!(writer)?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn respond(&self, line: &str, evidence: &mut Vec<Literal>) -> Option<Response> {
+        let (id, outcome) = match serde_json::from_str::<Value>(line) {
+            Err(e) => (Value::Null, Err((PARSE_ERROR, e.to_string()))),
+            Ok(value) => match Request::deserialize(&value) {
+                Err(e) => (
+                    value.get("id").cloned().unwrap_or(Value::Null),
+                    Err((INVALID_REQUEST, e.to_string())),
+                ),
+                Ok(request) if request.jsonrpc != "2.0" => (
+                    request.id.unwrap_or(Value::Null),
+                    Err((INVALID_REQUEST, String::from("jsonrpc must be \"2.0\""))),
+                ),
+                Ok(request) => {
+                    let outcome = self.call(&request.method, request.params, evidence);
+                    // notifications get no response
+                    (request.id?, outcome)
+                }
+            },
+        };
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err((code, message)) => (None, Some(RpcError { code, message })),
+        };
+        Some(Response {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        })
+    }
+
+    fn call(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        evidence: &mut Vec<Literal>,
+    ) -> Result<Box<RawValue>, (i64, String)> {
+        if !["condition", "wmc", "marginals", "map"].contains(&method) {
+            return Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method)));
+        }
+        let invalid = |e: String| (INVALID_PARAMS, e);
+        let params: Params = match params {
+            None => Params::default(),
+            Some(params) => serde_json::from_value(params).map_err(|e| invalid(e.to_string()))?,
+        };
+        if params.vars.is_some() && method != "map" {
+            return Err(invalid(String::from("only `map` takes `vars`")));
+        }
+        let mut observed = Vec::new();
+        for (name, value) in params.evidence.iter() {
+            observed.extend(self.problem.observe(name, value).map_err(invalid)?);
+        }
+        let weights = self.problem.reweighted(&params.weights).map_err(invalid)?;
+        let counter = Counter::new(self.diagram.condition, &weights, self.problem.num_vars);
+
+        let result = match method {
+            "condition" => {
+                if !params.weights.is_empty() {
+                    return Err(invalid(String::from("`condition` does not take `weights`")));
+                }
+                *evidence = observed;
+                let f = counter.condition(&counter.root(self.diagram.ptr), evidence);
+                to_raw_value(&self.partial_wmc(&counter, &f))
+            }
+            _ => {
+                let f = counter.condition(&counter.root(self.diagram.ptr), evidence);
+                let f = counter.condition(&f, &observed);
+                match method {
+                    "wmc" => to_raw_value(&self.partial_wmc(&counter, &f)),
+                    "marginals" => to_raw_value(&marginals(&counter, &f, &self.problem.variables)),
+                    "map" => to_raw_value(
+                        &map(&counter, &f, self.problem, params.vars.as_deref())
+                            .map_err(invalid)?,
+                    ),
+                    _ => unreachable!(),
+                }
+            }
+        };
+        Ok(result.unwrap())
+    }
+
+    /// the counts of `f`, with its conditions named by label
+    fn partial_wmc(
+        &self,
+        counter: &Counter<'_, P>,
+        f: &Conditioned<P>,
+    ) -> PartialWmcResult<RealSemiring, ExactCount> {
+        let assigned: Vec<Literal> = f
+            .assigned()
+            .iter()
+            .map(|(label, value)| Literal::new(*label, *value))
+            .collect();
+        PartialWmcResult {
+            partial_model: f
+                .assigned()
+                .iter()
+                .map(|(label, value)| (self.problem.names[label.value_usize()].clone(), *value))
+                .collect(),
+            wmc: RealSemiring(counter.wmc(f)),
+            mc: ExactCount(model_count(self.problem, &self.diagram, &assigned)),
+        }
+    }
+}
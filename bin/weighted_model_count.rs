@@ -3,6 +3,7 @@ use std::{
     fmt::Debug,
     fs::{self, File},
     io::Write,
+    process,
     time::Instant,
 };
 
@@ -10,37 +11,21 @@ use clap::Parser;
 use rsdd::{
     builder::{bdd::RobddBuilder, cache::LruIteTable, BottomUpBuilder},
     constants::primes,
-    repr::{BddPtr, DDNNFPtr, LogicalExpr, PartialModel, VarLabel, VarOrder, WmcParams},
-    serialize::LogicalSExpr,
+    repr::{
+        BddPtr, DDNNFPtr, LogicalExpr, OrderHeuristic, PartialModel, VarLabel, VarOrder, WmcParams,
+        BATCH_WIDTH,
+    },
+    serialize::{LogicalSExpr, PartialWmcOutput, PartialWmcResult, VariableWeight},
     util::semirings::{FiniteField, RealSemiring, Semiring},
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
-struct VariableWeight<T> {
-    low: T,
-    high: T,
-}
-
 #[derive(Serialize, Deserialize)]
 struct Config {
     order: Option<Vec<String>>,
     partials: Option<Vec<HashMap<String, bool>>>,
 }
 
-#[derive(Serialize, Debug)]
-struct PartialWmcResult<T: Semiring + Serialize + Debug> {
-    partial_model: HashMap<String, bool>,
-    wmc: T,
-    mc: u128,
-}
-
-#[derive(Serialize)]
-struct PartialWmcOutput<T: Semiring + Serialize + Debug> {
-    bdd_size: usize,
-    results: Vec<PartialWmcResult<T>>,
-}
-
 impl Config {
     fn to_var_order(&self, mapping: &HashMap<&String, usize>) -> Option<VarOrder> {
         self.order.as_ref().map(|o| {
@@ -72,6 +57,13 @@ struct Args {
     #[clap(short, long, value_parser)]
     config: Option<String>,
 
+    /// (optional) variable ordering heuristic, used when the config file does
+    /// not give an order. one of: `linear`, `minfill`, `weighted_minfill`,
+    /// `mindegree`, `force`, `mince`, `hypergraph`, `anneal_cutwidth`,
+    /// `anneal_bandwidth`
+    #[clap(long, value_parser)]
+    ordering: Option<String>,
+
     /// path to weights JSON file
     #[clap(short, long, value_parser)]
     weights: Option<String>,
@@ -195,25 +187,23 @@ fn partial_wmcs(
                 .map(|v| (VarLabel::new(v), (FiniteField::one(), FiniteField::one()))),
         ));
 
-    let mut results = Vec::new();
-
     let start = Instant::now();
 
     let bdd = builder.compile_logical_expr(&expr);
 
     let init_compilation = start.elapsed();
 
-    for model in partials {
-        let num_conditioned = model.true_assignments.len() + model.false_assignments.len();
-        let conditioned = builder.condition_model(bdd, model);
-        let smoothed = builder.smooth(conditioned, num_vars - num_conditioned);
-
-        let mc = smoothed.unsmoothed_wmc(&unweighted_params).value();
-        let wmc = smoothed.unsmoothed_wmc(params);
+    // assigned variables weigh one for their value, so every model is
+    // counted over the same smoothed diagram without conditioning it
+    let smoothed = builder.smooth(bdd, num_vars);
+    let mcs = smoothed.batch_wmc::<_, BATCH_WIDTH>(&unweighted_params, partials);
+    let wmcs = smoothed.batch_wmc::<_, BATCH_WIDTH>(params, partials);
 
+    let mut results = Vec::new();
+    for ((model, mc), wmc) in partials.iter().zip(mcs).zip(wmcs) {
         let res = PartialWmcResult {
             partial_model: serialize_partial_model(model, inverse_mapping),
-            mc,
+            mc: mc.value(),
             wmc,
         };
 
@@ -279,11 +269,9 @@ fn main() {
         panic!("no weights file provided");
     };
 
-    let sexpr = serde_sexpr::from_str::<LogicalSExpr>(&file).unwrap_or_else(|e| {
-        panic!(
-            "Error parsing {} as logical s-expression; error: {}",
-            file, e
-        )
+    let sexpr = LogicalSExpr::parse(&file).unwrap_or_else(|e| {
+        eprintln!("Error parsing {} as logical s-expression: {}", args.file, e);
+        process::exit(1)
     });
     let expr = LogicalExpr::from_sexpr(&sexpr);
     let mut num_vars = sexpr.unique_variables().len();
@@ -330,12 +318,30 @@ fn main() {
 
     let params: WmcParams<RealSemiring> = WmcParams::new(var_to_val);
 
-    let order = config.to_var_order(&mapping).unwrap_or_else(|| {
-        if !args.silent {
-            println!("No ordering in config; defaulting to linear order.")
-        }
-        VarOrder::linear_order(num_vars)
-    });
+    let order = config
+        .to_var_order(&mapping)
+        .unwrap_or_else(|| match &args.ordering {
+            None => {
+                if !args.silent {
+                    println!("No ordering in config; defaulting to linear order.")
+                }
+                VarOrder::linear_order(num_vars)
+            }
+            Some(name) => {
+                let heuristic = name.parse::<OrderHeuristic>().unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    process::exit(1)
+                });
+                let scopes = expr.scope_cnf();
+                // variables that only occur in the weights file go last
+                let order: Vec<VarLabel> = scopes
+                    .order_by(heuristic)
+                    .in_order_iter()
+                    .chain((scopes.num_vars()..num_vars).map(VarLabel::new_usize))
+                    .collect();
+                VarOrder::new(&order)
+            }
+        });
 
     if let Some(partials) = config.partials {
         let partials = generate_partial_assignments(&partials, &inverse_mapping, num_vars);
@@ -54,7 +54,7 @@ fn main() {
 
     let cnf_input = fs::read_to_string(args.file).expect("Should have been able to read the file");
 
-    let cnf = Cnf::from_dimacs(&cnf_input);
+    let cnf = Cnf::from_dimacs(&cnf_input).unwrap();
     println!("num vars: {}", cnf.num_vars());
 
     // TODO: allow user to pick varorder
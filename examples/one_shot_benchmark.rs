@@ -14,6 +14,7 @@ use rsdd::repr::VTree;
 use rsdd::repr::VarLabel;
 use rsdd::repr::VarOrder;
 use rsdd::serialize::{BDDSerializer, SDDSerializer, VTreeSerializer};
+use rsdd::util::hypergraph::PartitionConfig;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::fs::{self, File};
@@ -48,6 +49,7 @@ struct Args {
     /// Options:
     ///    bdd_topological
     ///    sdd_right_linear
+    ///    sdd_hypergraph: compile with a vtree from min-cut hypergraph bisection
     ///    sdd_topological_elim: compile in a topological elimination order
     ///    sdd_with_vtree: compile with a supplied vtree file
     #[clap(short, long, value_parser)]
@@ -79,7 +81,7 @@ struct BenchResult {
 }
 
 fn compile_topdown_nnf(str: String, _args: &Args) -> BenchResult {
-    let cnf = Cnf::from_dimacs(&str);
+    let cnf = Cnf::from_dimacs(&str).unwrap();
     let order = VarOrder::linear_order(cnf.num_vars());
     let builder = StandardDecisionNNFBuilder::new(order);
     // let order = cnf.force_order();
@@ -92,11 +94,21 @@ fn compile_topdown_nnf(str: String, _args: &Args) -> BenchResult {
 }
 
 fn compile_sdd_dtree(str: String, _args: &Args) -> BenchResult {
-    let cnf = Cnf::from_dimacs(&str);
+    let cnf = Cnf::from_dimacs(&str).unwrap();
     let dtree = DTree::from_cnf(&cnf, &cnf.min_fill_order());
     let vtree = VTree::from_dtree(&dtree).unwrap();
+    compile_sdd_with_vtree(&cnf, vtree, _args)
+}
+
+fn compile_sdd_hypergraph(str: String, _args: &Args) -> BenchResult {
+    let cnf = Cnf::from_dimacs(&str).unwrap();
+    let vtree = VTree::from_cnf_hypergraph(&cnf, &PartitionConfig::default());
+    compile_sdd_with_vtree(&cnf, vtree, _args)
+}
+
+fn compile_sdd_with_vtree(cnf: &Cnf, vtree: VTree, _args: &Args) -> BenchResult {
     let builder = CompressionSddBuilder::new(vtree.clone());
-    let sdd = builder.compile_cnf(&cnf);
+    let sdd = builder.compile_cnf(cnf);
 
     if let Some(path) = &_args.dump_sdd {
         let json = SDDSerializer::from_sdd(sdd);
@@ -119,7 +131,7 @@ fn compile_sdd_dtree(str: String, _args: &Args) -> BenchResult {
 }
 
 fn compile_sdd_rightlinear(str: String, _args: &Args) -> BenchResult {
-    let cnf = Cnf::from_dimacs(&str);
+    let cnf = Cnf::from_dimacs(&str).unwrap();
     let o: Vec<VarLabel> = (0..cnf.num_vars())
         .map(|x| VarLabel::new(x as u64))
         .collect();
@@ -148,7 +160,7 @@ fn compile_sdd_rightlinear(str: String, _args: &Args) -> BenchResult {
 }
 
 fn compile_bdd(str: String, _args: &Args) -> BenchResult {
-    let cnf = Cnf::from_dimacs(&str);
+    let cnf = Cnf::from_dimacs(&str).unwrap();
     let builder = RobddBuilder::<LruIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
     let bdd = builder.compile_cnf(&cnf);
 
@@ -166,7 +178,7 @@ fn compile_bdd(str: String, _args: &Args) -> BenchResult {
 }
 
 fn compile_bdd_dtree(str: String, _args: &Args) -> BenchResult {
-    let cnf = Cnf::from_dimacs(&str);
+    let cnf = Cnf::from_dimacs(&str).unwrap();
     let order = cnf.min_fill_order();
     let dtree = DTree::from_cnf(&cnf, &order);
     let builder = RobddBuilder::<LruIteTable<BddPtr>>::new(order);
@@ -198,6 +210,7 @@ fn main() {
         "dnnf_topdown" => compile_topdown_nnf(file, &args),
         "sdd_right_linear" => compile_sdd_rightlinear(file, &args),
         "sdd_dtree_minfill" => compile_sdd_dtree(file, &args),
+        "sdd_hypergraph" => compile_sdd_hypergraph(file, &args),
         x => panic!("Unknown mode option: {}", x),
     };
     let duration = start.elapsed();
@@ -131,7 +131,7 @@ fn main() {
 
     let cnf_input = fs::read_to_string(args.file).expect("Should have been able to read the file");
 
-    let cnf = Cnf::from_dimacs(&cnf_input);
+    let cnf = Cnf::from_dimacs(&cnf_input).unwrap();
 
     let mut avg_sem_nodes = 0;
     let mut avg_std_nodes = 0;
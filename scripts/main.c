@@ -10,8 +10,24 @@ char* cnf_string =
 int main() {
   VarOrder* order = var_order_linear(6);
   Cnf* cnf = cnf_from_dimacs(cnf_string);
+  if (cnf == NULL) {
+    fprintf(stderr, "%zu:%zu: %s\n", last_parse_error_line(),
+            last_parse_error_column(), last_parse_error_message());
+    return 1;
+  }
   RsddBddBuilder* builder = robdd_builder_all_table(order);
   BddPtr* bdd = robdd_builder_compile_cnf(builder, cnf);
   uint64_t mc = robdd_model_count(builder, bdd);
   printf("Model Count: %llu\n", mc);
+
+  BddPtr* projected = bdd_exists(builder, bdd, 0);
+  char* json = bdd_to_json(projected);
+  printf("Projected: %s\n", json);
+  string_free(json);
+
+  bdd_free(projected);
+  bdd_free(bdd);
+  robdd_builder_free(builder);
+  cnf_free(cnf);
+  var_order_free(order);
 }
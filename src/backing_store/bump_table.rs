@@ -23,7 +23,7 @@ struct HashTableElement<'a, T: Clone> {
     hash: u64,
     /// the psl is the *probe sequence length*: it is the distance of this item
     /// from the location that it hashes to in the table.
-    psl: u8,
+    psl: u32,
 }
 
 impl<'a, T: Clone> Default for HashTableElement<'a, T> {
@@ -37,7 +37,7 @@ impl<'a, T: Clone> Default for HashTableElement<'a, T> {
 }
 
 impl<'a, T: Clone> HashTableElement<'a, T> {
-    pub fn new(ptr: &'a T, hash: u64, psl: u8) -> HashTableElement<'a, T> {
+    pub fn new(ptr: &'a T, hash: u64, psl: u32) -> HashTableElement<'a, T> {
         HashTableElement {
             ptr: Some(ptr),
             hash,
@@ -142,6 +142,26 @@ where
         }
     }
 
+    /// Removes every element for which `keep` returns false from the table.
+    /// Removed elements stay allocated, so references to them remain valid,
+    /// but they are no longer found or returned by `iter`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&'a T) -> bool) {
+        let old = mem::replace(&mut self.tbl, vec![HashTableElement::default(); self.cap]);
+        self.len = 0;
+        for itm in old.into_iter().filter(|x| x.is_occupied()) {
+            if keep(itm.ptr.unwrap()) {
+                let pos = (itm.hash as usize) % self.cap;
+                propagate(
+                    &mut self.tbl,
+                    self.cap,
+                    HashTableElement { psl: 0, ..itm },
+                    pos,
+                );
+                self.len += 1;
+            }
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
         self.tbl
             .iter()
@@ -1,7 +1,8 @@
 use crate::{
     builder::{bdd::CompiledCNF, BottomUpBuilder},
-    repr::{BddNode, BddPtr, Cnf, DDNNFPtr, PartialModel, VarLabel},
+    repr::{BddNode, BddPtr, Cnf, DDNNFPtr, PartialModel, VarLabel, VarSet},
 };
+use rustc_hash::FxHashMap;
 use std::{cmp::Ordering, collections::BinaryHeap};
 
 pub trait BddBuilder<'a>: BottomUpBuilder<'a, BddPtr<'a>> {
@@ -33,6 +34,38 @@ pub trait BddBuilder<'a>: BottomUpBuilder<'a, BddPtr<'a>> {
         cur_bdd
     }
 
+    /// existentially quantifies all variables in `vars` in a single
+    /// traversal of `bdd`; `cache` memoizes the result for each sub-BDD
+    #[allow(clippy::mutable_key_type)]
+    fn exists_set_h(
+        &'a self,
+        bdd: BddPtr<'a>,
+        vars: &VarSet,
+        cache: &mut FxHashMap<BddPtr<'a>, BddPtr<'a>>,
+    ) -> BddPtr<'a> {
+        match bdd {
+            BddPtr::PtrTrue | BddPtr::PtrFalse => bdd,
+            BddPtr::Reg(node) | BddPtr::Compl(node) => {
+                if let Some(r) = cache.get(&bdd) {
+                    return *r;
+                }
+                let l = self.exists_set_h(bdd.low(), vars, cache);
+                let h = self.exists_set_h(bdd.high(), vars, cache);
+                let r = if vars.contains(node.var) {
+                    self.or(l, h)
+                } else if l == h {
+                    l
+                } else {
+                    // both children only mention variables below node.var, so
+                    // no reordering is necessary
+                    self.get_or_insert(BddNode::new(node.var, l, h))
+                };
+                cache.insert(bdd, r);
+                r
+            }
+        }
+    }
+
     fn compile_cnf_with_assignments(&'a self, cnf: &Cnf, assgn: &PartialModel) -> BddPtr<'a> {
         let clauses = cnf.clauses();
         if clauses.is_empty() {
@@ -162,6 +195,27 @@ where
         self.or(v1, v2)
     }
 
+    /// Existentially quantifies out every variable in `vars` from `f`
+    /// ```
+    /// # use rsdd::builder::bdd::RobddBuilder;
+    /// # use rsdd::builder::BottomUpBuilder;
+    /// # use rsdd::repr::{BddPtr, VarLabel, VarSet};
+    /// # use rsdd::builder::cache::AllIteTable;
+    /// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
+    /// let a = builder.var(VarLabel::new(0), true);
+    /// let b = builder.var(VarLabel::new(1), true);
+    /// let c = builder.var(VarLabel::new(2), true);
+    /// let f = builder.and(builder.and(a, b), c);
+    ///
+    /// let mut vars = VarSet::new();
+    /// vars.insert(VarLabel::new(0));
+    /// vars.insert(VarLabel::new(2));
+    /// assert_eq!(builder.exists_set(f, &vars), b);
+    /// ```
+    fn exists_set(&'a self, bdd: BddPtr<'a>, vars: &VarSet) -> BddPtr<'a> {
+        self.exists_set_h(bdd, vars, &mut FxHashMap::default())
+    }
+
     /// Compute the Boolean function `f | var = value`
     fn condition(&'a self, bdd: BddPtr<'a>, lbl: VarLabel, value: bool) -> BddPtr<'a> {
         let r = self.cond_helper(bdd, lbl, value);
@@ -202,15 +202,14 @@ impl<'a, T: IteTable<'a, BddPtr<'a>> + Default> RobddBuilder<'a, T> {
                 let l = self.cond_with_alloc(bdd.low_raw(), lbl, value, alloc);
                 let h = self.cond_with_alloc(bdd.high_raw(), lbl, value, alloc);
 
-                if l == h {
+                let res = if l == h {
                     // reduce the BDD -- two children identical
                     if bdd.is_neg() {
-                        return l.neg();
+                        l.neg()
                     } else {
-                        return l;
-                    };
-                };
-                let res = if l != bdd.low_raw() || h != bdd.high_raw() {
+                        l
+                    }
+                } else if l != bdd.low_raw() || h != bdd.high_raw() {
                     // cache and return the new BDD
                     let new_bdd = BddNode::new(node.var, l, h);
                     let r = self.get_or_insert(new_bdd);
@@ -336,6 +335,31 @@ mod tests {
         );
     }
 
+    // a node whose children become equal when conditioned must still be
+    // marked, or `clear_scratch` leaves stale indices in its children
+    #[test]
+    fn condition_clears_scratch_below_reduced_nodes() {
+        let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(4);
+        let x = |i| builder.var(VarLabel::new(i), true);
+        // both children of the root become ite(x1, x3, !x3) once x2 is true
+        let low = builder.ite(x(1), builder.and(x(2), x(3)), x(3).neg());
+        let high = builder.ite(x(1), x(3), builder.and(x(2), x(3).neg()));
+        let f = builder.ite(x(0), high, low);
+
+        let t = builder.condition(f, VarLabel::new(2), true);
+        assert!(builder.eq(t, builder.ite(x(1), x(3), x(3).neg())));
+        assert!(f.low_raw().is_scratch_cleared());
+        assert!(f.high_raw().is_scratch_cleared());
+
+        let e = builder.condition(f, VarLabel::new(2), false);
+        let expected = builder.ite(
+            x(0),
+            builder.and(x(1), x(3)),
+            builder.and(x(1).neg(), x(3).neg()),
+        );
+        assert!(builder.eq(e, expected));
+    }
+
     #[test]
     fn simple_ite1() {
         let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
@@ -602,8 +626,8 @@ mod tests {
     #[test]
     fn test_ite_1() {
         let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(16);
-        let c1 = Cnf::from_string("(1 || 2) && (0 || -2)");
-        let c2 = Cnf::from_string("(0 || 1) && (-4 || -7)");
+        let c1 = Cnf::from_string("(1 || 2) && (0 || -2)").unwrap();
+        let c2 = Cnf::from_string("(0 || 1) && (-4 || -7)").unwrap();
         let cnf1 = builder.compile_cnf(&c1);
         let cnf2 = builder.compile_cnf(&c2);
         let iff1 = builder.iff(cnf1, cnf2);
@@ -630,7 +654,7 @@ mod tests {
         p cnf 3 1
         1 2 3 0
         ";
-        let cnf = Cnf::from_dimacs(CNF);
+        let cnf = Cnf::from_dimacs(CNF).unwrap();
 
         let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
 
@@ -661,7 +685,7 @@ mod tests {
         -1 2 0
         1 -2 0
         ";
-        let cnf = Cnf::from_dimacs(CNF);
+        let cnf = Cnf::from_dimacs(CNF).unwrap();
 
         let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
 
@@ -687,7 +711,7 @@ mod tests {
         -2 -3 4 5 0
         -4 -5 6 6 0
         ";
-        let cnf = Cnf::from_dimacs(CNF);
+        let cnf = Cnf::from_dimacs(CNF).unwrap();
 
         let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
 
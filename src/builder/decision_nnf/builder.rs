@@ -4,6 +4,7 @@ use crate::{
     builder::TopDownBuilder,
     repr::{
         BddNode, BddPtr, Cnf, DDNNFPtr, DecisionResult, Literal, SATSolver, VarLabel, VarOrder,
+        VarSet,
     },
 };
 
@@ -54,6 +55,20 @@ pub trait DecisionNNFBuilder<'a>: TopDownBuilder<'a, BddPtr<'a>> {
         sat: &mut SATSolver,
         level: usize,
         cache: &mut FxHashMap<u128, BddPtr<'a>>,
+    ) -> BddPtr<'a> {
+        self.topdown_projected_h(cnf, sat, level, None, cache)
+    }
+
+    /// Like `topdown_h`, but if `projection` is supplied then every variable
+    /// outside of it is existentially quantified. Requires that the projected
+    /// variables precede all others in the order.
+    fn topdown_projected_h(
+        &'a self,
+        cnf: &Cnf,
+        sat: &mut SATSolver,
+        level: usize,
+        projection: Option<&VarSet>,
+        cache: &mut FxHashMap<u128, BddPtr<'a>>,
     ) -> BddPtr<'a> {
         // check for base case
         if level >= cnf.num_vars() || sat.is_sat() {
@@ -61,10 +76,20 @@ pub trait DecisionNNFBuilder<'a>: TopDownBuilder<'a, BddPtr<'a>> {
         }
         let cur_v = self.order().var_at_level(level);
 
+        // once every projected variable is decided, all that matters is
+        // whether the remaining CNF is satisfiable
+        if projection.is_some_and(|p| !p.contains(cur_v)) {
+            return if sat.solve() {
+                BddPtr::true_ptr()
+            } else {
+                BddPtr::false_ptr()
+            };
+        }
+
         // check if this literal is currently set in unit propagation; if
         // it is, skip it
         if sat.is_set(cur_v) {
-            return self.topdown_h(cnf, sat, level + 1, cache);
+            return self.topdown_projected_h(cnf, sat, level + 1, projection, cache);
         }
 
         // check cache
@@ -76,18 +101,24 @@ pub trait DecisionNNFBuilder<'a>: TopDownBuilder<'a, BddPtr<'a>> {
             }
         }
 
+        let is_projected = |v: VarLabel| projection.is_none_or(|p| p.contains(v));
+
         // recurse on both values of cur_v
         let high_bdd = match sat.decide(Literal::new(cur_v, true)) {
             DecisionResult::UNSAT => BddPtr::false_ptr(),
             DecisionResult::SAT => {
-                let new_assgn = sat.difference_iter().filter(|x| x.label() != cur_v);
+                let new_assgn = sat
+                    .difference_iter()
+                    .filter(|x| x.label() != cur_v && is_projected(x.label()));
                 let r = self.conjoin_implied(new_assgn, BddPtr::true_ptr());
                 sat.pop();
                 r
             }
             DecisionResult::Unknown => {
-                let sub = self.topdown_h(cnf, sat, level + 1, cache);
-                let new_assgn = sat.difference_iter().filter(|x| x.label() != cur_v);
+                let sub = self.topdown_projected_h(cnf, sat, level + 1, projection, cache);
+                let new_assgn = sat
+                    .difference_iter()
+                    .filter(|x| x.label() != cur_v && is_projected(x.label()));
                 let r = self.conjoin_implied(new_assgn, sub);
                 sat.pop();
                 r
@@ -96,14 +127,18 @@ pub trait DecisionNNFBuilder<'a>: TopDownBuilder<'a, BddPtr<'a>> {
         let low_bdd = match sat.decide(Literal::new(cur_v, false)) {
             DecisionResult::UNSAT => BddPtr::false_ptr(),
             DecisionResult::SAT => {
-                let new_assgn = sat.difference_iter().filter(|x| x.label() != cur_v);
+                let new_assgn = sat
+                    .difference_iter()
+                    .filter(|x| x.label() != cur_v && is_projected(x.label()));
                 let r = self.conjoin_implied(new_assgn, BddPtr::true_ptr());
                 sat.pop();
                 r
             }
             DecisionResult::Unknown => {
-                let sub = self.topdown_h(cnf, sat, level + 1, cache);
-                let new_assgn = sat.difference_iter().filter(|x| x.label() != cur_v);
+                let sub = self.topdown_projected_h(cnf, sat, level + 1, projection, cache);
+                let new_assgn = sat
+                    .difference_iter()
+                    .filter(|x| x.label() != cur_v && is_projected(x.label()));
                 let r = self.conjoin_implied(new_assgn, sub);
                 sat.pop();
                 r
@@ -122,15 +157,40 @@ pub trait DecisionNNFBuilder<'a>: TopDownBuilder<'a, BddPtr<'a>> {
 
     /// compile a decision DNNF top-down from a CNF
     fn compile_cnf_topdown(&'a self, cnf: &Cnf) -> BddPtr<'a> {
+        self.compile_cnf_topdown_h(cnf, None)
+    }
+
+    /// compile a decision DNNF top-down from a CNF, existentially quantifying
+    /// out every variable that is not in `projection`
+    ///
+    /// Panics unless the builder's order places all projected variables
+    /// before all others (see [`VarOrder::project_first`])
+    fn compile_cnf_topdown_projected(&'a self, cnf: &Cnf, projection: &VarSet) -> BddPtr<'a> {
+        let first_unprojected = (0..cnf.num_vars())
+            .position(|level| !projection.contains(self.order().var_at_level(level)))
+            .unwrap_or(cnf.num_vars());
+        assert!(
+            (first_unprojected..cnf.num_vars())
+                .all(|level| !projection.contains(self.order().var_at_level(level))),
+            "projected variables must precede all others in the order"
+        );
+        self.compile_cnf_topdown_h(cnf, Some(projection))
+    }
+
+    fn compile_cnf_topdown_h(&'a self, cnf: &Cnf, projection: Option<&VarSet>) -> BddPtr<'a> {
         let mut sat = match SATSolver::new(cnf.clone()) {
             Some(v) => v,
             None => return BddPtr::false_ptr(),
         };
 
-        let mut r = self.topdown_h(cnf, &mut sat, 0, &mut FxHashMap::default());
+        let mut r =
+            self.topdown_projected_h(cnf, &mut sat, 0, projection, &mut FxHashMap::default());
 
         // conjoin in any initially implied literals
-        for l in sat.difference_iter() {
+        let implied = sat
+            .difference_iter()
+            .filter(|l| projection.is_none_or(|p| p.contains(l.label())));
+        for l in implied {
             let node = if l.polarity() {
                 BddNode::new(l.label(), BddPtr::false_ptr(), r)
             } else {
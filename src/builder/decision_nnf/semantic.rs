@@ -120,7 +120,7 @@ mod tests {
         1 2 0
         ";
 
-        let cnf = Cnf::from_dimacs(CNF);
+        let cnf = Cnf::from_dimacs(CNF).unwrap();
 
         let linear_order = VarOrder::linear_order(cnf.num_vars());
 
@@ -8,8 +8,8 @@ pub mod decision_nnf;
 pub mod sdd;
 
 use crate::{
-    plan::BottomUpPlan,
-    repr::{Cnf, LogicalExpr, VarLabel},
+    plan::{BottomUpPlan, DagPlan, PlanNode, PlanNodeId},
+    repr::{Cnf, LogicalExpr, VarLabel, VarSet},
 };
 
 pub trait BottomUpBuilder<'a, Ptr> {
@@ -45,6 +45,22 @@ pub trait BottomUpBuilder<'a, Ptr> {
     /// existentially quantifies `v` out of `f`
     fn exists(&'a self, f: Ptr, v: VarLabel) -> Ptr;
 
+    /// existentially quantifies every variable in `vars` out of `f`
+    fn exists_set(&'a self, f: Ptr, vars: &VarSet) -> Ptr {
+        vars.iter().fold(f, |acc, v| self.exists(acc, v))
+    }
+
+    /// universally quantifies `v` out of `f`; defined by duality as
+    /// !(exists v. !f)
+    fn forall(&'a self, f: Ptr, v: VarLabel) -> Ptr {
+        self.negate(self.exists(self.negate(f), v))
+    }
+
+    /// universally quantifies every variable in `vars` out of `f`
+    fn forall_set(&'a self, f: Ptr, vars: &VarSet) -> Ptr {
+        vars.iter().fold(f, |acc, v| self.forall(acc, v))
+    }
+
     /// conditions f | v = value
     fn condition(&'a self, a: Ptr, v: VarLabel, value: bool) -> Ptr;
 
@@ -65,6 +81,19 @@ pub trait BottomUpBuilder<'a, Ptr> {
     /// directly compile a CNF
     fn compile_cnf(&'a self, cnf: &Cnf) -> Ptr;
 
+    /// compile a CNF, existentially quantifying out every variable of the CNF
+    /// that is not in `projection`
+    fn compile_cnf_projected(&'a self, cnf: &Cnf, projection: &VarSet) -> Ptr {
+        let compiled = self.compile_cnf(cnf);
+        let mut quantified = VarSet::new_with_num_vars(cnf.num_vars());
+        for v in (0..cnf.num_vars()).map(VarLabel::new_usize) {
+            if !projection.contains(v) {
+                quantified.insert(v);
+            }
+        }
+        self.exists_set(compiled, &quantified)
+    }
+
     /// directly compile a logical expression
     fn compile_logical_expr(&'a self, expr: &LogicalExpr) -> Ptr {
         match &expr {
@@ -103,38 +132,47 @@ pub trait BottomUpBuilder<'a, Ptr> {
         }
     }
 
-    /// Compiles from a BottomUpPlan, which represents a deferred computation
-    fn compile_plan(&'a self, expr: &BottomUpPlan) -> Ptr {
-        match &expr {
-            BottomUpPlan::Literal(var, polarity) => self.var(*var, *polarity),
-            BottomUpPlan::And(ref l, ref r) => {
-                let r1 = self.compile_plan(l);
-                let r2 = self.compile_plan(r);
-                self.and(r1, r2)
-            }
-            BottomUpPlan::Or(ref l, ref r) => {
-                let r1 = self.compile_plan(l);
-                let r2 = self.compile_plan(r);
-                self.or(r1, r2)
-            }
-            BottomUpPlan::Iff(ref l, ref r) => {
-                let r1 = self.compile_plan(l);
-                let r2 = self.compile_plan(r);
-                self.iff(r1, r2)
-            }
-            BottomUpPlan::Ite(ref f, ref g, ref h) => {
-                let f = self.compile_plan(f);
-                let g = self.compile_plan(g);
-                let h = self.compile_plan(h);
-                self.ite(f, g, h)
-            }
-            BottomUpPlan::Not(ref f) => {
-                let f = self.compile_plan(f);
-                self.negate(f)
-            }
-            BottomUpPlan::ConstTrue => self.true_ptr(),
-            BottomUpPlan::ConstFalse => self.false_ptr(),
+    /// Compiles from a BottomUpPlan, which represents a deferred computation.
+    /// Repeated subplans are shared first, so each is compiled only once.
+    fn compile_plan(&'a self, expr: &BottomUpPlan) -> Ptr
+    where
+        Ptr: Clone,
+    {
+        let mut plan = DagPlan::new();
+        let root = plan.add_plan(expr);
+        self.compile_dag_plan(&plan, &[root]).pop().unwrap()
+    }
+
+    /// compiles the nodes `roots` of a DAG plan, compiling each node reachable
+    /// from them exactly once no matter how many times it is shared
+    fn compile_dag_plan(&'a self, plan: &DagPlan, roots: &[PlanNodeId]) -> Vec<Ptr>
+    where
+        Ptr: Clone,
+    {
+        let mut compiled: Vec<Option<Ptr>> = vec![None; plan.len()];
+        // node ids are a topological order, so children are compiled first
+        for id in plan.reachable(roots) {
+            let c = |c: &PlanNodeId| compiled[c.value_usize()].clone().unwrap();
+            let r = match plan.node(id) {
+                PlanNode::And(a, b) => self.and(c(a), c(b)),
+                PlanNode::Or(a, b) => self.or(c(a), c(b)),
+                PlanNode::Iff(a, b) => self.iff(c(a), c(b)),
+                PlanNode::Ite(f, g, h) => self.ite(c(f), c(g), c(h)),
+                PlanNode::Not(a) => self.negate(c(a)),
+                PlanNode::Exists(vars, a) => self.exists_set(c(a), vars),
+                PlanNode::Forall(vars, a) => self.forall_set(c(a), vars),
+                PlanNode::Condition(lbl, value, a) => self.condition(c(a), *lbl, *value),
+                PlanNode::Compose(lbl, f, g) => self.compose(c(f), *lbl, c(g)),
+                PlanNode::ConstTrue => self.true_ptr(),
+                PlanNode::ConstFalse => self.false_ptr(),
+                PlanNode::Literal(lbl, polarity) => self.var(*lbl, *polarity),
+            };
+            compiled[id.value_usize()] = Some(r);
         }
+        roots
+            .iter()
+            .map(|r| compiled[r.value_usize()].clone().unwrap())
+            .collect()
     }
 }
 
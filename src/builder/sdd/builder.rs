@@ -2,12 +2,12 @@
 //! with SDDs.
 
 use crate::{
-    builder::{cache::Ite, BottomUpBuilder},
+    builder::{cache::Ite, sdd::dynamic::greedy_minimize, BottomUpBuilder},
     repr::{
         BinarySDD, Cnf, DDNNFPtr, SddAnd, SddOr, SddPtr, VTree, VTreeIndex, VTreeManager, VarLabel,
     },
 };
-use std::cmp::Ordering;
+use std::{cell::Ref, cmp::Ordering, collections::HashMap};
 
 #[derive(Default)]
 pub struct SddBuilderStats {
@@ -22,7 +22,7 @@ pub struct SddBuilderStats {
 
 pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
     // internal data structures
-    fn vtree_manager(&self) -> &VTreeManager;
+    fn vtree_manager(&self) -> Ref<'_, VTreeManager>;
 
     fn app_cache_get(&self, and: &SddAnd<'a>) -> Option<SddPtr<'a>>;
     fn app_cache_insert(&self, and: SddAnd<'a>, ptr: SddPtr<'a>);
@@ -38,12 +38,150 @@ pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
     // equality
     fn sdd_eq(&'a self, a: SddPtr<'a>, b: SddPtr<'a>) -> bool;
 
+    // dynamic vtrees
+
+    /// Switches this builder to `vtree`, rewriting only the nodes that are
+    /// normalized for a vtree node whose split changed, and their ancestors.
+    /// Every existing `SddPtr` stays valid: a pointer to a rewritten node is
+    /// mapped to its replacement by [`SddBuilder::resolve`], which this
+    /// builder's operations do on their arguments. Stale pointers still denote
+    /// the same function, so they can be counted or evaluated directly, but
+    /// anything that reads their vtree indices against the new vtree, such as
+    /// serializers, needs them resolved first.
+    ///
+    /// Panics if `vtree` does not have the same variables as the current one.
+    fn set_vtree(&'a self, vtree: VTree);
+
+    /// Maps `ptr` to its equivalent node for the current vtree, which differs
+    /// from `ptr` only if a vtree change rewrote its node. Pointers produced
+    /// before and after a vtree change are only pointer-equal once resolved.
+    fn resolve(&'a self, ptr: SddPtr<'a>) -> SddPtr<'a>;
+
+    /// Copies `ptr`, which may belong to a different builder (and vtree),
+    /// into this builder. `cache` memoizes copied nodes across calls.
+    #[allow(clippy::mutable_key_type)]
+    fn import<'b>(
+        &'a self,
+        ptr: SddPtr<'b>,
+        cache: &mut HashMap<SddPtr<'b>, SddPtr<'a>>,
+    ) -> SddPtr<'a> {
+        match ptr {
+            SddPtr::PtrTrue => SddPtr::PtrTrue,
+            SddPtr::PtrFalse => SddPtr::PtrFalse,
+            SddPtr::Var(label, polarity) => SddPtr::Var(label, polarity),
+            _ if ptr.is_neg() => self.import(ptr.neg(), cache).neg(),
+            _ => {
+                if let Some(r) = cache.get(&ptr) {
+                    return *r;
+                }
+                let r = self.rebuild(ptr, |child| self.import(child, cache));
+                cache.insert(ptr, r);
+                r
+            }
+        }
+    }
+
+    /// Builds the disjunction of the (prime, sub) pairs of the uncomplemented
+    /// node `node`, translating each prime and sub with `child`
+    fn rebuild<'b>(
+        &'a self,
+        node: SddPtr<'b>,
+        mut child: impl FnMut(SddPtr<'b>) -> SddPtr<'a>,
+    ) -> SddPtr<'a> {
+        debug_assert!(!node.is_neg());
+        let mut r = SddPtr::false_ptr();
+        for and in node.node_iter() {
+            let prime = child(and.prime());
+            let sub = child(and.sub());
+            let conj = self.and(prime, sub);
+            r = self.or(r, conj);
+        }
+        r
+    }
+
+    /// Rotates the vtree right at `idx` (see [`VTree::rotate_right`]), keeping
+    /// every existing `SddPtr` valid. Returns false and leaves the vtree
+    /// unchanged if the rotation is not possible.
+    fn rotate_right(&'a self, idx: VTreeIndex) -> bool {
+        let vtree = self.vtree_manager().vtree_root().rotate_right(idx);
+        match vtree {
+            Some(vtree) => {
+                self.set_vtree(vtree);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rotates the vtree left at `idx` (see [`VTree::rotate_left`]), keeping
+    /// every existing `SddPtr` valid. Returns false and leaves the vtree
+    /// unchanged if the rotation is not possible.
+    fn rotate_left(&'a self, idx: VTreeIndex) -> bool {
+        let vtree = self.vtree_manager().vtree_root().rotate_left(idx);
+        match vtree {
+            Some(vtree) => {
+                self.set_vtree(vtree);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Swaps the children of vtree node `idx` (see [`VTree::swap`]), keeping
+    /// every existing `SddPtr` valid. Returns false and leaves the vtree
+    /// unchanged if `idx` is a leaf.
+    fn swap(&'a self, idx: VTreeIndex) -> bool {
+        let vtree = self.vtree_manager().vtree_root().swap(idx);
+        match vtree {
+            Some(vtree) => {
+                self.set_vtree(vtree);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Greedily searches for a vtree on which `roots` have fewer nodes: scores
+    /// a left rotation, right rotation and child swap at every vtree node,
+    /// applying the first that helps, for at most `max_passes` passes. Every
+    /// existing `SddPtr` (including `roots`) remains valid; see
+    /// [`SddBuilder::resolve`]. Returns the number of nodes in `roots`
+    /// afterwards.
+    ///
+    /// Each candidate is scored by copying `roots` into a scratch builder for
+    /// its vtree, which is dropped afterwards, so rejected candidates leave
+    /// nothing behind. A move that is applied only rewrites the nodes
+    /// normalized for the vtree nodes it restructures and their ancestors.
+    /// ```
+    /// use rsdd::builder::{sdd::{num_shared_nodes, CompressionSddBuilder, SddBuilder}, BottomUpBuilder};
+    /// use rsdd::repr::{Cnf, DDNNFPtr, VarLabel, VTree};
+    ///
+    /// let vars: Vec<VarLabel> = (0..6).map(VarLabel::new).collect();
+    /// // a poor vtree for (x0 <=> x3) /\ (x1 <=> x4) /\ (x2 <=> x5)
+    /// let builder = CompressionSddBuilder::new(VTree::right_linear(&vars));
+    /// let cnf = Cnf::from_string("(0 || -3) && (-0 || 3) && (1 || -4) && (-1 || 4) && (2 || -5) && (-2 || 5)").unwrap();
+    /// let f = builder.compile_cnf(&cnf);
+    /// let before = num_shared_nodes(&[f]);
+    ///
+    /// let after = builder.minimize(&[f], 4);
+    /// assert!(after <= before);
+    /// // f still denotes the same function
+    /// assert!(builder.eq(f, builder.compile_cnf(&cnf)));
+    /// ```
+    fn minimize(&'a self, roots: &[SddPtr<'a>], max_passes: usize) -> usize
+    where
+        Self: Sized,
+    {
+        greedy_minimize(self, roots, max_passes)
+    }
+
+    // constants are never relocated, so these need not resolve `a`
     fn is_true(&'a self, a: SddPtr<'a>) -> bool {
-        self.eq(a, SddPtr::PtrTrue)
+        self.sdd_eq(a, SddPtr::PtrTrue)
     }
 
     fn is_false(&'a self, a: SddPtr<'a>) -> bool {
-        self.eq(a, SddPtr::PtrFalse)
+        self.sdd_eq(a, SddPtr::PtrFalse)
     }
 
     // compression & canonicalization
@@ -86,7 +224,7 @@ pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
     }
 
     fn unique_bdd(&'a self, bdd: BinarySDD<'a>) -> SddPtr<'a> {
-        if self.eq(bdd.high(), bdd.low()) {
+        if self.sdd_eq(bdd.high(), bdd.low()) {
             return bdd.high();
         }
         if self.is_false(bdd.high()) && self.is_true(bdd.low()) {
@@ -264,7 +402,7 @@ pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
             let p1 = a1.prime();
             let s1 = a1.sub();
             // // check if there exists an equal prime
-            let eq_itm = b.node_iter().find(|a| self.eq(a.prime(), p1));
+            let eq_itm = b.node_iter().find(|a| self.sdd_eq(a.prime(), p1));
             let s1 = if a.is_neg() { s1.neg() } else { s1 };
 
             match eq_itm {
@@ -301,7 +439,7 @@ pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
                 // check if p1 => p2 (which is true iff (p1 && p2) == p1); if it
                 // does we can stop early because the rest of the primes will be
                 // false
-                if self.eq(p1, p) {
+                if self.sdd_eq(p1, p) {
                     break;
                 }
             }
@@ -317,18 +455,18 @@ pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
         self.vtree_manager().num_vars()
     }
 
-    fn vtree(&self, ptr: SddPtr) -> &VTree {
+    fn vtree(&'a self, ptr: SddPtr<'a>) -> VTree {
+        let ptr = self.resolve(ptr);
+        let manager = self.vtree_manager();
         match ptr {
-            SddPtr::Var(lbl, _) => {
-                let idx = self.vtree_manager().var_index(lbl);
-                self.vtree_manager().vtree(idx)
-            }
-            SddPtr::Compl(_) | SddPtr::Reg(_) => self.vtree_manager().vtree(ptr.vtree()),
+            SddPtr::Var(lbl, _) => manager.vtree(manager.var_index(lbl)).clone(),
+            SddPtr::Compl(_) | SddPtr::Reg(_) => manager.vtree(ptr.vtree()).clone(),
             _ => panic!("called vtree on constant"),
         }
     }
 
-    fn vtree_index(&self, ptr: SddPtr) -> VTreeIndex {
+    fn vtree_index(&'a self, ptr: SddPtr<'a>) -> VTreeIndex {
+        let ptr = self.resolve(ptr);
         match ptr {
             SddPtr::Var(lbl, _) => self.vtree_manager().var_index(lbl),
             SddPtr::BDD(_) | SddPtr::ComplBDD(_) | SddPtr::Compl(_) | SddPtr::Reg(_) => ptr.vtree(),
@@ -396,7 +534,7 @@ pub trait SddBuilder<'a>: BottomUpBuilder<'a, SddPtr<'a>> {
                 }
             }
         }
-        let d = helper(ptr);
+        let d = helper(self.resolve(ptr));
         let mut w = Vec::new();
         d.render(10, &mut w).unwrap();
         String::from_utf8(w).unwrap()
@@ -431,11 +569,12 @@ where
     }
 
     fn eq(&'a self, a: SddPtr<'a>, b: SddPtr<'a>) -> bool {
-        self.sdd_eq(a, b)
+        self.sdd_eq(self.resolve(a), self.resolve(b))
     }
 
     fn and(&'a self, a: SddPtr<'a>, b: SddPtr<'a>) -> SddPtr<'a> {
         self.log_recursive_call();
+        let (a, b) = (self.resolve(a), self.resolve(b));
 
         // first, check for a base case
         match (a, b) {
@@ -443,8 +582,8 @@ where
             (a, b) if self.is_true(b) => return a,
             (a, _) if self.is_false(a) => return SddPtr::false_ptr(),
             (_, b) if self.is_false(b) => return SddPtr::false_ptr(),
-            (a, b) if self.eq(a, b) => return a,
-            (a, b) if self.eq(a, b.neg()) => return SddPtr::false_ptr(),
+            (a, b) if self.sdd_eq(a, b) => return a,
+            (a, b) if self.sdd_eq(a, b.neg()) => return SddPtr::false_ptr(),
             _ => (),
         };
 
@@ -501,6 +640,7 @@ where
     /// TODO : this can bail out early by checking the vtree
     fn condition(&'a self, f: SddPtr<'a>, lbl: VarLabel, value: bool) -> SddPtr<'a> {
         self.log_recursive_call();
+        let f = self.resolve(f);
         match f {
             SddPtr::PtrTrue | SddPtr::PtrFalse => f,
             SddPtr::Var(label, polarity) => {
@@ -551,6 +691,7 @@ where
 
     /// Computes the SDD representing the logical function `if f then g else h`
     fn ite(&'a self, f: SddPtr<'a>, g: SddPtr<'a>, h: SddPtr<'a>) -> SddPtr<'a> {
+        let (f, g, h) = (self.resolve(f), self.resolve(g), self.resolve(h));
         let ite = Ite::new(|a, b| self.vtree_manager().is_prime(a, b), f, g, h);
         if let Ite::IteConst(f) = ite {
             return f;
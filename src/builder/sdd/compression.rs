@@ -2,15 +2,18 @@ use crate::{
     backing_store::{BackedRobinhoodTable, UniqueTable},
     builder::{
         cache::{AllIteTable, Ite, IteTable},
-        sdd::{SddBuilder, SddBuilderStats},
+        sdd::{dynamic::DynamicVTree, SddBuilder, SddBuilderStats},
         BottomUpBuilder,
     },
     repr::{BinarySDD, DDNNFPtr, SddAnd, SddOr, SddPtr, VTree, VTreeIndex, VTreeManager},
 };
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+};
 
 pub struct CompressionSddBuilder<'a> {
-    vtree: VTreeManager,
+    vtree: DynamicVTree<'a>,
     should_compress: bool,
     // tables
     bdd_tbl: RefCell<BackedRobinhoodTable<'a, BinarySDD<'a>>>,
@@ -27,8 +30,22 @@ pub struct CompressionSddBuilder<'a> {
 
 impl<'a> SddBuilder<'a> for CompressionSddBuilder<'a> {
     #[inline]
-    fn vtree_manager(&self) -> &VTreeManager {
-        &self.vtree
+    fn vtree_manager(&self) -> Ref<'_, VTreeManager> {
+        self.vtree.manager()
+    }
+
+    fn set_vtree(&'a self, vtree: VTree) {
+        self.vtree.relocate(self, vtree, |stale| {
+            self.bdd_tbl.borrow_mut().retain(|bdd| !stale.contains(bdd));
+            self.sdd_tbl.borrow_mut().retain(|or| !stale.contains(or));
+            self.app_cache.borrow_mut().clear();
+            *self.ite_cache.borrow_mut() = AllIteTable::default();
+        });
+    }
+
+    #[inline]
+    fn resolve(&'a self, ptr: SddPtr<'a>) -> SddPtr<'a> {
+        self.vtree.resolve(ptr)
     }
 
     #[inline]
@@ -93,7 +110,7 @@ impl<'a> SddBuilder<'a> for CompressionSddBuilder<'a> {
             // see if we can compress i
             let mut j = i + 1;
             while j < node.len() {
-                if self.eq(node[i].sub(), node[j].sub()) {
+                if self.sdd_eq(node[i].sub(), node[j].sub()) {
                     // compress j into i and remove j from the node list
                     node[i] = SddAnd::new(self.or(node[i].prime(), node[j].prime()), node[i].sub());
                     node.swap_remove(j);
@@ -151,13 +168,12 @@ impl<'a> SddBuilder<'a> for CompressionSddBuilder<'a> {
 
 impl<'a> CompressionSddBuilder<'a> {
     pub fn new(vtree: VTree) -> CompressionSddBuilder<'a> {
-        let vtree_man = VTreeManager::new(vtree);
         CompressionSddBuilder {
             ite_cache: RefCell::new(AllIteTable::default()),
             app_cache: RefCell::new(HashMap::new()),
             bdd_tbl: RefCell::new(BackedRobinhoodTable::new()),
             sdd_tbl: RefCell::new(BackedRobinhoodTable::new()),
-            vtree: vtree_man,
+            vtree: DynamicVTree::new(vtree),
             should_compress: true,
             num_recursive_calls: RefCell::new(0),
             num_compressions: RefCell::new(0),
@@ -474,3 +490,117 @@ fn sdd_wmc1() {
         wmc_res
     );
 }
+
+#[test]
+fn sdd_pointers_survive_vtree_change() {
+    use crate::repr::{Cnf, VarLabel};
+
+    let vars: Vec<VarLabel> = (0..4).map(VarLabel::new).collect();
+    let builder = CompressionSddBuilder::new(VTree::right_linear(&vars));
+    let cnf = Cnf::from_string("(0 || 2) && (1 || -3) && (-0 || 3)").unwrap();
+    let f = builder.compile_cnf(&cnf);
+
+    // (x0, (x1, (x2, x3))) becomes ((x0, x1), (x2, x3))
+    assert!(builder.rotate_left(VTreeIndex::new(1)));
+
+    // the stale pointer still denotes the same function
+    let g = builder.compile_cnf(&cnf);
+    assert!(builder.eq(f, g));
+    assert_eq!(builder.resolve(f), g);
+    assert!(builder.resolve(f).is_canonical());
+
+    // and stale pointers can be used in further operations
+    let x0 = builder.var(VarLabel::new(0), true);
+    let h = builder.and(f, x0);
+    assert_eq!(h, builder.and(g, x0));
+}
+
+#[test]
+fn sdd_stale_pointers_resolve_at_every_entry_point() {
+    use crate::repr::{create_semantic_hash_map, Cnf, VarLabel};
+
+    let vars: Vec<VarLabel> = (0..4).map(VarLabel::new).collect();
+    let builder = CompressionSddBuilder::new(VTree::right_linear(&vars));
+    let cnf = Cnf::from_string("(0 || 2) && (1 || -3) && (-0 || 3)").unwrap();
+    let f = builder.compile_cnf(&cnf);
+    let weights = create_semantic_hash_map::<{ crate::constants::primes::U32_SMALL }>(4);
+    let count = f.unsmoothed_wmc(&weights);
+
+    assert!(builder.rotate_left(VTreeIndex::new(1)));
+    let g = builder.compile_cnf(&cnf);
+
+    assert_eq!(builder.vtree_index(f), builder.vtree_index(g));
+    let (x0, x1) = (
+        builder.var(VarLabel::new(0), true),
+        builder.var(VarLabel::new(1), true),
+    );
+    assert_eq!(builder.ite(f, x0, x1), builder.ite(g, x0, x1));
+    assert_eq!(builder.ite(x0, f, x1), builder.ite(x0, g, x1));
+    // counting reads only the structure of a node, which stays allocated
+    assert_eq!(f.unsmoothed_wmc(&weights), count);
+    assert_eq!(g.unsmoothed_wmc(&weights), count);
+}
+
+#[test]
+fn sdd_vtree_change_only_rewrites_affected_nodes() {
+    use crate::repr::VarLabel;
+
+    let vars: Vec<VarLabel> = (0..4).map(VarLabel::new).collect();
+    let builder = CompressionSddBuilder::new(VTree::right_linear(&vars));
+    let (x0, x1, x2, x3) = (
+        builder.var(VarLabel::new(0), true),
+        builder.var(VarLabel::new(1), true),
+        builder.var(VarLabel::new(2), true),
+        builder.var(VarLabel::new(3), true),
+    );
+    let inner = builder.or(x2, x3);
+    let outer = builder.and(builder.or(x0, x1), inner);
+
+    // (x0, (x1, (x2, x3))) becomes ((x0, x1), (x2, x3)), which leaves the
+    // subtree (x2, x3) alone
+    assert!(builder.rotate_left(VTreeIndex::new(1)));
+
+    assert_eq!(builder.resolve(inner), inner);
+    assert_eq!(builder.or(x2, x3), inner);
+    let moved = builder.resolve(outer);
+    assert_ne!(moved, outer);
+    assert_eq!(moved, builder.and(builder.or(x0, x1), inner));
+    // the replaced node is no longer in the unique tables
+    assert!(builder
+        .node_iter()
+        .iter()
+        .all(|n| *n != outer && *n != outer.neg()));
+}
+
+#[test]
+#[should_panic(expected = "same variables")]
+fn sdd_set_vtree_rejects_other_variables() {
+    use crate::repr::VarLabel;
+
+    let vars: Vec<VarLabel> = (0..4).map(VarLabel::new).collect();
+    let builder = CompressionSddBuilder::new(VTree::right_linear(&vars));
+    builder.set_vtree(VTree::right_linear(&vars[..3]));
+}
+
+#[test]
+fn sdd_minimize_rejected_candidates_leave_no_nodes() {
+    use crate::builder::sdd::num_shared_nodes;
+    use crate::repr::VarLabel;
+
+    let vars: Vec<VarLabel> = (0..4).map(VarLabel::new).collect();
+    let builder = CompressionSddBuilder::new(VTree::right_linear(&vars));
+    let f = vars.iter().fold(SddPtr::true_ptr(), |acc, v| {
+        builder.and(acc, builder.var(*v, true))
+    });
+    let nodes = builder.node_iter();
+
+    // a conjunction of literals is already smallest on a right-linear vtree,
+    // so every candidate is scored and rejected
+    assert_eq!(builder.minimize(&[f], 3), num_shared_nodes(&[f]));
+    assert_eq!(
+        builder.vtree_manager().vtree_root(),
+        &VTree::right_linear(&vars)
+    );
+    assert_eq!(builder.node_iter(), nodes);
+    assert_eq!(builder.resolve(f), f);
+}
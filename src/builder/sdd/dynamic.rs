@@ -0,0 +1,326 @@
+//! Support for changing the vtree of an SDD builder while it has live nodes,
+//! and a greedy vtree minimization search that uses it to apply the moves it
+//! keeps
+//!
+//! A vtree change only rewrites the nodes it has to. A node normalized for a
+//! vtree node whose split (the variables of its left and right children)
+//! survives the change keeps its elements; it is only re-hash-consed if its
+//! vtree index moved or one of its children was rewritten. Nodes normalized
+//! for a vtree node whose split does not survive (the nodes a rotation or swap
+//! restructures) are rebuilt by conjoining and disjoining their elements.
+//! Every other node is left in place, so pointers to it stay pointer-equal.
+//!
+//! Rewritten nodes are removed from the builder's unique tables, and record
+//! the node that replaced them so that [`SddBuilder::resolve`] can map stale
+//! pointers forward. They stay allocated, as every node does for the lifetime
+//! of its builder, so stale pointers remain safe to read. Until a builder's
+//! vtree first changes there is nothing to resolve, and resolving a pointer
+//! costs one flag check.
+
+use crate::{
+    builder::sdd::{CompressionSddBuilder, SddBuilder},
+    repr::{BinarySDD, DDNNFPtr, SddAnd, SddOr, SddPtr, VTree, VTreeIndex, VTreeManager},
+    util::btree::BTree,
+};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::{HashMap, HashSet},
+};
+
+/// the address of the node `ptr` points to, if it points to one
+fn node_address(ptr: SddPtr) -> Option<usize> {
+    match ptr {
+        SddPtr::BDD(bdd) | SddPtr::ComplBDD(bdd) => Some(bdd as *const BinarySDD as usize),
+        SddPtr::Reg(or) | SddPtr::Compl(or) => Some(or as *const SddOr as usize),
+        SddPtr::PtrTrue | SddPtr::PtrFalse | SddPtr::Var(_, _) => None,
+    }
+}
+
+/// The nodes a vtree change rewrites, which builders remove from their
+/// unique tables
+pub(crate) struct StaleNodes(HashSet<usize>);
+
+impl StaleNodes {
+    pub(crate) fn contains<T>(&self, node: &T) -> bool {
+        self.0.contains(&(node as *const T as usize))
+    }
+}
+
+/// The vtree of a builder, and where the nodes rewritten by past vtree
+/// changes were moved to
+pub(crate) struct DynamicVTree<'a> {
+    manager: RefCell<VTreeManager>,
+    relocations: RefCell<HashMap<usize, SddPtr<'a>>>,
+    /// false until a vtree change rewrites a node, so that builders whose
+    /// vtree never changes skip looking pointers up in `relocations`
+    relocated: Cell<bool>,
+}
+
+impl<'a> DynamicVTree<'a> {
+    pub(crate) fn new(vtree: VTree) -> DynamicVTree<'a> {
+        DynamicVTree {
+            manager: RefCell::new(VTreeManager::new(vtree)),
+            relocations: RefCell::new(HashMap::new()),
+            relocated: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn manager(&self) -> Ref<'_, VTreeManager> {
+        self.manager.borrow()
+    }
+
+    /// `SddBuilder::resolve`
+    #[inline]
+    pub(crate) fn resolve(&self, ptr: SddPtr<'a>) -> SddPtr<'a> {
+        if self.relocated.get() {
+            self.resolve_relocated(ptr)
+        } else {
+            ptr
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn resolve_relocated(&self, ptr: SddPtr<'a>) -> SddPtr<'a> {
+        let node = if ptr.is_neg() { ptr.neg() } else { ptr };
+        let addr = match node_address(node) {
+            Some(addr) => addr,
+            None => return ptr,
+        };
+        let target = match self.relocations.borrow().get(&addr) {
+            Some(target) => *target,
+            None => return ptr,
+        };
+        let resolved = self.resolve(target);
+        if resolved != target {
+            // shortcut chains of relocations
+            self.relocations.borrow_mut().insert(addr, resolved);
+        }
+        if ptr.is_neg() {
+            resolved.neg()
+        } else {
+            resolved
+        }
+    }
+
+    /// `SddBuilder::set_vtree`: switches `builder` to `vtree`, rewriting the
+    /// nodes that are not normalized for it. `remove` must remove the stale
+    /// nodes from the builder's unique tables and clear its caches; it is
+    /// called before any node is rebuilt.
+    ///
+    /// Panics if `vtree` does not have the same variables as the current one.
+    pub(crate) fn relocate<B: SddBuilder<'a>>(
+        &self,
+        builder: &'a B,
+        vtree: VTree,
+        remove: impl FnOnce(&StaleNodes),
+    ) {
+        let new_manager = VTreeManager::new(vtree);
+        let index_map = {
+            let old_manager = self.manager.borrow();
+            assert_eq!(
+                old_manager.vtree_root().all_vars(),
+                new_manager.vtree_root().all_vars(),
+                "a builder's vtree can only be replaced by one over the same variables"
+            );
+            surviving_splits(&old_manager, &new_manager)
+        };
+
+        let mut affected = HashMap::new();
+        let live = builder.node_iter();
+        for node in live.iter() {
+            is_affected(*node, &index_map, &mut affected);
+        }
+        let stale = StaleNodes(
+            affected
+                .into_iter()
+                .filter_map(|(addr, affected)| affected.then_some(addr))
+                .collect(),
+        );
+
+        remove(&stale);
+        *self.manager.borrow_mut() = new_manager;
+
+        let mut moved = HashMap::new();
+        for node in live {
+            relocated(builder, node, &index_map, &stale, &mut moved);
+        }
+        if !moved.is_empty() {
+            self.relocated.set(true);
+            self.relocations.borrow_mut().extend(moved);
+        }
+    }
+}
+
+/// the variables of the left and right children of an internal vtree node
+fn split(vtree: &VTree) -> Option<(Vec<usize>, Vec<usize>)> {
+    let sorted = |t: &VTree| {
+        let mut vars: Vec<usize> = t.all_vars().into_iter().collect();
+        vars.sort_unstable();
+        vars
+    };
+    match vtree {
+        BTree::Node((), l, r) => Some((sorted(l), sorted(r))),
+        BTree::Leaf(_) => None,
+    }
+}
+
+/// For every internal node of `old`, the index of the node of `new` with the
+/// same split, if there is one
+fn surviving_splits(old: &VTreeManager, new: &VTreeManager) -> Vec<Option<VTreeIndex>> {
+    let splits = |m: &VTreeManager| {
+        (0..m.vtree_root().num_nodes())
+            .map(|idx| split(m.vtree(VTreeIndex::new(idx))))
+            .collect::<Vec<_>>()
+    };
+    let new_index: HashMap<(Vec<usize>, Vec<usize>), VTreeIndex> = splits(new)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, split)| Some((split?, VTreeIndex::new(idx))))
+        .collect();
+    splits(old)
+        .into_iter()
+        .map(|split| new_index.get(&split?).copied())
+        .collect()
+}
+
+/// true if the node `ptr` points to must be rewritten: its vtree index does
+/// not map to itself, or one of its descendants must be rewritten
+fn is_affected(
+    ptr: SddPtr,
+    index_map: &[Option<VTreeIndex>],
+    memo: &mut HashMap<usize, bool>,
+) -> bool {
+    let addr = match node_address(ptr) {
+        Some(addr) => addr,
+        None => return false,
+    };
+    if let Some(affected) = memo.get(&addr) {
+        return *affected;
+    }
+    let affected = index_map[ptr.vtree().value()] != Some(ptr.vtree())
+        || ptr.node_iter().any(|and| {
+            is_affected(and.prime(), index_map, memo) || is_affected(and.sub(), index_map, memo)
+        });
+    memo.insert(addr, affected);
+    affected
+}
+
+/// the node for the current vtree that `ptr` is rewritten to, rewriting its
+/// descendants first
+fn relocated<'a, B: SddBuilder<'a>>(
+    builder: &'a B,
+    ptr: SddPtr<'a>,
+    index_map: &[Option<VTreeIndex>],
+    stale: &StaleNodes,
+    moved: &mut HashMap<usize, SddPtr<'a>>,
+) -> SddPtr<'a> {
+    if ptr.is_neg() {
+        return relocated(builder, ptr.neg(), index_map, stale, moved).neg();
+    }
+    let addr = match node_address(ptr) {
+        Some(addr) if stale.0.contains(&addr) => addr,
+        _ => return ptr,
+    };
+    if let Some(r) = moved.get(&addr) {
+        return *r;
+    }
+    let mut child = |c| relocated(builder, c, index_map, stale, moved);
+    let r = match (index_map[ptr.vtree().value()], ptr) {
+        // the split survives: keep the elements, with their children rewritten
+        (Some(idx), SddPtr::BDD(bdd)) => builder.unique_bdd(BinarySDD::new(
+            bdd.label(),
+            child(bdd.low()),
+            child(bdd.high()),
+            idx,
+        )),
+        (Some(idx), _) => {
+            let elements = ptr
+                .node_iter()
+                .map(|and| SddAnd::new(child(and.prime()), child(and.sub())))
+                .collect();
+            builder.unique_or(elements, idx)
+        }
+        // the split was restructured: normalize the node from scratch
+        (None, _) => builder.rebuild(ptr, child),
+    };
+    moved.insert(addr, r);
+    r
+}
+
+/// The number of distinct decision nodes reachable from `roots`, treating a
+/// node and its complement as the same node
+pub fn num_shared_nodes(roots: &[SddPtr]) -> usize {
+    fn visit(ptr: SddPtr, seen: &mut HashSet<usize>) {
+        let addr = match node_address(ptr) {
+            Some(addr) => addr,
+            None => return,
+        };
+        if !seen.insert(addr) {
+            return;
+        }
+        for and in ptr.node_iter() {
+            visit(and.prime(), seen);
+            visit(and.sub(), seen);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for root in roots {
+        visit(*root, &mut seen);
+    }
+    seen.len()
+}
+
+/// The number of nodes `roots` have when compiled for `vtree`, counted in a
+/// scratch builder that is dropped afterwards, so that scoring a vtree
+/// allocates nothing in the builder `roots` belong to
+#[allow(clippy::mutable_key_type)]
+fn size_for_vtree(roots: &[SddPtr], vtree: VTree) -> usize {
+    let scratch = CompressionSddBuilder::new(vtree);
+    let mut cache = HashMap::new();
+    let roots: Vec<SddPtr> = roots
+        .iter()
+        .map(|r| scratch.import(*r, &mut cache))
+        .collect();
+    num_shared_nodes(&roots)
+}
+
+/// `SddBuilder::minimize`: repeatedly scores a left rotation, right rotation
+/// and child swap at every vtree node of `builder`, applying the first that
+/// lowers the number of nodes in `roots`, until no operation helps or
+/// `max_passes` passes are done. Candidates are scored in scratch builders,
+/// so only the moves that are kept rewrite nodes of `builder`.
+pub(crate) fn greedy_minimize<'a, B: SddBuilder<'a>>(
+    builder: &'a B,
+    roots: &[SddPtr<'a>],
+    max_passes: usize,
+) -> usize {
+    let resolved = || -> Vec<SddPtr<'a>> { roots.iter().map(|r| builder.resolve(*r)).collect() };
+    let ops: [fn(&VTree, VTreeIndex) -> Option<VTree>; 3] =
+        [VTree::rotate_left, VTree::rotate_right, VTree::swap];
+    let mut best_size = size_for_vtree(&resolved(), builder.vtree_manager().vtree_root().clone());
+    for _ in 0..max_passes {
+        let mut improved = false;
+        let num_nodes = builder.vtree_manager().vtree_root().num_nodes();
+        for idx in (0..num_nodes).map(VTreeIndex::new) {
+            for op in ops {
+                let candidate = match op(builder.vtree_manager().vtree_root(), idx) {
+                    Some(candidate) => candidate,
+                    None => continue,
+                };
+                let candidate_size = size_for_vtree(&resolved(), candidate.clone());
+                if candidate_size < best_size {
+                    builder.set_vtree(candidate);
+                    best_size = candidate_size;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    num_shared_nodes(&resolved())
+}
@@ -1,7 +1,9 @@
 mod builder;
 mod compression;
+mod dynamic;
 mod semantic;
 
 pub use self::builder::*;
 pub use self::compression::*;
+pub use self::dynamic::*;
 pub use self::semantic::*;
@@ -2,7 +2,7 @@ use crate::{
     backing_store::BackedRobinhoodTable,
     builder::{
         cache::Ite,
-        sdd::{SddBuilder, SddBuilderStats},
+        sdd::{dynamic::DynamicVTree, SddBuilder, SddBuilderStats},
     },
     repr::{
         create_semantic_hash_map, BinarySDD, DDNNFPtr, SddAnd, SddOr, SddPtr, VTree, VTreeIndex,
@@ -12,13 +12,13 @@ use crate::{
 };
 use rustc_hash::FxHasher;
 use std::{
-    cell::RefCell,
+    cell::{Ref, RefCell},
     collections::{HashMap, HashSet},
     hash::{Hash, Hasher},
 };
 
 pub struct SemanticSddBuilder<'a, const P: u128> {
-    vtree: VTreeManager,
+    vtree: DynamicVTree<'a>,
     should_compress: bool,
     // tables
     bdd_tbl: RefCell<BackedRobinhoodTable<'a, BinarySDD<'a>>>,
@@ -36,8 +36,23 @@ pub struct SemanticSddBuilder<'a, const P: u128> {
 
 impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
     #[inline]
-    fn vtree_manager(&self) -> &VTreeManager {
-        &self.vtree
+    fn vtree_manager(&self) -> Ref<'_, VTreeManager> {
+        self.vtree.manager()
+    }
+
+    fn set_vtree(&'a self, vtree: VTree) {
+        // nodes are found by their semantic hash, which does not depend on the
+        // vtree, so stale nodes must be removed before anything is rebuilt
+        self.vtree.relocate(self, vtree, |stale| {
+            self.bdd_tbl.borrow_mut().retain(|bdd| !stale.contains(bdd));
+            self.sdd_tbl.borrow_mut().retain(|or| !stale.contains(or));
+            self.app_cache.borrow_mut().clear();
+        });
+    }
+
+    #[inline]
+    fn resolve(&'a self, ptr: SddPtr<'a>) -> SddPtr<'a> {
+        self.vtree.resolve(ptr)
     }
 
     #[inline]
@@ -54,7 +69,7 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
     }
 
     fn app_cache_get(&self, and: &SddAnd<'a>) -> Option<SddPtr<'a>> {
-        let h = and.semantic_hash(&self.vtree, &self.map);
+        let h = and.semantic_hash(&self.vtree_manager(), &self.map);
         match h.value() {
             0 => Some(SddPtr::PtrFalse),
             1 => Some(SddPtr::PtrTrue),
@@ -63,7 +78,7 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
     }
 
     fn app_cache_insert(&self, and: SddAnd<'a>, ptr: SddPtr<'a>) {
-        let h = and.semantic_hash(&self.vtree, &self.map);
+        let h = and.semantic_hash(&self.vtree_manager(), &self.map);
         if h.value() > 1 {
             self.app_cache.borrow_mut().insert(h.value(), ptr);
         }
@@ -83,7 +98,7 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
 
     fn get_or_insert_bdd(&'a self, bdd: BinarySDD<'a>) -> SddPtr<'a> {
         *self.num_get_or_insert_bdd.borrow_mut() += 1;
-        let semantic_hash = bdd.semantic_hash(&self.vtree, &self.map);
+        let semantic_hash = bdd.semantic_hash(&self.vtree_manager(), &self.map);
 
         if let Some(sdd) = self.check_cached_hash_and_neg(semantic_hash) {
             return sdd;
@@ -98,7 +113,7 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
 
     fn get_or_insert_sdd(&'a self, or: SddOr<'a>) -> SddPtr<'a> {
         *self.num_get_or_insert_sdd.borrow_mut() += 1;
-        let semantic_hash = or.semantic_hash(&self.vtree, &self.map);
+        let semantic_hash = or.semantic_hash(&self.vtree_manager(), &self.map);
         if let Some(sdd) = self.check_cached_hash_and_neg(semantic_hash) {
             return sdd;
         }
@@ -111,8 +126,8 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
     }
 
     fn sdd_eq(&'a self, a: SddPtr<'a>, b: SddPtr<'a>) -> bool {
-        let h1 = a.cached_semantic_hash(&self.vtree, &self.map);
-        let h2 = b.cached_semantic_hash(&self.vtree, &self.map);
+        let h1 = a.cached_semantic_hash(&self.vtree_manager(), &self.map);
+        let h2 = b.cached_semantic_hash(&self.vtree_manager(), &self.map);
         h1 == h2
     }
 
@@ -126,7 +141,7 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
         let mut s: HashSet<u128> = HashSet::new();
         let mut num_collisions = 0;
         for n in self.node_iter() {
-            let h = n.cached_semantic_hash(&self.vtree, &self.map);
+            let h = n.cached_semantic_hash(&self.vtree_manager(), &self.map);
             if s.contains(&h.value()) {
                 num_collisions += 1;
             }
@@ -151,11 +166,10 @@ impl<'a, const P: u128> SddBuilder<'a> for SemanticSddBuilder<'a, P> {
 
 impl<'a, const P: u128> SemanticSddBuilder<'a, P> {
     pub fn new(vtree: VTree) -> Self {
-        let vtree_man = VTreeManager::new(vtree.clone());
         let map = create_semantic_hash_map(vtree.num_vars());
         SemanticSddBuilder {
             should_compress: false,
-            vtree: vtree_man,
+            vtree: DynamicVTree::new(vtree),
             // ite_cache: RefCell::new(AllIteTable::new()),
             app_cache: RefCell::new(HashMap::new()),
             bdd_tbl: RefCell::new(BackedRobinhoodTable::new()),
@@ -167,8 +181,9 @@ impl<'a, const P: u128> SemanticSddBuilder<'a, P> {
         }
     }
 
-    pub fn cached_semantic_hash(&self, sdd: SddPtr) -> FiniteField<P> {
-        sdd.cached_semantic_hash(&self.vtree, &self.map)
+    pub fn cached_semantic_hash(&'a self, sdd: SddPtr<'a>) -> FiniteField<P> {
+        self.resolve(sdd)
+            .cached_semantic_hash(&self.vtree_manager(), &self.map)
     }
 
     pub fn map(&self) -> &WmcParams<FiniteField<P>> {
@@ -177,7 +192,7 @@ impl<'a, const P: u128> SemanticSddBuilder<'a, P> {
 
     fn hash_bdd(&self, elem: &BinarySDD) -> u64 {
         let mut hasher = FxHasher::default();
-        elem.semantic_hash(&self.vtree, &self.map)
+        elem.semantic_hash(&self.vtree_manager(), &self.map)
             .value()
             .hash(&mut hasher);
         hasher.finish()
@@ -185,7 +200,7 @@ impl<'a, const P: u128> SemanticSddBuilder<'a, P> {
 
     fn hash_sdd(&self, elem: &SddOr) -> u64 {
         let mut hasher = FxHasher::default();
-        elem.semantic_hash(&self.vtree, &self.map)
+        elem.semantic_hash(&self.vtree_manager(), &self.map)
             .value()
             .hash(&mut hasher);
         hasher.finish()
@@ -258,8 +273,8 @@ fn prob_equiv_sdd_demorgan() {
     let map: WmcParams<FiniteField<{ primes::U32_SMALL }>> =
         create_semantic_hash_map(builder.num_vars());
 
-    let sh1 = res.cached_semantic_hash(builder.vtree_manager(), &map);
-    let sh2 = expected.cached_semantic_hash(builder.vtree_manager(), &map);
+    let sh1 = res.cached_semantic_hash(&builder.vtree_manager(), &map);
+    let sh2 = expected.cached_semantic_hash(&builder.vtree_manager(), &map);
 
     assert!(sh1 == sh2, "Not eq:\nGot: {:?}\nExpected: {:?}", sh1, sh2);
 }
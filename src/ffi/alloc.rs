@@ -0,0 +1,38 @@
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    ptr,
+};
+
+/// hands `s` to the host, which must release it with `string_free`
+pub(super) fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s.replace('\0', "")).unwrap().into_raw()
+}
+
+/// reads a host string, replacing invalid UTF-8
+pub(super) unsafe fn from_c_string(s: *const c_char) -> String {
+    String::from_utf8_lossy(CStr::from_ptr(s).to_bytes()).into_owned()
+}
+
+/// hands `bytes` to the host, storing their length in `len`; the host must
+/// release them with `bytes_free`
+pub(super) unsafe fn into_c_bytes(bytes: Vec<u8>, len: *mut usize) -> *mut u8 {
+    *len = bytes.len();
+    Box::into_raw(bytes.into_boxed_slice()).cast()
+}
+
+/// releases a string returned by this library
+#[no_mangle]
+unsafe extern "C" fn string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// releases a byte buffer of length `len` returned by this library
+#[no_mangle]
+unsafe extern "C" fn bytes_free(bytes: *mut u8, len: usize) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)));
+    }
+}
@@ -1,10 +1,15 @@
+use super::{
+    alloc::{from_c_string, into_c_bytes, into_c_string},
+    error::{clear_last_parse_error, into_c_result, json_error, set_last_parse_error},
+};
 use crate::{
     builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder},
     constants::primes,
-    repr::{self, Cnf, DDNNFPtr, VarLabel, VarOrder, WmcParams},
+    repr::{self, Cnf, DDNNFPtr, ParseError, VarLabel, VarOrder, WmcParams},
+    serialize::{self, BDDSerializer},
     util::semirings::{Complex, FiniteField, RealSemiring, Semiring},
 };
-use std::{collections::HashMap, ffi::CStr, os::raw::c_char};
+use std::{collections::HashMap, ffi::CStr, os::raw::c_char, ptr};
 
 pub(super) type BddPtr = repr::BddPtr<'static>;
 
@@ -13,13 +18,22 @@ extern "C" fn var_order_linear(num_vars: usize) -> *const VarOrder {
     Box::into_raw(Box::new(VarOrder::linear_order(num_vars)))
 }
 
+/// parses a DIMACS string, returning NULL on malformed input (see
+/// `last_parse_error_message`)
 #[no_mangle]
 unsafe extern "C" fn cnf_from_dimacs(dimacs_str: *const c_char) -> *const Cnf {
     let cstr = CStr::from_ptr(dimacs_str);
 
-    Box::into_raw(Box::new(Cnf::from_dimacs(&String::from_utf8_lossy(
-        cstr.to_bytes(),
-    ))))
+    match Cnf::from_dimacs(&String::from_utf8_lossy(cstr.to_bytes())) {
+        Ok(cnf) => {
+            clear_last_parse_error();
+            Box::into_raw(Box::new(cnf))
+        }
+        Err(err) => {
+            set_last_parse_error(err);
+            ptr::null()
+        }
+    }
 }
 
 // directly inspired by https://users.rust-lang.org/t/how-to-deal-with-lifetime-when-need-to-expose-through-ffi/39583
@@ -39,21 +53,35 @@ unsafe fn robdd_builder_from_ptr<'_0>(
     &mut *(ptr.cast())
 }
 
+/// a builder for `order`, which is copied: the caller still frees it with
+/// `var_order_free`
 #[no_mangle]
-unsafe extern "C" fn robdd_builder_all_table(order: *mut VarOrder) -> *mut RsddBddBuilder {
+unsafe extern "C" fn robdd_builder_all_table(order: *const VarOrder) -> *mut RsddBddBuilder {
     if order.is_null() {
         eprintln!("Fatal error, got NULL `order` pointer");
         std::process::abort();
     }
 
-    let order = *Box::from_raw(order);
+    let order = (*order).clone();
     Box::into_raw(Box::new(RobddBuilder::<AllIteTable<BddPtr>>::new(order))).cast()
 }
 
+/// frees a builder; every BDD it built dangles afterwards
+#[no_mangle]
+unsafe extern "C" fn robdd_builder_free(builder: *mut RsddBddBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(
+            builder.cast::<RobddBuilder<'static, AllIteTable<BddPtr>>>(),
+        ));
+    }
+}
+
+/// compiles `cnf`, which is only borrowed: the caller still frees it with
+/// `cnf_free`
 #[no_mangle]
 unsafe extern "C" fn robdd_builder_compile_cnf(
     builder: *mut RsddBddBuilder,
-    cnf: *mut Cnf,
+    cnf: *const Cnf,
 ) -> *mut BddPtr {
     if cnf.is_null() {
         eprintln!("Fatal error, got NULL `cnf` pointer");
@@ -61,11 +89,16 @@ unsafe extern "C" fn robdd_builder_compile_cnf(
     }
 
     let builder = robdd_builder_from_ptr(builder);
-    let cnf = *Box::from_raw(cnf);
-    let ptr = builder.compile_cnf(&cnf);
+    let ptr = builder.compile_cnf(&*cnf);
     Box::into_raw(Box::new(ptr))
 }
 
+/// true if `label` is in the variable order of `builder`; operations on
+/// other labels panic, which must not unwind into the host
+fn in_order(builder: &RobddBuilder<'static, AllIteTable<BddPtr>>, label: u64) -> bool {
+    (label as usize) < builder.num_vars()
+}
+
 #[no_mangle]
 unsafe extern "C" fn robdd_model_count(builder: *mut RsddBddBuilder, bdd: *mut BddPtr) -> u64 {
     let builder = robdd_builder_from_ptr(builder);
@@ -97,6 +130,7 @@ unsafe extern "C" fn bdd_new_label(builder: *mut RsddBddBuilder) -> u64 {
     builder.new_label().value()
 }
 
+/// NULL if `label` is not in the variable order
 #[no_mangle]
 unsafe extern "C" fn bdd_var(
     builder: *mut RsddBddBuilder,
@@ -104,6 +138,9 @@ unsafe extern "C" fn bdd_var(
     polarity: bool,
 ) -> *mut BddPtr {
     let builder = robdd_builder_from_ptr(builder);
+    if !in_order(builder, label) {
+        return ptr::null_mut();
+    }
     let ptr = builder.var(VarLabel::new(label), polarity);
     Box::into_raw(Box::new(ptr))
 }
@@ -156,6 +193,99 @@ unsafe extern "C" fn bdd_negate(builder: *mut RsddBddBuilder, bdd: *mut BddPtr)
     Box::into_raw(Box::new(negate))
 }
 
+#[no_mangle]
+unsafe extern "C" fn bdd_iff(
+    builder: *mut RsddBddBuilder,
+    left: *mut BddPtr,
+    right: *mut BddPtr,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    let iff = builder.iff(*left, *right);
+    Box::into_raw(Box::new(iff))
+}
+
+#[no_mangle]
+unsafe extern "C" fn bdd_xor(
+    builder: *mut RsddBddBuilder,
+    left: *mut BddPtr,
+    right: *mut BddPtr,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    let xor = builder.xor(*left, *right);
+    Box::into_raw(Box::new(xor))
+}
+
+/// NULL if `label` is not in the variable order
+#[no_mangle]
+unsafe extern "C" fn bdd_exists(
+    builder: *mut RsddBddBuilder,
+    bdd: *mut BddPtr,
+    label: u64,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    if !in_order(builder, label) {
+        return ptr::null_mut();
+    }
+    let exists = builder.exists(*bdd, VarLabel::new(label));
+    Box::into_raw(Box::new(exists))
+}
+
+/// NULL if `label` is not in the variable order
+#[no_mangle]
+unsafe extern "C" fn bdd_forall(
+    builder: *mut RsddBddBuilder,
+    bdd: *mut BddPtr,
+    label: u64,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    if !in_order(builder, label) {
+        return ptr::null_mut();
+    }
+    let forall = builder.forall(*bdd, VarLabel::new(label));
+    Box::into_raw(Box::new(forall))
+}
+
+/// NULL if `label` is not in the variable order
+#[no_mangle]
+unsafe extern "C" fn bdd_condition(
+    builder: *mut RsddBddBuilder,
+    bdd: *mut BddPtr,
+    label: u64,
+    value: bool,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    if !in_order(builder, label) {
+        return ptr::null_mut();
+    }
+    let conditioned = builder.condition(*bdd, VarLabel::new(label), value);
+    Box::into_raw(Box::new(conditioned))
+}
+
+/// substitutes `g` for variable `label` in `f`; NULL if `label` is not in
+/// the variable order
+#[no_mangle]
+unsafe extern "C" fn bdd_compose(
+    builder: *mut RsddBddBuilder,
+    f: *mut BddPtr,
+    label: u64,
+    g: *mut BddPtr,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    if !in_order(builder, label) {
+        return ptr::null_mut();
+    }
+    let composed = builder.compose(*f, VarLabel::new(label), *g);
+    Box::into_raw(Box::new(composed))
+}
+
+/// frees the handle `bdd`; the node itself lives as long as its builder
+#[no_mangle]
+unsafe extern "C" fn bdd_free(bdd: *mut BddPtr) {
+    if !bdd.is_null() {
+        drop(Box::from_raw(bdd));
+    }
+}
+
 #[no_mangle]
 unsafe extern "C" fn bdd_is_true(bdd: *mut BddPtr) -> bool {
     (*bdd).is_true()
@@ -213,12 +343,50 @@ unsafe extern "C" fn bdd_high(bdd: *mut BddPtr) -> *mut BddPtr {
     Box::into_raw(Box::new((*bdd).high()))
 }
 
+/// the caller frees the result with `string_free`
+#[no_mangle]
+unsafe extern "C" fn print_bdd(bdd: *mut BddPtr) -> *mut c_char {
+    into_c_string((*bdd).print_bdd())
+}
+
+/// serializes `bdd` as JSON (see `BDDSerializer`); the caller frees the
+/// result with `string_free`
+#[no_mangle]
+unsafe extern "C" fn bdd_to_json(bdd: *mut BddPtr) -> *mut c_char {
+    into_c_string(serde_json::to_string(&BDDSerializer::from_bdd(*bdd)).unwrap())
+}
+
+/// rebuilds a BDD serialized by `bdd_to_json` in `builder`, returning NULL
+/// on malformed input (see `last_parse_error_message`)
+#[no_mangle]
+unsafe extern "C" fn bdd_from_json(
+    builder: *mut RsddBddBuilder,
+    json: *const c_char,
+) -> *mut BddPtr {
+    let builder = robdd_builder_from_ptr(builder);
+    let parsed = serde_json::from_str::<BDDSerializer>(&from_c_string(json))
+        .map_err(json_error)
+        .and_then(|ser| ser.try_into_builder(builder))
+        .and_then(|roots| match roots[..] {
+            [bdd] => Ok(bdd),
+            _ => Err(ParseError::unlocated(format!(
+                "expected one root, found {}",
+                roots.len()
+            ))),
+        });
+    into_c_result(parsed)
+}
+
+/// writes `bdd` in the binary diagram format (see `BinaryDiagram`), storing
+/// its length in `len`; the caller frees the result with `bytes_free`
 #[no_mangle]
-unsafe extern "C" fn print_bdd(bdd: *mut BddPtr) -> *const c_char {
-    let s = std::ffi::CString::new((*bdd).print_bdd()).unwrap();
-    let p = s.as_ptr();
-    std::mem::forget(s);
-    p
+unsafe extern "C" fn bdd_to_binary(
+    builder: *mut RsddBddBuilder,
+    bdd: *mut BddPtr,
+    len: *mut usize,
+) -> *mut u8 {
+    let builder = robdd_builder_from_ptr(builder);
+    into_c_bytes(serialize::bdd_to_binary(&[*bdd], builder.order()), len)
 }
 
 #[no_mangle]
@@ -236,3 +404,34 @@ unsafe extern "C" fn bdd_wmc(bdd: *mut BddPtr, wmc: *mut WmcParams<RealSemiring>
 unsafe extern "C" fn bdd_wmc_complex(bdd: *mut BddPtr, wmc: *mut WmcParams<Complex>) -> Complex {
     DDNNFPtr::unsmoothed_wmc(&(*bdd), &(*wmc))
 }
+
+#[test]
+fn ffi_bdd_borrows_its_inputs() {
+    use std::ffi::CString;
+
+    unsafe {
+        let order = var_order_linear(3);
+        let dimacs = CString::new("p cnf 3 2\n1 2 0\n-2 3 0\n").unwrap();
+        let cnf = cnf_from_dimacs(dimacs.as_ptr());
+        let builder = robdd_builder_all_table(order.cast_mut());
+        let bdd = robdd_builder_compile_cnf(builder, cnf);
+        // the builder copied the order and only borrowed the CNF
+        drop(Box::from_raw(order.cast_mut()));
+        drop(Box::from_raw(cnf.cast_mut()));
+        assert_eq!(robdd_model_count(builder, bdd), 4);
+
+        let x0 = bdd_var(builder, 0, true);
+        let conditioned = bdd_condition(builder, bdd, 0, true);
+        let conj = bdd_and(builder, bdd, x0);
+        assert_eq!(robdd_model_count(builder, conj), 3);
+        assert!(!bdd_is_const(conditioned));
+        assert!(bdd_var(builder, 3, true).is_null());
+        assert!(bdd_exists(builder, bdd, 7).is_null());
+        assert!(bdd_condition(builder, bdd, 3, false).is_null());
+
+        for ptr in [bdd, x0, conditioned, conj] {
+            bdd_free(ptr);
+        }
+        robdd_builder_free(builder);
+    }
+}
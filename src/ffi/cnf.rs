@@ -20,3 +20,10 @@ unsafe extern "C" fn cnf_new(clauses: *const Clause, len: usize) -> *mut Cnf {
 unsafe extern "C" fn cnf_min_fill_order(cnf: *mut Cnf) -> *mut VarOrder {
     Box::into_raw(Box::new((*cnf).min_fill_order()))
 }
+
+#[no_mangle]
+unsafe extern "C" fn cnf_free(cnf: *mut Cnf) {
+    if !cnf.is_null() {
+        drop(Box::from_raw(cnf));
+    }
+}
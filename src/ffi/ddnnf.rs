@@ -6,9 +6,11 @@ use crate::{
 
 type DDNNFBuilder = StandardDecisionNNFBuilder<'static>;
 
+/// a builder for `order`, which is copied: the caller still frees it with
+/// `var_order_free`
 #[no_mangle]
-unsafe extern "C" fn ddnnf_builder_new(order: *mut VarOrder) -> *mut DDNNFBuilder {
-    Box::into_raw(Box::new(DDNNFBuilder::new(*Box::from_raw(order))))
+unsafe extern "C" fn ddnnf_builder_new(order: *const VarOrder) -> *mut DDNNFBuilder {
+    Box::into_raw(Box::new(DDNNFBuilder::new((*order).clone())))
 }
 
 #[no_mangle]
@@ -18,3 +20,11 @@ unsafe extern "C" fn ddnnf_builder_compile_cnf_topdown(
 ) -> *mut BddPtr {
     Box::into_raw(Box::new((*builder).compile_cnf_topdown(&*cnf)))
 }
+
+/// frees a builder; every diagram it built dangles afterwards
+#[no_mangle]
+unsafe extern "C" fn ddnnf_builder_free(builder: *mut DDNNFBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
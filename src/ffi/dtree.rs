@@ -4,3 +4,10 @@ use crate::repr::{Cnf, DTree, VarOrder};
 unsafe extern "C" fn dtree_from_cnf(cnf: *const Cnf, elim_order: *const VarOrder) -> *mut DTree {
     Box::into_raw(Box::new(DTree::from_cnf(&*cnf, &*elim_order)))
 }
+
+#[no_mangle]
+unsafe extern "C" fn dtree_free(dtree: *mut DTree) {
+    if !dtree.is_null() {
+        drop(Box::from_raw(dtree));
+    }
+}
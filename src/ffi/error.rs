@@ -0,0 +1,63 @@
+use crate::repr::ParseError;
+use std::{cell::RefCell, ffi::CString, os::raw::c_char, ptr};
+
+// parsing functions return NULL on failure and stash the error here, in the
+// style of `errno`
+thread_local! {
+    static LAST_PARSE_ERROR: RefCell<Option<(ParseError, CString)>> = const { RefCell::new(None) };
+}
+
+pub(super) fn set_last_parse_error(err: ParseError) {
+    let message = CString::new(err.to_string().replace('\0', "")).unwrap();
+    LAST_PARSE_ERROR.with(|last| *last.borrow_mut() = Some((err, message)));
+}
+
+pub(super) fn clear_last_parse_error() {
+    LAST_PARSE_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// keeps the location serde_json found the error at
+pub(super) fn json_error(err: serde_json::Error) -> ParseError {
+    let suffix = format!(" at line {} column {}", err.line(), err.column());
+    let message = err.to_string();
+    let message = message.strip_suffix(&suffix).unwrap_or(&message);
+    ParseError::new(err.line(), err.column(), message)
+}
+
+/// boxes a parse result for the host: NULL on failure, recording the error
+pub(super) fn into_c_result<T>(result: Result<T, ParseError>) -> *mut T {
+    match result {
+        Ok(value) => {
+            clear_last_parse_error();
+            Box::into_raw(Box::new(value))
+        }
+        Err(err) => {
+            set_last_parse_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// the message of the last parse error on this thread, or NULL if the last
+/// parse succeeded. The string is valid until the next parse on this thread.
+#[no_mangle]
+pub(super) extern "C" fn last_parse_error_message() -> *const c_char {
+    LAST_PARSE_ERROR.with(|last| match &*last.borrow() {
+        Some((_, message)) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// the (1-indexed) line of the last parse error on this thread, or 0 if
+/// unknown
+#[no_mangle]
+pub(super) extern "C" fn last_parse_error_line() -> usize {
+    LAST_PARSE_ERROR.with(|last| last.borrow().as_ref().map_or(0, |(err, _)| err.line()))
+}
+
+/// the (1-indexed) column of the last parse error on this thread, or 0 if
+/// unknown
+#[no_mangle]
+pub(super) extern "C" fn last_parse_error_column() -> usize {
+    LAST_PARSE_ERROR.with(|last| last.borrow().as_ref().map_or(0, |(err, _)| err.column()))
+}
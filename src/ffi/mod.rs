@@ -1,7 +1,9 @@
+mod alloc;
 mod bdd;
 mod cnf;
 mod ddnnf;
 mod dtree;
+mod error;
 mod sdd;
 mod var;
 mod vtree;
@@ -1,27 +1,293 @@
+use super::{
+    alloc::{from_c_string, into_c_bytes, into_c_string},
+    error::{into_c_result, json_error},
+};
 use crate::{
-    builder::{sdd::CompressionSddBuilder, BottomUpBuilder},
-    repr::{self, Cnf, DDNNFPtr, VTree, WmcParams},
+    builder::{
+        sdd::{CompressionSddBuilder, SddBuilder as _},
+        BottomUpBuilder,
+    },
+    repr::{self, Cnf, DDNNFPtr, ParseError, VTree, VarLabel, WmcParams},
+    serialize::{self, SDDSerializer},
     util::semirings::RealSemiring,
 };
+use std::{os::raw::c_char, ptr};
 
 type SddBuilder = CompressionSddBuilder<'static>;
 type SddPtr = repr::SddPtr<'static>;
 
+/// a builder for `vtree`, which is copied: the caller still frees it with
+/// `vtree_free`
+#[no_mangle]
+unsafe extern "C" fn sdd_builder_new(vtree: *const VTree) -> *mut SddBuilder {
+    Box::into_raw(Box::new(CompressionSddBuilder::new((*vtree).clone())))
+}
+
+/// frees a builder; every SDD it built dangles afterwards
 #[no_mangle]
-unsafe extern "C" fn sdd_builder_new(vtree: *mut VTree) -> *mut SddBuilder {
-    let vtree = Box::from_raw(vtree);
-    Box::into_raw(Box::new(CompressionSddBuilder::new(*vtree)))
+unsafe extern "C" fn sdd_builder_free(builder: *mut SddBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
 }
 
+/// compiles `cnf`, which is only borrowed: the caller still frees it with
+/// `cnf_free`. NULL if `cnf` has a variable that is not in the vtree (see
+/// `last_parse_error_message`)
 #[no_mangle]
 unsafe extern "C" fn sdd_builder_compile_cnf(
     builder: *const SddBuilder,
     cnf: *const Cnf,
 ) -> *mut SddPtr {
-    Box::into_raw(Box::new((*builder).compile_cnf(&*cnf)))
+    let builder = &*builder;
+    let outside = (*cnf)
+        .clauses()
+        .iter()
+        .flatten()
+        .map(|lit| lit.label().value())
+        .find(|label| !in_vtree(builder, *label));
+    into_c_result(match outside {
+        Some(label) => Err(ParseError::unlocated(format!(
+            "variable {} is not in the vtree",
+            label
+        ))),
+        None => Ok(builder.compile_cnf(&*cnf)),
+    })
+}
+
+/// true if `label` is a leaf of the vtree of `builder`; operations on other
+/// labels panic, which must not unwind into the host
+fn in_vtree(builder: &SddBuilder, label: u64) -> bool {
+    let manager = builder.vtree_manager();
+    let label = VarLabel::new(label);
+    label.value_usize() < manager.vtree_root().num_vars()
+        && *manager.vtree(manager.var_index(label)) == VTree::new_leaf(label)
+}
+
+/// NULL if `label` is not in the vtree
+#[no_mangle]
+unsafe extern "C" fn sdd_var(
+    builder: *const SddBuilder,
+    label: u64,
+    polarity: bool,
+) -> *mut SddPtr {
+    if !in_vtree(&*builder, label) {
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*builder).var(VarLabel::new(label), polarity)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_true(builder: *const SddBuilder) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).true_ptr()))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_false(builder: *const SddBuilder) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).false_ptr()))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_and(
+    builder: *const SddBuilder,
+    left: *const SddPtr,
+    right: *const SddPtr,
+) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).and(*left, *right)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_or(
+    builder: *const SddBuilder,
+    left: *const SddPtr,
+    right: *const SddPtr,
+) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).or(*left, *right)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_negate(builder: *const SddBuilder, sdd: *const SddPtr) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).negate(*sdd)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_ite(
+    builder: *const SddBuilder,
+    f: *const SddPtr,
+    g: *const SddPtr,
+    h: *const SddPtr,
+) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).ite(*f, *g, *h)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_iff(
+    builder: *const SddBuilder,
+    left: *const SddPtr,
+    right: *const SddPtr,
+) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).iff(*left, *right)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_xor(
+    builder: *const SddBuilder,
+    left: *const SddPtr,
+    right: *const SddPtr,
+) -> *mut SddPtr {
+    Box::into_raw(Box::new((*builder).xor(*left, *right)))
+}
+
+/// NULL if `label` is not in the vtree
+#[no_mangle]
+unsafe extern "C" fn sdd_exists(
+    builder: *const SddBuilder,
+    sdd: *const SddPtr,
+    label: u64,
+) -> *mut SddPtr {
+    if !in_vtree(&*builder, label) {
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*builder).exists(*sdd, VarLabel::new(label))))
+}
+
+/// NULL if `label` is not in the vtree
+#[no_mangle]
+unsafe extern "C" fn sdd_forall(
+    builder: *const SddBuilder,
+    sdd: *const SddPtr,
+    label: u64,
+) -> *mut SddPtr {
+    if !in_vtree(&*builder, label) {
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*builder).forall(*sdd, VarLabel::new(label))))
+}
+
+/// NULL if `label` is not in the vtree
+#[no_mangle]
+unsafe extern "C" fn sdd_condition(
+    builder: *const SddBuilder,
+    sdd: *const SddPtr,
+    label: u64,
+    value: bool,
+) -> *mut SddPtr {
+    if !in_vtree(&*builder, label) {
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*builder).condition(
+        *sdd,
+        VarLabel::new(label),
+        value,
+    )))
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_eq(
+    builder: *const SddBuilder,
+    left: *const SddPtr,
+    right: *const SddPtr,
+) -> bool {
+    (*builder).eq(*left, *right)
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_is_true(sdd: *const SddPtr) -> bool {
+    (*sdd).is_true()
+}
+
+#[no_mangle]
+unsafe extern "C" fn sdd_is_false(sdd: *const SddPtr) -> bool {
+    (*sdd).is_false()
+}
+
+/// frees the handle `sdd`; the node itself lives as long as its builder
+#[no_mangle]
+unsafe extern "C" fn sdd_free(sdd: *mut SddPtr) {
+    if !sdd.is_null() {
+        drop(Box::from_raw(sdd));
+    }
 }
 
 #[no_mangle]
 unsafe extern "C" fn sdd_wmc(sdd: *const SddPtr, wmc: *const WmcParams<RealSemiring>) -> f64 {
     DDNNFPtr::unsmoothed_wmc(&*sdd, &*wmc).0
 }
+
+/// serializes `sdd` as JSON (see `SDDSerializer`); the caller frees the
+/// result with `string_free`
+#[no_mangle]
+unsafe extern "C" fn sdd_to_json(sdd: *const SddPtr) -> *mut c_char {
+    into_c_string(serde_json::to_string(&SDDSerializer::from_sdd(*sdd)).unwrap())
+}
+
+/// rebuilds an SDD serialized by `sdd_to_json` in `builder`, returning NULL
+/// on malformed input (see `last_parse_error_message`)
+#[no_mangle]
+unsafe extern "C" fn sdd_from_json(builder: *const SddBuilder, json: *const c_char) -> *mut SddPtr {
+    let builder: &'static SddBuilder = &*builder;
+    let parsed = serde_json::from_str::<SDDSerializer>(&from_c_string(json))
+        .map_err(json_error)
+        .and_then(|ser| ser.try_into_builder(builder))
+        .and_then(|roots| match roots[..] {
+            [sdd] => Ok(sdd),
+            _ => Err(ParseError::unlocated(format!(
+                "expected one root, found {}",
+                roots.len()
+            ))),
+        });
+    into_c_result(parsed)
+}
+
+/// writes `sdd` in the binary diagram format (see `BinaryDiagram`), storing
+/// its length in `len`; the caller frees the result with `bytes_free`
+#[no_mangle]
+unsafe extern "C" fn sdd_to_binary(
+    builder: *const SddBuilder,
+    sdd: *const SddPtr,
+    len: *mut usize,
+) -> *mut u8 {
+    let manager = (*builder).vtree_manager();
+    into_c_bytes(serialize::sdd_to_binary(&[*sdd], manager.vtree_root()), len)
+}
+
+#[test]
+fn ffi_sdd_borrows_its_inputs() {
+    use super::error::last_parse_error_message;
+    use std::ffi::CString;
+
+    unsafe {
+        let vars: Vec<VarLabel> = [0, 2, 5].into_iter().map(VarLabel::new).collect();
+        let vtree = Box::into_raw(Box::new(VTree::right_linear(&vars)));
+        let cnf = Box::into_raw(Box::new(Cnf::from_string("(0 || 2) && (-2 || 5)").unwrap()));
+        let builder = sdd_builder_new(vtree);
+        let sdd = sdd_builder_compile_cnf(builder, cnf);
+        // the builder copied the vtree and only borrowed the CNF
+        drop(Box::from_raw(vtree));
+        drop(Box::from_raw(cnf));
+
+        let x2 = sdd_var(builder, 2, true);
+        let conditioned = sdd_condition(builder, sdd, 2, true);
+        let conj = sdd_and(builder, sdd, x2);
+        let expected = sdd_and(builder, x2, conditioned);
+        assert!(sdd_eq(builder, conj, expected));
+        // labels outside the vtree, including gaps in it, are rejected
+        assert!(sdd_var(builder, 1, true).is_null());
+        let outside = Cnf::from_string("(0 || 1)").unwrap();
+        assert!(sdd_builder_compile_cnf(builder, &outside).is_null());
+        assert!(!last_parse_error_message().is_null());
+        assert!(sdd_exists(builder, sdd, 9).is_null());
+        assert!(sdd_forall(builder, sdd, 3).is_null());
+        assert!(sdd_condition(builder, sdd, 6, false).is_null());
+
+        let json = sdd_to_json(conj);
+        let parsed = sdd_from_json(builder, json);
+        drop(CString::from_raw(json));
+        assert!(sdd_eq(builder, parsed, conj));
+
+        for ptr in [sdd, x2, conditioned, conj, expected, parsed] {
+            sdd_free(ptr);
+        }
+        sdd_builder_free(builder);
+    }
+}
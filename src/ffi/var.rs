@@ -11,3 +11,10 @@ unsafe extern "C" fn var_order_new(order: *const VarLabel, len: usize) -> *mut V
     let order = slice::from_raw_parts(order, len);
     Box::into_raw(Box::new(VarOrder::new(order)))
 }
+
+#[no_mangle]
+unsafe extern "C" fn var_order_free(order: *mut VarOrder) {
+    if !order.is_null() {
+        drop(Box::from_raw(order));
+    }
+}
@@ -1,7 +1,114 @@
-use crate::repr::{DTree, VTree};
-use std::ptr;
+use super::{
+    alloc::{from_c_string, into_c_string},
+    error::{into_c_result, json_error},
+};
+use crate::{
+    repr::{DTree, ParseError, VTree, VarLabel},
+    serialize::VTreeSerializer,
+};
+use core::slice;
+use std::{os::raw::c_char, ptr};
+
+/// rejects vtrees an SDD builder cannot use: a variable must not occur twice
+fn checked_vtree(vtree: VTree) -> Result<VTree, ParseError> {
+    if VTree::is_valid_vtree(&vtree) {
+        Ok(vtree)
+    } else {
+        Err(ParseError::unlocated(
+            "a variable appears twice in the vtree",
+        ))
+    }
+}
+
+/// builds a vtree over `order` with `f`; NULL if `order` is empty or repeats
+/// a variable (see `last_parse_error_message`)
+unsafe fn vtree_over(
+    order: *const VarLabel,
+    len: usize,
+    f: impl FnOnce(&[VarLabel]) -> VTree,
+) -> *mut VTree {
+    if len == 0 {
+        return into_c_result(Err(ParseError::unlocated("empty variable order")));
+    }
+    into_c_result(checked_vtree(f(slice::from_raw_parts(order, len))))
+}
 
 #[no_mangle]
 unsafe extern "C" fn vtree_from_dtree(dtree: *const DTree) -> *mut VTree {
     VTree::from_dtree(&*dtree).map_or(ptr::null_mut(), |v| Box::into_raw(Box::new(v)))
 }
+
+/// a left-linear vtree over `order`, or NULL if `order` is empty or repeats
+/// a variable
+#[no_mangle]
+unsafe extern "C" fn vtree_left_linear(order: *const VarLabel, len: usize) -> *mut VTree {
+    vtree_over(order, len, VTree::left_linear)
+}
+
+/// a right-linear vtree over `order`, or NULL if `order` is empty or repeats
+/// a variable
+#[no_mangle]
+unsafe extern "C" fn vtree_right_linear(order: *const VarLabel, len: usize) -> *mut VTree {
+    vtree_over(order, len, VTree::right_linear)
+}
+
+/// halves `order` `num_splits` times, then continues right-linearly (see
+/// `VTree::even_split`); NULL if `order` is empty or repeats a variable
+#[no_mangle]
+unsafe extern "C" fn vtree_even_split(
+    order: *const VarLabel,
+    len: usize,
+    num_splits: usize,
+) -> *mut VTree {
+    vtree_over(order, len, |order| VTree::even_split(order, num_splits))
+}
+
+#[no_mangle]
+unsafe extern "C" fn vtree_free(vtree: *mut VTree) {
+    if !vtree.is_null() {
+        drop(Box::from_raw(vtree));
+    }
+}
+
+/// serializes `vtree` as JSON (see `VTreeSerializer`); the caller frees the
+/// result with `string_free`
+#[no_mangle]
+unsafe extern "C" fn vtree_to_json(vtree: *const VTree) -> *mut c_char {
+    into_c_string(serde_json::to_string(&VTreeSerializer::from_vtree(&*vtree)).unwrap())
+}
+
+/// parses a vtree serialized by `vtree_to_json`, returning NULL on malformed
+/// input or a repeated variable (see `last_parse_error_message`)
+#[no_mangle]
+unsafe extern "C" fn vtree_from_json(json: *const c_char) -> *mut VTree {
+    into_c_result(
+        serde_json::from_str::<VTreeSerializer>(&from_c_string(json))
+            .map_err(json_error)
+            .and_then(|ser| checked_vtree(ser.to_vtree())),
+    )
+}
+
+#[test]
+fn ffi_vtree_rejects_repeated_variables() {
+    use super::error::last_parse_error_message;
+    use std::ffi::CString;
+
+    unsafe {
+        let order: Vec<VarLabel> = [0, 1, 0].into_iter().map(VarLabel::new).collect();
+        assert!(vtree_left_linear(order.as_ptr(), order.len()).is_null());
+        assert!(!last_parse_error_message().is_null());
+        assert!(vtree_right_linear(order.as_ptr(), order.len()).is_null());
+        assert!(vtree_even_split(order.as_ptr(), order.len(), 1).is_null());
+        assert!(vtree_right_linear(order.as_ptr(), 0).is_null());
+
+        let vtree = vtree_right_linear(order.as_ptr(), 2);
+        assert!(!vtree.is_null());
+        assert!(last_parse_error_message().is_null());
+        vtree_free(vtree);
+
+        let json =
+            CString::new(r#"{"root":{"Node":{"left":{"Leaf":3},"right":{"Leaf":3}}}}"#).unwrap();
+        assert!(vtree_from_json(json.as_ptr()).is_null());
+        assert!(!last_parse_error_message().is_null());
+    }
+}
@@ -14,6 +14,20 @@ unsafe extern "C" fn new_wmc_params_complex() -> *mut WmcParams<Complex> {
     Box::into_raw(Box::new(WmcParams::new(HashMap::from([]))))
 }
 
+#[no_mangle]
+unsafe extern "C" fn wmc_params_f64_free(weights: *mut WmcParams<RealSemiring>) {
+    if !weights.is_null() {
+        drop(Box::from_raw(weights));
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn wmc_params_complex_free(weights: *mut WmcParams<Complex>) {
+    if !weights.is_null() {
+        drop(Box::from_raw(weights));
+    }
+}
+
 #[no_mangle]
 unsafe extern "C" fn wmc_param_f64_set_weight(
     weights: *mut WmcParams<RealSemiring>,
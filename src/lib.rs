@@ -1,5 +1,4 @@
 //! Defines exports and the C api
-extern crate dimacs;
 extern crate pretty;
 extern crate primal;
 extern crate rand;
@@ -29,3 +28,6 @@ pub mod wasm;
 // when using rsdd-ocaml
 #[cfg(feature = "ffi")]
 mod ffi;
+
+#[cfg(feature = "python")]
+pub mod python;
@@ -1,15 +1,35 @@
 //! Represents a deferred BDD computation
 //! BDD plans are executed according to an in-order left-first depth-first traversal of the tree
+//!
+//! Plans can be generated from a CNF (from a dtree, by bucket elimination, by
+//! clustering clauses, or as a balanced conjunction), or produced by an
+//! external scheduler and loaded from their serialized form. Plans serialize
+//! with serde; [`BottomUpPlan::from_sexpr`] and [`BottomUpPlan::to_sexpr`]
+//! handle the s-expression form, e.g.
+//! ```text
+//! (And (Or (Literal 0 true) (Literal 1 false)) (Exists (1) (Literal 1 true)))
+//! ```
+//!
+//! Quantifiers in a plan can be moved as close to the leaves as variable
+//! scopes allow with [`BottomUpPlan::push_quantifiers`].
 
-use crate::repr::{DTree, VarLabel};
+use crate::repr::{Cnf, DTree, Literal, ParseError, VarLabel, VarOrder, VarSet};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BottomUpPlan {
     And(Box<BottomUpPlan>, Box<BottomUpPlan>),
     Or(Box<BottomUpPlan>, Box<BottomUpPlan>),
     Iff(Box<BottomUpPlan>, Box<BottomUpPlan>),
     Ite(Box<BottomUpPlan>, Box<BottomUpPlan>, Box<BottomUpPlan>),
     Not(Box<BottomUpPlan>),
+    /// existentially quantifies a set of variables out of a subplan
+    Exists(VarSet, Box<BottomUpPlan>),
+    /// universally quantifies a set of variables out of a subplan
+    Forall(VarSet, Box<BottomUpPlan>),
+    /// conditions a subplan on a variable taking the given value
+    Condition(VarLabel, bool, Box<BottomUpPlan>),
+    /// `Compose(v, f, g)` substitutes `g` for the variable `v` in `f`
+    Compose(VarLabel, Box<BottomUpPlan>, Box<BottomUpPlan>),
     ConstTrue,
     ConstFalse,
     Literal(VarLabel, bool),
@@ -43,6 +63,51 @@ impl BottomUpPlan {
         Self::Literal(label, polarity)
     }
 
+    pub fn exists(vars: VarSet, p: BottomUpPlan) -> Self {
+        Self::Exists(vars, Box::new(p))
+    }
+
+    pub fn forall(vars: VarSet, p: BottomUpPlan) -> Self {
+        Self::Forall(vars, Box::new(p))
+    }
+
+    pub fn condition(label: VarLabel, value: bool, p: BottomUpPlan) -> Self {
+        Self::Condition(label, value, Box::new(p))
+    }
+
+    pub fn compose(label: VarLabel, f: BottomUpPlan, g: BottomUpPlan) -> Self {
+        Self::Compose(label, Box::new(f), Box::new(g))
+    }
+
+    /// a plan for a single clause: a left-deep disjunction of its literals
+    pub fn clause(clause: &[Literal]) -> BottomUpPlan {
+        match clause.split_first() {
+            None => Self::ConstFalse,
+            Some((first, rest)) => rest.iter().fold(
+                Self::literal(first.label(), first.polarity()),
+                |acc, lit| Self::or(acc, Self::literal(lit.label(), lit.polarity())),
+            ),
+        }
+    }
+
+    /// conjoins `plans` as a balanced binary tree; an empty conjunction is
+    /// true
+    pub fn balanced_and(plans: Vec<BottomUpPlan>) -> BottomUpPlan {
+        fn helper(mut plans: Vec<BottomUpPlan>) -> BottomUpPlan {
+            if plans.len() == 1 {
+                plans.pop().unwrap()
+            } else {
+                let r = plans.split_off(plans.len() / 2);
+                BottomUpPlan::and(helper(plans), helper(r))
+            }
+        }
+        if plans.is_empty() {
+            Self::ConstTrue
+        } else {
+            helper(plans)
+        }
+    }
+
     /// Given a dtree decomposition of a CNF, generates an appropriate plan for
     /// compiling the CNF
     /// i.e., for a dtree
@@ -76,19 +141,160 @@ impl BottomUpPlan {
                 clause,
                 cutset: _,
                 vars: _,
-            } => {
-                if clause.is_empty() {
-                    Self::ConstFalse
-                } else if clause.len() == 1 {
-                    Self::literal(clause[0].label(), clause[0].polarity())
-                } else {
-                    let first_lit = Self::literal(clause[0].label(), clause[0].polarity());
-                    clause.iter().skip(1).fold(first_lit, |acc, i| {
-                        let new_l = Self::literal(i.label(), i.polarity());
-                        Self::or(acc, new_l)
-                    })
+            } => Self::clause(clause),
+        }
+    }
+
+    /// Conjoins the clauses of `cnf` as a balanced binary tree, in the order
+    /// in which they appear in the CNF
+    pub fn from_cnf_balanced(cnf: &Cnf) -> BottomUpPlan {
+        Self::balanced_and(cnf.clauses().iter().map(|c| Self::clause(c)).collect())
+    }
+
+    /// Generates a bucket elimination plan. Each clause is placed in the
+    /// bucket of its first variable in `order`; buckets are then processed in
+    /// order, conjoining their contents and passing the result on to the
+    /// bucket of its next variable.
+    ///
+    /// If a `projection` is given, every variable outside of it is
+    /// existentially quantified as soon as its bucket has been conjoined, so
+    /// the plan computes the projection of `cnf` onto `projection` without
+    /// ever building the full conjunction.
+    /// ```
+    /// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+    /// use rsdd::plan::BottomUpPlan;
+    /// use rsdd::repr::{BddPtr, Cnf, DDNNFPtr, VarLabel, VarOrder, VarSet};
+    ///
+    /// // (a \/ b) /\ (!b \/ c), projected onto {a, c}, is (a \/ c)
+    /// let cnf = Cnf::from_string("(0 || 1) && (-1 || 2)").unwrap();
+    /// let mut projection = VarSet::new();
+    /// projection.insert(VarLabel::new(0));
+    /// projection.insert(VarLabel::new(2));
+    ///
+    /// let order = VarOrder::new(&[VarLabel::new(1), VarLabel::new(0), VarLabel::new(2)]);
+    /// let plan = BottomUpPlan::from_bucket_elimination(&cnf, &order, Some(&projection));
+    ///
+    /// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
+    /// let expected = builder.compile_cnf(&Cnf::from_string("(0 || 2)").unwrap());
+    /// assert_eq!(builder.compile_plan(&plan), expected);
+    /// ```
+    pub fn from_bucket_elimination(
+        cnf: &Cnf,
+        order: &VarOrder,
+        projection: Option<&VarSet>,
+    ) -> BottomUpPlan {
+        // each bucket holds (plan, scope) pairs
+        let mut buckets: Vec<Vec<(BottomUpPlan, VarSet)>> = vec![Vec::new(); order.num_vars()];
+        let mut done: Vec<BottomUpPlan> = Vec::new();
+
+        // the position of the earliest variable in `scope` after `after`
+        let next_bucket = |scope: &VarSet, after: Option<usize>| {
+            scope
+                .iter()
+                .map(|v| order.get(v))
+                .filter(|p| after.is_none_or(|a| *p > a))
+                .min()
+        };
+
+        for clause in cnf.clauses() {
+            let mut scope = VarSet::new();
+            for lit in clause.iter() {
+                scope.insert(lit.label());
+            }
+            match next_bucket(&scope, None) {
+                Some(pos) => buckets[pos].push((Self::clause(clause), scope)),
+                None => done.push(Self::clause(clause)),
+            }
+        }
+
+        for pos in 0..buckets.len() {
+            let contents = std::mem::take(&mut buckets[pos]);
+            if contents.is_empty() {
+                continue;
+            }
+            let var = order.var_at_level(pos);
+            let mut scope = VarSet::new();
+            let mut plans = Vec::with_capacity(contents.len());
+            for (plan, s) in contents {
+                scope.union_with(&s);
+                plans.push(plan);
+            }
+            let mut plan = Self::balanced_and(plans);
+            if projection.is_some_and(|p| !p.contains(var)) {
+                let mut eliminated = VarSet::new();
+                eliminated.insert(var);
+                plan = Self::exists(eliminated, plan);
+                scope.remove(var);
+            }
+            match next_bucket(&scope, Some(pos)) {
+                Some(next) => buckets[next].push((plan, scope)),
+                None => done.push(plan),
+            }
+        }
+        Self::balanced_and(done)
+    }
+
+    /// Generates a clustered conjunction plan. Clauses are sorted by the
+    /// position in `order` of their last variable, then greedily grouped into
+    /// clusters whose combined scope has at most `max_cluster_vars`
+    /// variables (a single clause may exceed it). Each cluster is conjoined as
+    /// a balanced tree, and the clusters are then conjoined in order.
+    pub fn from_clusters(cnf: &Cnf, order: &VarOrder, max_cluster_vars: usize) -> BottomUpPlan {
+        let mut clauses: Vec<&Vec<Literal>> = cnf.clauses().iter().collect();
+        clauses.sort_by_key(|c| c.iter().map(|l| order.get(l.label())).max());
+
+        let mut clusters: Vec<BottomUpPlan> = Vec::new();
+        let mut cur: Vec<BottomUpPlan> = Vec::new();
+        let mut scope = VarSet::new();
+        for clause in clauses {
+            let mut merged = scope.clone();
+            for lit in clause.iter() {
+                merged.insert(lit.label());
+            }
+            if !cur.is_empty() && merged.len() > max_cluster_vars {
+                clusters.push(Self::balanced_and(std::mem::take(&mut cur)));
+                merged = VarSet::new();
+                for lit in clause.iter() {
+                    merged.insert(lit.label());
                 }
             }
+            scope = merged;
+            cur.push(Self::clause(clause));
+        }
+        if !cur.is_empty() {
+            clusters.push(Self::balanced_and(cur));
         }
+        clusters
+            .into_iter()
+            .reduce(Self::and)
+            .unwrap_or(Self::ConstTrue)
     }
+
+    /// Parses a plan from its s-expression form (see the module
+    /// documentation)
+    pub fn from_sexpr(input: &str) -> Result<BottomUpPlan, ParseError> {
+        ParseError::check_parens(input)?;
+        Ok(serde_sexpr::from_str(input)?)
+    }
+
+    /// Writes this plan in s-expression form
+    pub fn to_sexpr(&self) -> String {
+        serde_sexpr::to_string(self).unwrap()
+    }
+}
+
+#[test]
+fn plan_sexpr_round_trip() {
+    let mut vars = VarSet::new();
+    vars.insert(VarLabel::new(1));
+    let plan = BottomUpPlan::and(
+        BottomUpPlan::or(
+            BottomUpPlan::literal(VarLabel::new(0), true),
+            BottomUpPlan::literal(VarLabel::new(1), false),
+        ),
+        BottomUpPlan::exists(vars, BottomUpPlan::literal(VarLabel::new(1), true)),
+    );
+    let text = plan.to_sexpr();
+    assert_eq!(BottomUpPlan::from_sexpr(&text).unwrap(), plan);
+    assert!(BottomUpPlan::from_sexpr("(And (Literal 0 true))").is_err());
 }
@@ -0,0 +1,354 @@
+//! A hash-consed DAG representation of bottom-up plans
+//!
+//! A [`BottomUpPlan`] is a tree, so a subformula that occurs several times is
+//! stored (and compiled) once per occurrence. A [`DagPlan`] instead stores
+//! each distinct node exactly once in an arena and refers to nodes by
+//! [`PlanNodeId`]; structurally equal subplans are shared automatically as
+//! they are added. Since a node is always added after its children, node ids
+//! are a topological order of the DAG.
+
+use super::BottomUpPlan;
+use crate::repr::{LogicalExpr, VarLabel, VarSet};
+use rustc_hash::FxHashMap;
+
+/// Identifies a node of a [`DagPlan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PlanNodeId(usize);
+
+impl PlanNodeId {
+    pub fn value_usize(&self) -> usize {
+        self.0
+    }
+}
+
+/// A node of a [`DagPlan`]; mirrors [`BottomUpPlan`] with children referred
+/// to by id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlanNode {
+    And(PlanNodeId, PlanNodeId),
+    Or(PlanNodeId, PlanNodeId),
+    Iff(PlanNodeId, PlanNodeId),
+    Ite(PlanNodeId, PlanNodeId, PlanNodeId),
+    Not(PlanNodeId),
+    Exists(VarSet, PlanNodeId),
+    Forall(VarSet, PlanNodeId),
+    Condition(VarLabel, bool, PlanNodeId),
+    Compose(VarLabel, PlanNodeId, PlanNodeId),
+    ConstTrue,
+    ConstFalse,
+    Literal(VarLabel, bool),
+}
+
+impl PlanNode {
+    /// the ids of the children of this node
+    pub fn children(&self) -> Vec<PlanNodeId> {
+        match self {
+            PlanNode::And(a, b)
+            | PlanNode::Or(a, b)
+            | PlanNode::Iff(a, b)
+            | PlanNode::Compose(_, a, b) => vec![*a, *b],
+            PlanNode::Ite(a, b, c) => vec![*a, *b, *c],
+            PlanNode::Not(a)
+            | PlanNode::Exists(_, a)
+            | PlanNode::Forall(_, a)
+            | PlanNode::Condition(_, _, a) => vec![*a],
+            PlanNode::ConstTrue | PlanNode::ConstFalse | PlanNode::Literal(_, _) => vec![],
+        }
+    }
+}
+
+/// A plan stored as a hash-consed DAG.
+///
+/// Nodes are added bottom-up through the constructors, each of which returns
+/// the id of an existing structurally equal node if there is one. Operands of
+/// commutative operations are put in a canonical order first, so e.g. `a /\ b`
+/// and `b /\ a` are the same node.
+///
+/// A plan serializes as its list of nodes, children first.
+/// ```
+/// use rsdd::plan::DagPlan;
+/// use rsdd::repr::LogicalExpr;
+/// use rsdd::serialize::LogicalSExpr;
+///
+/// // (a /\ b) appears twice but is stored once
+/// let sexpr = "(Or (And (Var A) (Var B)) (Not (And (Var B) (Var A))))";
+/// let expr = LogicalExpr::from_sexpr(&serde_sexpr::from_str::<LogicalSExpr>(sexpr).unwrap());
+/// let mut plan = DagPlan::new();
+/// let root = plan.add_logical_expr(&expr);
+/// // two literals, one conjunction, its negation and the disjunction
+/// assert_eq!(plan.len(), 5);
+/// assert_eq!(plan.reachable(&[root]).len(), 5);
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(into = "Vec<PlanNode>", try_from = "Vec<PlanNode>")]
+pub struct DagPlan {
+    nodes: Vec<PlanNode>,
+    table: FxHashMap<PlanNode, PlanNodeId>,
+}
+
+impl From<DagPlan> for Vec<PlanNode> {
+    fn from(plan: DagPlan) -> Self {
+        plan.nodes
+    }
+}
+
+impl TryFrom<Vec<PlanNode>> for DagPlan {
+    type Error = String;
+
+    /// checks that every node only refers to nodes before it
+    fn try_from(nodes: Vec<PlanNode>) -> Result<Self, Self::Error> {
+        let mut table = FxHashMap::default();
+        for (i, node) in nodes.iter().enumerate() {
+            if node.children().iter().any(|c| c.0 >= i) {
+                return Err(format!("plan node {i} refers to a later node"));
+            }
+            table.entry(node.clone()).or_insert(PlanNodeId(i));
+        }
+        Ok(DagPlan { nodes, table })
+    }
+}
+
+impl DagPlan {
+    pub fn new() -> DagPlan {
+        DagPlan::default()
+    }
+
+    /// the number of distinct nodes in the plan
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, id: PlanNodeId) -> &PlanNode {
+        &self.nodes[id.0]
+    }
+
+    /// iterates over all nodes together with their ids, children first
+    pub fn iter(&self) -> impl Iterator<Item = (PlanNodeId, &PlanNode)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (PlanNodeId(i), n))
+    }
+
+    /// Adds `node` to the plan, or returns the id of an equal node already in
+    /// it. Panics if a child of `node` is not in the plan.
+    pub fn add(&mut self, node: PlanNode) -> PlanNodeId {
+        let node = match node {
+            PlanNode::And(a, b) if b < a => PlanNode::And(b, a),
+            PlanNode::Or(a, b) if b < a => PlanNode::Or(b, a),
+            PlanNode::Iff(a, b) if b < a => PlanNode::Iff(b, a),
+            n => n,
+        };
+        if let Some(id) = self.table.get(&node) {
+            return *id;
+        }
+        assert!(
+            node.children().iter().all(|c| c.0 < self.nodes.len()),
+            "child of plan node not in plan"
+        );
+        let id = PlanNodeId(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.table.insert(node, id);
+        id
+    }
+
+    pub fn literal(&mut self, label: VarLabel, polarity: bool) -> PlanNodeId {
+        self.add(PlanNode::Literal(label, polarity))
+    }
+
+    pub fn const_true(&mut self) -> PlanNodeId {
+        self.add(PlanNode::ConstTrue)
+    }
+
+    pub fn const_false(&mut self) -> PlanNodeId {
+        self.add(PlanNode::ConstFalse)
+    }
+
+    pub fn and(&mut self, a: PlanNodeId, b: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::And(a, b))
+    }
+
+    pub fn or(&mut self, a: PlanNodeId, b: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Or(a, b))
+    }
+
+    pub fn iff(&mut self, a: PlanNodeId, b: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Iff(a, b))
+    }
+
+    pub fn ite(&mut self, f: PlanNodeId, g: PlanNodeId, h: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Ite(f, g, h))
+    }
+
+    pub fn not(&mut self, a: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Not(a))
+    }
+
+    pub fn exists(&mut self, vars: VarSet, a: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Exists(vars, a))
+    }
+
+    pub fn forall(&mut self, vars: VarSet, a: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Forall(vars, a))
+    }
+
+    pub fn condition(&mut self, label: VarLabel, value: bool, a: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Condition(label, value, a))
+    }
+
+    pub fn compose(&mut self, label: VarLabel, f: PlanNodeId, g: PlanNodeId) -> PlanNodeId {
+        self.add(PlanNode::Compose(label, f, g))
+    }
+
+    /// Adds a tree plan to this DAG, sharing every subplan that is already
+    /// present; returns the id of its root
+    pub fn add_plan(&mut self, plan: &BottomUpPlan) -> PlanNodeId {
+        match plan {
+            BottomUpPlan::And(l, r) => {
+                let (l, r) = (self.add_plan(l), self.add_plan(r));
+                self.and(l, r)
+            }
+            BottomUpPlan::Or(l, r) => {
+                let (l, r) = (self.add_plan(l), self.add_plan(r));
+                self.or(l, r)
+            }
+            BottomUpPlan::Iff(l, r) => {
+                let (l, r) = (self.add_plan(l), self.add_plan(r));
+                self.iff(l, r)
+            }
+            BottomUpPlan::Ite(f, g, h) => {
+                let (f, g, h) = (self.add_plan(f), self.add_plan(g), self.add_plan(h));
+                self.ite(f, g, h)
+            }
+            BottomUpPlan::Not(f) => {
+                let f = self.add_plan(f);
+                self.not(f)
+            }
+            BottomUpPlan::Exists(vars, f) => {
+                let f = self.add_plan(f);
+                self.exists(vars.clone(), f)
+            }
+            BottomUpPlan::Forall(vars, f) => {
+                let f = self.add_plan(f);
+                self.forall(vars.clone(), f)
+            }
+            BottomUpPlan::Condition(v, value, f) => {
+                let f = self.add_plan(f);
+                self.condition(*v, *value, f)
+            }
+            BottomUpPlan::Compose(v, f, g) => {
+                let (f, g) = (self.add_plan(f), self.add_plan(g));
+                self.compose(*v, f, g)
+            }
+            BottomUpPlan::ConstTrue => self.const_true(),
+            BottomUpPlan::ConstFalse => self.const_false(),
+            BottomUpPlan::Literal(v, polarity) => self.literal(*v, *polarity),
+        }
+    }
+
+    /// Adds a logical expression to this DAG, sharing every subexpression
+    /// that is already present; returns the id of its root. Exclusive-or is
+    /// added as a negated bi-implication.
+    pub fn add_logical_expr(&mut self, expr: &LogicalExpr) -> PlanNodeId {
+        match expr {
+            LogicalExpr::Literal(v, polarity) => self.literal(VarLabel::new_usize(*v), *polarity),
+            LogicalExpr::Not(e) => {
+                let e = self.add_logical_expr(e);
+                self.not(e)
+            }
+            LogicalExpr::And(l, r) => {
+                let (l, r) = (self.add_logical_expr(l), self.add_logical_expr(r));
+                self.and(l, r)
+            }
+            LogicalExpr::Or(l, r) => {
+                let (l, r) = (self.add_logical_expr(l), self.add_logical_expr(r));
+                self.or(l, r)
+            }
+            LogicalExpr::Iff(l, r) => {
+                let (l, r) = (self.add_logical_expr(l), self.add_logical_expr(r));
+                self.iff(l, r)
+            }
+            LogicalExpr::Xor(l, r) => {
+                let (l, r) = (self.add_logical_expr(l), self.add_logical_expr(r));
+                let iff = self.iff(l, r);
+                self.not(iff)
+            }
+            LogicalExpr::Ite { guard, thn, els } => {
+                let g = self.add_logical_expr(guard);
+                let t = self.add_logical_expr(thn);
+                let e = self.add_logical_expr(els);
+                self.ite(g, t, e)
+            }
+        }
+    }
+
+    /// the ids of every node reachable from `roots`, in increasing order
+    pub fn reachable(&self, roots: &[PlanNodeId]) -> Vec<PlanNodeId> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack: Vec<PlanNodeId> = roots.to_vec();
+        while let Some(id) = stack.pop() {
+            if !std::mem::replace(&mut seen[id.0], true) {
+                stack.extend(self.nodes[id.0].children());
+            }
+        }
+        (0..self.nodes.len())
+            .filter(|i| seen[*i])
+            .map(PlanNodeId)
+            .collect()
+    }
+
+    /// Expands the subplan rooted at `root` into a tree; shared nodes are
+    /// duplicated
+    pub fn to_plan(&self, root: PlanNodeId) -> BottomUpPlan {
+        let c = |id: &PlanNodeId| self.to_plan(*id);
+        match self.node(root) {
+            PlanNode::And(a, b) => BottomUpPlan::and(c(a), c(b)),
+            PlanNode::Or(a, b) => BottomUpPlan::or(c(a), c(b)),
+            PlanNode::Iff(a, b) => BottomUpPlan::iff(c(a), c(b)),
+            PlanNode::Ite(f, g, h) => BottomUpPlan::ite(c(f), c(g), c(h)),
+            PlanNode::Not(a) => BottomUpPlan::not(c(a)),
+            PlanNode::Exists(vars, a) => BottomUpPlan::exists(vars.clone(), c(a)),
+            PlanNode::Forall(vars, a) => BottomUpPlan::forall(vars.clone(), c(a)),
+            PlanNode::Condition(v, value, a) => BottomUpPlan::condition(*v, *value, c(a)),
+            PlanNode::Compose(v, f, g) => BottomUpPlan::compose(*v, c(f), c(g)),
+            PlanNode::ConstTrue => BottomUpPlan::ConstTrue,
+            PlanNode::ConstFalse => BottomUpPlan::ConstFalse,
+            PlanNode::Literal(v, polarity) => BottomUpPlan::literal(*v, *polarity),
+        }
+    }
+}
+
+#[test]
+fn dag_plan_shares_subplans() {
+    let mut plan = DagPlan::new();
+    let a = plan.literal(VarLabel::new(0), true);
+    let b = plan.literal(VarLabel::new(1), true);
+    let ab = plan.and(a, b);
+    assert_eq!(plan.and(b, a), ab);
+    assert_eq!(plan.literal(VarLabel::new(0), true), a);
+
+    // a tree with the shared subplan (a /\ b) twice
+    let tree = BottomUpPlan::or(
+        BottomUpPlan::and(
+            BottomUpPlan::literal(VarLabel::new(0), true),
+            BottomUpPlan::literal(VarLabel::new(1), true),
+        ),
+        BottomUpPlan::not(BottomUpPlan::and(
+            BottomUpPlan::literal(VarLabel::new(0), true),
+            BottomUpPlan::literal(VarLabel::new(1), true),
+        )),
+    );
+    let root = plan.add_plan(&tree);
+    assert_eq!(plan.len(), 5);
+    assert_eq!(plan.to_plan(root), tree);
+    assert_eq!(plan.reachable(&[ab]), vec![a, b, ab]);
+
+    let json = serde_json::to_string(&plan).unwrap();
+    let parsed: DagPlan = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.to_plan(root), tree);
+    assert!(serde_json::from_str::<DagPlan>("[{\"Not\": 0}]").is_err());
+}
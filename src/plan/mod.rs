@@ -1,3 +1,6 @@
 mod bottom_up_plan;
+mod dag_plan;
+mod optimize;
 
 pub use self::bottom_up_plan::*;
+pub use self::dag_plan::*;
@@ -0,0 +1,296 @@
+//! Rewrites of bottom-up plans that preserve the function a plan computes
+//! but reduce the size of the intermediate results built while executing it
+
+use super::BottomUpPlan;
+use crate::repr::{VarLabel, VarSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quantifier {
+    Exists,
+    Forall,
+}
+
+impl Quantifier {
+    fn dual(self) -> Quantifier {
+        match self {
+            Quantifier::Exists => Quantifier::Forall,
+            Quantifier::Forall => Quantifier::Exists,
+        }
+    }
+
+    fn wrap(self, vars: VarSet, p: BottomUpPlan) -> BottomUpPlan {
+        match self {
+            Quantifier::Exists => BottomUpPlan::exists(vars, p),
+            Quantifier::Forall => BottomUpPlan::forall(vars, p),
+        }
+    }
+}
+
+fn singleton(v: VarLabel) -> VarSet {
+    let mut s = VarSet::new();
+    s.insert(v);
+    s
+}
+
+impl BottomUpPlan {
+    /// the variables that the function computed by this plan depends on
+    /// syntactically, i.e. the variables that occur in it and are not bound by
+    /// a quantifier, condition or composition
+    /// ```
+    /// use rsdd::plan::BottomUpPlan;
+    /// use rsdd::repr::{VarLabel, VarSet};
+    ///
+    /// let mut bound = VarSet::new();
+    /// bound.insert(VarLabel::new(1));
+    /// let plan = BottomUpPlan::exists(
+    ///     bound,
+    ///     BottomUpPlan::and(
+    ///         BottomUpPlan::literal(VarLabel::new(0), true),
+    ///         BottomUpPlan::literal(VarLabel::new(1), true),
+    ///     ),
+    /// );
+    /// let free: Vec<VarLabel> = plan.free_vars().iter().collect();
+    /// assert_eq!(free, vec![VarLabel::new(0)]);
+    /// ```
+    pub fn free_vars(&self) -> VarSet {
+        match self {
+            BottomUpPlan::Literal(v, _) => singleton(*v),
+            BottomUpPlan::ConstTrue | BottomUpPlan::ConstFalse => VarSet::new(),
+            BottomUpPlan::Not(f) => f.free_vars(),
+            BottomUpPlan::And(l, r) | BottomUpPlan::Or(l, r) | BottomUpPlan::Iff(l, r) => {
+                l.free_vars().union(&r.free_vars())
+            }
+            BottomUpPlan::Ite(f, g, h) => f.free_vars().union(&g.free_vars()).union(&h.free_vars()),
+            BottomUpPlan::Exists(vars, f) | BottomUpPlan::Forall(vars, f) => {
+                f.free_vars().minus(vars)
+            }
+            BottomUpPlan::Condition(v, _, f) => {
+                let mut s = f.free_vars();
+                s.remove(*v);
+                s
+            }
+            BottomUpPlan::Compose(v, f, g) => {
+                let mut s = f.free_vars();
+                s.remove(*v);
+                s.union(&g.free_vars())
+            }
+        }
+    }
+
+    /// Pushes every quantifier in this plan as far towards the leaves as the
+    /// variable scopes allow, so that variables are eliminated from the
+    /// smallest subplan that mentions them:
+    /// - quantified variables that do not occur free in the subplan are
+    ///   dropped, and directly nested quantifiers of the same kind are merged;
+    /// - `Exists` distributes over `Or` and `Forall` over `And`;
+    /// - over an `And` (resp. `Or` for `Forall`), a variable that occurs in
+    ///   only one side is quantified on that side only;
+    /// - quantifiers move through negation (as their dual) and conditioning.
+    ///
+    /// The resulting plan computes the same function.
+    /// ```
+    /// use rsdd::plan::BottomUpPlan;
+    /// use rsdd::repr::{VarLabel, VarSet};
+    ///
+    /// let lit = |v| BottomUpPlan::literal(VarLabel::new(v), true);
+    /// let mut vars = VarSet::new();
+    /// vars.insert(VarLabel::new(1));
+    /// // exists 1. (x0 /\ x1) becomes x0 /\ (exists 1. x1)
+    /// let plan = BottomUpPlan::exists(vars.clone(), BottomUpPlan::and(lit(0), lit(1)));
+    /// assert_eq!(
+    ///     plan.push_quantifiers(),
+    ///     BottomUpPlan::and(lit(0), BottomUpPlan::exists(vars, lit(1)))
+    /// );
+    /// ```
+    pub fn push_quantifiers(&self) -> BottomUpPlan {
+        push(self).0
+    }
+}
+
+/// recursively pushes the quantifiers of `p`; returns the rewritten plan
+/// together with its free variables
+fn push(p: &BottomUpPlan) -> (BottomUpPlan, VarSet) {
+    match p {
+        BottomUpPlan::Literal(v, _) => (p.clone(), singleton(*v)),
+        BottomUpPlan::ConstTrue | BottomUpPlan::ConstFalse => (p.clone(), VarSet::new()),
+        BottomUpPlan::Not(f) => {
+            let (f, vf) = push(f);
+            (BottomUpPlan::not(f), vf)
+        }
+        BottomUpPlan::And(l, r) => binary(l, r, BottomUpPlan::and),
+        BottomUpPlan::Or(l, r) => binary(l, r, BottomUpPlan::or),
+        BottomUpPlan::Iff(l, r) => binary(l, r, BottomUpPlan::iff),
+        BottomUpPlan::Ite(f, g, h) => {
+            let (f, vf) = push(f);
+            let (g, vg) = push(g);
+            let (h, vh) = push(h);
+            (BottomUpPlan::ite(f, g, h), vf.union(&vg).union(&vh))
+        }
+        BottomUpPlan::Exists(vars, f) => {
+            let (f, vf) = push(f);
+            quantify(Quantifier::Exists, vars, f, vf)
+        }
+        BottomUpPlan::Forall(vars, f) => {
+            let (f, vf) = push(f);
+            quantify(Quantifier::Forall, vars, f, vf)
+        }
+        BottomUpPlan::Condition(v, value, f) => {
+            let (f, vf) = push(f);
+            condition(*v, *value, f, vf)
+        }
+        BottomUpPlan::Compose(v, f, g) => {
+            let (f, mut vf) = push(f);
+            let (g, vg) = push(g);
+            vf.remove(*v);
+            (BottomUpPlan::compose(*v, f, g), vf.union(&vg))
+        }
+    }
+}
+
+fn binary(
+    l: &BottomUpPlan,
+    r: &BottomUpPlan,
+    mk: fn(BottomUpPlan, BottomUpPlan) -> BottomUpPlan,
+) -> (BottomUpPlan, VarSet) {
+    let (l, vl) = push(l);
+    let (r, vr) = push(r);
+    (mk(l, r), vl.union(&vr))
+}
+
+/// conditions `p` (with free variables `vars`) on `v = value`, dropping the
+/// condition if `v` does not occur in `p`
+fn condition(
+    v: VarLabel,
+    value: bool,
+    p: BottomUpPlan,
+    mut vars: VarSet,
+) -> (BottomUpPlan, VarSet) {
+    if vars.contains(v) {
+        vars.remove(v);
+        (BottomUpPlan::condition(v, value, p), vars)
+    } else {
+        (p, vars)
+    }
+}
+
+/// quantifies `vars` out of `p`, whose quantifiers have already been pushed
+/// and whose free variables are `scope`
+fn quantify(
+    q: Quantifier,
+    vars: &VarSet,
+    p: BottomUpPlan,
+    scope: VarSet,
+) -> (BottomUpPlan, VarSet) {
+    let vars = vars.intersect_varset(&scope);
+    if vars.is_empty() {
+        return (p, scope);
+    }
+    match (q, p) {
+        // the quantifier distributes over both sides
+        (Quantifier::Exists, BottomUpPlan::Or(l, r)) => {
+            let (l, vl) = quantify_child(q, &vars, *l);
+            let (r, vr) = quantify_child(q, &vars, *r);
+            (BottomUpPlan::or(l, r), vl.union(&vr))
+        }
+        (Quantifier::Forall, BottomUpPlan::And(l, r)) => {
+            let (l, vl) = quantify_child(q, &vars, *l);
+            let (r, vr) = quantify_child(q, &vars, *r);
+            (BottomUpPlan::and(l, r), vl.union(&vr))
+        }
+        // only variables local to one side move into it
+        (Quantifier::Exists, BottomUpPlan::And(l, r)) => split(q, &vars, *l, *r, BottomUpPlan::and),
+        (Quantifier::Forall, BottomUpPlan::Or(l, r)) => split(q, &vars, *l, *r, BottomUpPlan::or),
+        (q, BottomUpPlan::Not(f)) => {
+            let (f, vf) = quantify_child(q.dual(), &vars, *f);
+            (BottomUpPlan::not(f), vf)
+        }
+        (Quantifier::Exists, BottomUpPlan::Exists(inner, f))
+        | (Quantifier::Forall, BottomUpPlan::Forall(inner, f)) => {
+            quantify_child(q, &vars.union(&inner), *f)
+        }
+        // the conditioned variable is not free, so it is not in `vars`
+        (q, BottomUpPlan::Condition(v, value, f)) => {
+            let (f, vf) = quantify_child(q, &vars, *f);
+            condition(v, value, f, vf)
+        }
+        (q, p) => (q.wrap(vars.clone(), p), scope.minus(&vars)),
+    }
+}
+
+fn quantify_child(q: Quantifier, vars: &VarSet, p: BottomUpPlan) -> (BottomUpPlan, VarSet) {
+    let scope = p.free_vars();
+    quantify(q, vars, p, scope)
+}
+
+/// quantifies `vars` out of `mk(l, r)` where the quantifier does not
+/// distribute over `mk`: variables occurring on only one side are pushed into
+/// that side, and the shared ones stay on top
+fn split(
+    q: Quantifier,
+    vars: &VarSet,
+    l: BottomUpPlan,
+    r: BottomUpPlan,
+    mk: fn(BottomUpPlan, BottomUpPlan) -> BottomUpPlan,
+) -> (BottomUpPlan, VarSet) {
+    let (sl, sr) = (l.free_vars(), r.free_vars());
+    let shared = vars.intersect_varset(&sl).intersect_varset(&sr);
+    let (only_l, only_r) = (vars.minus(&sr), vars.minus(&sl));
+    let (l, vl) = quantify(q, &only_l, l, sl);
+    let (r, vr) = quantify(q, &only_r, r, sr);
+    let (p, scope) = (mk(l, r), vl.union(&vr));
+    if shared.is_empty() {
+        (p, scope)
+    } else {
+        (q.wrap(shared.clone(), p), scope.minus(&shared))
+    }
+}
+
+#[test]
+fn push_quantifiers_splits_conjunctions() {
+    let lit = |v| BottomUpPlan::literal(VarLabel::new(v), true);
+    let set = |vs: &[u64]| {
+        let mut s = VarSet::new();
+        for v in vs {
+            s.insert(VarLabel::new(*v));
+        }
+        s
+    };
+    // exists {1, 2, 3}. (x0 \/ x1) /\ (x1 \/ x2) /\ x3
+    let plan = BottomUpPlan::exists(
+        set(&[1, 2, 3]),
+        BottomUpPlan::and(
+            BottomUpPlan::and(
+                BottomUpPlan::or(lit(0), lit(1)),
+                BottomUpPlan::or(lit(1), lit(2)),
+            ),
+            lit(3),
+        ),
+    );
+    let expected = BottomUpPlan::and(
+        BottomUpPlan::exists(
+            set(&[1]),
+            BottomUpPlan::and(
+                BottomUpPlan::or(lit(0), lit(1)),
+                BottomUpPlan::or(lit(1), BottomUpPlan::exists(set(&[2]), lit(2))),
+            ),
+        ),
+        BottomUpPlan::exists(set(&[3]), lit(3)),
+    );
+    let pushed = plan.push_quantifiers();
+    assert_eq!(pushed, expected);
+    assert_eq!(pushed.free_vars(), set(&[0]));
+
+    // forall distributes over the conjunction and through the negation
+    let plan = BottomUpPlan::forall(
+        set(&[1]),
+        BottomUpPlan::and(lit(0), BottomUpPlan::not(BottomUpPlan::or(lit(0), lit(1)))),
+    );
+    let expected = BottomUpPlan::and(
+        lit(0),
+        BottomUpPlan::not(BottomUpPlan::or(
+            lit(0),
+            BottomUpPlan::exists(set(&[1]), lit(1)),
+        )),
+    );
+    assert_eq!(plan.push_quantifiers(), expected);
+}
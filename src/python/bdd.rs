@@ -0,0 +1,227 @@
+use super::{
+    check_assignment, extend, marginal_map, meu, same_builder, var_label, Assignment, EuWeights,
+    PyCnf, PyVarOrder, PyWmcParams,
+};
+use crate::{
+    builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder},
+    repr::{BddPtr, DDNNFPtr, VarLabel},
+    serialize::BDDSerializer,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+type Builder = RobddBuilder<'static, AllIteTable<BddPtr<'static>>>;
+
+/// Builds reduced ordered BDDs over a fixed variable order
+#[pyclass(name = "BddBuilder", module = "rsdd", unsendable)]
+pub struct PyBddBuilder {
+    builder: Rc<Builder>,
+}
+
+impl PyBddBuilder {
+    fn wrap(&self, ptr: BddPtr<'static>) -> PyBdd {
+        PyBdd {
+            builder: self.builder.clone(),
+            ptr,
+        }
+    }
+}
+
+#[pymethods]
+impl PyBddBuilder {
+    #[new]
+    fn new(order: &PyVarOrder) -> PyBddBuilder {
+        PyBddBuilder {
+            builder: Rc::new(RobddBuilder::new(order.order.clone())),
+        }
+    }
+
+    #[pyo3(signature = (label, polarity = true))]
+    fn var(&self, label: u64, polarity: bool) -> PyResult<PyBdd> {
+        let label = var_label(label, self.builder.num_vars())?;
+        Ok(self.wrap(extend(&self.builder).var(label, polarity)))
+    }
+
+    /// appends a fresh variable to the order, returning its label and BDD
+    #[pyo3(signature = (polarity = true))]
+    fn new_var(&self, polarity: bool) -> (u64, PyBdd) {
+        let (label, ptr) = extend(&self.builder).new_var(polarity);
+        (label.value(), self.wrap(ptr))
+    }
+
+    fn r#true(&self) -> PyBdd {
+        self.wrap(extend(&self.builder).true_ptr())
+    }
+
+    fn r#false(&self) -> PyBdd {
+        self.wrap(extend(&self.builder).false_ptr())
+    }
+
+    fn compile_cnf(&self, cnf: &PyCnf) -> PyBdd {
+        self.wrap(extend(&self.builder).compile_cnf(&cnf.cnf))
+    }
+
+    /// rebuilds a BDD serialized by `Bdd.to_json`
+    fn load_json(&self, json: &str) -> PyResult<PyBdd> {
+        let ser: BDDSerializer =
+            serde_json::from_str(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let roots = ser
+            .try_into_builder(extend(&self.builder))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        match roots[..] {
+            [ptr] => Ok(self.wrap(ptr)),
+            _ => Err(PyValueError::new_err(format!(
+                "expected one root, found {}",
+                roots.len()
+            ))),
+        }
+    }
+
+    #[getter]
+    fn num_vars(&self) -> usize {
+        self.builder.num_vars()
+    }
+}
+
+/// A BDD, which keeps its builder alive
+#[pyclass(name = "Bdd", module = "rsdd", unsendable)]
+#[derive(Clone)]
+pub struct PyBdd {
+    builder: Rc<Builder>,
+    ptr: BddPtr<'static>,
+}
+
+impl PyBdd {
+    fn builder(&self) -> &'static Builder {
+        extend(&self.builder)
+    }
+
+    fn wrap(&self, ptr: BddPtr<'static>) -> PyBdd {
+        PyBdd {
+            builder: self.builder.clone(),
+            ptr,
+        }
+    }
+
+    fn label(&self, label: u64) -> PyResult<VarLabel> {
+        var_label(label, self.builder.num_vars())
+    }
+
+    fn apply(
+        &self,
+        other: &PyBdd,
+        op: impl FnOnce(&'static Builder, BddPtr<'static>, BddPtr<'static>) -> BddPtr<'static>,
+    ) -> PyResult<PyBdd> {
+        same_builder(&self.builder, &other.builder)?;
+        Ok(self.wrap(op(self.builder(), self.ptr, other.ptr)))
+    }
+}
+
+#[pymethods]
+impl PyBdd {
+    fn __and__(&self, other: &PyBdd) -> PyResult<PyBdd> {
+        self.apply(other, |b, f, g| b.and(f, g))
+    }
+
+    fn __or__(&self, other: &PyBdd) -> PyResult<PyBdd> {
+        self.apply(other, |b, f, g| b.or(f, g))
+    }
+
+    fn __xor__(&self, other: &PyBdd) -> PyResult<PyBdd> {
+        self.apply(other, |b, f, g| b.xor(f, g))
+    }
+
+    fn __invert__(&self) -> PyBdd {
+        self.wrap(self.builder().negate(self.ptr))
+    }
+
+    /// BDDs are canonical, so this is semantic equivalence
+    fn __eq__(&self, other: &PyBdd) -> bool {
+        Rc::ptr_eq(&self.builder, &other.builder) && self.ptr == other.ptr
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.ptr.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn iff(&self, other: &PyBdd) -> PyResult<PyBdd> {
+        self.apply(other, |b, f, g| b.iff(f, g))
+    }
+
+    /// `if self then g else h`
+    fn ite(&self, g: &PyBdd, h: &PyBdd) -> PyResult<PyBdd> {
+        same_builder(&self.builder, &g.builder)?;
+        same_builder(&self.builder, &h.builder)?;
+        Ok(self.wrap(self.builder().ite(self.ptr, g.ptr, h.ptr)))
+    }
+
+    fn exists(&self, label: u64) -> PyResult<PyBdd> {
+        let label = self.label(label)?;
+        Ok(self.wrap(self.builder().exists(self.ptr, label)))
+    }
+
+    fn forall(&self, label: u64) -> PyResult<PyBdd> {
+        let label = self.label(label)?;
+        Ok(self.wrap(self.builder().forall(self.ptr, label)))
+    }
+
+    fn condition(&self, label: u64, value: bool) -> PyResult<PyBdd> {
+        let label = self.label(label)?;
+        Ok(self.wrap(self.builder().condition(self.ptr, label, value)))
+    }
+
+    /// substitutes `g` for variable `label`
+    fn compose(&self, label: u64, g: &PyBdd) -> PyResult<PyBdd> {
+        same_builder(&self.builder, &g.builder)?;
+        let label = self.label(label)?;
+        Ok(self.wrap(self.builder().compose(self.ptr, label, g.ptr)))
+    }
+
+    fn is_true(&self) -> bool {
+        self.ptr.is_true()
+    }
+
+    fn is_false(&self) -> bool {
+        self.ptr.is_false()
+    }
+
+    fn evaluate(&self, assignment: Vec<bool>) -> PyResult<bool> {
+        check_assignment(&assignment, self.builder.num_vars())?;
+        Ok(self.ptr.evaluate(&assignment))
+    }
+
+    fn count_nodes(&self) -> usize {
+        self.ptr.count_nodes()
+    }
+
+    fn wmc(&self, params: &PyWmcParams) -> f64 {
+        self.ptr
+            .unsmoothed_wmc(&params.params(self.builder.num_vars()))
+            .0
+    }
+
+    /// the most likely assignment to `vars`, with its probability
+    fn marginal_map(&self, vars: Vec<u64>, params: &PyWmcParams) -> (f64, Assignment) {
+        marginal_map(self.ptr, vars, params, self.builder.num_vars())
+    }
+
+    /// the decisions maximizing expected utility, with their probability and
+    /// utility; `weights` maps labels to `((low p, low u), (high p, high u))`
+    fn meu(&self, decision_vars: Vec<u64>, weights: EuWeights) -> (f64, f64, Assignment) {
+        meu(self.ptr, decision_vars, weights, self.builder.num_vars())
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(&BDDSerializer::from_bdd(self.ptr)).unwrap()
+    }
+
+    fn __repr__(&self) -> String {
+        self.ptr.print_bdd()
+    }
+}
@@ -0,0 +1,162 @@
+use super::{
+    check_assignment, extend, marginal_map, meu, var_label, Assignment, EuWeights, PyCnf,
+    PyVarOrder, PyWmcParams,
+};
+use crate::{
+    builder::{
+        decision_nnf::{
+            DecisionNNFBuilder, SemanticDecisionNNFBuilder, StandardDecisionNNFBuilder,
+        },
+        TopDownBuilder,
+    },
+    constants::primes,
+    repr::{BddPtr, DDNNFPtr, VarLabel, VarOrder, VarSet},
+    serialize::BDDSerializer,
+};
+use pyo3::prelude::*;
+use std::rc::Rc;
+
+enum Builder {
+    Standard(StandardDecisionNNFBuilder<'static>),
+    Semantic(SemanticDecisionNNFBuilder<'static, { primes::U64_LARGEST }>),
+}
+
+impl Builder {
+    fn compile(&'static self, cnf: &PyCnf, projection: Option<&VarSet>) -> BddPtr<'static> {
+        match (self, projection) {
+            (Builder::Standard(b), None) => b.compile_cnf_topdown(&cnf.cnf),
+            (Builder::Standard(b), Some(p)) => b.compile_cnf_topdown_projected(&cnf.cnf, p),
+            (Builder::Semantic(b), None) => b.compile_cnf_topdown(&cnf.cnf),
+            (Builder::Semantic(b), Some(p)) => b.compile_cnf_topdown_projected(&cnf.cnf, p),
+        }
+    }
+
+    fn condition(
+        &'static self,
+        ptr: BddPtr<'static>,
+        label: VarLabel,
+        value: bool,
+    ) -> BddPtr<'static> {
+        match self {
+            Builder::Standard(b) => b.condition(ptr, label, value),
+            Builder::Semantic(b) => b.condition(ptr, label, value),
+        }
+    }
+
+    fn order(&'static self) -> &'static VarOrder {
+        match self {
+            Builder::Standard(b) => b.order(),
+            Builder::Semantic(b) => b.order(),
+        }
+    }
+}
+
+/// Compiles CNFs top-down into decision-DNNFs; the semantic variant
+/// deduplicates nodes by their weighted model count in a finite field
+#[pyclass(name = "DecisionNnfBuilder", module = "rsdd", unsendable)]
+pub struct PyDecisionNnfBuilder {
+    builder: Rc<Builder>,
+}
+
+#[pymethods]
+impl PyDecisionNnfBuilder {
+    #[new]
+    #[pyo3(signature = (order, semantic = false))]
+    fn new(order: &PyVarOrder, semantic: bool) -> PyDecisionNnfBuilder {
+        let order = order.order.clone();
+        let builder = if semantic {
+            Builder::Semantic(SemanticDecisionNNFBuilder::new(order))
+        } else {
+            Builder::Standard(StandardDecisionNNFBuilder::new(order))
+        };
+        PyDecisionNnfBuilder {
+            builder: Rc::new(builder),
+        }
+    }
+
+    /// compiles `cnf`, existentially quantifying every variable outside
+    /// `projection` if one is given
+    #[pyo3(signature = (cnf, projection = None))]
+    fn compile_cnf(&self, cnf: &PyCnf, projection: Option<Vec<u64>>) -> PyDecisionNnf {
+        let projection = projection.map(|p| {
+            let mut set = VarSet::new();
+            p.into_iter().for_each(|v| set.insert(VarLabel::new(v)));
+            set
+        });
+        PyDecisionNnf {
+            builder: self.builder.clone(),
+            ptr: extend(&self.builder).compile(cnf, projection.as_ref()),
+        }
+    }
+
+    #[getter]
+    fn num_vars(&self) -> usize {
+        extend(&self.builder).order().num_vars()
+    }
+}
+
+/// A decision-DNNF, which keeps its builder alive
+#[pyclass(name = "DecisionNnf", module = "rsdd", unsendable)]
+#[derive(Clone)]
+pub struct PyDecisionNnf {
+    builder: Rc<Builder>,
+    ptr: BddPtr<'static>,
+}
+
+impl PyDecisionNnf {
+    fn num_vars(&self) -> usize {
+        extend(&self.builder).order().num_vars()
+    }
+}
+
+#[pymethods]
+impl PyDecisionNnf {
+    fn condition(&self, label: u64, value: bool) -> PyResult<PyDecisionNnf> {
+        let label = var_label(label, self.num_vars())?;
+        Ok(PyDecisionNnf {
+            builder: self.builder.clone(),
+            ptr: extend(&self.builder).condition(self.ptr, label, value),
+        })
+    }
+
+    fn is_true(&self) -> bool {
+        self.ptr.is_true()
+    }
+
+    fn is_false(&self) -> bool {
+        self.ptr.is_false()
+    }
+
+    fn evaluate(&self, assignment: Vec<bool>) -> PyResult<bool> {
+        check_assignment(&assignment, self.num_vars())?;
+        Ok(self.ptr.evaluate(&assignment))
+    }
+
+    fn count_nodes(&self) -> usize {
+        self.ptr.count_nodes()
+    }
+
+    fn wmc(&self, params: &PyWmcParams) -> f64 {
+        self.ptr.unsmoothed_wmc(&params.params(self.num_vars())).0
+    }
+
+    /// the most likely assignment to `vars`, with its probability
+    fn marginal_map(&self, vars: Vec<u64>, params: &PyWmcParams) -> (f64, Assignment) {
+        marginal_map(self.ptr, vars, params, self.num_vars())
+    }
+
+    /// the decisions maximizing expected utility, with their probability and
+    /// utility; `weights` maps labels to `((low p, low u), (high p, high u))`
+    fn meu(&self, decision_vars: Vec<u64>, weights: EuWeights) -> (f64, f64, Assignment) {
+        meu(self.ptr, decision_vars, weights, self.num_vars())
+    }
+
+    /// the decision-DNNF as JSON, in the format of `Bdd.to_json`
+    fn to_json(&self) -> String {
+        serde_json::to_string(&BDDSerializer::from_bdd(self.ptr)).unwrap()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DecisionNnf({} nodes)", self.ptr.count_nodes())
+    }
+}
@@ -0,0 +1,259 @@
+//! Python bindings, built with e.g. `maturin develop` (see `pyproject.toml`)
+//!
+//! Builders hand out `'static` pointers into their own tables. Every handle
+//! given to Python therefore owns an `Rc` of the builder that made it, which
+//! keeps those tables alive for as long as the handle is reachable, and
+//! operations check that their operands share a builder.
+//! ```python
+//! import rsdd
+//!
+//! builder = rsdd.BddBuilder(rsdd.VarOrder.linear(2))
+//! x, y = builder.var(0), builder.var(1)
+//! f = (x & ~y) | (~x & y)
+//! assert f == x ^ y
+//! assert f.exists(0) == builder.true()
+//! ```
+mod bdd;
+mod dnnf;
+mod repr;
+mod sdd;
+
+pub use self::bdd::*;
+pub use self::dnnf::*;
+pub use self::repr::*;
+pub use self::sdd::*;
+
+use crate::{
+    repr::{BddPtr, ParseError, PartialModel, VarLabel, WmcParams},
+    util::semirings::ExpectedUtility,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::{collections::HashMap, rc::Rc};
+
+/// an assignment to some labels
+type Assignment = HashMap<u64, bool>;
+
+/// per-label `((low probability, low utility), (high probability, high
+/// utility))`
+type EuWeights = HashMap<u64, ((f64, f64), (f64, f64))>;
+
+/// borrows the builder behind `owner` for as long as pointers into it
+/// live; sound because those pointers are only handed out next to a clone
+/// of `owner`
+fn extend<T>(owner: &Rc<T>) -> &'static T {
+    unsafe { &*Rc::as_ptr(owner) }
+}
+
+/// fails unless `a` and `b` were made by the same builder
+fn same_builder<T>(a: &Rc<T>, b: &Rc<T>) -> PyResult<()> {
+    if Rc::ptr_eq(a, b) {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(
+            "operands come from different builders",
+        ))
+    }
+}
+
+/// `label`, unless it is outside the `num_vars` variables of a builder
+fn var_label(label: u64, num_vars: usize) -> PyResult<VarLabel> {
+    if (label as usize) < num_vars {
+        Ok(VarLabel::new(label))
+    } else {
+        Err(PyValueError::new_err(format!(
+            "variable {} is not among the builder's {} variables",
+            label, num_vars
+        )))
+    }
+}
+
+/// fails unless `assignment` gives a value to each of `num_vars` variables
+fn check_assignment(assignment: &[bool], num_vars: usize) -> PyResult<()> {
+    if assignment.len() >= num_vars {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "expected an assignment to {} variables, found {}",
+            num_vars,
+            assignment.len()
+        )))
+    }
+}
+
+fn parse_error(err: ParseError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// the assignment `model` makes to `vars`
+fn assignment(model: &PartialModel, vars: &[VarLabel]) -> Assignment {
+    vars.iter()
+        .filter_map(|v| model.get(*v).map(|value| (v.value(), value)))
+        .collect()
+}
+
+/// the labels `0..num_vars` and every label in `vars`
+fn num_vars_with(num_vars: usize, vars: &[u64]) -> usize {
+    vars.iter()
+        .map(|v| *v as usize + 1)
+        .fold(num_vars, usize::max)
+}
+
+/// the most likely assignment to `vars` and its probability (see
+/// [`BddPtr::marginal_map`])
+fn marginal_map(
+    ptr: BddPtr<'_>,
+    vars: Vec<u64>,
+    params: &PyWmcParams,
+    num_vars: usize,
+) -> (f64, Assignment) {
+    let num_vars = num_vars_with(num_vars, &vars);
+    let vars: Vec<VarLabel> = vars.into_iter().map(VarLabel::new).collect();
+    let (p, model) = ptr.marginal_map(&vars, num_vars, &params.params(num_vars));
+    (p, assignment(&model, &vars))
+}
+
+/// the decisions maximizing expected utility, with their probability and
+/// utility (see [`BddPtr::meu`])
+fn meu(
+    ptr: BddPtr<'_>,
+    decision_vars: Vec<u64>,
+    weights: EuWeights,
+    num_vars: usize,
+) -> (f64, f64, Assignment) {
+    let num_vars = num_vars_with(num_vars, &decision_vars);
+    let num_vars = num_vars_with(num_vars, &weights.keys().copied().collect::<Vec<_>>());
+    let mut params = WmcParams::default();
+    for v in 0..num_vars as u64 {
+        let ((lp, lu), (hp, hu)) = weights.get(&v).copied().unwrap_or(((1.0, 0.0), (1.0, 0.0)));
+        params.set_weight(
+            VarLabel::new(v),
+            ExpectedUtility(lp, lu),
+            ExpectedUtility(hp, hu),
+        );
+    }
+    let vars: Vec<VarLabel> = decision_vars.into_iter().map(VarLabel::new).collect();
+    let (eu, model) = ptr.meu(&vars, num_vars, &params);
+    (eu.0, eu.1, assignment(&model, &vars))
+}
+
+#[pymodule]
+fn rsdd(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCnf>()?;
+    m.add_class::<PyVarOrder>()?;
+    m.add_class::<PyVTree>()?;
+    m.add_class::<PyWmcParams>()?;
+    m.add_class::<PyBddBuilder>()?;
+    m.add_class::<PyBdd>()?;
+    m.add_class::<PySddBuilder>()?;
+    m.add_class::<PySdd>()?;
+    m.add_class::<PyDecisionNnfBuilder>()?;
+    m.add_class::<PyDecisionNnf>()?;
+    Ok(())
+}
+
+/// runs `code` in an embedded interpreter, where `import rsdd` finds this
+/// module
+#[cfg(test)]
+fn run_python(code: &str) -> PyResult<()> {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let module = PyModule::new(py, "rsdd")?;
+        rsdd(&module)?;
+        py.import("sys")?
+            .getattr("modules")?
+            .set_item("rsdd", module)?;
+        py.run(&std::ffi::CString::new(code)?, None, None)
+    })
+}
+
+#[test]
+fn python_module_example() {
+    run_python(
+        r#"
+import rsdd
+
+builder = rsdd.BddBuilder(rsdd.VarOrder.linear(2))
+x, y = builder.var(0), builder.var(1)
+f = (x & ~y) | (~x & y)
+assert f == x ^ y
+assert f.exists(0) == builder.true()
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn python_rejects_unknown_labels() {
+    run_python(
+        r#"
+import rsdd
+
+def raises_value_error(f):
+    try:
+        f()
+    except ValueError:
+        return True
+    return False
+
+bdds = rsdd.BddBuilder(rsdd.VarOrder.linear(2))
+x = bdds.var(0)
+assert raises_value_error(lambda: bdds.var(2))
+assert raises_value_error(lambda: x.exists(2))
+assert raises_value_error(lambda: x.forall(7))
+assert raises_value_error(lambda: x.condition(2, True))
+assert raises_value_error(lambda: x.compose(2, x))
+assert raises_value_error(lambda: x.evaluate([True]))
+
+sdds = rsdd.SddBuilder(rsdd.VTree.right_linear([1, 0]))
+s = sdds.var(1)
+assert raises_value_error(lambda: sdds.var(2))
+assert raises_value_error(lambda: s.exists(2))
+assert raises_value_error(lambda: s.forall(5))
+assert raises_value_error(lambda: s.condition(2, False))
+assert raises_value_error(lambda: s.evaluate([True]))
+
+nnfs = rsdd.DecisionNnfBuilder(rsdd.VarOrder.linear(2))
+n = nnfs.compile_cnf(rsdd.Cnf([[1, 2]]))
+assert raises_value_error(lambda: n.condition(2, True))
+assert not n.condition(0, True).is_false()
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn python_handles_outlive_their_builder() {
+    run_python(
+        r#"
+import gc
+import rsdd
+
+def make_bdds():
+    builder = rsdd.BddBuilder(rsdd.VarOrder.linear(2))
+    return builder.var(0), builder.var(1)
+
+def make_sdds():
+    builder = rsdd.SddBuilder(rsdd.VTree.right_linear([0, 1]))
+    return builder.var(0), builder.var(1)
+
+for make in (make_bdds, make_sdds):
+    x, y = make()
+    gc.collect()
+    f = (x & ~y) | (~x & y)
+    assert f == x ^ y
+    assert f.evaluate([True, False])
+    assert not f.evaluate([True, True])
+
+    # operands from different builders are rejected, not mixed
+    other, _ = make()
+    try:
+        x & other
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("mixed builders")
+    assert x != other
+"#,
+    )
+    .unwrap();
+}
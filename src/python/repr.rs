@@ -0,0 +1,256 @@
+use super::parse_error;
+use crate::{
+    repr::{Cnf, DTree, Literal, VTree, VarLabel, VarOrder, WmcParams},
+    serialize::VTreeSerializer,
+    util::semirings::RealSemiring,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::collections::HashMap;
+
+/// A CNF; clauses are lists of DIMACS literals, so variable `v` has label
+/// `v - 1`
+#[pyclass(name = "Cnf", module = "rsdd")]
+#[derive(Clone)]
+pub struct PyCnf {
+    pub(super) cnf: Cnf,
+}
+
+#[pymethods]
+impl PyCnf {
+    #[new]
+    fn new(clauses: Vec<Vec<i64>>) -> PyResult<PyCnf> {
+        let clauses = clauses
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|l| match l {
+                        0 => Err(PyValueError::new_err("0 is not a DIMACS literal")),
+                        _ => Ok(Literal::new(VarLabel::new(l.unsigned_abs() - 1), *l > 0)),
+                    })
+                    .collect()
+            })
+            .collect::<PyResult<Vec<Vec<Literal>>>>()?;
+        Ok(PyCnf {
+            cnf: Cnf::new(&clauses),
+        })
+    }
+
+    #[staticmethod]
+    fn from_dimacs(dimacs: &str) -> PyResult<PyCnf> {
+        Cnf::from_dimacs(dimacs)
+            .map(|cnf| PyCnf { cnf })
+            .map_err(parse_error)
+    }
+
+    fn to_dimacs(&self) -> String {
+        self.cnf.to_dimacs()
+    }
+
+    #[getter]
+    fn num_vars(&self) -> usize {
+        self.cnf.num_vars()
+    }
+
+    fn clauses(&self) -> Vec<Vec<i64>> {
+        self.cnf
+            .clauses()
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|l| {
+                        let v = l.label().value() as i64 + 1;
+                        if l.polarity() {
+                            v
+                        } else {
+                            -v
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn linear_order(&self) -> PyVarOrder {
+        PyVarOrder {
+            order: self.cnf.linear_order(),
+        }
+    }
+
+    fn min_fill_order(&self) -> PyVarOrder {
+        PyVarOrder {
+            order: self.cnf.min_fill_order(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Cnf({} variables, {} clauses)",
+            self.cnf.num_vars(),
+            self.cnf.clauses().len()
+        )
+    }
+}
+
+/// fails unless `labels` is a permutation of `0..labels.len()`
+fn check_permutation(labels: &[u64]) -> PyResult<Vec<VarLabel>> {
+    let mut seen = vec![false; labels.len()];
+    for l in labels {
+        match seen.get_mut(*l as usize) {
+            Some(seen) if !*seen => *seen = true,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "expected each of the labels 0..{} exactly once",
+                    labels.len()
+                )))
+            }
+        }
+    }
+    Ok(labels.iter().map(|l| VarLabel::new(*l)).collect())
+}
+
+/// An ordering of the labels `0..n`, first to last
+#[pyclass(name = "VarOrder", module = "rsdd")]
+#[derive(Clone)]
+pub struct PyVarOrder {
+    pub(super) order: VarOrder,
+}
+
+#[pymethods]
+impl PyVarOrder {
+    #[new]
+    fn new(labels: Vec<u64>) -> PyResult<PyVarOrder> {
+        Ok(PyVarOrder {
+            order: VarOrder::new(&check_permutation(&labels)?),
+        })
+    }
+
+    #[staticmethod]
+    fn linear(num_vars: usize) -> PyVarOrder {
+        PyVarOrder {
+            order: VarOrder::linear_order(num_vars),
+        }
+    }
+
+    fn labels(&self) -> Vec<u64> {
+        self.order.in_order_iter().map(|v| v.value()).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.order.num_vars()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("VarOrder({:?})", self.labels())
+    }
+}
+
+/// A vtree, which fixes the structure of the SDDs built over it
+#[pyclass(name = "VTree", module = "rsdd")]
+#[derive(Clone)]
+pub struct PyVTree {
+    pub(super) vtree: VTree,
+}
+
+impl PyVTree {
+    fn from_order(labels: Vec<u64>, make: impl FnOnce(&[VarLabel]) -> VTree) -> PyResult<PyVTree> {
+        if labels.is_empty() {
+            return Err(PyValueError::new_err("a vtree needs at least one variable"));
+        }
+        Ok(PyVTree {
+            vtree: make(&check_permutation(&labels)?),
+        })
+    }
+}
+
+#[pymethods]
+impl PyVTree {
+    #[staticmethod]
+    fn left_linear(labels: Vec<u64>) -> PyResult<PyVTree> {
+        PyVTree::from_order(labels, VTree::left_linear)
+    }
+
+    #[staticmethod]
+    fn right_linear(labels: Vec<u64>) -> PyResult<PyVTree> {
+        PyVTree::from_order(labels, VTree::right_linear)
+    }
+
+    /// halves `labels` `num_splits` times, then continues right-linearly
+    #[staticmethod]
+    fn even_split(labels: Vec<u64>, num_splits: usize) -> PyResult<PyVTree> {
+        PyVTree::from_order(labels, |order| VTree::even_split(order, num_splits))
+    }
+
+    /// a vtree following the decomposition of `cnf` along `elim_order`
+    #[staticmethod]
+    fn from_cnf(cnf: &PyCnf, elim_order: &PyVarOrder) -> PyResult<PyVTree> {
+        VTree::from_dtree(&DTree::from_cnf(&cnf.cnf, &elim_order.order))
+            .map(|vtree| PyVTree { vtree })
+            .ok_or_else(|| PyValueError::new_err("the CNF has no variables"))
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<PyVTree> {
+        serde_json::from_str::<VTreeSerializer>(json)
+            .map(|ser| PyVTree {
+                vtree: ser.to_vtree(),
+            })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(&VTreeSerializer::from_vtree(&self.vtree)).unwrap()
+    }
+
+    #[getter]
+    fn num_vars(&self) -> usize {
+        self.vtree.num_vars()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("VTree({} variables)", self.vtree.num_vars())
+    }
+}
+
+/// Real weights `(low, high)` for each label; unset labels weigh `(1, 1)`
+#[pyclass(name = "WmcParams", module = "rsdd")]
+#[derive(Clone)]
+pub struct PyWmcParams {
+    weights: HashMap<u64, (f64, f64)>,
+}
+
+impl PyWmcParams {
+    /// the weights of the labels `0..num_vars`, and of any other set label
+    pub(super) fn params(&self, num_vars: usize) -> WmcParams<RealSemiring> {
+        let mut params = WmcParams::default();
+        for v in 0..num_vars as u64 {
+            params.set_weight(VarLabel::new(v), RealSemiring(1.0), RealSemiring(1.0));
+        }
+        for (v, (l, h)) in self.weights.iter() {
+            params.set_weight(VarLabel::new(*v), RealSemiring(*l), RealSemiring(*h));
+        }
+        params
+    }
+}
+
+#[pymethods]
+impl PyWmcParams {
+    #[new]
+    #[pyo3(signature = (weights = HashMap::new()))]
+    fn new(weights: HashMap<u64, (f64, f64)>) -> PyWmcParams {
+        PyWmcParams { weights }
+    }
+
+    fn set_weight(&mut self, label: u64, low: f64, high: f64) {
+        self.weights.insert(label, (low, high));
+    }
+
+    fn var_weight(&self, label: u64) -> (f64, f64) {
+        self.weights.get(&label).copied().unwrap_or((1.0, 1.0))
+    }
+
+    fn __repr__(&self) -> String {
+        let mut weights: Vec<_> = self.weights.iter().collect();
+        weights.sort_by_key(|(v, _)| **v);
+        format!("WmcParams({:?})", weights)
+    }
+}
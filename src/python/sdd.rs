@@ -0,0 +1,221 @@
+use super::{check_assignment, extend, same_builder, PyCnf, PyVTree, PyWmcParams};
+use crate::{
+    builder::{
+        sdd::{CompressionSddBuilder, SddBuilder},
+        BottomUpBuilder,
+    },
+    repr::{DDNNFPtr, SddPtr, VTree, VarLabel},
+    serialize::SDDSerializer,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+type Builder = CompressionSddBuilder<'static>;
+
+/// `label`, unless it is not a leaf of the vtree of `builder`
+fn vtree_label(builder: &Builder, label: u64) -> PyResult<VarLabel> {
+    let manager = builder.vtree_manager();
+    let label = VarLabel::new(label);
+    if label.value_usize() < manager.vtree_root().num_vars()
+        && *manager.vtree(manager.var_index(label)) == VTree::new_leaf(label)
+    {
+        Ok(label)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "variable {} is not in the builder's vtree",
+            label.value()
+        )))
+    }
+}
+
+/// Builds compressed SDDs over a fixed vtree
+#[pyclass(name = "SddBuilder", module = "rsdd", unsendable)]
+pub struct PySddBuilder {
+    builder: Rc<Builder>,
+}
+
+impl PySddBuilder {
+    fn wrap(&self, ptr: SddPtr<'static>) -> PySdd {
+        PySdd {
+            builder: self.builder.clone(),
+            ptr,
+        }
+    }
+}
+
+#[pymethods]
+impl PySddBuilder {
+    #[new]
+    fn new(vtree: &PyVTree) -> PySddBuilder {
+        PySddBuilder {
+            builder: Rc::new(CompressionSddBuilder::new(vtree.vtree.clone())),
+        }
+    }
+
+    #[pyo3(signature = (label, polarity = true))]
+    fn var(&self, label: u64, polarity: bool) -> PyResult<PySdd> {
+        let label = vtree_label(&self.builder, label)?;
+        Ok(self.wrap(extend(&self.builder).var(label, polarity)))
+    }
+
+    fn r#true(&self) -> PySdd {
+        self.wrap(extend(&self.builder).true_ptr())
+    }
+
+    fn r#false(&self) -> PySdd {
+        self.wrap(extend(&self.builder).false_ptr())
+    }
+
+    fn compile_cnf(&self, cnf: &PyCnf) -> PySdd {
+        self.wrap(extend(&self.builder).compile_cnf(&cnf.cnf))
+    }
+
+    /// rebuilds an SDD serialized by `Sdd.to_json`
+    fn load_json(&self, json: &str) -> PyResult<PySdd> {
+        let ser: SDDSerializer =
+            serde_json::from_str(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let roots = ser
+            .try_into_builder(extend(&self.builder))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        match roots[..] {
+            [ptr] => Ok(self.wrap(ptr)),
+            _ => Err(PyValueError::new_err(format!(
+                "expected one root, found {}",
+                roots.len()
+            ))),
+        }
+    }
+
+    fn vtree(&self) -> PyVTree {
+        PyVTree {
+            vtree: self.builder.vtree_manager().vtree_root().clone(),
+        }
+    }
+
+    #[getter]
+    fn num_vars(&self) -> usize {
+        self.builder.vtree_manager().vtree_root().num_vars()
+    }
+}
+
+/// An SDD, which keeps its builder alive
+#[pyclass(name = "Sdd", module = "rsdd", unsendable)]
+#[derive(Clone)]
+pub struct PySdd {
+    builder: Rc<Builder>,
+    ptr: SddPtr<'static>,
+}
+
+impl PySdd {
+    fn builder(&self) -> &'static Builder {
+        extend(&self.builder)
+    }
+
+    fn wrap(&self, ptr: SddPtr<'static>) -> PySdd {
+        PySdd {
+            builder: self.builder.clone(),
+            ptr,
+        }
+    }
+
+    fn apply(
+        &self,
+        other: &PySdd,
+        op: impl FnOnce(&'static Builder, SddPtr<'static>, SddPtr<'static>) -> SddPtr<'static>,
+    ) -> PyResult<PySdd> {
+        same_builder(&self.builder, &other.builder)?;
+        Ok(self.wrap(op(self.builder(), self.ptr, other.ptr)))
+    }
+}
+
+#[pymethods]
+impl PySdd {
+    fn __and__(&self, other: &PySdd) -> PyResult<PySdd> {
+        self.apply(other, |b, f, g| b.and(f, g))
+    }
+
+    fn __or__(&self, other: &PySdd) -> PyResult<PySdd> {
+        self.apply(other, |b, f, g| b.or(f, g))
+    }
+
+    fn __xor__(&self, other: &PySdd) -> PyResult<PySdd> {
+        self.apply(other, |b, f, g| b.xor(f, g))
+    }
+
+    fn __invert__(&self) -> PySdd {
+        self.wrap(self.builder().negate(self.ptr))
+    }
+
+    /// compressed SDDs are canonical, so this is semantic equivalence
+    fn __eq__(&self, other: &PySdd) -> bool {
+        Rc::ptr_eq(&self.builder, &other.builder) && self.builder().eq(self.ptr, other.ptr)
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.ptr.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn iff(&self, other: &PySdd) -> PyResult<PySdd> {
+        self.apply(other, |b, f, g| b.iff(f, g))
+    }
+
+    /// `if self then g else h`
+    fn ite(&self, g: &PySdd, h: &PySdd) -> PyResult<PySdd> {
+        same_builder(&self.builder, &g.builder)?;
+        same_builder(&self.builder, &h.builder)?;
+        Ok(self.wrap(self.builder().ite(self.ptr, g.ptr, h.ptr)))
+    }
+
+    fn exists(&self, label: u64) -> PyResult<PySdd> {
+        let label = vtree_label(&self.builder, label)?;
+        Ok(self.wrap(self.builder().exists(self.ptr, label)))
+    }
+
+    fn forall(&self, label: u64) -> PyResult<PySdd> {
+        let label = vtree_label(&self.builder, label)?;
+        Ok(self.wrap(self.builder().forall(self.ptr, label)))
+    }
+
+    fn condition(&self, label: u64, value: bool) -> PyResult<PySdd> {
+        let label = vtree_label(&self.builder, label)?;
+        Ok(self.wrap(self.builder().condition(self.ptr, label, value)))
+    }
+
+    fn is_true(&self) -> bool {
+        self.ptr.is_true()
+    }
+
+    fn is_false(&self) -> bool {
+        self.ptr.is_false()
+    }
+
+    fn evaluate(&self, assignment: Vec<bool>) -> PyResult<bool> {
+        let num_vars = self.builder.vtree_manager().vtree_root().num_vars();
+        check_assignment(&assignment, num_vars)?;
+        Ok(self.ptr.evaluate(&assignment))
+    }
+
+    fn count_nodes(&self) -> usize {
+        self.ptr.count_nodes()
+    }
+
+    fn wmc(&self, params: &PyWmcParams) -> f64 {
+        let num_vars = self.builder.vtree_manager().vtree_root().num_vars();
+        self.ptr.unsmoothed_wmc(&params.params(num_vars)).0
+    }
+
+    /// the SDD as JSON; its vtree is `SddBuilder.vtree().to_json()`
+    fn to_json(&self) -> String {
+        serde_json::to_string(&SDDSerializer::from_sdd(self.ptr)).unwrap()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Sdd({} nodes)", self.ptr.count_nodes())
+    }
+}
@@ -0,0 +1,295 @@
+//! Discrete Bayesian networks, and their encoding as weighted CNFs
+//!
+//! Networks are read in the JSON format of the files in `bayesian_networks/`:
+//! ```json
+//! { "variables": ["A", "B"],
+//!   "states": { "A": ["t", "f"], "B": ["t", "f"] },
+//!   "parents": { "A": [], "B": ["A"] },
+//!   "cpts": { "A": [[0.3], [0.7]], "B": [[0.9, 0.2], [0.1, 0.8]] } }
+//! ```
+//! where `cpts[X][i][j]` is the probability that `X` is in its `i`th state
+//! given the `j`th assignment to its parents, enumerated with the last parent
+//! varying fastest.
+
+use std::collections::HashMap;
+
+use crate::{
+    repr::{Cnf, Literal, VarLabel, WmcParams},
+    util::semirings::RealSemiring,
+};
+
+/// A variable of a [`BayesianNetwork`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BnVariable {
+    pub name: String,
+    pub states: Vec<String>,
+    /// indices of the parents among the network's variables
+    pub parents: Vec<usize>,
+    /// `cpt[i][j]` is the probability of state `i` given the `j`th
+    /// assignment to the parents
+    pub cpt: Vec<Vec<f64>>,
+}
+
+/// An acyclic network of discrete variables, each with a conditional
+/// probability table given its parents
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "BnJson")]
+pub struct BayesianNetwork {
+    variables: Vec<BnVariable>,
+}
+
+#[derive(Deserialize)]
+struct BnJson {
+    variables: Vec<String>,
+    states: HashMap<String, Vec<String>>,
+    parents: HashMap<String, Vec<String>>,
+    cpts: HashMap<String, Vec<Vec<f64>>>,
+}
+
+fn lookup<T: Clone>(map: &HashMap<String, T>, v: &String, what: &str) -> Result<T, String> {
+    map.get(v)
+        .cloned()
+        .ok_or_else(|| format!("variable `{}` has no {}", v, what))
+}
+
+impl TryFrom<BnJson> for BayesianNetwork {
+    type Error = String;
+
+    fn try_from(json: BnJson) -> Result<BayesianNetwork, String> {
+        let index: HashMap<&String, usize> = json
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v, i))
+            .collect();
+        let mut variables = Vec::with_capacity(json.variables.len());
+        for name in json.variables.iter() {
+            let states: Vec<String> = lookup(&json.states, name, "states")?;
+            let parents = lookup(&json.parents, name, "parents")?
+                .iter()
+                .map(|p| {
+                    index
+                        .get(p)
+                        .copied()
+                        .ok_or_else(|| format!("`{}` has unknown parent `{}`", name, p))
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
+            let cpt = lookup(&json.cpts, name, "CPT")?;
+            variables.push(BnVariable {
+                name: name.clone(),
+                states,
+                parents,
+                cpt,
+            });
+        }
+        BayesianNetwork::new(variables)
+    }
+}
+
+impl BayesianNetwork {
+    /// Creates a network, checking that it is acyclic and that every CPT has
+    /// a row per state and a column per parent assignment
+    pub fn new(variables: Vec<BnVariable>) -> Result<BayesianNetwork, String> {
+        for v in variables.iter() {
+            if v.states.is_empty() {
+                return Err(format!("variable `{}` has no states", v.name));
+            }
+            if let Some(p) = v.parents.iter().find(|p| **p >= variables.len()) {
+                return Err(format!("`{}` has unknown parent {}", v.name, p));
+            }
+            let columns: usize = v
+                .parents
+                .iter()
+                .map(|p| variables[*p].states.len())
+                .product();
+            if v.cpt.len() != v.states.len() || v.cpt.iter().any(|row| row.len() != columns) {
+                return Err(format!(
+                    "the CPT of `{}` should be {} by {}",
+                    v.name,
+                    v.states.len(),
+                    columns
+                ));
+            }
+        }
+        let bn = BayesianNetwork { variables };
+        bn.check_acyclic()?;
+        Ok(bn)
+    }
+
+    pub fn variables(&self) -> &[BnVariable] {
+        &self.variables
+    }
+
+    fn check_acyclic(&self) -> Result<(), String> {
+        // 0: unvisited, 1: on the current path, 2: done
+        fn visit(bn: &BayesianNetwork, v: usize, mark: &mut [u8]) -> Result<(), String> {
+            match mark[v] {
+                1 => return Err(format!("`{}` is its own ancestor", bn.variables[v].name)),
+                2 => return Ok(()),
+                _ => (),
+            }
+            mark[v] = 1;
+            for p in bn.variables[v].parents.iter() {
+                visit(bn, *p, mark)?;
+            }
+            mark[v] = 2;
+            Ok(())
+        }
+        let mut mark = vec![0; self.variables.len()];
+        for v in 0..self.variables.len() {
+            visit(self, v, &mut mark)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the network as a CNF whose weighted model count under the
+    /// returned weights is the probability of its evidence.
+    ///
+    /// Each state of each variable gets an indicator, exactly one of which
+    /// holds. Each CPT column then chooses a state through a chain of
+    /// "flip" variables: the `i`th flip holds with the probability of the
+    /// `i`th state given that no earlier state was chosen. Indicators weigh
+    /// `(1, 1)` and flips `(1 - q, q)`, so every variable that a diagram of
+    /// the CNF skips has weights summing to 1, and unsmoothed counts are
+    /// exact.
+    /// ```
+    /// use rsdd::{
+    ///     builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder},
+    ///     repr::{BayesianNetwork, BddPtr, DDNNFPtr},
+    /// };
+    ///
+    /// let bn: BayesianNetwork = serde_json::from_str(r#"{
+    ///     "variables": ["A", "B"],
+    ///     "states": {"A": ["t", "f"], "B": ["t", "f"]},
+    ///     "parents": {"A": [], "B": ["A"]},
+    ///     "cpts": {"A": [[0.3], [0.7]], "B": [[0.9, 0.2], [0.1, 0.8]]}
+    /// }"#).unwrap();
+    /// let encoding = bn.encode();
+    /// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(encoding.cnf.num_vars());
+    /// let bdd = builder.compile_cnf(&encoding.cnf);
+    /// // P(B = t) = 0.3 * 0.9 + 0.7 * 0.2
+    /// let b = builder.and(bdd, builder.var(encoding.indicators[1][0], true));
+    /// assert!((b.unsmoothed_wmc(&encoding.weights).0 - 0.41).abs() < 1e-9);
+    /// ```
+    pub fn encode(&self) -> BnEncoding {
+        let mut names = Vec::new();
+        let mut indicators = Vec::with_capacity(self.variables.len());
+        for v in self.variables.iter() {
+            indicators.push(
+                v.states
+                    .iter()
+                    .map(|s| {
+                        names.push(format!("{}={}", v.name, s));
+                        VarLabel::new_usize(names.len() - 1)
+                    })
+                    .collect::<Vec<VarLabel>>(),
+            );
+        }
+        let mut weights = WmcParams::default();
+        let one = RealSemiring(1.0);
+        for label in 0..names.len() {
+            weights.set_weight(VarLabel::new_usize(label), one, one);
+        }
+
+        let mut clauses: Vec<Vec<Literal>> = Vec::new();
+        for (x, v) in self.variables.iter().enumerate() {
+            let states = &indicators[x];
+            // exactly one state holds
+            clauses.push(states.iter().map(|l| Literal::new(*l, true)).collect());
+            for (i, a) in states.iter().enumerate() {
+                for b in states[i + 1..].iter() {
+                    clauses.push(vec![Literal::new(*a, false), Literal::new(*b, false)]);
+                }
+            }
+
+            let radices: Vec<usize> = v
+                .parents
+                .iter()
+                .map(|p| self.variables[*p].states.len())
+                .collect();
+            let num_columns: usize = radices.iter().product();
+            for column in 0..num_columns {
+                // negated indicators of this column's parent states, last
+                // parent varying fastest
+                let mut guard = Vec::with_capacity(radices.len());
+                let mut rest = column;
+                for (p, radix) in v.parents.iter().zip(radices.iter()).rev() {
+                    guard.push(Literal::new(indicators[*p][rest % radix], false));
+                    rest /= radix;
+                }
+
+                let mut remaining = 1.0;
+                let mut earlier_flips: Vec<Literal> = Vec::new();
+                for (i, state) in states.iter().enumerate() {
+                    let mut clause = guard.clone();
+                    clause.extend(earlier_flips.iter().copied());
+                    if i + 1 < states.len() {
+                        let theta = v.cpt[i][column];
+                        let q = if remaining > 0.0 {
+                            (theta / remaining).clamp(0.0, 1.0)
+                        } else {
+                            0.0
+                        };
+                        remaining -= theta;
+                        let flip = VarLabel::new_usize(names.len());
+                        names.push(format!("{}#{}.{}", v.name, column, i));
+                        weights.set_weight(flip, RealSemiring(1.0 - q), RealSemiring(q));
+                        clause.push(Literal::new(flip, false));
+                        earlier_flips.push(Literal::new(flip, true));
+                    }
+                    clause.push(Literal::new(*state, true));
+                    clauses.push(clause);
+                }
+            }
+        }
+        BnEncoding {
+            cnf: Cnf::new(&clauses),
+            weights,
+            indicators,
+            names,
+        }
+    }
+}
+
+/// The weighted CNF of a [`BayesianNetwork`], from
+/// [`BayesianNetwork::encode`]
+#[derive(Debug, Clone)]
+pub struct BnEncoding {
+    pub cnf: Cnf,
+    pub weights: WmcParams<RealSemiring>,
+    /// `indicators[v][s]` holds exactly when variable `v` is in state `s`
+    pub indicators: Vec<Vec<VarLabel>>,
+    /// a name for every label of the CNF: `variable=state` for indicators
+    pub names: Vec<String>,
+}
+
+#[test]
+fn test_bn_marginal() {
+    use crate::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+    use crate::repr::{BddPtr, DDNNFPtr};
+
+    let json = std::fs::read_to_string("bayesian_networks/cancer.json").unwrap();
+    let bn: BayesianNetwork = serde_json::from_str(&json).unwrap();
+    let encoding = bn.encode();
+    let builder =
+        RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(encoding.cnf.num_vars());
+    let bdd = builder.compile_cnf(&encoding.cnf);
+    assert!((bdd.unsmoothed_wmc(&encoding.weights).0 - 1.0).abs() < 1e-9);
+
+    // P(Cancer = True) = sum over Pollution and Smoker
+    let expected = 0.9 * (0.3 * 0.03 + 0.7 * 0.001) + 0.1 * (0.3 * 0.05 + 0.7 * 0.02);
+    let cancer = builder.and(bdd, builder.var(encoding.indicators[2][0], true));
+    assert!((cancer.unsmoothed_wmc(&encoding.weights).0 - expected).abs() < 1e-9);
+}
+
+#[test]
+fn test_bn_rejects_cycles() {
+    let json = r#"{
+        "variables": ["A", "B"],
+        "states": {"A": ["t", "f"], "B": ["t", "f"]},
+        "parents": {"A": ["B"], "B": ["A"]},
+        "cpts": {"A": [[0.3, 0.1], [0.7, 0.9]], "B": [[0.9, 0.2], [0.1, 0.8]]}
+    }"#;
+    let err = serde_json::from_str::<BayesianNetwork>(json).unwrap_err();
+    assert!(err.to_string().contains("own ancestor"));
+}
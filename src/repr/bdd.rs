@@ -1084,6 +1084,9 @@ impl<'a> PartialEq for BddNode<'a> {
     }
 }
 
+/// Hashes only the variable and children, never the scratch data, so
+/// collections keyed by `BddNode` or `BddPtr` are sound even though clippy's
+/// `mutable_key_type` lint flags them
 impl<'a> Hash for BddNode<'a> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.var.hash(state);
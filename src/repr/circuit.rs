@@ -0,0 +1,709 @@
+//! Boolean circuits read from BLIF and AIGER netlists
+//!
+//! A [`Circuit`] keeps the gate structure of the netlist as a hash-consed
+//! [`DagPlan`]: every primary input and every latch (current-state) output
+//! is a variable, and every primary output and latch next-state function is a
+//! node of the plan. Compiling a circuit therefore introduces no auxiliary
+//! (Tseitin) variables, unlike going through a CNF encoding:
+//! ```
+//! use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+//! use rsdd::repr::{BddPtr, Circuit, VarLabel};
+//!
+//! let blif = "
+//! .model majority
+//! .inputs a b c
+//! .outputs m
+//! .names a b c m
+//! 11- 1
+//! 1-1 1
+//! -11 1
+//! .end
+//! ";
+//! let circuit = Circuit::from_blif(blif).unwrap();
+//! let builder = RobddBuilder::<AllIteTable<BddPtr>>::new(circuit.fanin_order());
+//! let m = builder.compile_dag_plan(circuit.plan(), &circuit.output_nodes())[0];
+//! let [a, b, c] = [0, 1, 2].map(|v| builder.var(VarLabel::new(v), true));
+//! let expected = builder.or(builder.and(a, builder.or(b, c)), builder.and(b, c));
+//! assert_eq!(m, expected);
+//! ```
+//!
+//! Supported are the combinational subset of BLIF (`.model`, `.inputs`,
+//! `.outputs`, `.names`, `.latch`, `.end`) and the ASCII (`aag`) and binary
+//! (`aig`) AIGER formats without the 1.9 extensions (bad states,
+//! constraints, justice and fairness properties).
+
+use super::{ParseError, VarLabel, VarOrder};
+use crate::plan::{DagPlan, PlanNode, PlanNodeId};
+use rustc_hash::FxHashMap;
+use std::{collections::HashSet, fmt::Display, hash::Hash};
+
+/// A latch of a sequential circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Latch {
+    pub name: String,
+    /// the variable holding the current state of the latch
+    pub var: VarLabel,
+    /// the next-state function
+    pub next: PlanNodeId,
+    /// the initial value, if it is specified
+    pub init: Option<bool>,
+}
+
+/// A circuit whose gates are the nodes of a [`DagPlan`]. Input `i` is
+/// variable `i`; the current state of latch `j` is variable
+/// `inputs().len() + j`.
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    inputs: Vec<String>,
+    latches: Vec<Latch>,
+    outputs: Vec<(String, PlanNodeId)>,
+    plan: DagPlan,
+}
+
+impl Circuit {
+    /// the names of the primary inputs, in declaration order
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn latches(&self) -> &[Latch] {
+        &self.latches
+    }
+
+    /// the names and nodes of the primary outputs, in declaration order
+    pub fn outputs(&self) -> &[(String, PlanNodeId)] {
+        &self.outputs
+    }
+
+    pub fn output_nodes(&self) -> Vec<PlanNodeId> {
+        self.outputs.iter().map(|(_, id)| *id).collect()
+    }
+
+    /// the plan holding every gate of the circuit
+    pub fn plan(&self) -> &DagPlan {
+        &self.plan
+    }
+
+    /// the number of variables: one per input and one per latch
+    pub fn num_vars(&self) -> usize {
+        self.inputs.len() + self.latches.len()
+    }
+
+    /// The nodes whose functions describe the circuit: the primary outputs
+    /// followed by the latch next-state functions
+    pub fn roots(&self) -> Vec<PlanNodeId> {
+        self.outputs
+            .iter()
+            .map(|(_, id)| *id)
+            .chain(self.latches.iter().map(|l| l.next))
+            .collect()
+    }
+
+    /// Orders the variables by a depth-first traversal of the fan-in cones of
+    /// [`Circuit::roots`], i.e. of the outputs first and then of the latch
+    /// next-state functions. At each gate the deeper fan-in is visited
+    /// first, so variables that feed long paths are placed early and those
+    /// feeding a common gate end up close together. Variables that no root
+    /// depends on come last.
+    pub fn fanin_order(&self) -> VarOrder {
+        let mut depth = vec![0; self.plan.len()];
+        for (id, node) in self.plan.iter() {
+            depth[id.value_usize()] = node
+                .children()
+                .iter()
+                .map(|c| depth[c.value_usize()] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+
+        let mut order = Vec::with_capacity(self.num_vars());
+        let mut placed = vec![false; self.num_vars()];
+        let mut visited = vec![false; self.plan.len()];
+        for root in self.roots() {
+            let mut stack = vec![root];
+            while let Some(id) = stack.pop() {
+                if std::mem::replace(&mut visited[id.value_usize()], true) {
+                    continue;
+                }
+                let node = self.plan.node(id);
+                if let PlanNode::Literal(v, _) = node {
+                    if !std::mem::replace(&mut placed[v.value_usize()], true) {
+                        order.push(*v);
+                    }
+                }
+                // the stack is last-in first-out: push the deepest child last
+                let mut children = node.children();
+                children.sort_by_key(|c| depth[c.value_usize()]);
+                stack.extend(children);
+            }
+        }
+        order.extend(
+            (0..self.num_vars())
+                .filter(|v| !placed[*v])
+                .map(VarLabel::new_usize),
+        );
+        VarOrder::new(&order)
+    }
+
+    /// Parses the first model of a BLIF netlist. Hierarchical designs
+    /// (`.subckt`) and library gates (`.gate`) are not supported.
+    pub fn from_blif(input: &str) -> Result<Circuit, ParseError> {
+        // logical lines, joined across `\` continuations, as lists of tokens
+        let mut lines: Vec<Vec<&str>> = Vec::new();
+        let mut continued = false;
+        for line in input.lines() {
+            let line = line.split('#').next().unwrap();
+            let mut tokens: Vec<&str> = line.split_whitespace().collect();
+            let next_continued = tokens.last() == Some(&"\\");
+            if next_continued {
+                tokens.pop();
+            }
+            if continued {
+                lines.last_mut().unwrap().extend(tokens);
+            } else if !tokens.is_empty() {
+                lines.push(tokens);
+            }
+            continued = next_continued;
+        }
+
+        let mut inputs: Vec<&str> = Vec::new();
+        let mut outputs: Vec<&str> = Vec::new();
+        // (input, output, init) for every latch
+        let mut latches: Vec<(&str, &str, Option<bool>)> = Vec::new();
+        let mut covers: FxHashMap<&str, Cover> = FxHashMap::default();
+
+        let mut lines = lines.into_iter().peekable();
+        while let Some(tokens) = lines.next() {
+            let err = |msg: &str| Err(ParseError::at(input, tokens[0], msg));
+            match tokens[0] {
+                ".model" => (),
+                ".inputs" => inputs.extend(&tokens[1..]),
+                ".outputs" => outputs.extend(&tokens[1..]),
+                ".latch" => {
+                    if tokens.len() < 3 || tokens.len() > 6 {
+                        return err(
+                            "expected `.latch <input> <output> [<type> <control>] [<init>]`",
+                        );
+                    }
+                    // the initial value is the last field if there are 3 or 5
+                    let init = match tokens.len() {
+                        4 | 6 => match *tokens.last().unwrap() {
+                            "0" => Some(false),
+                            "1" => Some(true),
+                            "2" | "3" => None,
+                            v => {
+                                return Err(ParseError::at(
+                                    input,
+                                    v,
+                                    format!("invalid latch initial value `{}`", v),
+                                ))
+                            }
+                        },
+                        _ => None,
+                    };
+                    latches.push((tokens[1], tokens[2], init));
+                }
+                ".names" => {
+                    if tokens.len() < 2 {
+                        return err("expected `.names <inputs>* <output>`");
+                    }
+                    let (out, ins) = tokens[1..].split_last().unwrap();
+                    let mut cover = Cover {
+                        inputs: ins.to_vec(),
+                        rows: Vec::new(),
+                        on: true,
+                    };
+                    while let Some(row) = lines.next_if(|l| !l[0].starts_with('.')) {
+                        let (pattern, value) = match (ins.len(), row.as_slice()) {
+                            (0, [value]) => ("", *value),
+                            (_, [pattern, value]) if pattern.len() == ins.len() => {
+                                (*pattern, *value)
+                            }
+                            _ => {
+                                return Err(ParseError::at(
+                                    input,
+                                    row[0],
+                                    format!("expected a cover row over {} inputs", ins.len()),
+                                ))
+                            }
+                        };
+                        let value = match value {
+                            "1" => true,
+                            "0" => false,
+                            _ => return Err(ParseError::at(input, value, "expected `0` or `1`")),
+                        };
+                        if !cover.rows.is_empty() && value != cover.on {
+                            return Err(ParseError::at(
+                                input,
+                                row[0],
+                                "cover mixes on-set and off-set rows",
+                            ));
+                        }
+                        cover.on = value;
+                        let mut cube = Vec::with_capacity(pattern.len());
+                        for (i, c) in pattern.char_indices() {
+                            cube.push(match c {
+                                '1' => Some(true),
+                                '0' => Some(false),
+                                '-' => None,
+                                _ => {
+                                    return Err(ParseError::at(
+                                        input,
+                                        &pattern[i..],
+                                        format!("invalid cover character `{}`", c),
+                                    ))
+                                }
+                            });
+                        }
+                        cover.rows.push(cube);
+                    }
+                    if covers.insert(out, cover).is_some() {
+                        return Err(ParseError::at(
+                            input,
+                            out,
+                            format!("signal `{}` is defined twice", out),
+                        ));
+                    }
+                }
+                ".end" => break,
+                cmd => return err(&format!("unsupported BLIF construct `{}`", cmd)),
+            }
+        }
+
+        let mut plan = DagPlan::new();
+        let mut memo: FxHashMap<&str, PlanNodeId> = FxHashMap::default();
+        for (i, name) in inputs
+            .iter()
+            .chain(latches.iter().map(|(_, out, _)| out))
+            .enumerate()
+        {
+            let v = plan.literal(VarLabel::new_usize(i), true);
+            if memo.insert(*name, v).is_some() || covers.contains_key(name) {
+                return Err(ParseError::at(
+                    input,
+                    name,
+                    format!("signal `{}` is defined twice", name),
+                ));
+            }
+        }
+
+        let roots: Vec<&str> = outputs
+            .iter()
+            .chain(latches.iter().map(|(i, _, _)| i))
+            .copied()
+            .collect();
+        elaborate(
+            &mut plan,
+            &roots,
+            &mut memo,
+            |s| match covers.get(s) {
+                Some(cover) => Ok(cover.inputs.clone()),
+                None => Err(ParseError::at(
+                    input,
+                    s,
+                    format!("signal `{}` is never defined", s),
+                )),
+            },
+            |plan, s, fanins| covers[s].build(plan, fanins),
+        )?;
+
+        Ok(Circuit {
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            latches: latches
+                .iter()
+                .enumerate()
+                .map(|(j, (next, out, init))| Latch {
+                    name: out.to_string(),
+                    var: VarLabel::new_usize(inputs.len() + j),
+                    next: memo[next],
+                    init: *init,
+                })
+                .collect(),
+            outputs: outputs.iter().map(|s| (s.to_string(), memo[s])).collect(),
+            plan,
+        })
+    }
+
+    /// Parses an and-inverter graph in the ASCII (`aag`) or binary (`aig`)
+    /// AIGER format. Names from the symbol table are used when present;
+    /// otherwise inputs, latches and outputs are named `i<k>`, `l<k>` and
+    /// `o<k>`.
+    pub fn from_aiger(input: &[u8]) -> Result<Circuit, ParseError> {
+        let mut reader = AigerReader {
+            input,
+            pos: 0,
+            line: 0,
+        };
+        let header = reader.text_line().unwrap_or_default().to_string();
+        let mut tokens = header.split_whitespace();
+        let binary = match tokens.next() {
+            Some("aag") => false,
+            Some("aig") => true,
+            _ => return Err(reader.error("expected an `aag` or `aig` header")),
+        };
+        let mut header: Vec<u64> = tokens
+            .map(|t| t.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| reader.error(format!("invalid header `{}`", header)))?;
+        if header.len() < 5 || header.len() > 9 {
+            return Err(reader.error("expected a header `M I L O A`"));
+        }
+        header.resize(9, 0);
+        let (m, num_inputs, num_latches, num_outputs, num_ands) =
+            (header[0], header[1], header[2], header[3], header[4]);
+        if header[5..].iter().any(|n| *n != 0) {
+            return Err(reader.error("AIGER 1.9 properties are not supported"));
+        }
+        if num_inputs + num_latches + num_ands > m {
+            return Err(reader.error("M is smaller than I + L + A"));
+        }
+        let lit = |reader: &AigerReader, l: u64| {
+            if l / 2 > m {
+                Err(reader.error(format!("literal {} exceeds the maximum variable {}", l, m)))
+            } else {
+                Ok(l)
+            }
+        };
+
+        // the literal defining each input and latch, and each latch's
+        // next-state literal and initial value
+        let mut input_lits = Vec::new();
+        for k in 0..num_inputs {
+            if binary {
+                input_lits.push(2 * (k + 1));
+            } else {
+                let l = reader.numbers(1, 1)?[0];
+                input_lits.push(lit(&reader, l)?);
+            }
+        }
+        let mut latches = Vec::new();
+        for j in 0..num_latches {
+            let fields = if binary {
+                reader.numbers(1, 2)?
+            } else {
+                reader.numbers(2, 3)?
+            };
+            let (cur, rest) = if binary {
+                (2 * (num_inputs + j + 1), &fields[..])
+            } else {
+                (lit(&reader, fields[0])?, &fields[1..])
+            };
+            let next = lit(&reader, rest[0])?;
+            let init = match rest.get(1) {
+                None | Some(0) => Some(false),
+                Some(1) => Some(true),
+                Some(l) if *l == cur => None,
+                Some(l) => return Err(reader.error(format!("invalid latch initial value {}", l))),
+            };
+            latches.push((cur, next, init));
+        }
+        let mut output_lits = Vec::new();
+        for _ in 0..num_outputs {
+            let l = reader.numbers(1, 1)?[0];
+            output_lits.push(lit(&reader, l)?);
+        }
+
+        // and gates, by the variable they define
+        let mut ands: FxHashMap<u64, (u64, u64)> = FxHashMap::default();
+        for k in 0..num_ands {
+            let (lhs, rhs0, rhs1) = if binary {
+                let lhs = 2 * (num_inputs + num_latches + k + 1);
+                let (d0, d1) = (reader.varint()?, reader.varint()?);
+                let rhs0 = lhs.checked_sub(d0);
+                match (rhs0, rhs0.and_then(|r| r.checked_sub(d1))) {
+                    (Some(rhs0), Some(rhs1)) => (lhs, rhs0, rhs1),
+                    _ => return Err(reader.error("invalid delta in binary and gate")),
+                }
+            } else {
+                let fields = reader.numbers(3, 3)?;
+                (
+                    fields[0],
+                    lit(&reader, fields[1])?,
+                    lit(&reader, fields[2])?,
+                )
+            };
+            if lhs % 2 == 1 || lhs < 2 || ands.insert(lhs / 2, (rhs0, rhs1)).is_some() {
+                return Err(reader.error(format!("invalid and gate output {}", lhs)));
+            }
+        }
+
+        let mut names: FxHashMap<String, String> = FxHashMap::default();
+        while let Some(line) = reader.text_line() {
+            if line == "c" {
+                break;
+            }
+            match line.split_once(' ') {
+                Some((key, name)) if key.len() > 1 && "ilo".contains(&key[..1]) => {
+                    names.insert(key.to_string(), name.to_string());
+                }
+                _ => return Err(reader.error(format!("invalid symbol table entry `{}`", line))),
+            }
+        }
+        let name = |kind: &str, k: usize| {
+            let key = format!("{}{}", kind, k);
+            names.get(&key).cloned().unwrap_or(key)
+        };
+
+        let mut plan = DagPlan::new();
+        let mut memo: FxHashMap<u64, PlanNodeId> = FxHashMap::default();
+        let f = plan.const_false();
+        memo.insert(0, f);
+        for (i, l) in input_lits
+            .iter()
+            .chain(latches.iter().map(|(cur, _, _)| cur))
+            .enumerate()
+        {
+            let v = plan.literal(VarLabel::new_usize(i), true);
+            if l % 2 == 1
+                || *l < 2
+                || memo.insert(l / 2, v).is_some()
+                || ands.contains_key(&(l / 2))
+            {
+                return Err(ParseError::unlocated(format!(
+                    "variable {} is defined twice",
+                    l / 2
+                )));
+            }
+        }
+
+        let root_lits: Vec<u64> = output_lits
+            .iter()
+            .chain(latches.iter().map(|(_, next, _)| next))
+            .copied()
+            .collect();
+        let roots: Vec<u64> = root_lits.iter().map(|l| l / 2).collect();
+        elaborate(
+            &mut plan,
+            &roots,
+            &mut memo,
+            |v| match ands.get(v) {
+                Some((a, b)) => Ok(vec![a / 2, b / 2]),
+                None => Err(ParseError::unlocated(format!(
+                    "variable {} is never defined",
+                    v
+                ))),
+            },
+            |plan, v, fanins| {
+                let (a, b) = ands[v];
+                let a = signed(plan, fanins[0], a);
+                let b = signed(plan, fanins[1], b);
+                plan.and(a, b)
+            },
+        )?;
+        let node = |plan: &mut DagPlan, l: u64| signed(plan, memo[&(l / 2)], l);
+
+        let outputs = output_lits
+            .iter()
+            .enumerate()
+            .map(|(k, l)| (name("o", k), node(&mut plan, *l)))
+            .collect();
+        let latches = latches
+            .iter()
+            .enumerate()
+            .map(|(j, (_, next, init))| Latch {
+                name: name("l", j),
+                var: VarLabel::new_usize(input_lits.len() + j),
+                next: node(&mut plan, *next),
+                init: *init,
+            })
+            .collect();
+        Ok(Circuit {
+            inputs: (0..input_lits.len()).map(|k| name("i", k)).collect(),
+            latches,
+            outputs,
+            plan,
+        })
+    }
+}
+
+/// the node for AIGER literal `lit`, given the node for its variable
+fn signed(plan: &mut DagPlan, var: PlanNodeId, lit: u64) -> PlanNodeId {
+    if lit % 2 == 1 {
+        plan.not(var)
+    } else {
+        var
+    }
+}
+
+/// A BLIF single-output cover: the sum of `rows` if `on`, and its complement
+/// otherwise
+struct Cover<'a> {
+    inputs: Vec<&'a str>,
+    rows: Vec<Vec<Option<bool>>>,
+    on: bool,
+}
+
+impl Cover<'_> {
+    fn build(&self, plan: &mut DagPlan, fanins: &[PlanNodeId]) -> PlanNodeId {
+        let mut sum = plan.const_false();
+        for (k, row) in self.rows.iter().enumerate() {
+            let mut cube = None;
+            for (i, value) in row.iter().enumerate() {
+                if let Some(value) = value {
+                    let lit = if *value {
+                        fanins[i]
+                    } else {
+                        plan.not(fanins[i])
+                    };
+                    cube = Some(cube.map_or(lit, |c| plan.and(c, lit)));
+                }
+            }
+            let cube = cube.unwrap_or_else(|| plan.const_true());
+            sum = if k == 0 { cube } else { plan.or(sum, cube) };
+        }
+        if self.on {
+            sum
+        } else {
+            plan.not(sum)
+        }
+    }
+}
+
+/// Adds the signals `roots` and everything they depend on to `plan`, in
+/// topological order. `fanins` gives the signals a signal reads and `build`
+/// adds a signal given the nodes of its fan-ins; signals already in `memo`
+/// are not rebuilt. Uses an explicit stack, since netlists can be much
+/// deeper than the call stack.
+fn elaborate<K, F, B>(
+    plan: &mut DagPlan,
+    roots: &[K],
+    memo: &mut FxHashMap<K, PlanNodeId>,
+    fanins: F,
+    mut build: B,
+) -> Result<(), ParseError>
+where
+    K: Hash + Eq + Clone + Display,
+    F: Fn(&K) -> Result<Vec<K>, ParseError>,
+    B: FnMut(&mut DagPlan, &K, &[PlanNodeId]) -> PlanNodeId,
+{
+    let mut on_stack: HashSet<K> = HashSet::new();
+    for root in roots {
+        if memo.contains_key(root) {
+            continue;
+        }
+        // (signal, its fan-ins, index of the next fan-in to visit)
+        let mut stack = vec![(root.clone(), fanins(root)?, 0)];
+        on_stack.insert(root.clone());
+        while let Some((signal, signal_fanins, next)) = stack.last_mut() {
+            if let Some(child) = signal_fanins.get(*next).cloned() {
+                *next += 1;
+                if memo.contains_key(&child) {
+                    continue;
+                }
+                if !on_stack.insert(child.clone()) {
+                    return Err(ParseError::unlocated(format!(
+                        "combinational cycle through `{}`",
+                        child
+                    )));
+                }
+                let child_fanins = fanins(&child)?;
+                stack.push((child, child_fanins, 0));
+            } else {
+                let ids: Vec<PlanNodeId> = signal_fanins.iter().map(|c| memo[c]).collect();
+                let id = build(plan, signal, &ids);
+                on_stack.remove(signal);
+                memo.insert(signal.clone(), id);
+                stack.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the mix of text lines and binary data in an AIGER file
+struct AigerReader<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// the 1-indexed line last read
+    line: usize,
+}
+
+impl<'a> AigerReader<'a> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, 1, message)
+    }
+
+    fn text_line(&mut self) -> Option<&'a str> {
+        if self.pos >= self.input.len() {
+            return None;
+        }
+        let rest: &'a [u8] = &self.input[self.pos..];
+        let len = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        self.pos += len + 1;
+        self.line += 1;
+        std::str::from_utf8(&rest[..len])
+            .ok()
+            .map(|s| s.trim_end_matches('\r'))
+    }
+
+    /// reads a line of between `min` and `max` whitespace-separated numbers
+    fn numbers(&mut self, min: usize, max: usize) -> Result<Vec<u64>, ParseError> {
+        let line = self.text_line().map(|s| s.to_string());
+        let line = line.ok_or_else(|| self.error("unexpected end of file"))?;
+        let numbers: Result<Vec<u64>, _> = line.split_whitespace().map(|t| t.parse()).collect();
+        match numbers {
+            Ok(n) if n.len() >= min && n.len() <= max => Ok(n),
+            _ => Err(self.error(format!(
+                "expected {} to {} numbers, found `{}`",
+                min, max, line
+            ))),
+        }
+    }
+
+    /// reads a 7-bit little-endian variable-length number
+    fn varint(&mut self) -> Result<u64, ParseError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .input
+                .get(self.pos)
+                .ok_or_else(|| self.error("unexpected end of binary and gates"))?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error("binary delta is too long"))
+    }
+}
+
+#[test]
+fn aiger_ascii_and_binary_agree() {
+    use crate::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+    use crate::repr::BddPtr;
+
+    // out = x0 /\ !x1, with a latch whose next state is !out
+    let ascii = "aag 4 2 1 1 1\n2\n4\n6 9 1\n8\n8 5 2\ni0 a\no0 out\nc\ncomment\n";
+    let mut binary = b"aig 4 2 1 1 1\n9 1\n8\n".to_vec();
+    // deltas 8 - 5 = 3 and 5 - 2 = 3
+    binary.extend([3, 3]);
+    binary.extend(b"i0 a\no0 out\n");
+
+    let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
+    let expected = builder.and(
+        builder.var(VarLabel::new(0), true),
+        builder.var(VarLabel::new(1), false),
+    );
+    for input in [ascii.as_bytes(), &binary] {
+        let circuit = Circuit::from_aiger(input).unwrap();
+        assert_eq!(circuit.inputs(), &["a".to_string(), "i1".to_string()]);
+        assert_eq!(circuit.outputs()[0].0, "out");
+        assert_eq!(circuit.latches()[0].var, VarLabel::new(2));
+        assert_eq!(circuit.latches()[0].init, Some(true));
+        let compiled = builder.compile_dag_plan(circuit.plan(), &circuit.roots());
+        assert_eq!(compiled, vec![expected, builder.negate(expected)]);
+    }
+
+    assert!(Circuit::from_aiger(b"aag 1 0 0 1 0\n3\n").is_err());
+    assert!(Circuit::from_aiger(b"aag 2 0 0 1 1\n2\n2 4 1\n4 2 1\n").is_err());
+}
+
+#[test]
+fn blif_errors_are_located() {
+    let err = Circuit::from_blif(".inputs a\n.outputs b\n.names a b\n1x 1\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (4, 1));
+    let err = Circuit::from_blif(".inputs a\n.outputs b\n.subckt foo\n").unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert!(Circuit::from_blif(".outputs b\n.names c b\n1 1\n.names b c\n1 1\n").is_err());
+}
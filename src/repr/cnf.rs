@@ -3,10 +3,13 @@
 use crate::{
     repr::{
         model::PartialModel,
-        var_label::{Literal, VarLabel},
-        VarOrder, WmcParams,
+        var_label::{Literal, VarLabel, VarSet},
+        ParseError, VTree, VarOrder, WmcParams,
+    },
+    util::{
+        hypergraph::PartitionConfig,
+        semirings::{RealSemiring, Semiring},
     },
-    util::semirings::Semiring,
 };
 use petgraph::graph::NodeIndex;
 use petgraph::prelude::UnGraph;
@@ -14,7 +17,7 @@ use quickcheck::{Arbitrary, Gen};
 use rand::{self, rngs::ThreadRng, Rng};
 use std::{
     cmp::{max, min},
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt,
 };
 
@@ -218,6 +221,16 @@ pub struct Cnf {
     hasher: CnfHasher,
 }
 
+/// A CNF together with the literal weights and projection that accompany it
+/// in weighted DIMACS formats (see [`Cnf::from_weighted_dimacs`])
+#[derive(Debug, Clone)]
+pub struct WeightedCnf {
+    pub cnf: Cnf,
+    pub weights: WmcParams<RealSemiring>,
+    /// the variables listed in `c p show` lines, if any
+    pub projection: Option<VarSet>,
+}
+
 pub struct AssignmentIter {
     cur: Option<Vec<bool>>,
     num_vars: usize,
@@ -261,8 +274,208 @@ impl Iterator for AssignmentIter {
     }
 }
 
+/// The contents of a (possibly weighted and projected) DIMACS file, with
+/// literals still in their 1-indexed DIMACS form
+pub(crate) struct DimacsInstance {
+    /// the number of variables declared in the `p` header, or the largest
+    /// label used if the header undercounts
+    pub num_vars: usize,
+    pub clauses: Vec<Vec<i64>>,
+    /// literal weights from `c p weight` and `w` lines
+    pub weights: HashMap<i64, f64>,
+    /// variables from `c p show` lines
+    pub projection: Option<VarSet>,
+}
+
+impl DimacsInstance {
+    /// the clauses as 0-indexed literals
+    pub fn clauses(&self) -> Vec<Vec<Literal>> {
+        self.clauses
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|lit| Literal::new(VarLabel::new(lit.unsigned_abs() - 1), *lit > 0))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn out_of_range(input: &str, tok: &str, num_vars: usize) -> ParseError {
+    ParseError::at(
+        input,
+        tok,
+        format!(
+            "`{}` is out of range, the header declares {} variables",
+            tok, num_vars
+        ),
+    )
+}
+
+/// Parses DIMACS CNF input, including the weight and projection extensions
+/// used by the Model Counting Competition
+pub(crate) fn parse_dimacs(input: &str) -> Result<DimacsInstance, ParseError> {
+    fn parse<T: std::str::FromStr>(input: &str, tok: &str, what: &str) -> Result<T, ParseError> {
+        tok.parse()
+            .map_err(|_| ParseError::at(input, tok, format!("failed to parse {} `{}`", what, tok)))
+    }
+
+    let mut header: Option<usize> = None;
+    let mut instance = DimacsInstance {
+        num_vars: 0,
+        clauses: Vec::new(),
+        weights: HashMap::new(),
+        projection: None,
+    };
+    let mut cur_clause: Vec<i64> = Vec::new();
+    // for `p wcnf` files whose header gives one, the weight of hard clauses;
+    // only then does each clause start with its weight
+    let mut top: Option<f64> = None;
+    let mut clause_start = true;
+    // the largest variable used anywhere and its token, which may exceed the
+    // header's count
+    let mut widest: Option<(&str, u64)> = None;
+    let mut read = |tok, v: u64| {
+        if widest.map_or(true, |(_, w)| v > w) {
+            widest = Some((tok, v));
+        }
+    };
+    let mut shown: Vec<u64> = Vec::new();
+    for line in input.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            [] => (),
+            // some benchmark suites terminate the file with a `%` line
+            ["%", ..] => break,
+            ["p", rest @ ..] => {
+                if header.is_some() {
+                    return Err(ParseError::at(input, tokens[0], "duplicate `p` header"));
+                }
+                match rest {
+                    ["cnf", num_vars, num_clauses, ..] => {
+                        header = Some(parse(input, num_vars, "variable count")?);
+                        parse::<usize>(input, num_clauses, "clause count")?;
+                    }
+                    ["wcnf", num_vars, num_clauses, rest @ ..] => {
+                        header = Some(parse(input, num_vars, "variable count")?);
+                        parse::<usize>(input, num_clauses, "clause count")?;
+                        if let [weight, ..] = rest {
+                            top = Some(parse(input, weight, "hard clause weight")?);
+                        }
+                    }
+                    [kind, ..] if *kind != "cnf" && *kind != "wcnf" => {
+                        return Err(ParseError::at(
+                            input,
+                            kind,
+                            format!(
+                                "unsupported problem type `{}`, expected `cnf` or `wcnf`",
+                                kind
+                            ),
+                        ))
+                    }
+                    _ => {
+                        return Err(ParseError::at(
+                            input,
+                            tokens[0],
+                            "malformed header, expected `p cnf <vars> <clauses>`",
+                        ))
+                    }
+                }
+            }
+            ["c", "p", "weight", tok, weight, ..] | ["w", tok, weight, ..] => {
+                let lit: i64 = parse(input, tok, "literal")?;
+                read(tok, lit.unsigned_abs());
+                instance
+                    .weights
+                    .insert(lit, parse(input, weight, "weight")?);
+            }
+            ["c", "p", "show", vars @ ..] => {
+                instance.projection.get_or_insert_with(VarSet::new);
+                for tok in vars {
+                    let v: u64 = parse(input, tok, "projection variable")?;
+                    if v == 0 {
+                        break;
+                    }
+                    read(tok, v);
+                    shown.push(v);
+                }
+            }
+            ["c", ..] => (),
+            _ => {
+                if header.is_none() {
+                    return Err(ParseError::at(
+                        input,
+                        tokens[0],
+                        "expected a `p cnf` header before the first clause",
+                    ));
+                }
+                for tok in tokens {
+                    if std::mem::replace(&mut clause_start, false) {
+                        if tok == "h" {
+                            continue;
+                        }
+                        if let Some(top) = top {
+                            let weight: f64 = parse(input, tok, "clause weight")?;
+                            if weight < top {
+                                // soft clauses only make sense for MaxSAT
+                                return Err(ParseError::at(
+                                    input,
+                                    tok,
+                                    "soft clauses are not supported, expected a hard clause",
+                                ));
+                            }
+                            continue;
+                        }
+                    }
+                    let lit: i64 = parse(input, tok, "literal")?;
+                    if lit != 0 {
+                        read(tok, lit.unsigned_abs());
+                    }
+                    if lit == 0 {
+                        instance.clauses.push(std::mem::take(&mut cur_clause));
+                        clause_start = true;
+                    } else {
+                        cur_clause.push(lit);
+                    }
+                }
+            }
+        }
+    }
+    if !cur_clause.is_empty() {
+        instance.clauses.push(cur_clause);
+    }
+    let header = header.ok_or_else(|| {
+        ParseError::new(input.lines().count().max(1), 1, "missing `p cnf` header")
+    })?;
+    // some benchmark headers undercount their variables, so labels may exceed
+    // the header by up to the length of the input; tables sized by a label are
+    // then no larger than the input itself, and wider labels are rejected
+    // before anything is sized by them
+    instance.num_vars = header;
+    if let Some((tok, v)) = widest {
+        if v > (header as u64).saturating_add(input.len() as u64) {
+            return Err(out_of_range(input, tok, header));
+        }
+        instance.num_vars = max(header, v as usize);
+    }
+    if let Some(projection) = &mut instance.projection {
+        for v in shown {
+            // subtract 1, we are 0-indexed
+            projection.insert(VarLabel::new(v - 1));
+        }
+    }
+    Ok(instance)
+}
+
 impl Cnf {
     pub fn new(clauses: &[Vec<Literal>]) -> Cnf {
+        Cnf::new_with_num_vars(clauses, 0)
+    }
+
+    /// Like [`Cnf::new`], but the CNF has at least `num_vars` variables even if
+    /// some of them occur in no clause
+    pub fn new_with_num_vars(clauses: &[Vec<Literal>], num_vars: usize) -> Cnf {
         let clauses: Vec<Vec<Literal>> = clauses
             .iter()
             .filter(|clause| !clause.is_empty())
@@ -274,7 +487,7 @@ impl Cnf {
             })
             .collect();
 
-        let num_vars = clauses
+        let used_vars = clauses
             .iter()
             .map(|clause| {
                 clause
@@ -285,6 +498,7 @@ impl Cnf {
             })
             .max()
             .unwrap_or(0) as usize;
+        let num_vars = max(num_vars, used_vars);
 
         Cnf {
             hasher: CnfHasher::new(&clauses, num_vars),
@@ -293,33 +507,91 @@ impl Cnf {
         }
     }
 
-    pub fn from_dimacs(input: &str) -> Cnf {
-        use dimacs::*;
-        let (_, cvec) = match parse_dimacs(input).unwrap() {
-            Instance::Cnf { num_vars, clauses } => (num_vars, clauses),
-            Instance::Sat {
-                num_vars: _,
-                extensions: _,
-                formula: _,
-            } => panic!("Received (valid) SAT input, not CNF"),
-        };
-        let mut clause_vec: Vec<Vec<Literal>> = Vec::new();
-        let mut m = 0;
-        for itm in cvec.iter() {
-            let mut lit_vec: Vec<Literal> = Vec::new();
-            for l in itm.lits().iter() {
-                let b = match l.sign() {
-                    Sign::Neg => false,
-                    Sign::Pos => true,
-                };
-                // subtract 1, we are 0-indexed
-                let lbl = VarLabel::new(l.var().to_u64() - 1);
-                m = max(l.var().to_u64() as usize, m);
-                lit_vec.push(Literal::new(lbl, b));
-            }
-            clause_vec.push(lit_vec);
+    /// Parses a CNF from DIMACS, e.g.
+    /// ```
+    /// use rsdd::repr::Cnf;
+    ///
+    /// let cnf = Cnf::from_dimacs("
+    /// c a comment
+    /// p cnf 3 2
+    /// 1 -2 0
+    /// 2 3 0
+    /// ").unwrap();
+    /// assert_eq!(cnf.clauses().len(), 2);
+    ///
+    /// let err = Cnf::from_dimacs("p cnf 2 1\n1 x 0\n").unwrap_err();
+    /// assert_eq!((err.line(), err.column()), (2, 3));
+    /// ```
+    pub fn from_dimacs(input: &str) -> Result<Cnf, ParseError> {
+        let instance = parse_dimacs(input)?;
+        Ok(Cnf::new(&instance.clauses()))
+    }
+
+    /// Parses a weighted CNF in one of the Model Counting Competition formats:
+    /// - `p cnf` or `p wcnf <vars> <clauses> [<top>]` headers; when the latter
+    ///   gives `<top>`, each clause starts with its weight, or `h`. Every
+    ///   clause is a hard constraint, so a clause whose weight is below
+    ///   `<top>` is rejected as a soft clause
+    /// - literal weights given as `c p weight <lit> <weight> 0` (MCC 2021+) or
+    ///   as `w <lit> <weight> [0]` (MCC 2020)
+    /// - projection variables given as `c p show <vars> 0`
+    ///
+    /// Literals without a weight line have weight 1. All other comment lines
+    /// (e.g., `c t wmc`) are ignored.
+    /// ```
+    /// use rsdd::repr::{Cnf, VarLabel};
+    ///
+    /// let weighted = Cnf::from_weighted_dimacs("
+    /// p cnf 2 1
+    /// w 1 0.4 0
+    /// w -1 0.6 0
+    /// c p weight 2 0.3 0
+    /// 1 2 0
+    /// ").unwrap();
+    ///
+    /// assert_eq!(weighted.cnf.clauses().len(), 1);
+    /// assert_eq!(weighted.weights.var_weight(VarLabel::new(0)).0 .0, 0.6);
+    /// assert_eq!(weighted.weights.var_weight(VarLabel::new(1)).0 .0, 1.0);
+    /// assert_eq!(weighted.weights.var_weight(VarLabel::new(1)).1 .0, 0.3);
+    /// assert!(weighted.projection.is_none());
+    /// ```
+    pub fn from_weighted_dimacs(input: &str) -> Result<WeightedCnf, ParseError> {
+        let instance = parse_dimacs(input)?;
+        let cnf = Cnf::new(&instance.clauses());
+        let mut weights = WmcParams::default();
+        for v in 0..max(instance.num_vars, cnf.num_vars()) {
+            let lit = v as i64 + 1;
+            weights.set_weight(
+                VarLabel::new_usize(v),
+                RealSemiring(*instance.weights.get(&-lit).unwrap_or(&1.0)),
+                RealSemiring(*instance.weights.get(&lit).unwrap_or(&1.0)),
+            );
         }
-        Cnf::new(&clause_vec)
+        Ok(WeightedCnf {
+            cnf,
+            weights,
+            projection: instance.projection,
+        })
+    }
+
+    /// Reads the projection variables from the `c p show` lines of a DIMACS
+    /// string, as used in the Model Counting Competition. Returns None if
+    /// there are no such lines.
+    /// ```
+    /// use rsdd::repr::{Cnf, VarLabel};
+    ///
+    /// let projection = Cnf::projection_from_dimacs("
+    /// p cnf 3 1
+    /// c p show 1 3 0
+    /// 1 2 3 0
+    /// ").unwrap().unwrap();
+    ///
+    /// assert!(projection.contains(VarLabel::new(0)));
+    /// assert!(!projection.contains(VarLabel::new(1)));
+    /// assert!(projection.contains(VarLabel::new(2)));
+    /// ```
+    pub fn projection_from_dimacs(input: &str) -> Result<Option<VarSet>, ParseError> {
+        Ok(parse_dimacs(input)?.projection)
     }
 
     /// Parses a CNF string into a CNF
@@ -328,7 +600,7 @@ impl Cnf {
     /// - Each clause in its own parenthesis and separated by &&
     /// - Each literal separated by ||
     /// - Literal negation implied by "-"
-    pub fn from_string(s: &str) -> Cnf {
+    pub fn from_string(s: &str) -> Result<Cnf, ParseError> {
         let clauses = s.split("&&");
         let mut clause_vec: Vec<Vec<Literal>> = Vec::new();
         for clause in clauses {
@@ -338,13 +610,13 @@ impl Cnf {
             chars.next();
             chars.next_back();
             for lit in chars.as_str().split("||") {
-                if lit.trim().is_empty() {
+                let lit = lit.trim();
+                if lit.is_empty() {
                     continue;
                 }
-                let parsed: i64 = lit
-                    .trim()
-                    .parse()
-                    .unwrap_or_else(|_| panic!("failed to parse literal {}", lit));
+                let parsed: i64 = lit.parse().map_err(|_| {
+                    ParseError::at(s, lit, format!("failed to parse literal `{}`", lit))
+                })?;
                 let neg = parsed <= 0;
                 c.push(Literal::new(
                     VarLabel::new_usize(i64::abs(parsed) as usize),
@@ -353,7 +625,7 @@ impl Cnf {
             }
             clause_vec.push(c);
         }
-        Cnf::new(&clause_vec)
+        Ok(Cnf::new(&clause_vec))
     }
 
     pub fn rand_cnf(rng: &mut ThreadRng, num_vars: usize, num_clauses: usize) -> Cnf {
@@ -490,6 +762,46 @@ impl Cnf {
         total
     }
 
+    /// compute a weighted model count of a CNF projected onto the variables
+    /// in `projection`, i.e. the weighted count of assignments to
+    /// `projection` that can be extended to a model of the CNF
+    /// Note: not efficient! this is exponential in #variables
+    /// mostly for internal testing purposes
+    pub fn projected_wmc<T: Semiring + std::ops::Mul<Output = T> + std::ops::Add<Output = T>>(
+        &self,
+        weights: &WmcParams<T>,
+        projection: &VarSet,
+    ) -> T {
+        // map from each extendable projected assignment to its weight
+        let mut projected_models: HashMap<Vec<bool>, T> = HashMap::new();
+        for assgn in AssignmentIter::new(self.num_vars()) {
+            if assgn.is_empty() {
+                break;
+            };
+            if !self.eval(&assgn) {
+                continue;
+            }
+            let key: Vec<bool> = assgn
+                .iter()
+                .enumerate()
+                .map(|(idx, &polarity)| polarity && projection.contains(VarLabel::new_usize(idx)))
+                .collect();
+            projected_models.entry(key).or_insert_with(|| {
+                assgn
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| projection.contains(VarLabel::new_usize(*idx)))
+                    .fold(T::one(), |v, (idx, &polarity)| {
+                        let (loww, highw) = weights.var_weight(VarLabel::new_usize(idx));
+                        v.mul(if polarity { *highw } else { *loww })
+                    })
+            });
+        }
+        projected_models
+            .into_values()
+            .fold(T::zero(), |acc, w| acc + w)
+    }
+
     pub fn linear_order(&self) -> VarOrder {
         VarOrder::linear_order(self.num_vars)
     }
@@ -620,6 +932,14 @@ impl Cnf {
         VarOrder::new(&ord)
     }
 
+    /// get a variable order from the left-to-right leaves of a vtree found by
+    /// hypergraph partitioning (see [`VTree::from_cnf_hypergraph`])
+    pub fn hypergraph_order(&self) -> VarOrder {
+        let vtree = VTree::from_cnf_hypergraph(self, &PartitionConfig::default());
+        let ord: Vec<VarLabel> = VTree::flatten_vtree(&vtree).into_iter().copied().collect();
+        VarOrder::new(&ord)
+    }
+
     pub fn to_dimacs(&self) -> String {
         let mut r = String::new();
         for clause in self.clauses.iter() {
@@ -730,3 +1050,144 @@ fn test_cnf_wmc() {
     ]);
     assert_eq!(cnf.wmc(&WmcParams::new(weights)), FiniteField::new(3));
 }
+
+#[test]
+fn test_dimacs_errors() {
+    let err = Cnf::from_dimacs("1 2 0\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 1));
+
+    let err = Cnf::from_dimacs("c sat\np sat 2\n(1)\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 3));
+
+    let err = Cnf::from_dimacs("p cnf 2 1\nc p weight 1 x 0\n1 2 0\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 14));
+
+    let err = Cnf::from_string("(1 || 2) && (-1 || y)").unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 20));
+}
+
+#[test]
+fn test_dimacs_labels_out_of_range() {
+    for (input, at) in [
+        ("p cnf 2 1\n5000000000 0\n", (2, 1)),
+        ("p cnf 2 1\n-9223372036854775808 0\n", (2, 1)),
+        ("p cnf 2 1\n1 -4294967296 0\n", (2, 3)),
+        (
+            "p cnf 2 1\nc p show 18446744073709551615 0\n1 2 0\n",
+            (2, 10),
+        ),
+        ("c p show 1 4294967296 0\np cnf 2 1\n1 2 0\n", (1, 12)),
+        ("p cnf 2 1\nc p weight -4294967296 0.5 0\n1 2 0\n", (2, 12)),
+        ("w 4294967296 0.5\np cnf 2 1\n1 2 0\n", (1, 3)),
+        ("p cnf 1 1\n4294967295 0\n", (2, 1)),
+        ("p cnf 2 1\n1 -99 0\n", (2, 3)),
+    ] {
+        let err = Cnf::from_weighted_dimacs(input).unwrap_err();
+        assert_eq!((err.line(), err.column()), at, "{}", input);
+    }
+}
+
+#[test]
+fn test_dimacs_header_undercounts() {
+    let instance = parse_dimacs("p cnf 2 1\n1 -3 0\n").unwrap();
+    assert_eq!(instance.num_vars, 3);
+    assert_eq!(
+        Cnf::from_dimacs("p cnf 2 1\n1 -3 0\n").unwrap().num_vars(),
+        3
+    );
+
+    let weighted = Cnf::from_weighted_dimacs("c p show 4 0\np cnf 2 1\n1 2 0\nw 5 0.5\n").unwrap();
+    assert_eq!(
+        *weighted.weights.var_weight(VarLabel::new(4)),
+        (RealSemiring(1.0), RealSemiring(0.5))
+    );
+    assert!(weighted.projection.unwrap().contains(VarLabel::new(3)));
+}
+
+#[test]
+fn test_weighted_dimacs() {
+    static MCC: &str = "
+c t pwmc
+p cnf 3 2
+c p weight 1 0.25 0
+c p weight -1 0.75 0
+c p weight -3 0.5 0
+c p show 1 2 0
+1 2
+3 0
+-1 -3 0
+";
+    let weighted = Cnf::from_weighted_dimacs(MCC).unwrap();
+    assert_eq!(weighted.cnf.clauses().len(), 2);
+    assert_eq!(weighted.cnf.clauses()[0].len(), 3);
+    assert_eq!(
+        *weighted.weights.var_weight(VarLabel::new(0)),
+        (RealSemiring(0.75), RealSemiring(0.25))
+    );
+    assert_eq!(
+        *weighted.weights.var_weight(VarLabel::new(2)),
+        (RealSemiring(0.5), RealSemiring(1.0))
+    );
+    let projection = weighted.projection.unwrap();
+    assert!(projection.contains(VarLabel::new(1)));
+    assert!(!projection.contains(VarLabel::new(2)));
+
+    // a: (0.75, 0.25); b: (1, 1); projected onto {a, b}, every assignment
+    // extends to a model by picking c = !a
+    let pwmc = weighted.cnf.projected_wmc(&weighted.weights, &projection);
+    assert!((pwmc.0 - (0.25 * 2.0 + 0.75 * 2.0)).abs() < 1e-9);
+}
+
+#[test]
+fn test_weighted_wcnf() {
+    // clause weights precede the literals; `h` and the top weight mark hard
+    // clauses, and a clause may span several lines
+    static WCNF: &str = "
+p wcnf 3 3 100
+c p weight 1 0.2 0
+100 1 2 0
+h -1
+3 0
+100 2 3 0
+";
+    let weighted = Cnf::from_weighted_dimacs(WCNF).unwrap();
+    let clause = |lits: &[i64]| -> Vec<Literal> {
+        lits.iter()
+            .map(|l| Literal::new(VarLabel::new(l.unsigned_abs() - 1), *l > 0))
+            .collect()
+    };
+    assert_eq!(
+        weighted.cnf.clauses(),
+        &[clause(&[1, 2]), clause(&[-1, 3]), clause(&[2, 3])]
+    );
+    assert_eq!(weighted.weights.var_weight(VarLabel::new(0)).1 .0, 0.2);
+
+    // without a top weight, clauses have no weights
+    let unbounded = Cnf::from_weighted_dimacs("p wcnf 2 2\n1 -2 0\n2 0\n").unwrap();
+    assert_eq!(unbounded.cnf.clauses(), &[clause(&[1, -2]), clause(&[2])]);
+    let unbounded = Cnf::from_weighted_dimacs("p wcnf 2 1\nw 1 0.4\nw -1 0.6\n1 2 0\n").unwrap();
+    assert_eq!(unbounded.cnf.clauses(), &[clause(&[1, 2])]);
+    assert_eq!(unbounded.weights.var_weight(VarLabel::new(0)).1 .0, 0.4);
+
+    // clauses below the top weight are soft
+    let err = Cnf::from_weighted_dimacs("p wcnf 2 2 10\n10 1 2 0\n3 -1 0\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (3, 1));
+}
+
+#[test]
+fn test_cnf_projected_wmc() {
+    use crate::util::semirings::RealSemiring;
+
+    // (a \/ b) /\ (!a \/ c), projected onto {b, c}
+    let cnf = Cnf::from_dimacs("p cnf 3 2\n1 2 0\n-1 3 0\n").unwrap();
+    let mut projection = VarSet::new();
+    projection.insert(VarLabel::new(1));
+    projection.insert(VarLabel::new(2));
+    let weights = WmcParams::new(HashMap::from_iter([
+        (VarLabel::new(0), (RealSemiring(1.0), RealSemiring(1.0))),
+        (VarLabel::new(1), (RealSemiring(1.0), RealSemiring(1.0))),
+        (VarLabel::new(2), (RealSemiring(1.0), RealSemiring(1.0))),
+    ]));
+    // only b = c = false cannot be extended
+    assert_eq!(cnf.projected_wmc(&weights, &projection).0, 3.0);
+}
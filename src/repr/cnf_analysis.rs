@@ -0,0 +1,167 @@
+//! Structural analysis of a CNF, used to predict how expensive it will be to
+//! compile before doing so
+//!
+//! The width measures reported here bound the cost of compilation:
+//! bottom-up compilation along an elimination order is exponential in its
+//! induced width at worst, and top-down compilation along a dtree is
+//! exponential in its cutwidth (for decisions) and context width (for cache
+//! entries). See Chapter 9 of 'Modeling and Reasoning with Bayesian Networks'
+//! by Adnan Darwiche.
+
+use crate::repr::{
+    order_heuristics::{adjacency, eliminate},
+    Cnf, DTree, VarOrder,
+};
+use std::collections::HashSet;
+
+/// A structural summary of a CNF, produced by [`Cnf::analyze`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CnfAnalysis {
+    pub num_vars: usize,
+    pub num_clauses: usize,
+    /// the induced width of the elimination order passed to `analyze`
+    pub induced_width: usize,
+    /// the smallest induced width among the min-fill and min-degree orders;
+    /// an upper bound on the treewidth of the interaction graph
+    pub treewidth_upper_bound: usize,
+    /// cutwidth of the dtree generated from the analyzed order
+    pub dtree_cutwidth: usize,
+    /// context width of the dtree generated from the analyzed order
+    pub dtree_context_width: usize,
+    /// the number of edges of the interaction graph
+    pub num_edges: usize,
+    /// the number of connected components of the interaction graph
+    /// (including variables that occur in no clause)
+    pub num_components: usize,
+    /// the number of variables in the largest connected component
+    pub largest_component: usize,
+    pub max_degree: usize,
+    pub mean_degree: f64,
+    /// `degree_histogram[d]` is the number of variables with degree `d`
+    pub degree_histogram: Vec<usize>,
+}
+
+impl Cnf {
+    /// the induced width of eliminating variables in `order`: the largest
+    /// number of neighbors a variable has at the time it is eliminated from
+    /// the interaction graph
+    /// ```
+    /// use rsdd::repr::{Cnf, VarLabel, VarOrder};
+    /// // a chain 0 - 1 - 2
+    /// let cnf = Cnf::from_string("(0 || 1) && (1 || 2)").unwrap();
+    /// assert_eq!(cnf.induced_width(&VarOrder::linear_order(3)), 1);
+    /// // eliminating the middle first connects its neighbors
+    /// let order = VarOrder::new(&[VarLabel::new(1), VarLabel::new(0), VarLabel::new(2)]);
+    /// assert_eq!(cnf.induced_width(&order), 2);
+    /// ```
+    pub fn induced_width(&self, order: &VarOrder) -> usize {
+        let mut vars = order.in_order_iter();
+        eliminate(self, |_, _| vars.next().map(|v| v.value_usize())).1
+    }
+
+    /// an upper bound on the treewidth of the interaction graph, given by the
+    /// best induced width among the min-fill and min-degree orders
+    pub fn treewidth_upper_bound(&self) -> usize {
+        usize::min(
+            self.induced_width(&self.min_fill_order()),
+            self.induced_width(&self.min_degree_order()),
+        )
+    }
+
+    /// Reports width measures for the elimination order `order` together with
+    /// statistics of the interaction graph.
+    /// ```
+    /// use rsdd::repr::Cnf;
+    /// let cnf = Cnf::from_string("(0 || 1) && (1 || 2) && (3 || 4)").unwrap();
+    /// let analysis = cnf.analyze(&cnf.min_fill_order());
+    /// assert_eq!(analysis.treewidth_upper_bound, 1);
+    /// assert_eq!(analysis.num_components, 2);
+    /// assert_eq!(analysis.largest_component, 3);
+    /// assert_eq!(analysis.degree_histogram, vec![0, 4, 1]);
+    /// ```
+    pub fn analyze(&self, order: &VarOrder) -> CnfAnalysis {
+        let adj = adjacency(self);
+        let degrees: Vec<usize> = adj.iter().map(|n| n.len()).collect();
+        let max_degree = degrees.iter().copied().max().unwrap_or(0);
+        let mut degree_histogram = vec![0; max_degree + 1];
+        for d in degrees.iter() {
+            degree_histogram[*d] += 1;
+        }
+
+        // connected components by depth-first search
+        let mut seen = HashSet::new();
+        let (mut num_components, mut largest_component) = (0, 0);
+        for root in 0..self.num_vars() {
+            if !seen.insert(root) {
+                continue;
+            }
+            num_components += 1;
+            let mut size = 0;
+            let mut stack = vec![root];
+            while let Some(v) = stack.pop() {
+                size += 1;
+                stack.extend(adj[v].iter().filter(|n| seen.insert(**n)));
+            }
+            largest_component = usize::max(largest_component, size);
+        }
+
+        let (dtree_cutwidth, dtree_context_width) = if self.clauses().is_empty() {
+            (0, 0)
+        } else {
+            let dtree = DTree::from_cnf(self, order);
+            (dtree.cutwidth(), dtree.context_width())
+        };
+
+        CnfAnalysis {
+            num_vars: self.num_vars(),
+            num_clauses: self.clauses().len(),
+            induced_width: self.induced_width(order),
+            treewidth_upper_bound: self.treewidth_upper_bound(),
+            dtree_cutwidth,
+            dtree_context_width,
+            num_edges: degrees.iter().sum::<usize>() / 2,
+            num_components,
+            largest_component,
+            max_degree,
+            mean_degree: if degrees.is_empty() {
+                0.0
+            } else {
+                degrees.iter().sum::<usize>() as f64 / degrees.len() as f64
+            },
+            degree_histogram,
+        }
+    }
+}
+
+#[test]
+fn analyze_grid() {
+    // a 3x3 grid of binary constraints has treewidth 3
+    let cnf = Cnf::from_string(
+        "(0 || 1) && (1 || 2) && (3 || 4) && (4 || 5) && (6 || 7) && (7 || 8) \
+         && (0 || 3) && (3 || 6) && (1 || 4) && (4 || 7) && (2 || 5) && (5 || 8)",
+    )
+    .unwrap();
+    let analysis = cnf.analyze(&cnf.min_fill_order());
+    assert_eq!(analysis.num_edges, 12);
+    assert_eq!(analysis.num_components, 1);
+    assert_eq!(analysis.max_degree, 4);
+    assert!(analysis.treewidth_upper_bound >= 3);
+    assert!(analysis.treewidth_upper_bound <= analysis.induced_width);
+    // cutsets at a node are contained in that node's context or its children's
+    assert!(analysis.dtree_context_width >= analysis.dtree_cutwidth);
+}
+
+#[test]
+fn induced_width_of_clique() {
+    // every order of a clique leaves all other variables as neighbors of the
+    // first one eliminated
+    let cnf = Cnf::from_string("(0 || 1 || 2 || 3)").unwrap();
+    for order in [
+        cnf.min_fill_order(),
+        cnf.min_degree_order(),
+        cnf.linear_order(),
+    ] {
+        assert_eq!(cnf.induced_width(&order), 3);
+    }
+    assert_eq!(cnf.treewidth_upper_bound(), 3);
+}
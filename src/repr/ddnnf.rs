@@ -3,9 +3,9 @@
 use crate::{
     repr::{
         var_label::{VarLabel, VarSet},
-        WmcParams,
+        PartialModel, WmcParams,
     },
-    util::semirings::{BooleanSemiring, FiniteField, Semiring},
+    util::semirings::{BooleanSemiring, FiniteField, Semiring, VecSemiring},
 };
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
@@ -44,6 +44,10 @@ pub fn create_semantic_hash_map<const P: u128>(num_vars: usize) -> WmcParams<Fin
     WmcParams::new(map)
 }
 
+/// A lane width for [`DDNNFPtr::batch_wmc`]: the number of partial models
+/// evaluated per traversal
+pub const BATCH_WIDTH: usize = 8;
+
 /// A base d-DNNF type
 pub enum DDNNF<T> {
     /// contains the cached values for the children, and the VarSet that
@@ -88,6 +92,50 @@ pub trait DDNNFPtr<'a>: Clone + Debug + PartialEq + Eq + Hash + Copy {
         })
     }
 
+    /// Unsmoothed weighted-model counts of `self` under each of `models`, in
+    /// order. A variable a model assigns weighs `params.one` for its value and
+    /// `params.zero` for the other, so each count equals conditioning on the
+    /// model and then counting, without building the conditioned diagrams.
+    ///
+    /// Models are evaluated `W` at a time, each batch in one traversal of the
+    /// diagram that carries `W` values per node, so `N` models take
+    /// `ceil(N / W)` traversals. A wider `W` means fewer traversals but more
+    /// memory per node; [`BATCH_WIDTH`] is a reasonable default.
+    fn batch_wmc<T: Semiring + 'static, const W: usize>(
+        &self,
+        params: &WmcParams<T>,
+        models: &[PartialModel],
+    ) -> Vec<T> {
+        let mut results = Vec::with_capacity(models.len());
+        for batch in models.chunks(W) {
+            let lanes = self.fold(|ddnnf| {
+                use DDNNF::*;
+                match ddnnf {
+                    Or(l, r, _) => l + r,
+                    And(l, r) => l * r,
+                    True => VecSemiring::<T, W>::splat(params.one),
+                    False => VecSemiring::splat(params.zero),
+                    Lit(lbl, polarity) => VecSemiring(std::array::from_fn(|i| {
+                        match batch.get(i).and_then(|m| m.get(lbl)) {
+                            Some(value) if value == polarity => params.one,
+                            Some(_) => params.zero,
+                            None => {
+                                let (low_w, high_w) = params.var_weight(lbl);
+                                if polarity {
+                                    *high_w
+                                } else {
+                                    *low_w
+                                }
+                            }
+                        }
+                    })),
+                }
+            });
+            results.extend_from_slice(&lanes.0[..batch.len()]);
+        }
+        results
+    }
+
     fn evaluate(&self, instantations: &[bool]) -> bool {
         self.unsmoothed_wmc(&WmcParams::new(HashMap::from_iter(
             instantations.iter().enumerate().map(|(index, polarity)| {
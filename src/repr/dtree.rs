@@ -42,7 +42,13 @@
 //! The *cut-width* of a dtree is the size of the largest cutset. An effective
 //! dtree is one that does not have large cutwidth.
 
-use crate::repr::{var_label::Literal, var_label::VarSet, Cnf, VarOrder};
+use crate::{
+    repr::{var_label::Literal, var_label::VarSet, Cnf, VarOrder},
+    util::{
+        btree::BTree,
+        hypergraph::{recursive_bisection, PartitionConfig},
+    },
+};
 use serde::Serialize;
 
 #[derive(Clone, Debug, Serialize)]
@@ -200,6 +206,50 @@ impl DTree {
         res
     }
 
+    /// generate a dtree by recursive min-cut bisection of the CNF's dual
+    /// hypergraph, whose vertices are clauses and whose hyperedges are
+    /// variables (connecting the clauses they occur in). Each bisection thus
+    /// approximately minimizes the size of the cutset at that node.
+    ///
+    /// Panics if `cnf` has no clauses.
+    /// ```
+    /// use rsdd::repr::{Cnf, DTree};
+    /// use rsdd::util::hypergraph::PartitionConfig;
+    /// let cnf = Cnf::from_string("(0 || 1) && (1 || 2) && (2 || 3) && (3 || 4)").unwrap();
+    /// let dtree = DTree::from_cnf_hypergraph(&cnf, &PartitionConfig::default());
+    /// assert_eq!(dtree.cutwidth(), 1);
+    /// ```
+    pub fn from_cnf_hypergraph(cnf: &Cnf, config: &PartitionConfig) -> DTree {
+        fn to_dtree(tree: &BTree<(), usize>, clauses: &[Vec<Literal>]) -> DTree {
+            match tree {
+                BTree::Leaf(i) => DTree::Leaf {
+                    clause: clauses[*i].clone(),
+                    cutset: VarSet::new(),
+                    vars: VarSet::new(),
+                },
+                BTree::Node((), l, r) => DTree::Node {
+                    l: Box::new(to_dtree(l, clauses)),
+                    r: Box::new(to_dtree(r, clauses)),
+                    cutset: VarSet::new(),
+                    vars: VarSet::new(),
+                },
+            }
+        }
+
+        let clauses = cnf.clauses();
+        let mut occurrences: Vec<Vec<usize>> = vec![Vec::new(); cnf.num_vars()];
+        for (i, clause) in clauses.iter().enumerate() {
+            for lit in clause {
+                occurrences[lit.label().value_usize()].push(i);
+            }
+        }
+        let tree = recursive_bisection(clauses.len(), &occurrences, config);
+        let mut res = to_dtree(&tree, clauses);
+        res.init_vars();
+        res.gen_cutset(&VarSet::new());
+        res
+    }
+
     /// computes the cutwidth of the dtree, which is the size of the largest cut
     ///
     /// the width is a measure of the complexity of elimination for this dtree
@@ -223,11 +273,34 @@ impl DTree {
             }
         }
     }
+
+    /// computes the context width of the dtree: the size of the largest
+    /// context, where the context of a node is vars(n) ∩ acutset(n) and
+    /// acutset(n) is the union of the cutsets of n's ancestors
+    ///
+    /// the context of a node determines how many distinct subproblems it can
+    /// be asked to solve, so it bounds the size of a cache at that node
+    pub fn context_width(&self) -> usize {
+        fn helper(t: &DTree, acutset: &VarSet) -> usize {
+            let context = t.get_vars().intersect_varset(acutset).iter().count();
+            match t {
+                DTree::Leaf { .. } => context,
+                DTree::Node { l, r, cutset, .. } => {
+                    let acutset = acutset.union(cutset);
+                    usize::max(
+                        context,
+                        usize::max(helper(l, &acutset), helper(r, &acutset)),
+                    )
+                }
+            }
+        }
+        helper(self, &VarSet::new())
+    }
 }
 
 #[test]
 fn test_dtree() {
-    let cnf = Cnf::from_string("(1 || -2) && (2 || 3) && (3 || 4) ");
+    let cnf = Cnf::from_string("(1 || -2) && (2 || 3) && (3 || 4) ").unwrap();
     // let cnf = Cnf::from_string(String::from("(1 || -2 || 3) && (2 || 3)"));
     // let cnf = Cnf::from_string(String::from("(3 || 4) && (1 || 2) "));
     println!("cnf: {:?}", cnf.to_string());
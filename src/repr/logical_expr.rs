@@ -1,7 +1,9 @@
 //! A representation of an arbitrary logical formula
 
-use crate::{repr::VarLabel, serialize::LogicalSExpr};
-use dimacs::*;
+use crate::{
+    repr::{cnf::parse_dimacs, Cnf, Literal, ParseError, VarLabel},
+    serialize::LogicalSExpr,
+};
 use rand::{self, rngs::ThreadRng, Rng};
 use std::collections::HashMap;
 
@@ -37,47 +39,38 @@ impl LogicalExpr {
     /// 1 -2 3 0
     /// "#;
     ///
-    /// let expr = LogicalExpr::from_dimacs(CNF);
+    /// let expr = LogicalExpr::from_dimacs(CNF).unwrap();
     /// assert!(matches!(expr, LogicalExpr::Or(_, _)));
     /// ```
-    pub fn from_dimacs(input: &str) -> LogicalExpr {
-        let (_, cvec) = match parse_dimacs(input).unwrap() {
-            Instance::Cnf { num_vars, clauses } => (num_vars, clauses),
-            Instance::Sat {
-                num_vars: _,
-                extensions: _,
-                formula: _,
-            } => panic!("Received (valid) SAT input, not CNF"),
-        };
+    pub fn from_dimacs(input: &str) -> Result<LogicalExpr, ParseError> {
+        let instance = parse_dimacs(input)?;
         let mut clause_vec: Vec<LogicalExpr> = Vec::new();
-        for itm in cvec.iter() {
-            let mut lit_vec: Vec<LogicalExpr> = Vec::new();
-            for l in itm.lits().iter() {
-                let b = match l.sign() {
-                    Sign::Neg => false,
-                    Sign::Pos => true,
-                };
-                lit_vec.push(LogicalExpr::Literal(l.var().to_u64() as usize, b));
-            }
-            if lit_vec.len() == 1 {
-                clause_vec.push(lit_vec.pop().unwrap());
-            } else {
-                let mut clause = lit_vec.pop().unwrap();
-                for lit in lit_vec {
-                    clause = LogicalExpr::Or(Box::new(clause), Box::new(lit));
+        for itm in instance.clauses.iter() {
+            let mut lit_vec: Vec<LogicalExpr> = itm
+                .iter()
+                .map(|l| LogicalExpr::Literal(l.unsigned_abs() as usize, *l > 0))
+                .collect();
+            match lit_vec.pop() {
+                None => {
+                    return Err(ParseError::unlocated(
+                        "empty clauses cannot be represented as a LogicalExpr",
+                    ))
+                }
+                Some(mut clause) => {
+                    for lit in lit_vec {
+                        clause = LogicalExpr::Or(Box::new(clause), Box::new(lit));
+                    }
+                    clause_vec.push(clause);
                 }
-                clause_vec.push(clause);
             }
         }
-        if clause_vec.len() == 1 {
-            clause_vec.pop().unwrap()
-        } else {
-            let mut e = clause_vec.pop().unwrap();
-            for clause in clause_vec {
-                e = LogicalExpr::And(Box::new(e), Box::new(clause))
-            }
-            e
+        let mut e = clause_vec
+            .pop()
+            .ok_or_else(|| ParseError::unlocated("expected at least one clause"))?;
+        for clause in clause_vec {
+            e = LogicalExpr::And(Box::new(e), Box::new(clause))
         }
+        Ok(e)
     }
 
     /// ```
@@ -141,6 +134,69 @@ impl LogicalExpr {
         helper(sexpr, &mapping)
     }
 
+    /// A CNF with one all-positive clause per top-level conjunct of this
+    /// expression, containing that conjunct's variables. It is *not*
+    /// equivalent to the expression, but it shares the expression's
+    /// interaction structure, so the ordering heuristics on `Cnf` (e.g.,
+    /// [`Cnf::order_by`]) can be applied to it.
+    /// ```
+    /// use rsdd::repr::{LogicalExpr, OrderHeuristic};
+    /// use rsdd::serialize::LogicalSExpr;
+    ///
+    /// let sexpr = LogicalSExpr::parse("(And (Or (Var A) (Var B)) (Iff (Var B) (Not (Var C))))").unwrap();
+    /// let expr = LogicalExpr::from_sexpr(&sexpr);
+    /// let scopes = expr.scope_cnf();
+    /// assert_eq!(scopes.clauses().len(), 2);
+    /// let order = scopes.order_by(OrderHeuristic::MinFill);
+    /// assert_eq!(order.num_vars(), 3);
+    /// ```
+    pub fn scope_cnf(&self) -> Cnf {
+        fn conjuncts<'a>(e: &'a LogicalExpr, acc: &mut Vec<&'a LogicalExpr>) {
+            match e {
+                LogicalExpr::And(l, r) => {
+                    conjuncts(l, acc);
+                    conjuncts(r, acc);
+                }
+                _ => acc.push(e),
+            }
+        }
+        fn vars(e: &LogicalExpr, acc: &mut Vec<Literal>) {
+            match e {
+                LogicalExpr::Literal(v, _) => {
+                    let lit = Literal::new(VarLabel::new_usize(*v), true);
+                    if !acc.contains(&lit) {
+                        acc.push(lit);
+                    }
+                }
+                LogicalExpr::Not(e) => vars(e, acc),
+                LogicalExpr::And(l, r)
+                | LogicalExpr::Or(l, r)
+                | LogicalExpr::Iff(l, r)
+                | LogicalExpr::Xor(l, r) => {
+                    vars(l, acc);
+                    vars(r, acc);
+                }
+                LogicalExpr::Ite { guard, thn, els } => {
+                    vars(guard, acc);
+                    vars(thn, acc);
+                    vars(els, acc);
+                }
+            }
+        }
+
+        let mut cs = Vec::new();
+        conjuncts(self, &mut cs);
+        let clauses: Vec<Vec<Literal>> = cs
+            .into_iter()
+            .map(|c| {
+                let mut acc = Vec::new();
+                vars(c, &mut acc);
+                acc
+            })
+            .collect();
+        Cnf::new(&clauses)
+    }
+
     /// Build a random CNF expression
     pub fn rand_cnf(rng: &mut ThreadRng, num_vars: usize, num_clauses: usize) -> LogicalExpr {
         assert!(num_clauses > 2, "requires at least 2 clauses in CNF");
@@ -192,7 +248,7 @@ impl LogicalExpr {
     /// 1 -2 3 0
     /// "#;
     ///
-    /// let expr = LogicalExpr::from_dimacs(CNF);
+    /// let expr = LogicalExpr::from_dimacs(CNF).unwrap();
     ///
     /// assert!(expr.eval(
     ///     &HashMap::from([
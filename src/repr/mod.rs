@@ -4,12 +4,19 @@
 //!
 //! (i.e., conjunctive normal forms, arbitrary logical formulae, etc.)
 
+mod bayesian_network;
 mod bdd;
+mod circuit;
 mod cnf;
+mod cnf_analysis;
 mod ddnnf;
 mod dtree;
 mod logical_expr;
 mod model;
+mod nnf;
+mod order_heuristics;
+mod parse_error;
+mod preprocess;
 mod sdd;
 mod unit_prop;
 mod var_label;
@@ -17,12 +24,19 @@ mod var_order;
 mod vtree;
 mod wmc;
 
+pub use self::bayesian_network::*;
 pub use self::bdd::*;
+pub use self::circuit::*;
 pub use self::cnf::*;
+pub use self::cnf_analysis::*;
 pub use self::ddnnf::*;
 pub use self::dtree::*;
 pub use self::logical_expr::*;
 pub use self::model::*;
+pub use self::nnf::*;
+pub use self::order_heuristics::*;
+pub use self::parse_error::*;
+pub use self::preprocess::*;
 pub use self::sdd::*;
 pub use self::sdd::*;
 pub use self::unit_prop::*;
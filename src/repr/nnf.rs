@@ -0,0 +1,469 @@
+//! A generic d-DNNF circuit, for diagrams compiled by other tools
+//!
+//! Unlike BDDs and SDDs, these circuits are not canonical and have no
+//! builder: they are read whole (e.g. from the c2d `.nnf` format) and then
+//! queried through [`DDNNFPtr`].
+//!
+//! Circuits are kept exactly as they are read, and files written by other
+//! compilers are often not smooth: a disjunction's children may mention
+//! different variables. Model counts of such a circuit's [`NnfCircuit::root`]
+//! (e.g. [`DDNNFPtr::unsmoothed_wmc`]) then miss the variables a branch does
+//! not mention. Use [`NnfCircuit::wmc`], or count on [`NnfCircuit::smooth`],
+//! to count over every variable.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+
+use super::{DDNNFPtr, ParseError, VarLabel, VarSet, WmcParams, DDNNF};
+use crate::util::semirings::Semiring;
+
+/// A node of an [`NnfCircuit`]; children are indices of earlier nodes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NnfNode {
+    Lit(VarLabel, bool),
+    /// the empty conjunction is true
+    And(Vec<usize>),
+    /// a deterministic disjunction with its decision variable, if any; the
+    /// empty disjunction is false
+    Or(Option<VarLabel>, Vec<usize>),
+}
+
+/// A d-DNNF stored as a list of nodes, children before parents, whose root
+/// is the last node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NnfCircuit {
+    nodes: Vec<NnfNode>,
+    num_vars: usize,
+}
+
+impl NnfCircuit {
+    /// Creates a circuit from `nodes`, checking that every child precedes
+    /// its parent and that every variable is below `num_vars`
+    pub fn new(nodes: Vec<NnfNode>, num_vars: usize) -> Result<NnfCircuit, String> {
+        if nodes.is_empty() {
+            return Err("a circuit needs at least one node".to_string());
+        }
+        for (id, node) in nodes.iter().enumerate() {
+            match node {
+                NnfNode::Lit(v, _) | NnfNode::Or(Some(v), _) if v.value_usize() >= num_vars => {
+                    return Err(format!(
+                        "node {} uses variable {}, but there are only {} variables",
+                        id,
+                        v.value() + 1,
+                        num_vars
+                    ))
+                }
+                NnfNode::And(c) | NnfNode::Or(_, c) => {
+                    if let Some(c) = c.iter().find(|c| **c >= id) {
+                        return Err(format!("node {} refers to later node {}", id, c));
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(NnfCircuit { nodes, num_vars })
+    }
+
+    /// Parses the c2d `.nnf` format: a header `nnf <nodes> <edges> <vars>`
+    /// followed by one node per line, `L <lit>`, `A <count> <children>*` or
+    /// `O <decision var> <count> <children>*`, where variables are numbered
+    /// from 1 and children by line
+    /// ```
+    /// use rsdd::repr::{DDNNFPtr, NnfCircuit};
+    ///
+    /// // x1 /\ (x2 \/ -x2)
+    /// let circuit = NnfCircuit::from_c2d("nnf 5 4 2\nL 1\nL 2\nL -2\nO 2 2 1 2\nA 2 0 3\n").unwrap();
+    /// let root = circuit.root();
+    /// assert!(root.evaluate(&[true, false]));
+    /// assert!(!root.evaluate(&[false, true]));
+    /// ```
+    pub fn from_c2d(input: &str) -> Result<NnfCircuit, ParseError> {
+        let mut lines = input
+            .lines()
+            .map(|l| l.split_whitespace().collect::<Vec<&str>>())
+            .filter(|l| !l.is_empty() && l[0] != "c");
+        let header = lines
+            .next()
+            .ok_or_else(|| ParseError::unlocated("missing `nnf` header"))?;
+        if header[0] != "nnf" || header.len() != 4 {
+            return Err(ParseError::at(
+                input,
+                header[0],
+                "expected `nnf <nodes> <edges> <vars>`",
+            ));
+        }
+        let num_nodes: usize = ParseError::number_at(input, header[1])?;
+        let num_vars: usize = ParseError::number_at(input, header[3])?;
+
+        let mut nodes = Vec::with_capacity(num_nodes);
+        for line in lines {
+            let id = nodes.len();
+            let children = |count: &str, rest: &[&str]| -> Result<Vec<usize>, ParseError> {
+                let count: usize = ParseError::number_at(input, count)?;
+                if rest.len() != count {
+                    return Err(ParseError::at(
+                        input,
+                        line[0],
+                        format!("expected {} children, found {}", count, rest.len()),
+                    ));
+                }
+                rest.iter()
+                    .map(|c| {
+                        let c: usize = ParseError::number_at(input, c)?;
+                        if c >= id {
+                            Err(ParseError::at(
+                                input,
+                                line[0],
+                                "child is not an earlier node",
+                            ))
+                        } else {
+                            Ok(c)
+                        }
+                    })
+                    .collect()
+            };
+            let var = |v: i64, token: &str| {
+                if v == 0 || v.unsigned_abs() as usize > num_vars {
+                    Err(ParseError::at(
+                        input,
+                        token,
+                        format!("variable out of range 1..={}", num_vars),
+                    ))
+                } else {
+                    Ok(VarLabel::new(v.unsigned_abs() - 1))
+                }
+            };
+            let node = match (line[0], line.len()) {
+                ("L", 2) => {
+                    let lit: i64 = ParseError::number_at(input, line[1])?;
+                    NnfNode::Lit(var(lit, line[1])?, lit > 0)
+                }
+                ("A", n) if n >= 2 => NnfNode::And(children(line[1], &line[2..])?),
+                ("O", n) if n >= 3 => {
+                    let decision: i64 = ParseError::number_at(input, line[1])?;
+                    let decision = match decision {
+                        0 => None,
+                        _ => Some(var(decision, line[1])?),
+                    };
+                    NnfNode::Or(decision, children(line[2], &line[3..])?)
+                }
+                _ => return Err(ParseError::at(
+                    input,
+                    line[0],
+                    "expected `L <lit>`, `A <count> <children>*` or `O <var> <count> <children>*`",
+                )),
+            };
+            nodes.push(node);
+        }
+        if nodes.len() != num_nodes {
+            return Err(ParseError::unlocated(format!(
+                "header declares {} nodes, found {}",
+                num_nodes,
+                nodes.len()
+            )));
+        }
+        NnfCircuit::new(nodes, num_vars).map_err(ParseError::unlocated)
+    }
+
+    /// Returns an equivalent smooth circuit, in which every child of a
+    /// disjunction mentions the same variables as the disjunction and the
+    /// root mentions every variable. Each missing variable `v` is conjoined to
+    /// a child as `v \/ -v`.
+    pub fn smooth(&self) -> NnfCircuit {
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        // `v \/ -v`, built once per variable
+        let mut tautologies: HashMap<VarLabel, usize> = HashMap::new();
+        // conjoins node `id` with a tautology for every variable in `missing`
+        let mut pad = |nodes: &mut Vec<NnfNode>, id: usize, missing: VarSet| {
+            if missing.is_empty() {
+                return id;
+            }
+            let mut children = vec![id];
+            for v in missing.iter() {
+                let tautology = *tautologies.entry(v).or_insert_with(|| {
+                    nodes.push(NnfNode::Lit(v, true));
+                    nodes.push(NnfNode::Lit(v, false));
+                    nodes.push(NnfNode::Or(Some(v), vec![nodes.len() - 2, nodes.len() - 1]));
+                    nodes.len() - 1
+                });
+                children.push(tautology);
+            }
+            nodes.push(NnfNode::And(children));
+            nodes.len() - 1
+        };
+
+        // the new id and the variables of every original node
+        let mut ids: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        let mut vars: Vec<VarSet> = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.iter() {
+            let mut node_vars = VarSet::new();
+            let node = match node {
+                NnfNode::Lit(v, polarity) => {
+                    node_vars.insert(*v);
+                    NnfNode::Lit(*v, *polarity)
+                }
+                NnfNode::And(c) => {
+                    c.iter().for_each(|c| node_vars.union_with(&vars[*c]));
+                    NnfNode::And(c.iter().map(|c| ids[*c]).collect())
+                }
+                NnfNode::Or(decision, c) => {
+                    c.iter().for_each(|c| node_vars.union_with(&vars[*c]));
+                    let children = c
+                        .iter()
+                        .map(|c| pad(&mut nodes, ids[*c], node_vars.minus(&vars[*c])))
+                        .collect();
+                    NnfNode::Or(*decision, children)
+                }
+            };
+            nodes.push(node);
+            ids.push(nodes.len() - 1);
+            vars.push(node_vars);
+        }
+
+        let mut all = VarSet::new_with_num_vars(self.num_vars);
+        (0..self.num_vars).for_each(|v| all.insert(VarLabel::new_usize(v)));
+        let root = nodes.len() - 1;
+        pad(&mut nodes, root, all.minus(&vars[vars.len() - 1]));
+        NnfCircuit {
+            nodes,
+            num_vars: self.num_vars,
+        }
+    }
+
+    /// The weighted model count of the circuit over all of its variables,
+    /// which smooths it first (see [`NnfCircuit::smooth`])
+    /// ```
+    /// use rsdd::repr::{DDNNFPtr, NnfCircuit, VarLabel, WmcParams};
+    /// use rsdd::util::semirings::RealSemiring;
+    /// use std::collections::HashMap;
+    ///
+    /// // x1 \/ (-x1 /\ x2), over 3 variables; neither branch mentions x3
+    /// let circuit = NnfCircuit::from_c2d("nnf 5 4 3\nL 1\nL -1\nL 2\nA 2 1 2\nO 0 2 0 3\n").unwrap();
+    /// let weights = WmcParams::new(HashMap::from_iter(
+    ///     (0..3).map(|v| (VarLabel::new(v), (RealSemiring(1.0), RealSemiring(1.0)))),
+    /// ));
+    /// assert_eq!(circuit.wmc(&weights).0, 6.0);
+    /// // each branch of the unsmoothed circuit only counts what it mentions
+    /// assert_eq!(circuit.root().unsmoothed_wmc(&weights).0, 2.0);
+    /// ```
+    pub fn wmc<T: Semiring + 'static>(&self, params: &WmcParams<T>) -> T {
+        self.smooth().root().unsmoothed_wmc(params)
+    }
+
+    pub fn nodes(&self) -> &[NnfNode] {
+        &self.nodes
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// a pointer to the last node
+    pub fn root(&self) -> NnfPtr<'_> {
+        self.ptr(self.nodes.len() - 1)
+    }
+
+    /// a pointer to node `id`
+    pub fn ptr(&self, id: usize) -> NnfPtr<'_> {
+        match &self.nodes[id] {
+            NnfNode::Lit(v, polarity) => NnfPtr::Lit(*v, *polarity),
+            NnfNode::And(c) if c.is_empty() => NnfPtr::True,
+            NnfNode::Or(_, c) if c.is_empty() => NnfPtr::False,
+            _ => NnfPtr::Node(self, id),
+        }
+    }
+
+    /// marks the nodes reachable from `root`
+    fn reachable(&self, root: usize) -> Vec<bool> {
+        let mut reachable = vec![false; root + 1];
+        reachable[root] = true;
+        for id in (0..=root).rev() {
+            if reachable[id] {
+                if let NnfNode::And(c) | NnfNode::Or(_, c) = &self.nodes[id] {
+                    c.iter().for_each(|c| reachable[*c] = true);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+/// A pointer into an [`NnfCircuit`]. Constants and literals stand alone, so
+/// that they can be negated; negating any other node panics, since d-DNNFs
+/// are not closed under negation.
+#[derive(Clone, Copy)]
+pub enum NnfPtr<'a> {
+    True,
+    False,
+    Lit(VarLabel, bool),
+    Node(&'a NnfCircuit, usize),
+}
+
+impl<'a> Debug for NnfPtr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NnfPtr::True => write!(f, "True"),
+            NnfPtr::False => write!(f, "False"),
+            NnfPtr::Lit(v, polarity) => write!(f, "Lit({:?}, {})", v, polarity),
+            NnfPtr::Node(_, id) => write!(f, "Node({})", id),
+        }
+    }
+}
+
+impl<'a> PartialEq for NnfPtr<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NnfPtr::True, NnfPtr::True) | (NnfPtr::False, NnfPtr::False) => true,
+            (NnfPtr::Lit(v1, p1), NnfPtr::Lit(v2, p2)) => v1 == v2 && p1 == p2,
+            (NnfPtr::Node(c1, id1), NnfPtr::Node(c2, id2)) => std::ptr::eq(*c1, *c2) && id1 == id2,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for NnfPtr<'a> {}
+
+impl<'a> Hash for NnfPtr<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            NnfPtr::True | NnfPtr::False => (),
+            NnfPtr::Lit(v, polarity) => (v, polarity).hash(state),
+            NnfPtr::Node(c, id) => (*c as *const NnfCircuit, id).hash(state),
+        }
+    }
+}
+
+impl<'a> DDNNFPtr<'a> for NnfPtr<'a> {
+    fn fold<T: 'static + Clone + Copy + Debug, F: Fn(DDNNF<T>) -> T>(&self, f: F) -> T {
+        let (circuit, root) = match *self {
+            NnfPtr::True => return f(DDNNF::True),
+            NnfPtr::False => return f(DDNNF::False),
+            NnfPtr::Lit(v, polarity) => return f(DDNNF::Lit(v, polarity)),
+            NnfPtr::Node(circuit, root) => (circuit, root),
+        };
+        // children precede their parents, so a single forward pass suffices;
+        // n-ary nodes are folded as left-deep binary ones
+        let reachable = circuit.reachable(root);
+        let mut values: Vec<Option<T>> = vec![None; root + 1];
+        for id in (0..=root).filter(|id| reachable[*id]) {
+            let child = |c: &usize| values[*c].unwrap();
+            let v = match &circuit.nodes[id] {
+                NnfNode::Lit(v, polarity) => f(DDNNF::Lit(*v, *polarity)),
+                NnfNode::And(c) => c
+                    .iter()
+                    .fold(f(DDNNF::True), |acc, c| f(DDNNF::And(acc, child(c)))),
+                NnfNode::Or(decision, c) => {
+                    let mut vars = VarSet::new();
+                    if let Some(d) = decision {
+                        vars.insert(*d);
+                    }
+                    c.iter().fold(f(DDNNF::False), |acc, c| {
+                        f(DDNNF::Or(acc, child(c), vars.clone()))
+                    })
+                }
+            };
+            values[id] = Some(v);
+        }
+        values[root].unwrap()
+    }
+
+    fn neg(&self) -> Self {
+        match *self {
+            NnfPtr::True => NnfPtr::False,
+            NnfPtr::False => NnfPtr::True,
+            NnfPtr::Lit(v, polarity) => NnfPtr::Lit(v, !polarity),
+            NnfPtr::Node(_, _) => panic!("d-DNNF circuits cannot be negated"),
+        }
+    }
+
+    fn false_ptr() -> Self {
+        NnfPtr::False
+    }
+
+    fn true_ptr() -> Self {
+        NnfPtr::True
+    }
+
+    fn is_true(&self) -> bool {
+        matches!(self, NnfPtr::True)
+    }
+
+    fn is_false(&self) -> bool {
+        matches!(self, NnfPtr::False)
+    }
+
+    fn is_neg(&self) -> bool {
+        matches!(self, NnfPtr::Lit(_, false))
+    }
+
+    fn count_nodes(&self) -> usize {
+        match *self {
+            NnfPtr::Node(circuit, root) => circuit.reachable(root).iter().filter(|r| **r).count(),
+            _ => 1,
+        }
+    }
+}
+
+#[test]
+fn c2d_errors_are_located() {
+    let err = NnfCircuit::from_c2d("nnf 2 1 1\nL 1\nA 1 1\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (3, 1));
+    let err = NnfCircuit::from_c2d("nnf 1 0 1\nL 2\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 3));
+    assert!(NnfCircuit::from_c2d("nnf 2 0 1\nL 1\n").is_err());
+}
+
+#[test]
+fn smoothing_counts_unmentioned_variables() {
+    use crate::{
+        repr::{Cnf, Literal},
+        util::semirings::RealSemiring,
+    };
+
+    fn vars(circuit: &NnfCircuit, id: usize) -> VarSet {
+        let mut r = VarSet::new();
+        match &circuit.nodes()[id] {
+            NnfNode::Lit(v, _) => r.insert(*v),
+            NnfNode::And(c) | NnfNode::Or(_, c) => {
+                c.iter().for_each(|c| r.union_with(&vars(circuit, *c)))
+            }
+        }
+        r
+    }
+
+    // (x1 /\ x3) \/ (-x1 /\ x2), as c2d writes it: neither branch mentions
+    // every variable, and nothing mentions x4
+    let circuit =
+        NnfCircuit::from_c2d("nnf 7 6 4\nL 1\nL 3\nA 2 0 1\nL -1\nL 2\nA 2 3 4\nO 1 2 2 5\n")
+            .unwrap();
+    let smooth = circuit.smooth();
+    for (id, node) in smooth.nodes().iter().enumerate() {
+        if let NnfNode::Or(_, c) = node {
+            let expected = vars(&smooth, id);
+            assert!(c.iter().all(|c| vars(&smooth, *c) == expected));
+        }
+    }
+    assert_eq!(vars(&smooth, smooth.nodes().len() - 1).len(), 4);
+
+    let weights = WmcParams::new(HashMap::from_iter((0..4).map(|v| {
+        (
+            VarLabel::new(v),
+            (RealSemiring(0.2 + v as f64 / 10.0), RealSemiring(0.7)),
+        )
+    })));
+    let lit = |v, polarity| Literal::new(VarLabel::new(v), polarity);
+    let cnf = Cnf::new_with_num_vars(
+        &[
+            vec![lit(0, true), lit(1, true)],
+            vec![lit(0, false), lit(2, true)],
+        ],
+        4,
+    );
+    let expected = cnf.wmc(&weights).0;
+    assert!((circuit.wmc(&weights).0 - expected).abs() < 1e-9);
+    assert!((circuit.root().unsmoothed_wmc(&weights).0 - expected).abs() > 1e-3);
+}
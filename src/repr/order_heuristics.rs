@@ -0,0 +1,380 @@
+//! Heuristics for choosing variable orders for a CNF
+//!
+//! In addition to `Cnf::linear_order`, `Cnf::force_order`,
+//! `Cnf::min_fill_order` and `Cnf::hypergraph_order`, this module provides:
+//!   - greedy elimination orders: min-degree and weighted min-fill
+//!   - MINCE, which orders variables by recursive min-cut bisection of the
+//!     CNF's hypergraph (Aloul, Markov and Sakallah. "MINCE: A Static Global
+//!     Variable-Ordering for SAT and BDD." IWLS 2001)
+//!   - simulated annealing over orders, minimizing either the cutwidth of
+//!     the induced dtree or the bandwidth of the interaction graph
+//!
+//! [`OrderHeuristic`] names every heuristic so that they can be selected at
+//! runtime (e.g., from a command-line flag).
+
+use crate::{
+    repr::{Cnf, DTree, VarLabel, VarOrder},
+    util::{
+        btree::BTree,
+        hypergraph::{recursive_bisection, PartitionConfig},
+    },
+};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::{collections::HashSet, fmt, str::FromStr};
+
+/// The quantity minimized by [`Cnf::annealed_order`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderObjective {
+    /// cutwidth of the dtree generated from the order (see
+    /// [`DTree::from_cnf`])
+    Cutwidth,
+    /// the largest distance in the order between two variables that share a
+    /// clause
+    Bandwidth,
+}
+
+/// Parameters for [`Cnf::annealed_order`]
+#[derive(Debug, Clone)]
+pub struct AnnealingConfig {
+    /// number of candidate moves to try. Each move re-evaluates the objective
+    /// from scratch, which for cutwidth means building a dtree
+    pub iterations: usize,
+    /// starting temperature; a move that worsens the objective by 1 is
+    /// initially accepted with probability `exp(-1 / initial_temperature)`
+    pub initial_temperature: f64,
+    /// the temperature is multiplied by this factor after every move
+    pub cooling: f64,
+    pub seed: u64,
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        AnnealingConfig {
+            iterations: 250,
+            initial_temperature: 1.0,
+            cooling: 0.98,
+            seed: 0,
+        }
+    }
+}
+
+/// A variable ordering heuristic, selectable by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderHeuristic {
+    Linear,
+    MinFill,
+    WeightedMinFill,
+    MinDegree,
+    Force,
+    Mince,
+    Hypergraph,
+    AnnealCutwidth,
+    AnnealBandwidth,
+}
+
+impl OrderHeuristic {
+    /// every heuristic, paired with the name accepted by `from_str`
+    pub const ALL: [(&'static str, OrderHeuristic); 9] = [
+        ("linear", OrderHeuristic::Linear),
+        ("minfill", OrderHeuristic::MinFill),
+        ("weighted_minfill", OrderHeuristic::WeightedMinFill),
+        ("mindegree", OrderHeuristic::MinDegree),
+        ("force", OrderHeuristic::Force),
+        ("mince", OrderHeuristic::Mince),
+        ("hypergraph", OrderHeuristic::Hypergraph),
+        ("anneal_cutwidth", OrderHeuristic::AnnealCutwidth),
+        ("anneal_bandwidth", OrderHeuristic::AnnealBandwidth),
+    ];
+}
+
+impl FromStr for OrderHeuristic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OrderHeuristic::ALL
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, h)| *h)
+            .ok_or_else(|| {
+                let names: Vec<&str> = OrderHeuristic::ALL.iter().map(|(n, _)| *n).collect();
+                format!(
+                    "unknown order heuristic `{}`, expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for OrderHeuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = OrderHeuristic::ALL.iter().find(|(_, h)| h == self).unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+impl Cnf {
+    /// generate a variable order with the given heuristic, using default
+    /// parameters
+    /// ```
+    /// use rsdd::repr::{Cnf, OrderHeuristic};
+    /// let cnf = Cnf::from_string("(0 || 1) && (1 || 2)").unwrap();
+    /// let order = cnf.order_by("mindegree".parse::<OrderHeuristic>().unwrap());
+    /// assert_eq!(order.num_vars(), 3);
+    /// ```
+    pub fn order_by(&self, heuristic: OrderHeuristic) -> VarOrder {
+        match heuristic {
+            OrderHeuristic::Linear => self.linear_order(),
+            OrderHeuristic::MinFill => self.min_fill_order(),
+            OrderHeuristic::WeightedMinFill => self.weighted_min_fill_order(),
+            OrderHeuristic::MinDegree => self.min_degree_order(),
+            OrderHeuristic::Force => self.force_order(),
+            OrderHeuristic::Mince => self.mince_order(),
+            OrderHeuristic::Hypergraph => self.hypergraph_order(),
+            OrderHeuristic::AnnealCutwidth => {
+                self.annealed_order(OrderObjective::Cutwidth, &AnnealingConfig::default())
+            }
+            OrderHeuristic::AnnealBandwidth => {
+                self.annealed_order(OrderObjective::Bandwidth, &AnnealingConfig::default())
+            }
+        }
+    }
+
+    /// get an elimination order that repeatedly eliminates the variable with
+    /// the fewest neighbors in the (partially eliminated) interaction graph
+    pub fn min_degree_order(&self) -> VarOrder {
+        greedy_elimination(self, |adj, v| adj[v].len())
+    }
+
+    /// get a weighted min-fill elimination order: each fill edge `(a, b)`
+    /// costs `deg(a) * deg(b)`, so fill edges between highly connected
+    /// variables are avoided first
+    pub fn weighted_min_fill_order(&self) -> VarOrder {
+        greedy_elimination(self, |adj, v| {
+            let neighbors: Vec<usize> = adj[v].iter().copied().collect();
+            let mut cost = 0;
+            for (i, a) in neighbors.iter().enumerate() {
+                for b in neighbors[i + 1..].iter() {
+                    if !adj[*a].contains(b) {
+                        cost += adj[*a].len() * adj[*b].len();
+                    }
+                }
+            }
+            cost
+        })
+    }
+
+    /// get a MINCE order: the leaves, left to right, of a recursive min-cut
+    /// bisection of the hypergraph whose vertices are variables and whose
+    /// hyperedges are clauses
+    pub fn mince_order(&self) -> VarOrder {
+        if self.num_vars() == 0 {
+            return self.linear_order();
+        }
+        let edges: Vec<Vec<usize>> = self
+            .clauses()
+            .iter()
+            .map(|c| c.iter().map(|l| l.label().value_usize()).collect())
+            .collect();
+        let tree = recursive_bisection(self.num_vars(), &edges, &PartitionConfig::default());
+        let order: Vec<VarLabel> = tree
+            .inorder_dfs_iter()
+            .filter_map(|t| match t {
+                BTree::Leaf(v) => Some(VarLabel::new_usize(*v)),
+                _ => None,
+            })
+            .collect();
+        VarOrder::new(&order)
+    }
+
+    /// the largest distance in `order` between two variables that occur in
+    /// a common clause
+    pub fn bandwidth(&self, order: &VarOrder) -> usize {
+        bandwidth_cost(self, order).0
+    }
+
+    /// Searches for an order minimizing `objective` by simulated annealing,
+    /// starting from the min-fill order. Each move swaps two variables; ties
+    /// in the objective are broken by the sum of all cutset sizes (for
+    /// cutwidth) or of all clause spans (for bandwidth), which gives the
+    /// search a gradient on plateaus.
+    /// ```
+    /// use rsdd::repr::{AnnealingConfig, Cnf, OrderObjective};
+    /// let cnf = Cnf::from_string("(0 || 3) && (3 || 1) && (1 || 4) && (4 || 2)").unwrap();
+    /// let order = cnf.annealed_order(OrderObjective::Bandwidth, &AnnealingConfig::default());
+    /// assert!(cnf.bandwidth(&order) <= cnf.bandwidth(&cnf.min_fill_order()));
+    /// ```
+    pub fn annealed_order(&self, objective: OrderObjective, config: &AnnealingConfig) -> VarOrder {
+        let n = self.num_vars();
+        if n < 2 || self.clauses().is_empty() {
+            return self.linear_order();
+        }
+        let cost = |order: &VarOrder| -> f64 {
+            let (max, total, bound) = match objective {
+                OrderObjective::Cutwidth => cutwidth_cost(self, order),
+                OrderObjective::Bandwidth => bandwidth_cost(self, order),
+            };
+            max as f64 + total as f64 / (bound + 1) as f64
+        };
+
+        let mut rng = SmallRng::seed_from_u64(config.seed);
+        let mut cur: Vec<VarLabel> = self.min_fill_order().in_order_iter().collect();
+        let mut cur_cost = cost(&VarOrder::new(&cur));
+        let mut best = cur.clone();
+        let mut best_cost = cur_cost;
+        let mut temperature = config.initial_temperature;
+        for _ in 0..config.iterations {
+            let i = rng.gen_range(0..n);
+            let j = rng.gen_range(0..n);
+            if i == j {
+                continue;
+            }
+            cur.swap(i, j);
+            let next_cost = cost(&VarOrder::new(&cur));
+            let delta = next_cost - cur_cost;
+            if delta <= 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+                cur_cost = next_cost;
+                if cur_cost < best_cost {
+                    best_cost = cur_cost;
+                    best = cur.clone();
+                }
+            } else {
+                cur.swap(i, j);
+            }
+            temperature *= config.cooling;
+        }
+        VarOrder::new(&best)
+    }
+}
+
+/// the interaction graph as adjacency sets, indexed by variable
+pub(crate) fn adjacency(cnf: &Cnf) -> Vec<HashSet<usize>> {
+    let mut adj = vec![HashSet::new(); cnf.num_vars()];
+    for clause in cnf.clauses() {
+        for a in clause.iter() {
+            for b in clause.iter() {
+                let (a, b) = (a.label().value_usize(), b.label().value_usize());
+                if a != b {
+                    adj[a].insert(b);
+                }
+            }
+        }
+    }
+    adj
+}
+
+/// repeatedly eliminates the variable with the lowest `score` (breaking ties
+/// by label), connecting its neighbors
+fn greedy_elimination(cnf: &Cnf, score: impl Fn(&[HashSet<usize>], usize) -> usize) -> VarOrder {
+    eliminate(cnf, |adj, remaining| {
+        remaining
+            .iter()
+            .copied()
+            .min_by_key(|v| (score(adj, *v), *v))
+    })
+    .0
+}
+
+/// Eliminates variables from the interaction graph of `cnf` one at a time,
+/// connecting the neighbors of each, until `next` returns `None`. `next` is
+/// given the graph and the variables not yet eliminated, and picks the next
+/// variable to eliminate. Returns the elimination order and its induced
+/// width: the largest number of neighbors a variable had when eliminated.
+pub(crate) fn eliminate(
+    cnf: &Cnf,
+    mut next: impl FnMut(&[HashSet<usize>], &[usize]) -> Option<usize>,
+) -> (VarOrder, usize) {
+    let mut adj = adjacency(cnf);
+    let mut remaining: Vec<usize> = (0..cnf.num_vars()).collect();
+    let mut order = Vec::with_capacity(remaining.len());
+    let mut width = 0;
+    while let Some(v) = next(&adj, &remaining) {
+        remaining.retain(|r| *r != v);
+        let neighbors: Vec<usize> = adj[v].drain().collect();
+        width = usize::max(width, neighbors.len());
+        for a in neighbors.iter() {
+            adj[*a].remove(&v);
+            for b in neighbors.iter() {
+                if a != b {
+                    adj[*a].insert(*b);
+                }
+            }
+        }
+        order.push(VarLabel::new_usize(v));
+    }
+    (VarOrder::new(&order), width)
+}
+
+/// (bandwidth, sum of clause spans, bound on that sum)
+fn bandwidth_cost(cnf: &Cnf, order: &VarOrder) -> (usize, usize, usize) {
+    let (mut max, mut total) = (0, 0);
+    for clause in cnf.clauses() {
+        let positions = clause.iter().map(|l| order.get(l.label()));
+        let (lo, hi) = positions.fold((usize::MAX, 0), |(lo, hi), p| (lo.min(p), hi.max(p)));
+        if lo <= hi {
+            max = max.max(hi - lo);
+            total += hi - lo;
+        }
+    }
+    (max, total, cnf.clauses().len() * cnf.num_vars())
+}
+
+/// (cutwidth, sum of cutset sizes, bound on that sum) of the dtree for `order`
+fn cutwidth_cost(cnf: &Cnf, order: &VarOrder) -> (usize, usize, usize) {
+    fn visit(dtree: &DTree, max: &mut usize, total: &mut usize, nodes: &mut usize) {
+        if let DTree::Node { l, r, cutset, .. } = dtree {
+            let size = cutset.iter().count();
+            *max = usize::max(*max, size);
+            *total += size;
+            *nodes += 1;
+            visit(l, max, total, nodes);
+            visit(r, max, total, nodes);
+        }
+    }
+    let (mut max, mut total, mut nodes) = (0, 0, 0);
+    visit(
+        &DTree::from_cnf(cnf, order),
+        &mut max,
+        &mut total,
+        &mut nodes,
+    );
+    (max, total, nodes * cnf.num_vars())
+}
+
+#[test]
+fn heuristics_are_permutations() {
+    let cnf =
+        Cnf::from_string("(0 || 1 || -2) && (2 || 3) && (-3 || 4 || 0) && (5 || 6) && (1 || 6)")
+            .unwrap();
+    for (name, heuristic) in OrderHeuristic::ALL {
+        let order = cnf.order_by(heuristic);
+        let mut vars: Vec<usize> = order.in_order_iter().map(|v| v.value_usize()).collect();
+        vars.sort_unstable();
+        assert_eq!(vars, (0..cnf.num_vars()).collect::<Vec<_>>(), "{}", name);
+        assert_eq!(name.parse::<OrderHeuristic>().unwrap().to_string(), name);
+    }
+}
+
+#[test]
+fn min_degree_eliminates_leaves_first() {
+    // a star centered at 0: every leaf has degree 1, the center degree 4
+    let cnf = Cnf::from_string("(0 || 1) && (0 || 2) && (0 || 3) && (0 || 4)").unwrap();
+    let order = cnf.min_degree_order();
+    assert_eq!(order.var_at_level(0), VarLabel::new(1));
+    // once three leaves are gone, the center and last leaf tie at degree 1
+    assert_eq!(order.var_at_level(3), VarLabel::new(0));
+}
+
+#[test]
+fn annealing_never_worsens_cutwidth() {
+    // a ladder of implications, with variables interleaved so that a linear
+    // order has a wide dtree
+    let cnf = Cnf::from_string(
+        "(0 || 5) && (5 || 1) && (1 || 6) && (6 || 2) && (2 || 7) && (7 || 3) && (3 || 8) && (8 || 4)",
+    )
+    .unwrap();
+    let annealed = cnf.annealed_order(OrderObjective::Cutwidth, &AnnealingConfig::default());
+    let annealed_width = DTree::from_cnf(&cnf, &annealed).cutwidth();
+    let start_width = DTree::from_cnf(&cnf, &cnf.min_fill_order()).cutwidth();
+    assert!(annealed_width <= start_width);
+}
@@ -0,0 +1,108 @@
+//! Errors produced when parsing textual input formats (DIMACS, s-expressions,
+//! etc.)
+
+use std::{fmt, str::FromStr};
+
+/// An error encountered while parsing, along with where in the input it
+/// occurred. Lines and columns are 1-indexed; a line of 0 means that the
+/// underlying parser could not determine a location (e.g., for some
+/// s-expression errors).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// an error without location information
+    pub fn unlocated(message: impl Into<String>) -> ParseError {
+        ParseError::new(0, 0, message)
+    }
+
+    /// an error located at the start of `token`, which must be a subslice of
+    /// `input`
+    pub(crate) fn at(input: &str, token: &str, message: impl Into<String>) -> ParseError {
+        let offset = (token.as_ptr() as usize).saturating_sub(input.as_ptr() as usize);
+        let prefix = &input[..offset.min(input.len())];
+        let line = prefix.matches('\n').count() + 1;
+        let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+        let column = prefix[line_start..].chars().count() + 1;
+        ParseError::new(line, column, message)
+    }
+
+    /// parses `token`, which must be a subslice of `input`, as a number
+    pub(crate) fn number_at<T: FromStr>(input: &str, token: &str) -> Result<T, ParseError> {
+        token.parse().map_err(|_| {
+            ParseError::at(
+                input,
+                token,
+                format!("expected a number, found `{}`", token),
+            )
+        })
+    }
+
+    /// checks that the parentheses in an s-expression are balanced, so that
+    /// the most common syntax errors are reported with a location
+    pub(crate) fn check_parens(input: &str) -> Result<(), ParseError> {
+        let mut open: Vec<&str> = Vec::new();
+        for (idx, c) in input.char_indices() {
+            if c == '(' {
+                open.push(&input[idx..]);
+            } else if c == ')' && open.pop().is_none() {
+                return Err(ParseError::at(input, &input[idx..], "unmatched `)`"));
+            }
+        }
+        match open.pop() {
+            Some(unclosed) => Err(ParseError::at(input, unclosed, "unclosed `(`")),
+            None => Ok(()),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}:{}: {}", self.line, self.column, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<serde_sexpr::Error> for ParseError {
+    fn from(err: serde_sexpr::Error) -> ParseError {
+        ParseError::unlocated(format!("invalid s-expression: {}", err))
+    }
+}
+
+#[test]
+fn test_parse_error_location() {
+    let input = "p cnf 2 1\n1 -x 0\n";
+    let token = &input[input.find('x').unwrap() - 1..];
+    let err = ParseError::at(input, token, "bad literal");
+    assert_eq!((err.line(), err.column()), (2, 3));
+    assert_eq!(err.to_string(), "2:3: bad literal");
+}
@@ -0,0 +1,717 @@
+//! Simplification of CNFs prior to compilation
+//!
+//! Preprocessing shrinks a CNF using a collection of standard techniques:
+//!   - unit propagation
+//!   - pure-literal elimination
+//!   - subsumption and self-subsuming resolution
+//!   - equivalent-literal substitution (via strongly-connected components of
+//!     the binary implication graph)
+//!   - bounded variable elimination
+//!
+//! Only the variables in a *projection set* are guaranteed to keep their
+//! meaning. Variables outside of it are treated as existentially quantified,
+//! and may be removed by pure-literal elimination and variable elimination.
+//! When every variable is projected, the simplified CNF is equivalent to the
+//! original one up to the recorded unit and equivalence substitutions.
+//!
+//! Every simplification is recorded in a [`ReconstructionMap`], which extends
+//! models of the simplified CNF to models of the original one and maps weights
+//! so that the weighted model count of the original CNF can be recovered
+//! exactly from the simplified one.
+
+use crate::{
+    repr::{Cnf, Literal, PartialModel, VarLabel, VarSet, WmcParams},
+    util::semirings::Semiring,
+};
+use petgraph::{algo::tarjan_scc, graph::DiGraph, graph::NodeIndex};
+
+/// Selects which simplifications are performed by [`Cnf::preprocess`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreprocessConfig {
+    pub unit_propagation: bool,
+    pub pure_literals: bool,
+    pub subsumption: bool,
+    pub self_subsumption: bool,
+    pub equivalent_literals: bool,
+    pub variable_elimination: bool,
+    /// a variable is only eliminated if this does not increase the number of
+    /// clauses by more than this amount
+    pub elim_clause_growth: usize,
+    /// variables that occur in more than this many clauses are never eliminated
+    pub elim_occurrence_limit: usize,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        PreprocessConfig {
+            unit_propagation: true,
+            pure_literals: true,
+            subsumption: true,
+            self_subsumption: true,
+            equivalent_literals: true,
+            variable_elimination: true,
+            elim_clause_growth: 0,
+            elim_occurrence_limit: 16,
+        }
+    }
+}
+
+/// A single simplification performed during preprocessing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconstructionStep {
+    /// the literal is implied by the CNF
+    Unit(Literal),
+    /// the literal is pure and its variable is not projected, so it was set
+    /// to true
+    Pure(Literal),
+    /// every occurrence of `var` was replaced by `repr` (i.e., `var <=> repr`)
+    Equivalent { var: VarLabel, repr: Literal },
+    /// `var` (which is not projected) was eliminated by resolution; `clauses`
+    /// are the clauses it occurred in at the time of elimination
+    Eliminated {
+        var: VarLabel,
+        clauses: Vec<Vec<Literal>>,
+    },
+    /// `var` no longer occurs in the simplified CNF (e.g., because every
+    /// clause it occurred in was satisfied or subsumed), so it is
+    /// unconstrained
+    Free(VarLabel),
+}
+
+/// Records how a CNF was simplified, so that models and weights of the
+/// simplified CNF can be related back to the original CNF
+#[derive(Debug, Clone)]
+pub struct ReconstructionMap {
+    num_vars: usize,
+    projected: VarSet,
+    steps: Vec<ReconstructionStep>,
+}
+
+impl ReconstructionMap {
+    /// The simplifications that were performed, in order
+    pub fn steps(&self) -> &[ReconstructionStep] {
+        &self.steps
+    }
+
+    /// The set of projected variables
+    pub fn projected(&self) -> &VarSet {
+        &self.projected
+    }
+
+    /// Extends a model of the simplified CNF to a total model of the original
+    /// CNF.
+    ///
+    /// Variables left unset in `model` are assumed to be false.
+    pub fn extend_model(&self, model: &PartialModel) -> PartialModel {
+        let mut m = PartialModel::new(self.num_vars);
+        for v in 0..self.num_vars {
+            let label = VarLabel::new_usize(v);
+            m.set(label, model.get(label).unwrap_or(false));
+        }
+        for step in self.steps.iter().rev() {
+            match step {
+                ReconstructionStep::Unit(lit) | ReconstructionStep::Pure(lit) => {
+                    m.set(lit.label(), lit.polarity())
+                }
+                ReconstructionStep::Equivalent { var, repr } => {
+                    let value = m.get(repr.label()).unwrap();
+                    m.set(*var, value == repr.polarity());
+                }
+                ReconstructionStep::Eliminated { var, clauses } => {
+                    // var must be true iff some clause is only satisfied by var
+                    let needs_true = clauses.iter().any(|clause| {
+                        clause.contains(&Literal::new(*var, true))
+                            && clause
+                                .iter()
+                                .filter(|lit| lit.label() != *var)
+                                .all(|lit| m.lit_neg_implied(*lit))
+                    });
+                    m.set(*var, needs_true);
+                }
+                // any value extends the model; keep the one already set
+                ReconstructionStep::Free(_) => (),
+            }
+        }
+        m
+    }
+
+    /// Maps weights for the original CNF to weights for the simplified CNF.
+    ///
+    /// Returns `(params, scale)` such that the weighted model count of the
+    /// original CNF (projected onto the projected variables) is `scale` times
+    /// the weighted model count of the simplified CNF under `params` (again
+    /// projected onto the projected variables). Removed variables are given the
+    /// weight `(one, zero)` so that they do not affect smoothed counts.
+    ///
+    /// Panics if a weight is missing for a projected variable that was removed.
+    pub fn map_weights<T: Semiring>(&self, params: &WmcParams<T>) -> (WmcParams<T>, T) {
+        let mut new_params = params.clone();
+        let mut scale = T::one();
+        for step in self.steps.iter() {
+            match step {
+                ReconstructionStep::Unit(lit) => {
+                    if self.projected.contains(lit.label()) {
+                        let (low, high) = *new_params.var_weight(lit.label());
+                        scale = scale * if lit.polarity() { high } else { low };
+                    }
+                    new_params.set_weight(lit.label(), T::one(), T::zero());
+                }
+                ReconstructionStep::Equivalent { var, repr } => {
+                    if self.projected.contains(*var) {
+                        // fold the weight of var into its representative
+                        let (var_low, var_high) = *new_params.var_weight(*var);
+                        let (repr_low, repr_high) = *new_params.var_weight(repr.label());
+                        let (low, high) = if repr.polarity() {
+                            (repr_low * var_low, repr_high * var_high)
+                        } else {
+                            (repr_low * var_high, repr_high * var_low)
+                        };
+                        new_params.set_weight(repr.label(), low, high);
+                    }
+                    new_params.set_weight(*var, T::one(), T::zero());
+                }
+                ReconstructionStep::Pure(lit) => {
+                    new_params.set_weight(lit.label(), T::one(), T::zero());
+                }
+                ReconstructionStep::Eliminated { var, .. } => {
+                    new_params.set_weight(*var, T::one(), T::zero());
+                }
+                ReconstructionStep::Free(var) => {
+                    if self.projected.contains(*var) {
+                        let (low, high) = *new_params.var_weight(*var);
+                        scale = scale * (low + high);
+                    }
+                    new_params.set_weight(*var, T::one(), T::zero());
+                }
+            }
+        }
+        (new_params, scale)
+    }
+}
+
+/// The result of preprocessing a CNF
+#[derive(Debug, Clone)]
+pub struct PreprocessedCnf {
+    cnf: Cnf,
+    map: ReconstructionMap,
+}
+
+impl PreprocessedCnf {
+    /// The simplified CNF
+    pub fn cnf(&self) -> &Cnf {
+        &self.cnf
+    }
+
+    /// The mapping from the simplified CNF back to the original CNF
+    pub fn map(&self) -> &ReconstructionMap {
+        &self.map
+    }
+
+    pub fn into_parts(self) -> (Cnf, ReconstructionMap) {
+        (self.cnf, self.map)
+    }
+}
+
+/// Raised internally when the empty clause is derived
+struct Unsat;
+
+fn lit_idx(lit: Literal) -> usize {
+    lit.label().value_usize() * 2 + (lit.polarity() as usize)
+}
+
+fn idx_lit(idx: usize) -> Literal {
+    Literal::new(VarLabel::new_usize(idx / 2), idx % 2 == 1)
+}
+
+/// sorts and deduplicates a clause; None if the clause is a tautology
+fn normalize(mut clause: Vec<Literal>) -> Option<Vec<Literal>> {
+    clause.sort_by_key(|l| (l.label(), l.polarity()));
+    clause.dedup();
+    if clause.windows(2).any(|w| w[0].label() == w[1].label()) {
+        None
+    } else {
+        Some(clause)
+    }
+}
+
+/// A clause database with occurrence lists, used while simplifying
+struct ClauseDb<'a> {
+    clauses: Vec<Option<Vec<Literal>>>,
+    /// occurs\[lit_idx(l)\] contains the indices of clauses that contain l; it
+    /// may contain stale entries, which are filtered out by `occurrences`
+    occurs: Vec<Vec<usize>>,
+    /// discovered units that have not been propagated yet
+    units: Vec<Literal>,
+    assigned: PartialModel,
+    num_vars: usize,
+    projected: &'a VarSet,
+    config: &'a PreprocessConfig,
+    steps: Vec<ReconstructionStep>,
+}
+
+impl<'a> ClauseDb<'a> {
+    fn new(cnf: &Cnf, projected: &'a VarSet, config: &'a PreprocessConfig) -> Result<Self, Unsat> {
+        let mut db = ClauseDb {
+            clauses: Vec::new(),
+            occurs: vec![Vec::new(); cnf.num_vars() * 2],
+            units: Vec::new(),
+            assigned: PartialModel::new(cnf.num_vars()),
+            num_vars: cnf.num_vars(),
+            projected,
+            config,
+            steps: Vec::new(),
+        };
+        for clause in cnf.clauses() {
+            db.add_clause(clause.clone())?;
+        }
+        Ok(db)
+    }
+
+    fn add_clause(&mut self, clause: Vec<Literal>) -> Result<(), Unsat> {
+        self.clauses.push(None);
+        self.set_clause(self.clauses.len() - 1, clause)
+    }
+
+    /// replaces the clause at `idx`, normalizing it first
+    fn set_clause(&mut self, idx: usize, clause: Vec<Literal>) -> Result<(), Unsat> {
+        self.clauses[idx] = None;
+        let clause = match normalize(clause) {
+            None => return Ok(()),
+            Some(c) => c,
+        };
+        if clause.is_empty() {
+            return Err(Unsat);
+        }
+        if clause.len() == 1 && self.config.unit_propagation {
+            self.units.push(clause[0]);
+            return Ok(());
+        }
+        for lit in clause.iter() {
+            self.occurs[lit_idx(*lit)].push(idx);
+        }
+        self.clauses[idx] = Some(clause);
+        Ok(())
+    }
+
+    /// the live clauses containing `lit`; also compacts its occurrence list
+    fn occurrences(&mut self, lit: Literal) -> Vec<usize> {
+        let clauses = &self.clauses;
+        let occ = &mut self.occurs[lit_idx(lit)];
+        occ.sort_unstable();
+        occ.dedup();
+        occ.retain(|idx| matches!(&clauses[*idx], Some(c) if c.contains(&lit)));
+        occ.clone()
+    }
+
+    fn remove_literal(&mut self, idx: usize, lit: Literal) -> Result<(), Unsat> {
+        let mut clause = self.clauses[idx].take().unwrap();
+        clause.retain(|l| *l != lit);
+        self.set_clause(idx, clause)
+    }
+
+    fn propagate(&mut self) -> Result<bool, Unsat> {
+        let mut changed = false;
+        while let Some(lit) = self.units.pop() {
+            match self.assigned.get(lit.label()) {
+                Some(v) if v == lit.polarity() => continue,
+                Some(_) => return Err(Unsat),
+                None => (),
+            }
+            changed = true;
+            self.assigned.set(lit.label(), lit.polarity());
+            self.steps.push(ReconstructionStep::Unit(lit));
+            for idx in self.occurrences(lit) {
+                self.clauses[idx] = None;
+            }
+            for idx in self.occurrences(lit.negated()) {
+                self.remove_literal(idx, lit.negated())?;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Finds equivalent literals as strongly-connected components of the
+    /// binary implication graph and replaces each by a representative
+    fn substitute_equivalences(&mut self) -> Result<bool, Unsat> {
+        let mut g: DiGraph<(), ()> = DiGraph::new();
+        for _ in 0..(self.num_vars * 2) {
+            g.add_node(());
+        }
+        for clause in self.clauses.iter().flatten() {
+            if let [a, b] = clause[..] {
+                // (a \/ b) is equivalent to (!a => b) /\ (!b => a)
+                g.add_edge(
+                    NodeIndex::new(lit_idx(a.negated())),
+                    NodeIndex::new(lit_idx(b)),
+                    (),
+                );
+                g.add_edge(
+                    NodeIndex::new(lit_idx(b.negated())),
+                    NodeIndex::new(lit_idx(a)),
+                    (),
+                );
+            }
+        }
+
+        let mut repr: Vec<Option<Literal>> = vec![None; self.num_vars * 2];
+        for scc in tarjan_scc(&g) {
+            if scc.len() < 2 {
+                continue;
+            }
+            let lits: Vec<Literal> = scc.iter().map(|n| idx_lit(n.index())).collect();
+            if lits.iter().any(|l| lits.contains(&l.negated())) {
+                return Err(Unsat);
+            }
+            // prefer projected representatives, so that a projected variable
+            // is never replaced by an unprojected one
+            let r = *lits
+                .iter()
+                .min_by_key(|l| (!self.projected.contains(l.label()), l.label()))
+                .unwrap();
+            for l in lits {
+                if l.label() != r.label() {
+                    repr[lit_idx(l)] = Some(r);
+                }
+            }
+        }
+
+        let mut changed = false;
+        for v in 0..self.num_vars {
+            let var = VarLabel::new_usize(v);
+            let r = match repr[lit_idx(Literal::new(var, true))] {
+                None => continue,
+                Some(r) => r,
+            };
+            changed = true;
+            self.steps
+                .push(ReconstructionStep::Equivalent { var, repr: r });
+            for polarity in [true, false] {
+                let lit = Literal::new(var, polarity);
+                let new_lit = if polarity { r } else { r.negated() };
+                for idx in self.occurrences(lit) {
+                    let clause = self.clauses[idx]
+                        .take()
+                        .unwrap()
+                        .into_iter()
+                        .map(|l| if l == lit { new_lit } else { l })
+                        .collect();
+                    self.set_clause(idx, clause)?;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Removes subsumed clauses and strengthens clauses by self-subsuming
+    /// resolution
+    fn subsume(&mut self) -> Result<bool, Unsat> {
+        let mut changed = false;
+        let mut order: Vec<usize> = (0..self.clauses.len())
+            .filter(|idx| self.clauses[*idx].is_some())
+            .collect();
+        order.sort_by_key(|idx| self.clauses[*idx].as_ref().unwrap().len());
+
+        for idx in order {
+            let clause = match &self.clauses[idx] {
+                None => continue,
+                Some(c) => c.clone(),
+            };
+            if self.config.subsumption {
+                // every clause subsumed by this one contains its rarest literal
+                let pivot = *clause
+                    .iter()
+                    .min_by_key(|l| self.occurs[lit_idx(**l)].len())
+                    .unwrap();
+                for other in self.occurrences(pivot) {
+                    if other != idx
+                        && clause
+                            .iter()
+                            .all(|l| self.clauses[other].as_ref().unwrap().contains(l))
+                    {
+                        self.clauses[other] = None;
+                        changed = true;
+                    }
+                }
+            }
+            if self.config.self_subsumption {
+                // (l \/ A) and (!l \/ B) with A a subset of B resolve to B,
+                // which subsumes (!l \/ B)
+                for lit in clause.iter() {
+                    for other in self.occurrences(lit.negated()) {
+                        if other == idx {
+                            continue;
+                        }
+                        let other_clause = self.clauses[other].as_ref().unwrap();
+                        if clause.iter().all(|l| l == lit || other_clause.contains(l)) {
+                            self.remove_literal(other, lit.negated())?;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    fn is_unprojected_free(&self, var: VarLabel) -> bool {
+        !self.projected.contains(var) && !self.assigned.is_set(var)
+    }
+
+    /// Sets unprojected variables that occur with only one polarity
+    fn eliminate_pure_literals(&mut self) -> bool {
+        let mut changed = false;
+        for v in 0..self.num_vars {
+            let var = VarLabel::new_usize(v);
+            if !self.is_unprojected_free(var) {
+                continue;
+            }
+            let pos = self.occurrences(Literal::new(var, true));
+            let neg = self.occurrences(Literal::new(var, false));
+            let (lit, occ) = match (pos.is_empty(), neg.is_empty()) {
+                (false, true) => (Literal::new(var, true), pos),
+                (true, false) => (Literal::new(var, false), neg),
+                _ => continue,
+            };
+            changed = true;
+            self.steps.push(ReconstructionStep::Pure(lit));
+            for idx in occ {
+                self.clauses[idx] = None;
+            }
+        }
+        changed
+    }
+
+    /// Eliminates unprojected variables by clause distribution whenever this
+    /// does not grow the CNF by more than the configured bound
+    fn eliminate_variables(&mut self) -> Result<bool, Unsat> {
+        let mut changed = false;
+        for v in 0..self.num_vars {
+            let var = VarLabel::new_usize(v);
+            if !self.is_unprojected_free(var) {
+                continue;
+            }
+            let pos = self.occurrences(Literal::new(var, true));
+            let neg = self.occurrences(Literal::new(var, false));
+            let num_occ = pos.len() + neg.len();
+            if num_occ == 0 || num_occ > self.config.elim_occurrence_limit {
+                continue;
+            }
+
+            let mut resolvents = Vec::new();
+            for p in pos.iter() {
+                for n in neg.iter() {
+                    let resolvent = self.clauses[*p]
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .chain(self.clauses[*n].as_ref().unwrap().iter())
+                        .filter(|l| l.label() != var)
+                        .copied()
+                        .collect();
+                    if let Some(r) = normalize(resolvent) {
+                        resolvents.push(r);
+                    }
+                }
+                if resolvents.len() > num_occ + self.config.elim_clause_growth {
+                    break;
+                }
+            }
+            if resolvents.len() > num_occ + self.config.elim_clause_growth {
+                continue;
+            }
+
+            changed = true;
+            let clauses = pos
+                .iter()
+                .chain(neg.iter())
+                .map(|idx| self.clauses[*idx].take().unwrap())
+                .collect();
+            self.steps
+                .push(ReconstructionStep::Eliminated { var, clauses });
+            for r in resolvents {
+                self.add_clause(r)?;
+            }
+        }
+        Ok(changed)
+    }
+
+    fn simplify(&mut self) -> Result<(), Unsat> {
+        loop {
+            let mut changed = self.propagate()?;
+            if self.config.equivalent_literals {
+                changed |= self.substitute_equivalences()?;
+                changed |= self.propagate()?;
+            }
+            if self.config.subsumption || self.config.self_subsumption {
+                changed |= self.subsume()?;
+                changed |= self.propagate()?;
+            }
+            if self.config.pure_literals {
+                changed |= self.eliminate_pure_literals();
+            }
+            if self.config.variable_elimination {
+                changed |= self.eliminate_variables()?;
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Records every variable of `cnf` that vanished from the clauses without
+    /// a step of its own as [`ReconstructionStep::Free`]
+    fn record_free_variables(&mut self, cnf: &Cnf) {
+        let mut occurred = vec![false; self.num_vars];
+        for lit in cnf.clauses().iter().flatten() {
+            occurred[lit.label().value_usize()] = true;
+        }
+        let mut accounted = vec![false; self.num_vars];
+        for lit in self.clauses.iter().flatten().flatten() {
+            accounted[lit.label().value_usize()] = true;
+        }
+        for step in self.steps.iter() {
+            let var = match step {
+                ReconstructionStep::Unit(lit) | ReconstructionStep::Pure(lit) => lit.label(),
+                ReconstructionStep::Equivalent { var, .. }
+                | ReconstructionStep::Eliminated { var, .. }
+                | ReconstructionStep::Free(var) => *var,
+            };
+            accounted[var.value_usize()] = true;
+        }
+        for v in 0..self.num_vars {
+            if occurred[v] && !accounted[v] {
+                self.steps
+                    .push(ReconstructionStep::Free(VarLabel::new_usize(v)));
+            }
+        }
+    }
+}
+
+impl Cnf {
+    /// Simplifies this CNF, treating the variables outside of `projected` as
+    /// existentially quantified.
+    ///
+    /// Returns None if the CNF is found to be unsatisfiable.
+    /// ```
+    /// use rsdd::repr::{Cnf, PreprocessConfig, VarLabel, VarSet};
+    ///
+    /// // (a \/ b) /\ (!a \/ b) /\ (b \/ c)
+    /// let cnf = Cnf::from_dimacs("p cnf 3 3\n1 2 0\n-1 2 0\n2 3 0\n").unwrap();
+    ///
+    /// let mut projected = VarSet::new();
+    /// projected.insert(VarLabel::new(1));
+    /// projected.insert(VarLabel::new(2));
+    ///
+    /// let simplified = cnf.preprocess(&projected, &PreprocessConfig::default()).unwrap();
+    /// // b is implied, which satisfies every clause
+    /// assert!(simplified.cnf().clauses().is_empty());
+    /// ```
+    pub fn preprocess(
+        &self,
+        projected: &VarSet,
+        config: &PreprocessConfig,
+    ) -> Option<PreprocessedCnf> {
+        let mut db = ClauseDb::new(self, projected, config).ok()?;
+        db.simplify().ok()?;
+        db.record_free_variables(self);
+        let clauses: Vec<Vec<Literal>> = db.clauses.into_iter().flatten().collect();
+        Some(PreprocessedCnf {
+            cnf: Cnf::new_with_num_vars(&clauses, self.num_vars()),
+            map: ReconstructionMap {
+                num_vars: self.num_vars(),
+                projected: projected.clone(),
+                steps: db.steps,
+            },
+        })
+    }
+
+    /// Simplifies this CNF with the default configuration, keeping every
+    /// variable projected (so that unweighted and weighted model counts can be
+    /// recovered exactly).
+    ///
+    /// Returns None if the CNF is found to be unsatisfiable.
+    pub fn simplify(&self) -> Option<PreprocessedCnf> {
+        let mut projected = VarSet::new_with_num_vars(self.num_vars());
+        for v in 0..self.num_vars() {
+            projected.insert(VarLabel::new_usize(v));
+        }
+        self.preprocess(&projected, &PreprocessConfig::default())
+    }
+}
+
+#[test]
+fn test_equivalent_literals() {
+    // a <=> b, (a \/ c)
+    let cnf = Cnf::from_dimacs("p cnf 3 3\n-1 2 0\n1 -2 0\n2 3 0\n").unwrap();
+    let simplified = cnf.simplify().unwrap();
+    assert_eq!(simplified.cnf().clauses().len(), 1);
+    assert_eq!(
+        simplified.map().steps()[0],
+        ReconstructionStep::Equivalent {
+            var: VarLabel::new(1),
+            repr: Literal::new(VarLabel::new(0), true)
+        }
+    );
+
+    let model = PartialModel::from_assignments(&[Some(true), None, Some(false)]);
+    let extended = simplified.map().extend_model(&model);
+    assert_eq!(extended.get(VarLabel::new(1)), Some(true));
+}
+
+#[test]
+fn test_variable_elimination() {
+    // x is unprojected: (a \/ x) /\ (b \/ !x) simplifies to (a \/ b)
+    let cnf = Cnf::from_dimacs("p cnf 3 2\n1 3 0\n2 -3 0\n").unwrap();
+    let mut projected = VarSet::new();
+    projected.insert(VarLabel::new(0));
+    projected.insert(VarLabel::new(1));
+    let config = PreprocessConfig {
+        pure_literals: false,
+        ..Default::default()
+    };
+    let simplified = cnf.preprocess(&projected, &config).unwrap();
+    assert_eq!(
+        simplified.cnf().clauses(),
+        &[vec![
+            Literal::new(VarLabel::new(0), true),
+            Literal::new(VarLabel::new(1), true)
+        ]]
+    );
+
+    let model = PartialModel::from_assignments(&[Some(false), Some(true)]);
+    let extended = simplified.map().extend_model(&model);
+    assert!(cnf.is_sat_partial(&extended));
+}
+
+#[test]
+fn test_free_variables() {
+    use crate::util::semirings::RealSemiring;
+
+    // (a) /\ (a \/ c): c vanishes once a is propagated
+    let cnf = Cnf::from_dimacs("p cnf 2 2\n1 0\n1 2 0\n").unwrap();
+    let simplified = cnf.simplify().unwrap();
+    assert_eq!(simplified.cnf().num_vars(), 2);
+    assert_eq!(
+        simplified.map().steps(),
+        &[
+            ReconstructionStep::Unit(Literal::new(VarLabel::new(0), true)),
+            ReconstructionStep::Free(VarLabel::new(1))
+        ]
+    );
+
+    let mut params = WmcParams::default();
+    params.set_weight(VarLabel::new(0), RealSemiring(0.3), RealSemiring(0.7));
+    params.set_weight(VarLabel::new(1), RealSemiring(0.4), RealSemiring(0.6));
+    let (new_params, scale) = simplified.map().map_weights(&params);
+    assert!((scale.0 - 0.7).abs() < 1e-9);
+    assert_eq!(
+        *new_params.var_weight(VarLabel::new(1)),
+        (RealSemiring(1.0), RealSemiring(0.0))
+    );
+}
+
+#[test]
+fn test_preprocess_unsat() {
+    let cnf = Cnf::from_dimacs("p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+    assert!(cnf.simplify().is_none());
+}
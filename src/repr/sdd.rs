@@ -31,6 +31,9 @@ pub enum SddPtr<'a> {
     Reg(&'a SddOr<'a>),
 }
 
+/// Hashes by node identity, never by the scratch data or cached semantic hash
+/// inside nodes, so collections keyed by `SddPtr` are sound even though
+/// clippy's `mutable_key_type` lint flags them
 impl<'a> Hash for SddPtr<'a> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
@@ -279,6 +279,9 @@ pub struct SATSolver {
     contains_pos_lit: Vec<BitSet>,
     contains_neg_lit: Vec<BitSet>,
     state_stack: Vec<SatState>,
+    // result of the most recent call to `solve` or `solve_under_assumptions`
+    last_model: Option<PartialModel>,
+    last_core: Option<Vec<Literal>>,
 }
 
 impl SATSolver {
@@ -417,6 +420,8 @@ impl SATSolver {
                     contains_pos_lit: pos_lit,
                     contains_neg_lit: neg_lit,
                     state_stack: vec![top_state],
+                    last_model: None,
+                    last_core: None,
                 };
 
                 let (new_hash, new_sat_set) = solver.update_hash_and_sat_set(&state);
@@ -481,6 +486,164 @@ impl SATSolver {
     pub fn is_set(&self, var: VarLabel) -> bool {
         self.top_state().model.is_set(var)
     }
+
+    /// Checks whether the CNF is satisfiable under the current decisions.
+    ///
+    /// On success the satisfying assignment is available through
+    /// [`SATSolver::model`]. The decision stack is left unchanged, so the
+    /// solver can be queried again (incrementally).
+    /// ```
+    /// use rsdd::repr::{Cnf, SATSolver, VarLabel};
+    ///
+    /// let cnf = Cnf::from_dimacs("p cnf 2 2\n1 2 0\n-1 0\n").unwrap();
+    /// let mut solver = SATSolver::new(cnf).unwrap();
+    ///
+    /// assert!(solver.solve());
+    /// let model = solver.model().unwrap();
+    /// assert_eq!(model.get(VarLabel::new(0)), Some(false));
+    /// assert_eq!(model.get(VarLabel::new(1)), Some(true));
+    /// ```
+    pub fn solve(&mut self) -> bool {
+        self.solve_under_assumptions(&[])
+    }
+
+    /// Checks whether the CNF is satisfiable when all literals in
+    /// `assumptions` are forced to hold.
+    ///
+    /// Returns true if SAT; the model can then be retrieved with
+    /// [`SATSolver::model`]. Returns false if UNSAT; in that case
+    /// [`SATSolver::unsat_core`] gives a minimal subset of the assumptions
+    /// that is by itself inconsistent with the CNF (empty if the CNF is
+    /// unsatisfiable under the current decisions regardless of assumptions).
+    /// The assumptions are retracted before returning.
+    /// ```
+    /// use rsdd::repr::{Cnf, Literal, SATSolver, VarLabel};
+    ///
+    /// // (a \/ b) /\ (!a \/ c)
+    /// let cnf = Cnf::from_dimacs("p cnf 3 2\n1 2 0\n-1 3 0\n").unwrap();
+    /// let mut solver = SATSolver::new(cnf).unwrap();
+    ///
+    /// let a = Literal::new(VarLabel::new(0), true);
+    /// let b = Literal::new(VarLabel::new(1), true);
+    /// let not_c = Literal::new(VarLabel::new(2), false);
+    ///
+    /// assert!(!solver.solve_under_assumptions(&[b, a, not_c]));
+    /// assert_eq!(solver.unsat_core().unwrap(), &[a, not_c]);
+    ///
+    /// assert!(solver.solve_under_assumptions(&[not_c]));
+    /// assert!(solver.model().unwrap().lit_implied(not_c));
+    /// ```
+    pub fn solve_under_assumptions(&mut self, assumptions: &[Literal]) -> bool {
+        self.last_model = None;
+        self.last_core = None;
+        match self.check_assumptions(assumptions) {
+            Ok(model) => {
+                self.last_model = Some(model);
+                true
+            }
+            Err(failed) => {
+                self.last_core = Some(self.minimize_core(&assumptions[..failed]));
+                false
+            }
+        }
+    }
+
+    /// The satisfying assignment found by the most recent successful call to
+    /// [`SATSolver::solve`] or [`SATSolver::solve_under_assumptions`]
+    ///
+    /// Variables left unset in the model may take either value.
+    pub fn model(&self) -> Option<&PartialModel> {
+        self.last_model.as_ref()
+    }
+
+    /// The conflicting assumptions found by the most recent unsuccessful call
+    /// to [`SATSolver::solve_under_assumptions`]
+    ///
+    /// The core is minimal: removing any literal from it makes the CNF
+    /// satisfiable again.
+    pub fn unsat_core(&self) -> Option<&[Literal]> {
+        self.last_core.as_deref()
+    }
+
+    /// Decides every assumption in order and then searches for a model,
+    /// restoring the decision stack afterwards.
+    ///
+    /// On failure, returns the length of the shortest prefix of `assumptions`
+    /// that was found to be inconsistent.
+    fn check_assumptions(&mut self, assumptions: &[Literal]) -> Result<PartialModel, usize> {
+        let depth = self.state_stack.len();
+        let mut result = Err(assumptions.len());
+        for (idx, lit) in assumptions.iter().enumerate() {
+            if let DecisionResult::UNSAT = self.decide(*lit) {
+                result = Err(idx + 1);
+                break;
+            }
+        }
+        if self.state_stack.len() == depth + assumptions.len() {
+            if let Some(model) = self.search() {
+                result = Ok(model);
+            }
+        }
+        self.state_stack.truncate(depth);
+        result
+    }
+
+    /// Deletion-based core minimization: drops each assumption in turn and
+    /// keeps it out of the core if the remaining assumptions are still UNSAT
+    fn minimize_core(&mut self, assumptions: &[Literal]) -> Vec<Literal> {
+        let mut core = assumptions.to_vec();
+        let mut idx = 0;
+        while idx < core.len() {
+            let mut candidate = core.clone();
+            candidate.remove(idx);
+            match self.check_assumptions(&candidate) {
+                Ok(_) => idx += 1,
+                Err(failed) => {
+                    candidate.truncate(failed);
+                    core = candidate;
+                }
+            }
+        }
+        core
+    }
+
+    /// A DPLL search from the current top state. Returns a satisfying model
+    /// if one exists; leaves the decision stack as it was found.
+    fn search(&mut self) -> Option<PartialModel> {
+        if self.is_sat() {
+            return Some(self.top_state().model.clone());
+        }
+
+        // branch on an unassigned literal of the first unsatisfied clause
+        let state = self.top_state();
+        let branch = (0..self.clauses.len())
+            .filter(|idx| !state.sat_clauses.contains(*idx))
+            .find_map(|idx| {
+                self.clauses[idx]
+                    .iter()
+                    .map(|(lit, _)| *lit)
+                    .find(|lit| !state.model.is_set(lit.label()))
+            })?;
+
+        for lit in [branch, branch.negated()] {
+            match self.decide(lit) {
+                DecisionResult::UNSAT => (),
+                DecisionResult::SAT => {
+                    let model = self.top_state().model.clone();
+                    self.pop();
+                    return Some(model);
+                }
+                DecisionResult::Unknown => {
+                    let model = self.search();
+                    self.pop();
+                    if model.is_some() {
+                        return model;
+                    }
+                }
+            }
+        }
+        None
+    }
 }
 
 #[test]
@@ -561,6 +724,43 @@ fn test_unit_propagate_3() {
     }
 }
 
+#[test]
+fn test_solve_unsat_core() {
+    // (a \/ b) /\ (!a \/ b) /\ (a \/ !b \/ c) /\ (b \/ c \/ d)
+    let cnf = Cnf::from_dimacs(
+        "
+p cnf 4 4
+1 2 0
+-1 2 0
+1 -2 3 0
+2 3 4 0
+",
+    )
+    .unwrap();
+    let mut solver = SATSolver::new(cnf.clone()).unwrap();
+    assert!(solver.solve());
+    let model = solver.model().unwrap();
+    assert!(cnf.is_sat_partial(model));
+    assert_eq!(solver.unsat_core(), None);
+
+    let not_b = Literal::new(VarLabel::new(1), false);
+    let not_a = Literal::new(VarLabel::new(0), false);
+    let not_c = Literal::new(VarLabel::new(2), false);
+    let d = Literal::new(VarLabel::new(3), true);
+
+    assert!(!solver.solve_under_assumptions(&[d, not_b]));
+    assert_eq!(solver.unsat_core().unwrap(), &[not_b]);
+    assert_eq!(solver.model(), None);
+
+    assert!(!solver.solve_under_assumptions(&[not_a, d, not_c]));
+    assert_eq!(solver.unsat_core().unwrap(), &[not_a, not_c]);
+
+    // assumptions are retracted between calls
+    assert!(solver.solve_under_assumptions(&[d]));
+    assert!(solver.model().unwrap().lit_implied(d));
+    assert!(!solver.is_set(VarLabel::new(3)));
+}
+
 // #[test]
 // fn test_unit_propagate_3() {
 //     let v = vec![
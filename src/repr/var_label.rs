@@ -148,6 +148,18 @@ impl Serialize for VarSet {
     }
 }
 
+impl<'de> serde::Deserialize<'de> for VarSet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let vars: Vec<usize> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(VarSet {
+            b: vars.into_iter().collect(),
+        })
+    }
+}
+
 impl Display for VarSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_fmt(format_args!("{:?}", self.b.iter().collect::<Vec<usize>>()))
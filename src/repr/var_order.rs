@@ -2,7 +2,7 @@
 //! in the order occur first in the BDD, starting from the root.
 //! Lower numbers occur first in the order (i.e., closer to the root)
 
-use crate::repr::VarLabel;
+use crate::repr::{VarLabel, VarSet};
 use std::fmt::{Debug, Display};
 
 #[derive(Debug, Clone)]
@@ -42,6 +42,24 @@ impl VarOrder {
         VarOrder::new(&v)
     }
 
+    /// Reorders this order so that every variable in `projection` precedes
+    /// every variable outside of it, otherwise preserving the relative order.
+    /// This is the decision order required for projected top-down compilation.
+    /// ```
+    /// # use rsdd::repr::{VarLabel, VarOrder, VarSet};
+    /// let mut projection = VarSet::new();
+    /// projection.insert(VarLabel::new(2));
+    /// let o = VarOrder::linear_order(3).project_first(&projection);
+    /// assert_eq!(o.var_at_level(0), VarLabel::new(2));
+    /// assert_eq!(o.var_at_level(1), VarLabel::new(0));
+    /// ```
+    pub fn project_first(&self, projection: &VarSet) -> VarOrder {
+        let (mut order, rest): (Vec<VarLabel>, Vec<VarLabel>) =
+            self.in_order_iter().partition(|v| projection.contains(*v));
+        order.extend(rest);
+        VarOrder::new(&order)
+    }
+
     /// Gives the number of variables in the order
     /// ```
     /// # use rsdd::repr::VarOrder;
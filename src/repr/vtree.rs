@@ -2,11 +2,15 @@
 
 use crate::{
     repr::{
+        cnf::Cnf,
         dtree::DTree,
         sdd::SddPtr,
         var_label::{VarLabel, VarSet},
     },
-    util::btree::{BTree, LeastCommonAncestor},
+    util::{
+        btree::{BTree, LeastCommonAncestor},
+        hypergraph::PartitionConfig,
+    },
 };
 use quickcheck::{Arbitrary, Gen};
 use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
@@ -198,6 +202,29 @@ impl VTree {
         }
     }
 
+    /// Generates a vtree for `cnf` from a dtree found by recursive min-cut
+    /// bisection (see [`DTree::from_cnf_hypergraph`]). Variables that do not
+    /// occur in any clause are placed right-linearly at the end.
+    ///
+    /// Panics if `cnf` has no variables.
+    pub fn from_cnf_hypergraph(cnf: &Cnf, config: &PartitionConfig) -> VTree {
+        let vtree = if cnf.clauses().is_empty() {
+            None
+        } else {
+            VTree::from_dtree(&DTree::from_cnf_hypergraph(cnf, config))
+        };
+        let present = vtree.as_ref().map_or(HashSet::new(), |v| v.all_vars());
+        let missing: Vec<VarLabel> = (0..cnf.num_vars())
+            .filter(|v| !present.contains(v))
+            .map(VarLabel::new_usize)
+            .collect();
+        match vtree {
+            Some(v) if missing.is_empty() => v,
+            Some(v) => VTree::new_node(Box::new(v), Box::new(VTree::right_linear(&missing))),
+            None => VTree::right_linear(&missing),
+        }
+    }
+
     /// generate an even vtree by splitting a variable ordering in half repeatedly
     /// times; then reverts to a right-linear vtree for the remainder
     pub fn even_split(order: &[VarLabel], num_splits: usize) -> VTree {
@@ -255,6 +282,97 @@ impl VTree {
     }
 }
 
+impl VTree {
+    /// the number of nodes (leaves and internal nodes) in this vtree
+    pub fn num_nodes(&self) -> usize {
+        match self {
+            BTree::Leaf(_) => 1,
+            BTree::Node((), l, r) => l.num_nodes() + 1 + r.num_nodes(),
+        }
+    }
+
+    /// applies `f` to the subtree at vtree index `idx`, returning the new
+    /// vtree if `f` succeeds
+    fn update_at(&self, idx: VTreeIndex, f: &impl Fn(&VTree) -> Option<VTree>) -> Option<VTree> {
+        match self {
+            BTree::Leaf(_) if idx.0 == 0 => f(self),
+            BTree::Leaf(_) => None,
+            BTree::Node((), l, r) => {
+                let l_size = l.num_nodes();
+                match idx.0.cmp(&l_size) {
+                    std::cmp::Ordering::Less => {
+                        let l = l.update_at(idx, f)?;
+                        Some(VTree::new_node(Box::new(l), r.clone()))
+                    }
+                    std::cmp::Ordering::Equal => f(self),
+                    std::cmp::Ordering::Greater => {
+                        let r = r.update_at(VTreeIndex(idx.0 - l_size - 1), f)?;
+                        Some(VTree::new_node(l.clone(), Box::new(r)))
+                    }
+                }
+            }
+        }
+    }
+
+    /// rotates the node at `idx` right, i.e. `((a, b), c)` becomes
+    /// `(a, (b, c))`. Since indices are assigned in-order, the root of the
+    /// rotated fragment moves to index `idx - |b| - 1`, where `|b|` is the size
+    /// of `b`. Returns None if the left child of `idx` is a leaf.
+    /// ```
+    /// use rsdd::repr::{VarLabel, VTree, VTreeIndex};
+    ///
+    /// let vars = [VarLabel::new(0), VarLabel::new(1), VarLabel::new(2)];
+    /// let rotated = VTree::left_linear(&vars).rotate_right(VTreeIndex::new(3));
+    /// assert_eq!(rotated, Some(VTree::right_linear(&vars)));
+    /// ```
+    pub fn rotate_right(&self, idx: VTreeIndex) -> Option<VTree> {
+        self.update_at(idx, &|node| match node {
+            BTree::Node((), l, c) => match l.as_ref() {
+                BTree::Node((), a, b) => Some(VTree::new_node(
+                    a.clone(),
+                    Box::new(VTree::new_node(b.clone(), c.clone())),
+                )),
+                BTree::Leaf(_) => None,
+            },
+            BTree::Leaf(_) => None,
+        })
+    }
+
+    /// rotates the node at `idx` left, i.e. `(a, (b, c))` becomes
+    /// `((a, b), c)`. The root of the rotated fragment moves to index
+    /// `idx + |b| + 1`. Returns None if the right child of `idx` is a leaf.
+    /// ```
+    /// use rsdd::repr::{VarLabel, VTree, VTreeIndex};
+    ///
+    /// let vars = [VarLabel::new(0), VarLabel::new(1), VarLabel::new(2)];
+    /// let rotated = VTree::right_linear(&vars).rotate_left(VTreeIndex::new(1));
+    /// assert_eq!(rotated, Some(VTree::left_linear(&vars)));
+    /// ```
+    pub fn rotate_left(&self, idx: VTreeIndex) -> Option<VTree> {
+        self.update_at(idx, &|node| match node {
+            BTree::Node((), a, r) => match r.as_ref() {
+                BTree::Node((), b, c) => Some(VTree::new_node(
+                    Box::new(VTree::new_node(a.clone(), b.clone())),
+                    c.clone(),
+                )),
+                BTree::Leaf(_) => None,
+            },
+            BTree::Leaf(_) => None,
+        })
+    }
+
+    /// swaps the children of the node at `idx`. Since indices are assigned
+    /// in-order, the swapped node moves to index `idx - |l| + |r|`, where `|l|`
+    /// and `|r|` are the sizes of its original children. Returns None if `idx`
+    /// is a leaf.
+    pub fn swap(&self, idx: VTreeIndex) -> Option<VTree> {
+        self.update_at(idx, &|node| match node {
+            BTree::Node((), l, r) => Some(VTree::new_node(r.clone(), l.clone())),
+            BTree::Leaf(_) => None,
+        })
+    }
+}
+
 impl Arbitrary for VTree {
     /// generate an arbitrary vtree on 16 variables
     fn arbitrary(g: &mut Gen) -> VTree {
@@ -294,6 +412,10 @@ impl Arbitrary for VTree {
 pub struct VTreeIndex(usize);
 
 impl VTreeIndex {
+    pub fn new(idx: usize) -> VTreeIndex {
+        VTreeIndex(idx)
+    }
+
     pub fn value(&self) -> usize {
         self.0
     }
@@ -402,9 +524,33 @@ fn from_dtree_is_valid_vtree() {
     1 2 3 4 0
     -2 -3 4 5 0
     -4 -5 6 6 0";
-    let cnf = super::Cnf::from_dimacs(cnf_input);
+    let cnf = super::Cnf::from_dimacs(cnf_input).unwrap();
     let dtree = DTree::from_cnf(&cnf, &cnf.min_fill_order());
     let vtree = VTree::from_dtree(&dtree).unwrap();
     println!("{:?}", VTree::flatten_vtree(&vtree));
     assert!(VTree::is_valid_vtree(&vtree));
 }
+
+#[test]
+fn rotations_are_inverses() {
+    let vars: Vec<VarLabel> = (0..6).map(VarLabel::new).collect();
+    let vtree = VTree::even_split(&vars, 1);
+    for idx in 0..vtree.num_nodes() {
+        if let Some(rotated) = vtree.rotate_right(VTreeIndex(idx)) {
+            assert!(VTree::is_valid_vtree(&rotated));
+            // ((a, b), c) at idx becomes (a, (b, c)) at idx - |b| - 1
+            let node = vtree.inorder_dfs_iter().nth(idx).unwrap();
+            let b_size = node.left().right().num_nodes();
+            let fragment = VTreeIndex(idx - b_size - 1);
+            assert_eq!(rotated.rotate_left(fragment), Some(vtree.clone()));
+        }
+        if let Some(swapped) = vtree.swap(VTreeIndex(idx)) {
+            assert!(VTree::is_valid_vtree(&swapped));
+        }
+    }
+
+    let root = VTreeIndex(vtree.left().num_nodes());
+    let swapped = vtree.swap(root).unwrap();
+    let swapped_root = VTreeIndex(swapped.left().num_nodes());
+    assert_eq!(swapped.swap(swapped_root), Some(vtree));
+}
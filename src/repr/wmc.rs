@@ -1,6 +1,6 @@
 use crate::{
     repr::{Literal, VarLabel},
-    util::semirings::Semiring,
+    util::semirings::{Semiring, VecSemiring},
 };
 use core::fmt::Debug;
 use std::collections::HashMap;
@@ -136,6 +136,69 @@ impl<T: Semiring> WmcParams<T> {
     }
 }
 
+impl<T: Semiring, const N: usize> WmcParams<VecSemiring<T, N>> {
+    /// Stacks `N` weightings into one, so that a single count evaluates all of
+    /// them: lane `i` of the result is the count under `lanes[i]`.
+    ///
+    /// Panics if a label is weighted in some lanes but not in others, as its
+    /// stacked weight would be missing some lanes.
+    /// ```
+    /// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+    /// use rsdd::repr::{BddPtr, DDNNFPtr, VarLabel, WmcParams};
+    /// use rsdd::util::semirings::{RealSemiring, VecSemiring};
+    /// use std::collections::HashMap;
+    ///
+    /// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+    /// let (a, b) = (VarLabel::new(0), VarLabel::new(1));
+    /// let f = builder.or(builder.var(a, true), builder.var(b, true));
+    ///
+    /// // sweep the probability of `a`
+    /// let sweep: [WmcParams<RealSemiring>; 4] = std::array::from_fn(|i| {
+    ///     let p = i as f64 / 4.0;
+    ///     WmcParams::new(HashMap::from([
+    ///         (a, (RealSemiring(1.0 - p), RealSemiring(p))),
+    ///         (b, (RealSemiring(0.5), RealSemiring(0.5))),
+    ///     ]))
+    /// });
+    /// let VecSemiring(counts) = f.unsmoothed_wmc(&WmcParams::from_lanes(&sweep));
+    ///
+    /// for (count, params) in counts.iter().zip(sweep.iter()) {
+    ///     assert_eq!(*count, f.unsmoothed_wmc(params));
+    /// }
+    /// assert_eq!(counts[2], RealSemiring(0.75));
+    /// ```
+    pub fn from_lanes(lanes: &[WmcParams<T>; N]) -> Self {
+        let num_labels = lanes.iter().map(|p| p.var_to_val.len()).max().unwrap_or(0);
+        let var_to_val = (0..num_labels)
+            .map(|label| {
+                let weights: Vec<Option<(T, T)>> = lanes
+                    .iter()
+                    .map(|p| p.var_to_val.get(label).copied().flatten())
+                    .collect();
+                if weights.iter().all(Option::is_none) {
+                    return None;
+                }
+                let missing: Vec<usize> = (0..N).filter(|i| weights[*i].is_none()).collect();
+                assert!(
+                    missing.is_empty(),
+                    "label {} is weighted in some lanes but not in lanes {:?}",
+                    label,
+                    missing
+                );
+                Some((
+                    VecSemiring(std::array::from_fn(|i| weights[i].unwrap().0)),
+                    VecSemiring(std::array::from_fn(|i| weights[i].unwrap().1)),
+                ))
+            })
+            .collect();
+        WmcParams {
+            zero: VecSemiring(std::array::from_fn(|i| lanes[i].zero)),
+            one: VecSemiring(std::array::from_fn(|i| lanes[i].one)),
+            var_to_val,
+        }
+    }
+}
+
 impl<T: Semiring> Debug for WmcParams<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("WmcParams")
@@ -179,3 +242,18 @@ impl<T: Semiring> Default for WmcParams<T> {
         }
     }
 }
+
+#[test]
+#[should_panic(expected = "label 1 is weighted in some lanes but not in lanes [1]")]
+fn stacking_rejects_partially_weighted_labels() {
+    use crate::util::semirings::RealSemiring;
+
+    let weights = |labels: &[u64]| {
+        WmcParams::new(HashMap::from_iter(
+            labels
+                .iter()
+                .map(|v| (VarLabel::new(*v), (RealSemiring(0.5), RealSemiring(0.5)))),
+        ))
+    };
+    WmcParams::from_lanes(&[weights(&[0, 1]), weights(&[0])]);
+}
@@ -1,11 +1,23 @@
 //! contains representations of core datastructures that can be serialized
 
 mod ser_bdd;
+mod ser_binary;
+mod ser_dddmp;
+mod ser_graph;
 mod ser_logical_expr;
+mod ser_nnf;
 mod ser_sdd;
+mod ser_ucla;
 mod ser_vtree;
+mod ser_wmc;
 
 pub use self::ser_bdd::*;
+pub use self::ser_binary::*;
+pub use self::ser_dddmp::*;
+pub use self::ser_graph::*;
 pub use self::ser_logical_expr::*;
+pub use self::ser_nnf::*;
 pub use self::ser_sdd::*;
+pub use self::ser_ucla::*;
 pub use self::ser_vtree::*;
+pub use self::ser_wmc::*;
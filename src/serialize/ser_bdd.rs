@@ -2,7 +2,10 @@
 
 use std::collections::HashMap;
 
-use crate::repr::{BddNode, BddPtr, DDNNFPtr};
+use crate::{
+    builder::{bdd::RobddBuilder, cache::IteTable, BottomUpBuilder},
+    repr::{BddNode, BddPtr, DDNNFPtr, ParseError, VarLabel},
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerBDDPtr {
@@ -20,8 +23,7 @@ pub struct SerBDD {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BDDSerializer {
-    /// list of nodes allocated in the BDD
-    /// `nodes[0]` is true, `nodes[1]` is false
+    /// list of nodes allocated in the BDD, children before parents
     nodes: Vec<SerBDD>,
     /// list of all roots (to support multi-rooted BDD serialization with shared structure)
     roots: Vec<SerBDDPtr>,
@@ -65,14 +67,124 @@ impl BDDSerializer {
     }
 
     pub fn from_bdd(bdd: BddPtr) -> BDDSerializer {
+        BDDSerializer::from_bdds(&[bdd])
+    }
+
+    /// serializes several BDDs from the same builder, sharing their common
+    /// nodes
+    #[allow(clippy::mutable_key_type)]
+    pub fn from_bdds(bdds: &[BddPtr]) -> BDDSerializer {
         let mut nodes = Vec::new();
-        #[allow(clippy::mutable_key_type)]
-        // this is a false positive, since BddNode's Hash/Ord ignore the scratch.
         let mut table = HashMap::new();
-        let r = BDDSerializer::serialize_helper(bdd, &mut table, &mut nodes);
-        BDDSerializer {
-            nodes,
-            roots: vec![r],
+        let roots = bdds
+            .iter()
+            .map(|bdd| BDDSerializer::serialize_helper(*bdd, &mut table, &mut nodes))
+            .collect();
+        BDDSerializer { nodes, roots }
+    }
+
+    /// Rebuilds the serialized roots in `builder`, in the order they were
+    /// serialized. The builder's variable order need not be the one the BDDs
+    /// were compiled with, but it must contain all of their variables.
+    /// ```
+    /// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+    /// use rsdd::repr::{BddPtr, VarLabel};
+    /// use rsdd::serialize::BDDSerializer;
+    ///
+    /// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+    /// let f = builder.or(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), false));
+    /// let json = serde_json::to_string(&BDDSerializer::from_bdds(&[f, builder.negate(f)])).unwrap();
+    ///
+    /// let other = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+    /// let ser: BDDSerializer = serde_json::from_str(&json).unwrap();
+    /// let g = other.or(other.var(VarLabel::new(0), true), other.var(VarLabel::new(1), false));
+    /// assert_eq!(ser.into_builder(&other), vec![g, other.negate(g)]);
+    /// ```
+    ///
+    /// Panics if the serialized BDDs are malformed; see
+    /// [`BDDSerializer::try_into_builder`].
+    pub fn into_builder<'a, T: IteTable<'a, BddPtr<'a>> + Default>(
+        &self,
+        builder: &'a RobddBuilder<'a, T>,
+    ) -> Vec<BddPtr<'a>> {
+        self.try_into_builder(builder)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`BDDSerializer::into_builder`], but returns an error instead of
+    /// panicking if a node refers to itself or a node after it, or if a node's
+    /// variable is not in `builder`'s order. Nothing is added to `builder`
+    /// when an error is returned.
+    pub fn try_into_builder<'a, T: IteTable<'a, BddPtr<'a>> + Default>(
+        &self,
+        builder: &'a RobddBuilder<'a, T>,
+    ) -> Result<Vec<BddPtr<'a>>, ParseError> {
+        let num_vars = builder.num_vars();
+        let check = |p: &SerBDDPtr, bound: usize, what: &str| match p {
+            SerBDDPtr::Ptr { index, .. } if *index >= bound => Err(ParseError::unlocated(format!(
+                "{} refers to node {}, which is not before it",
+                what, index
+            ))),
+            _ => Ok(()),
+        };
+        for (i, node) in self.nodes.iter().enumerate() {
+            let what = format!("node {}", i);
+            check(&node.low, i, &what)?;
+            check(&node.high, i, &what)?;
+            if node.topvar >= num_vars {
+                return Err(ParseError::unlocated(format!(
+                    "node {} has variable {}, but the builder has {} variables",
+                    i, node.topvar, num_vars
+                )));
+            }
         }
+        for r in self.roots.iter() {
+            check(r, self.nodes.len(), "a root")?;
+        }
+
+        let mut built: Vec<BddPtr<'a>> = Vec::with_capacity(self.nodes.len());
+        let ptr = |p: &SerBDDPtr, built: &[BddPtr<'a>]| match p {
+            SerBDDPtr::True => builder.true_ptr(),
+            SerBDDPtr::False => builder.false_ptr(),
+            SerBDDPtr::Ptr { index, compl } => {
+                if *compl {
+                    builder.negate(built[*index])
+                } else {
+                    built[*index]
+                }
+            }
+        };
+        for node in self.nodes.iter() {
+            let (low, high) = (ptr(&node.low, &built), ptr(&node.high, &built));
+            let var = builder.var(VarLabel::new_usize(node.topvar), true);
+            built.push(builder.ite(var, high, low));
+        }
+        Ok(self.roots.iter().map(|r| ptr(r, &built)).collect())
     }
 }
+
+#[test]
+fn malformed_bdds_are_rejected() {
+    use crate::builder::cache::AllIteTable;
+
+    let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+    let f = builder.and(
+        builder.var(VarLabel::new(0), true),
+        builder.var(VarLabel::new(1), false),
+    );
+    let ser = BDDSerializer::from_bdd(f);
+    let other = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+    assert!(ser.try_into_builder(&other).is_ok());
+
+    // a node may only refer to nodes before it
+    let mut forward = ser.clone();
+    forward.nodes[0].low = SerBDDPtr::Ptr {
+        index: 0,
+        compl: false,
+    };
+    assert!(forward.try_into_builder(&other).is_err());
+
+    // every variable must be in the builder's order
+    let small = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(1);
+    assert!(ser.try_into_builder(&small).is_err());
+}
@@ -0,0 +1,773 @@
+//! A compact, versioned binary format for BDDs and SDDs
+//!
+//! A file holds a multi-rooted DAG together with the variable order (for
+//! BDDs) or vtree (for SDDs) it was compiled with. All integers are
+//! little-endian `u32`s, laid out as:
+//!
+//! - a 32-byte header: the magic bytes `RSDD`, the format version and the
+//!   diagram kind (two `u16`s), then `vars`, the number of roots, nodes and
+//!   elements, the length of the shape, and a reserved zero. `vars` is one
+//!   more than the largest variable label, which for an SDD whose vtree does
+//!   not use every label below it is more than the number of leaves;
+//! - the shape: for BDDs the variables in order, for SDDs the vtree in
+//!   post-order, with a leaf written as its variable and an internal node as
+//!   `u32::MAX`;
+//! - the roots, as edges;
+//! - the node table, children before parents: a BDD node is `var high low`,
+//!   an SDD node is `first count`, a range of the element table;
+//! - the element table (SDDs only): `prime sub` pairs.
+//!
+//! An edge is `target << 1 | complemented`, where target 0 is the true
+//! constant, targets `1..=vars` are the positive literals, and target
+//! `vars + 1 + i` is node `i`.
+//!
+//! [`BinaryDiagram`] evaluates a file in place, without rebuilding it in a
+//! builder, so that a memory-mapped file (see `MappedDiagram`, behind the
+//! `mmap` feature) can serve queries as soon as it is opened.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    repr::{
+        BddNode, BddPtr, DDNNFPtr, ParseError, SddPtr, VTree, VarLabel, VarOrder, VarSet, DDNNF,
+    },
+    util::btree::BTree,
+};
+
+use super::ser_ucla::sdd_elements;
+
+const MAGIC: &[u8; 4] = b"RSDD";
+/// the format version written by this crate
+pub const BINARY_FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: usize = 32;
+const VTREE_NODE: u32 = u32::MAX;
+
+/// The kind of diagram stored in a binary file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramKind {
+    Bdd,
+    Sdd,
+}
+
+/// assembles a file from its tables
+struct BinaryWriter {
+    kind: DiagramKind,
+    num_vars: usize,
+    shape: Vec<u32>,
+    nodes: Vec<u32>,
+    elements: Vec<u32>,
+    num_nodes: usize,
+}
+
+impl BinaryWriter {
+    fn new(kind: DiagramKind, num_vars: usize, shape: Vec<u32>) -> BinaryWriter {
+        BinaryWriter {
+            kind,
+            num_vars,
+            shape,
+            nodes: Vec::new(),
+            elements: Vec::new(),
+            num_nodes: 0,
+        }
+    }
+
+    fn constant(value: bool) -> u32 {
+        !value as u32
+    }
+
+    fn literal(&self, label: VarLabel, polarity: bool) -> u32 {
+        (label.value() as u32 + 1) << 1 | !polarity as u32
+    }
+
+    /// adds a node with the given fields, returning a regular edge to it
+    fn push_node(&mut self, fields: &[u32]) -> u32 {
+        self.nodes.extend_from_slice(fields);
+        self.num_nodes += 1;
+        ((self.num_vars + self.num_nodes) as u32) << 1
+    }
+
+    fn finish(self, roots: &[u32]) -> Vec<u8> {
+        let kind = match self.kind {
+            DiagramKind::Bdd => 0u16,
+            DiagramKind::Sdd => 1u16,
+        };
+        let mut out = Vec::with_capacity(
+            HEADER_LEN
+                + 4 * (self.shape.len() + roots.len() + self.nodes.len() + self.elements.len()),
+        );
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&BINARY_FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&kind.to_le_bytes());
+        for field in [
+            self.num_vars,
+            roots.len(),
+            self.num_nodes,
+            self.elements.len() / 2,
+            self.shape.len(),
+            0,
+        ] {
+            out.extend_from_slice(&(field as u32).to_le_bytes());
+        }
+        for word in self
+            .shape
+            .iter()
+            .chain(roots)
+            .chain(&self.nodes)
+            .chain(&self.elements)
+        {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn bdd<'a>(&mut self, bdd: BddPtr<'a>, memo: &mut HashMap<&'a BddNode<'a>, u32>) -> u32 {
+        let node = match bdd {
+            BddPtr::PtrTrue => return BinaryWriter::constant(true),
+            BddPtr::PtrFalse => return BinaryWriter::constant(false),
+            BddPtr::Reg(node) | BddPtr::Compl(node) => node,
+        };
+        let edge = match memo.get(node) {
+            Some(edge) => *edge,
+            None => {
+                let high = self.bdd(bdd.high_raw(), memo);
+                let low = self.bdd(bdd.low_raw(), memo);
+                let edge = self.push_node(&[node.var.value() as u32, high, low]);
+                memo.insert(node, edge);
+                edge
+            }
+        };
+        edge | bdd.is_neg() as u32
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn sdd<'a>(&mut self, sdd: SddPtr<'a>, memo: &mut HashMap<SddPtr<'a>, u32>) -> u32 {
+        let (reg, compl) = match sdd {
+            SddPtr::PtrTrue => return BinaryWriter::constant(true),
+            SddPtr::PtrFalse => return BinaryWriter::constant(false),
+            SddPtr::Var(label, polarity) => return self.literal(label, polarity),
+            SddPtr::BDD(bdd) | SddPtr::ComplBDD(bdd) => {
+                (SddPtr::BDD(bdd), matches!(sdd, SddPtr::ComplBDD(_)))
+            }
+            SddPtr::Reg(or) | SddPtr::Compl(or) => {
+                (SddPtr::Reg(or), matches!(sdd, SddPtr::Compl(_)))
+            }
+        };
+        let edge = match memo.get(&reg) {
+            Some(edge) => *edge,
+            None => {
+                let elements: Vec<u32> = sdd_elements(reg)
+                    .into_iter()
+                    .flat_map(|(p, s)| [p, s])
+                    .map(|c| self.sdd(c, memo))
+                    .collect();
+                let first = (self.elements.len() / 2) as u32;
+                self.elements.extend(elements.iter());
+                let edge = self.push_node(&[first, (elements.len() / 2) as u32]);
+                memo.insert(reg, edge);
+                edge
+            }
+        };
+        edge | compl as u32
+    }
+}
+
+/// Writes `roots`, BDDs sharing the variable order `order`, in the binary
+/// format
+#[allow(clippy::mutable_key_type)]
+pub fn bdd_to_binary(roots: &[BddPtr], order: &VarOrder) -> Vec<u8> {
+    let shape = order.in_order_iter().map(|v| v.value() as u32).collect();
+    let mut writer = BinaryWriter::new(DiagramKind::Bdd, order.num_vars(), shape);
+    let mut memo = HashMap::new();
+    let roots: Vec<u32> = roots.iter().map(|r| writer.bdd(*r, &mut memo)).collect();
+    writer.finish(&roots)
+}
+
+/// Writes `roots`, SDDs normalized for `vtree`, in the binary format
+#[allow(clippy::mutable_key_type)]
+pub fn sdd_to_binary(roots: &[SddPtr], vtree: &VTree) -> Vec<u8> {
+    fn shape(t: &VTree, out: &mut Vec<u32>) {
+        match t {
+            BTree::Leaf(v) => out.push(v.value() as u32),
+            BTree::Node(_, l, r) => {
+                shape(l, out);
+                shape(r, out);
+                out.push(VTREE_NODE);
+            }
+        }
+    }
+    let mut words = Vec::new();
+    shape(vtree, &mut words);
+    // literal edges are indexed by label, and `VTree::num_vars` is one more
+    // than the largest label rather than the number of leaves
+    let mut writer = BinaryWriter::new(DiagramKind::Sdd, vtree.num_vars(), words);
+    let mut memo = HashMap::new();
+    let roots: Vec<u32> = roots.iter().map(|r| writer.sdd(*r, &mut memo)).collect();
+    writer.finish(&roots)
+}
+
+/// A validated, read-only view of a binary diagram that is evaluated in
+/// place
+/// ```
+/// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+/// use rsdd::repr::{BddPtr, DDNNFPtr, VarLabel};
+/// use rsdd::serialize::{bdd_to_binary, BinaryDiagram};
+///
+/// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+/// let f = builder.or(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), false));
+/// let bytes = bdd_to_binary(&[f], builder.order());
+///
+/// let diagram = BinaryDiagram::new(&bytes).unwrap();
+/// let root = diagram.roots()[0];
+/// assert!(root.evaluate(&[false, false]));
+/// assert!(!root.evaluate(&[false, true]));
+/// assert!(root.neg().evaluate(&[false, true]));
+/// ```
+pub struct BinaryDiagram<'d> {
+    kind: DiagramKind,
+    num_vars: usize,
+    shape: &'d [u8],
+    roots: &'d [u8],
+    nodes: &'d [u8],
+    elements: &'d [u8],
+}
+
+/// the `i`th little-endian word of `bytes`
+fn word(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap())
+}
+
+impl<'d> BinaryDiagram<'d> {
+    /// Checks the header and that every node only refers to earlier nodes,
+    /// in a single pass over `bytes`
+    pub fn new(bytes: &'d [u8]) -> Result<BinaryDiagram<'d>, ParseError> {
+        let err = |msg: String| Err(ParseError::unlocated(msg));
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return err("not a binary diagram: missing `RSDD` header".to_string());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != BINARY_FORMAT_VERSION {
+            return err(format!(
+                "unsupported binary format version {} (expected {})",
+                version, BINARY_FORMAT_VERSION
+            ));
+        }
+        let kind = match u16::from_le_bytes([bytes[6], bytes[7]]) {
+            0 => DiagramKind::Bdd,
+            1 => DiagramKind::Sdd,
+            k => return err(format!("unknown diagram kind {}", k)),
+        };
+        let header = |i: usize| word(bytes, 2 + i) as usize;
+        let (num_vars, num_roots, num_nodes, num_elements, shape_len) =
+            (header(0), header(1), header(2), header(3), header(4));
+        let node_len = match kind {
+            DiagramKind::Bdd => 3,
+            DiagramKind::Sdd => 2,
+        };
+        let lengths = [shape_len, num_roots, node_len * num_nodes, 2 * num_elements];
+        let expected = HEADER_LEN + 4 * lengths.iter().sum::<usize>();
+        if bytes.len() != expected {
+            return err(format!(
+                "expected {} bytes from the header, found {}",
+                expected,
+                bytes.len()
+            ));
+        }
+        let mut tables = Vec::with_capacity(4);
+        let mut offset = HEADER_LEN;
+        for len in lengths {
+            tables.push(&bytes[offset..offset + 4 * len]);
+            offset += 4 * len;
+        }
+        let diagram = BinaryDiagram {
+            kind,
+            num_vars,
+            shape: tables[0],
+            roots: tables[1],
+            nodes: tables[2],
+            elements: tables[3],
+        };
+        diagram.validate(num_nodes, num_elements)?;
+        Ok(diagram)
+    }
+
+    fn validate(&self, num_nodes: usize, num_elements: usize) -> Result<(), ParseError> {
+        let err = |msg: String| Err(ParseError::unlocated(msg));
+        // the shape is a permutation of the variables, or a vtree over some
+        // of them
+        let mut seen = vec![false; self.num_vars];
+        let mut depth = 0usize;
+        for i in 0..self.shape.len() / 4 {
+            let w = word(self.shape, i);
+            if self.kind == DiagramKind::Sdd && w == VTREE_NODE {
+                if depth < 2 {
+                    return err("malformed vtree".to_string());
+                }
+                depth -= 1;
+                continue;
+            }
+            match seen.get_mut(w as usize) {
+                Some(s) if !*s => *s = true,
+                _ => return err(format!("variable {} is out of range or repeated", w)),
+            }
+            depth += 1;
+        }
+        let complete = match self.kind {
+            DiagramKind::Bdd => seen.iter().all(|s| *s),
+            DiagramKind::Sdd => depth == 1,
+        };
+        if !complete {
+            return err("malformed variable order or vtree".to_string());
+        }
+
+        let check_edge = |edge: u32, bound: usize, context: &str| {
+            if (edge >> 1) as usize >= bound {
+                err(format!("{} refers to a later or missing node", context))
+            } else {
+                Ok(())
+            }
+        };
+        for i in 0..num_nodes {
+            let bound = self.num_vars + 1 + i;
+            let context = format!("node {}", i);
+            match self.kind {
+                DiagramKind::Bdd => {
+                    let (var, high, low) = self.bdd_node(i);
+                    if var as usize >= self.num_vars {
+                        return err(format!("node {} has variable {} out of range", i, var));
+                    }
+                    check_edge(high, bound, &context)?;
+                    check_edge(low, bound, &context)?;
+                }
+                DiagramKind::Sdd => {
+                    let (first, count) = self.sdd_node(i);
+                    if count == 0 || first + count > num_elements {
+                        return err(format!("node {} has an invalid element range", i));
+                    }
+                    for e in first..first + count {
+                        check_edge(word(self.elements, 2 * e), bound, &context)?;
+                        check_edge(word(self.elements, 2 * e + 1), bound, &context)?;
+                    }
+                }
+            }
+        }
+        for i in 0..self.roots.len() / 4 {
+            check_edge(
+                word(self.roots, i),
+                self.num_vars + 1 + num_nodes,
+                &format!("root {}", i),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn kind(&self) -> DiagramKind {
+        self.kind
+    }
+
+    /// one more than the largest variable label
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        match self.kind {
+            DiagramKind::Bdd => self.nodes.len() / 12,
+            DiagramKind::Sdd => self.nodes.len() / 8,
+        }
+    }
+
+    /// the variable order of a BDD
+    pub fn order(&self) -> Option<VarOrder> {
+        match self.kind {
+            DiagramKind::Bdd => Some(VarOrder::new(
+                &(0..self.num_vars)
+                    .map(|i| VarLabel::new(word(self.shape, i) as u64))
+                    .collect::<Vec<_>>(),
+            )),
+            DiagramKind::Sdd => None,
+        }
+    }
+
+    /// the vtree of an SDD
+    pub fn vtree(&self) -> Option<VTree> {
+        if self.kind != DiagramKind::Sdd {
+            return None;
+        }
+        let mut stack: Vec<VTree> = Vec::new();
+        for i in 0..self.shape.len() / 4 {
+            match word(self.shape, i) {
+                VTREE_NODE => {
+                    let r = stack.pop().unwrap();
+                    let l = stack.pop().unwrap();
+                    stack.push(VTree::new_node(Box::new(l), Box::new(r)));
+                }
+                v => stack.push(VTree::new_leaf(VarLabel::new(v as u64))),
+            }
+        }
+        stack.pop()
+    }
+
+    /// pointers to the roots, in the order they were written
+    pub fn roots(&self) -> Vec<BinaryPtr<'_>> {
+        (0..self.roots.len() / 4)
+            .map(|i| BinaryPtr::new(self, word(self.roots, i)))
+            .collect()
+    }
+
+    fn bdd_node(&self, i: usize) -> (u32, u32, u32) {
+        (
+            word(self.nodes, 3 * i),
+            word(self.nodes, 3 * i + 1),
+            word(self.nodes, 3 * i + 2),
+        )
+    }
+
+    fn sdd_node(&self, i: usize) -> (usize, usize) {
+        (
+            word(self.nodes, 2 * i) as usize,
+            word(self.nodes, 2 * i + 1) as usize,
+        )
+    }
+
+    /// the edges out of node `i`
+    fn children(&self, i: usize) -> Vec<u32> {
+        match self.kind {
+            DiagramKind::Bdd => {
+                let (_, high, low) = self.bdd_node(i);
+                vec![high, low]
+            }
+            DiagramKind::Sdd => {
+                let (first, count) = self.sdd_node(i);
+                (2 * first..2 * (first + count))
+                    .map(|e| word(self.elements, e))
+                    .collect()
+            }
+        }
+    }
+
+    /// the node that `edge` points to, if it is not a constant or literal
+    fn node_index(&self, edge: u32) -> Option<usize> {
+        ((edge >> 1) as usize).checked_sub(self.num_vars + 1)
+    }
+
+    /// marks the nodes reachable from `edge`
+    fn reachable(&self, edge: u32) -> Vec<bool> {
+        let top = match self.node_index(edge) {
+            Some(top) => top,
+            None => return Vec::new(),
+        };
+        let mut reachable = vec![false; top + 1];
+        reachable[top] = true;
+        for i in (0..=top).rev() {
+            if reachable[i] {
+                for c in self.children(i) {
+                    if let Some(c) = self.node_index(c) {
+                        reachable[c] = true;
+                    }
+                }
+            }
+        }
+        reachable
+    }
+}
+
+/// A (possibly complemented) edge into a [`BinaryDiagram`]. The constants
+/// belong to no diagram, so they compare equal across diagrams and are
+/// available as [`DDNNFPtr::true_ptr`] and [`DDNNFPtr::false_ptr`].
+#[derive(Clone, Copy)]
+pub struct BinaryPtr<'d> {
+    /// `None` exactly when `edge` is a constant
+    diagram: Option<&'d BinaryDiagram<'d>>,
+    edge: u32,
+}
+
+impl<'d> BinaryPtr<'d> {
+    fn new(diagram: &'d BinaryDiagram<'d>, edge: u32) -> BinaryPtr<'d> {
+        BinaryPtr {
+            diagram: if edge >> 1 == 0 { None } else { Some(diagram) },
+            edge,
+        }
+    }
+}
+
+impl<'d> Debug for BinaryPtr<'d> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BinaryPtr({})", self.edge)
+    }
+}
+
+impl<'d> PartialEq for BinaryPtr<'d> {
+    fn eq(&self, other: &Self) -> bool {
+        let same_diagram = match (self.diagram, other.diagram) {
+            (Some(a), Some(b)) => std::ptr::eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        same_diagram && self.edge == other.edge
+    }
+}
+
+impl<'d> Eq for BinaryPtr<'d> {}
+
+impl<'d> Hash for BinaryPtr<'d> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let diagram = self.diagram.map(|d| d as *const BinaryDiagram);
+        (diagram, self.edge).hash(state);
+    }
+}
+
+impl<'d> DDNNFPtr<'d> for BinaryPtr<'d> {
+    fn fold<T: 'static + Clone + Copy + Debug, F: Fn(DDNNF<T>) -> T>(&self, f: F) -> T {
+        let diagram = match self.diagram {
+            Some(diagram) => diagram,
+            None => {
+                return f(if self.is_true() {
+                    DDNNF::True
+                } else {
+                    DDNNF::False
+                })
+            }
+        };
+        let num_vars = diagram.num_vars;
+        // the (regular, complemented) values of each reachable node, computed
+        // children first
+        let reachable = diagram.reachable(self.edge);
+        let mut values: Vec<Option<(T, T)>> = vec![None; reachable.len()];
+        let value = |edge: u32, values: &[Option<(T, T)>]| {
+            let (reg, compl) = match (edge >> 1) as usize {
+                0 => (f(DDNNF::True), f(DDNNF::False)),
+                v if v <= num_vars => {
+                    let label = VarLabel::new_usize(v - 1);
+                    (f(DDNNF::Lit(label, true)), f(DDNNF::Lit(label, false)))
+                }
+                v => values[v - num_vars - 1].unwrap(),
+            };
+            if edge & 1 == 1 {
+                compl
+            } else {
+                reg
+            }
+        };
+        for i in (0..reachable.len()).filter(|i| reachable[*i]) {
+            let pair = match diagram.kind {
+                DiagramKind::Bdd => {
+                    let (var, high, low) = diagram.bdd_node(i);
+                    let var = VarLabel::new(var as u64);
+                    let mut varset = VarSet::new();
+                    varset.insert(var);
+                    let node = |high: T, low: T| {
+                        let and_low = f(DDNNF::And(f(DDNNF::Lit(var, false)), low));
+                        let and_high = f(DDNNF::And(f(DDNNF::Lit(var, true)), high));
+                        f(DDNNF::Or(and_low, and_high, varset.clone()))
+                    };
+                    (
+                        node(value(high, &values), value(low, &values)),
+                        node(value(high ^ 1, &values), value(low ^ 1, &values)),
+                    )
+                }
+                DiagramKind::Sdd => {
+                    // the primes partition, so negation only reaches the subs
+                    let children = diagram.children(i);
+                    let node = |compl: u32| {
+                        children.chunks(2).fold(f(DDNNF::False), |acc, e| {
+                            let and = f(DDNNF::And(
+                                value(e[0], &values),
+                                value(e[1] ^ compl, &values),
+                            ));
+                            f(DDNNF::Or(acc, and, VarSet::new()))
+                        })
+                    };
+                    (node(0), node(1))
+                }
+            };
+            values[i] = Some(pair);
+        }
+        value(self.edge, &values)
+    }
+
+    fn neg(&self) -> Self {
+        BinaryPtr {
+            diagram: self.diagram,
+            edge: self.edge ^ 1,
+        }
+    }
+
+    fn false_ptr() -> Self {
+        BinaryPtr {
+            diagram: None,
+            edge: BinaryWriter::constant(false),
+        }
+    }
+
+    fn true_ptr() -> Self {
+        BinaryPtr {
+            diagram: None,
+            edge: BinaryWriter::constant(true),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        self.edge == BinaryWriter::constant(true)
+    }
+
+    fn is_false(&self) -> bool {
+        self.edge == BinaryWriter::constant(false)
+    }
+
+    fn is_neg(&self) -> bool {
+        self.edge & 1 == 1
+    }
+
+    fn count_nodes(&self) -> usize {
+        self.diagram
+            .map_or(0, |d| d.reachable(self.edge).iter().filter(|r| **r).count())
+    }
+}
+
+/// A binary diagram file mapped into memory
+/// ```no_run
+/// use rsdd::repr::{DDNNFPtr, VarLabel, WmcParams};
+/// use rsdd::serialize::MappedDiagram;
+/// use rsdd::util::semirings::RealSemiring;
+/// use std::collections::HashMap;
+///
+/// let file = MappedDiagram::open("model.rsdd").unwrap();
+/// let diagram = file.diagram().unwrap();
+/// let params = WmcParams::new(HashMap::from_iter((0..diagram.num_vars()).map(|v| {
+///     (VarLabel::new_usize(v), (RealSemiring(0.5), RealSemiring(0.5)))
+/// })));
+/// println!("{}", diagram.roots()[0].unsmoothed_wmc(&params));
+/// ```
+#[cfg(feature = "mmap")]
+pub struct MappedDiagram {
+    mmap: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedDiagram {
+    /// Maps the file at `path`. The file must not be modified while it is
+    /// mapped.
+    pub fn open(path: impl AsRef<std::path::Path>) -> std::io::Result<MappedDiagram> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is read-only, and the caller promises not to
+        // modify the file while it is mapped
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(MappedDiagram { mmap })
+    }
+
+    /// validates the mapped bytes and views them as a diagram
+    pub fn diagram(&self) -> Result<BinaryDiagram<'_>, ParseError> {
+        BinaryDiagram::new(&self.mmap)
+    }
+}
+
+#[test]
+fn binary_diagrams_match_their_sources() {
+    use crate::builder::{
+        bdd::RobddBuilder, cache::AllIteTable, sdd::CompressionSddBuilder, BottomUpBuilder,
+    };
+    use crate::repr::{Cnf, WmcParams};
+    use crate::util::semirings::RealSemiring;
+
+    let cnf = Cnf::from_string("(1 || -2) && (2 || 3 || -4) && (-1 || 4) && (3 || -5)").unwrap();
+    let order: Vec<VarLabel> = (0..6).map(VarLabel::new).collect();
+    let bdd_builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(6);
+    let bdd = bdd_builder.compile_cnf(&cnf);
+    let vtree = VTree::even_split(&order, 2);
+    let sdd_builder = CompressionSddBuilder::new(vtree.clone());
+    let sdd = sdd_builder.compile_cnf(&cnf);
+
+    let bdd_bytes = bdd_to_binary(&[bdd, bdd.neg()], bdd_builder.order());
+    let sdd_bytes = sdd_to_binary(&[sdd, sdd.neg()], &vtree);
+    let bdd_diagram = BinaryDiagram::new(&bdd_bytes).unwrap();
+    let sdd_diagram = BinaryDiagram::new(&sdd_bytes).unwrap();
+    let written: Vec<VarLabel> = bdd_diagram.order().unwrap().in_order_iter().collect();
+    assert_eq!(written, order);
+    assert_eq!(sdd_diagram.vtree().unwrap(), vtree);
+
+    let params = WmcParams::new(
+        order
+            .iter()
+            .map(|v| (*v, (RealSemiring(0.3), RealSemiring(0.7))))
+            .collect(),
+    );
+    let expected = [
+        bdd.unsmoothed_wmc(&params).0,
+        bdd.neg().unsmoothed_wmc(&params).0,
+    ];
+    for diagram in [&bdd_diagram, &sdd_diagram] {
+        let roots = diagram.roots();
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root.unsmoothed_wmc(&params).0 - expected).abs() < 1e-9);
+        }
+        for m in 0..64u32 {
+            let assignment: Vec<bool> = (0..6).map(|v| m & (1 << v) != 0).collect();
+            assert_eq!(roots[0].evaluate(&assignment), bdd.evaluate(&assignment));
+            assert_eq!(roots[1].evaluate(&assignment), !bdd.evaluate(&assignment));
+        }
+    }
+
+    // truncated files and other versions are rejected
+    assert!(BinaryDiagram::new(&bdd_bytes[..bdd_bytes.len() - 4]).is_err());
+    let mut newer = bdd_bytes.clone();
+    newer[4] = 2;
+    assert!(BinaryDiagram::new(&newer).is_err());
+}
+
+#[test]
+fn binary_sdds_keep_non_contiguous_labels_and_constants() {
+    use crate::builder::{sdd::CompressionSddBuilder, BottomUpBuilder};
+
+    // the vtree skips labels 1, 2, 4, 5 and 6
+    let vars: Vec<VarLabel> = [0, 3, 7, 8].into_iter().map(VarLabel::new).collect();
+    let vtree = VTree::even_split(&vars, 1);
+    let builder = CompressionSddBuilder::new(vtree.clone());
+    let lit = |v, polarity| builder.var(VarLabel::new(v), polarity);
+    let f = builder.or(
+        builder.and(lit(0, true), lit(8, false)),
+        builder.and(lit(3, true), lit(7, true)),
+    );
+    let bytes = sdd_to_binary(&[f, SddPtr::true_ptr(), lit(8, false)], &vtree);
+    let diagram = BinaryDiagram::new(&bytes).unwrap();
+    assert_eq!(diagram.num_vars(), 9);
+    assert_eq!(diagram.vtree().unwrap(), vtree);
+
+    let roots = diagram.roots();
+    for m in 0..512u32 {
+        let assignment: Vec<bool> = (0..9).map(|v| m & (1 << v) != 0).collect();
+        assert_eq!(roots[0].evaluate(&assignment), f.evaluate(&assignment));
+        assert_eq!(roots[2].evaluate(&assignment), !assignment[8]);
+    }
+
+    // constants compare equal to the diagram-free constants, and count
+    assert_eq!(roots[1], BinaryPtr::true_ptr());
+    assert_eq!(roots[1].neg(), BinaryPtr::false_ptr());
+    assert!(BinaryPtr::true_ptr().evaluate(&[]));
+    assert_eq!(BinaryPtr::false_ptr().count_nodes(), 0);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mapped_diagrams_read_written_files() {
+    use crate::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+
+    let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
+    let f = builder.xor(
+        builder.var(VarLabel::new(0), true),
+        builder.var(VarLabel::new(2), true),
+    );
+    let path = std::env::temp_dir().join(format!("rsdd-mapped-{}.rsdd", std::process::id()));
+    std::fs::write(&path, bdd_to_binary(&[f], builder.order())).unwrap();
+    let file = MappedDiagram::open(&path).unwrap();
+    let diagram = file.diagram().unwrap();
+    let root = diagram.roots()[0];
+    assert_eq!(root.count_nodes(), 2);
+    assert!(root.evaluate(&[true, true, false]));
+    assert!(!root.evaluate(&[true, false, true]));
+    drop(file);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,254 @@
+//! BDDs in the text (`.mode A`) DDDMP format used by CUDD
+//!
+//! Nodes are numbered from 1, where node 1 is the constant true, and listed
+//! children first as `<id> <var id> <support index> <then id> <else id>`. A
+//! negative id denotes a complemented edge; as in CUDD, then-edges are never
+//! complemented, so nodes whose high edge is complemented here are written
+//! negated.
+//!
+//! When reading, node variables are taken from the support index (the third
+//! field) through `.ids`, so files written with any `.varinfo` can be read.
+
+use std::collections::HashMap;
+
+use crate::{
+    builder::{bdd::RobddBuilder, cache::IteTable, BottomUpBuilder},
+    repr::{BddPtr, DDNNFPtr, ParseError, VarLabel, VarOrder},
+};
+
+/// Writes `roots`, a set of BDDs sharing the variable order `order`, in the
+/// DDDMP format. Variable ids are this crate's variable labels, and the
+/// permutation ids are their positions in `order`.
+/// ```
+/// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+/// use rsdd::repr::{BddPtr, VarLabel};
+/// use rsdd::serialize::bdd_to_dddmp;
+///
+/// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
+/// let f = builder.and(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(2), false));
+/// let text = bdd_to_dddmp(&[f], builder.order());
+/// assert!(text.contains(".nnodes 3\n"));
+/// assert!(text.contains(".ids 0 2\n"));
+/// // x0 /\ !x2 is written as !(x0 ? x2 : true)
+/// assert!(text.contains(".rootids -3\n"));
+/// assert!(text.contains(".nodes\n1 T 1 0 0\n2 2 1 1 -1\n3 0 0 2 1\n.end\n"));
+/// ```
+pub fn bdd_to_dddmp(roots: &[BddPtr], order: &VarOrder) -> String {
+    let mut nodes: Vec<(usize, i64, i64)> = Vec::new();
+    let mut memo = HashMap::new();
+    let root_ids: Vec<i64> = roots
+        .iter()
+        .map(|r| dddmp_helper(*r, &mut memo, &mut nodes))
+        .collect();
+
+    let mut support: Vec<usize> = nodes.iter().map(|(v, _, _)| *v).collect();
+    support.sort_unstable();
+    support.dedup();
+    let support_index: HashMap<usize, usize> =
+        support.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+    let join = |items: Vec<String>| items.join(" ");
+
+    let mut out = String::new();
+    out.push_str(".ver DDDMP-2.0\n.mode A\n.varinfo 0\n");
+    out.push_str(&format!(".nnodes {}\n", nodes.len() + 1));
+    out.push_str(&format!(".nvars {}\n", order.num_vars()));
+    out.push_str(&format!(".nsuppvars {}\n", support.len()));
+    out.push_str(&format!(
+        ".ids {}\n",
+        join(support.iter().map(|v| v.to_string()).collect())
+    ));
+    out.push_str(&format!(
+        ".permids {}\n",
+        join(
+            support
+                .iter()
+                .map(|v| order.get(VarLabel::new_usize(*v)).to_string())
+                .collect()
+        )
+    ));
+    out.push_str(&format!(".nroots {}\n", roots.len()));
+    out.push_str(&format!(
+        ".rootids {}\n",
+        join(root_ids.iter().map(|r| r.to_string()).collect())
+    ));
+    out.push_str(".nodes\n1 T 1 0 0\n");
+    for (i, (v, t, e)) in nodes.iter().enumerate() {
+        out.push_str(&format!(
+            "{} {} {} {} {}\n",
+            i + 2,
+            v,
+            support_index[v],
+            t,
+            e
+        ));
+    }
+    out.push_str(".end\n");
+    out
+}
+
+/// Reads the roots of a text-mode DDDMP file into `builder`. Nodes are
+/// rebuilt with `ite`, so the roots are canonical for the builder's variable
+/// order even if it differs from the file's `.permids`.
+/// ```
+/// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+/// use rsdd::repr::{BddPtr, VarLabel, VarOrder};
+/// use rsdd::serialize::{bdd_from_dddmp, bdd_to_dddmp};
+///
+/// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(3);
+/// let f = builder.and(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(2), false));
+/// let text = bdd_to_dddmp(&[f, builder.negate(f)], builder.order());
+///
+/// let order = VarOrder::new(&[VarLabel::new(2), VarLabel::new(1), VarLabel::new(0)]);
+/// let reversed = RobddBuilder::<AllIteTable<BddPtr>>::new(order);
+/// let roots = bdd_from_dddmp(&text, &reversed).unwrap();
+/// let g = reversed.and(reversed.var(VarLabel::new(0), true), reversed.var(VarLabel::new(2), false));
+/// assert_eq!(roots, vec![g, reversed.negate(g)]);
+/// ```
+pub fn bdd_from_dddmp<'a, T: IteTable<'a, BddPtr<'a>> + Default>(
+    input: &str,
+    builder: &'a RobddBuilder<'a, T>,
+) -> Result<Vec<BddPtr<'a>>, ParseError> {
+    let mut lines = input
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<&str>>())
+        .filter(|l| !l.is_empty());
+    let number = |token: &str| ParseError::number_at::<i64>(input, token);
+
+    let mut ids: Option<Vec<usize>> = None;
+    let mut root_ids: Option<Vec<&str>> = None;
+    let mut num_nodes = None;
+    for line in lines.by_ref() {
+        match line[0] {
+            ".nodes" => break,
+            ".mode" if line.get(1) != Some(&"A") => {
+                return Err(ParseError::at(
+                    input,
+                    line[0],
+                    "only `.mode A` is supported",
+                ))
+            }
+            ".nnodes" if line.len() == 2 => num_nodes = Some(number(line[1])? as usize),
+            ".ids" => {
+                ids = Some(
+                    line[1..]
+                        .iter()
+                        .map(|t| ParseError::number_at(input, t))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            ".rootids" => root_ids = Some(line[1..].to_vec()),
+            ".end" => return Err(ParseError::at(input, line[0], "missing `.nodes`")),
+            _ => (),
+        }
+    }
+
+    // index i holds the node with DDDMP id i + 1
+    let mut nodes: Vec<BddPtr<'a>> = Vec::new();
+    let mut ended = false;
+    for line in lines.by_ref() {
+        if line[0] == ".end" {
+            ended = true;
+            break;
+        }
+        if line.len() != 5 {
+            return Err(ParseError::at(
+                input,
+                line[0],
+                "expected `<id> <var> <support index> <then> <else>`",
+            ));
+        }
+        if number(line[0])? != nodes.len() as i64 + 1 {
+            return Err(ParseError::at(
+                input,
+                line[0],
+                "node ids must be consecutive",
+            ));
+        }
+        if line[1] == "T" {
+            nodes.push(BddPtr::true_ptr());
+            continue;
+        }
+        let var = match &ids {
+            Some(ids) => *ids
+                .get(number(line[2])? as usize)
+                .ok_or_else(|| ParseError::at(input, line[2], "support index out of range"))?,
+            None => number(line[1])? as usize,
+        };
+        if var >= builder.num_vars() {
+            return Err(ParseError::at(
+                input,
+                line[1],
+                "variable is not in the builder's order",
+            ));
+        }
+        let (t, e) = (
+            signed_node(input, line[3], &nodes)?,
+            signed_node(input, line[4], &nodes)?,
+        );
+        nodes.push(builder.ite(builder.var(VarLabel::new_usize(var), true), t, e));
+    }
+    if !ended {
+        return Err(ParseError::unlocated("missing `.end`"));
+    }
+    if num_nodes.is_some_and(|n| n != nodes.len()) {
+        return Err(ParseError::unlocated(format!(
+            "header declares {} nodes, found {}",
+            num_nodes.unwrap(),
+            nodes.len()
+        )));
+    }
+    root_ids
+        .ok_or_else(|| ParseError::unlocated("missing `.rootids`"))?
+        .into_iter()
+        .map(|token| signed_node(input, token, &nodes))
+        .collect()
+}
+
+/// the node referred to by the signed DDDMP id `token`, where index `i` of
+/// `nodes` holds the node with id `i + 1`
+fn signed_node<'a>(
+    input: &str,
+    token: &str,
+    nodes: &[BddPtr<'a>],
+) -> Result<BddPtr<'a>, ParseError> {
+    let id: i64 = ParseError::number_at(input, token)?;
+    let n = id.unsigned_abs() as usize;
+    if n == 0 || n > nodes.len() {
+        return Err(ParseError::at(
+            input,
+            token,
+            "reference to an undefined node",
+        ));
+    }
+    Ok(if id < 0 {
+        nodes[n - 1].neg()
+    } else {
+        nodes[n - 1]
+    })
+}
+
+/// the signed DDDMP id of `bdd`, after writing its nodes
+#[allow(clippy::mutable_key_type)]
+fn dddmp_helper<'a>(
+    bdd: BddPtr<'a>,
+    memo: &mut HashMap<BddPtr<'a>, i64>,
+    nodes: &mut Vec<(usize, i64, i64)>,
+) -> i64 {
+    match bdd {
+        BddPtr::PtrTrue => return 1,
+        BddPtr::PtrFalse => return -1,
+        _ => (),
+    }
+    if bdd.high().is_neg() || bdd.high().is_false() {
+        return -dddmp_helper(bdd.neg(), memo, nodes);
+    }
+    if let Some(id) = memo.get(&bdd) {
+        return *id;
+    }
+    let t = dddmp_helper(bdd.high(), memo, nodes);
+    let e = dddmp_helper(bdd.low(), memo, nodes);
+    nodes.push((bdd.var_safe().unwrap().value_usize(), t, e));
+    let id = nodes.len() as i64 + 1;
+    memo.insert(bdd, id);
+    id
+}
@@ -0,0 +1,469 @@
+//! Graphviz DOT and Mermaid drawings of BDDs, SDDs, vtrees and dtrees
+//!
+//! Every drawing takes a map from variables to display names; variables
+//! without a name are shown as their label, as in
+//! [`BddPtr::print_bdd_lbl`].
+//!
+//! - BDD nodes are circles with solid high edges and dashed low edges, and
+//!   complemented edges end in a hollow dot (in Mermaid, they are labeled
+//!   `¬`). Each root gets its own entry point `f0`, `f1`, ...
+//! - SDD decision nodes are circles labeled by their vtree node and drawn
+//!   in rows by the depth of that node in the vtree. Their elements are
+//!   `prime | sub` records, with constants and literals written inline and
+//!   other children drawn as `•` with an edge to their decision node.
+//! - vtree nodes are labeled by their in-order index, and leaves by their
+//!   variable.
+//! - dtree nodes are labeled by their cutsets, and leaves by their clauses.
+
+use std::collections::HashMap;
+
+use crate::{
+    repr::{BddPtr, DDNNFPtr, DTree, SddPtr, VTree, VarLabel, VarSet},
+    util::btree::BTree,
+};
+
+use super::ser_ucla::sdd_elements;
+
+/// The language to draw in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+enum Shape {
+    Circle,
+    Box,
+    Plain,
+    /// a row of fields, which edges may leave from
+    Record(Vec<String>),
+}
+
+struct Edge {
+    from: usize,
+    /// the record field the edge leaves from
+    port: Option<usize>,
+    to: usize,
+    dashed: bool,
+    complemented: bool,
+}
+
+/// A drawing, independent of the language it is written in
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<(String, Shape)>,
+    edges: Vec<Edge>,
+    /// groups of nodes drawn in the same row
+    rows: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    fn node(&mut self, label: String, shape: Shape) -> usize {
+        self.nodes.push((label, shape));
+        self.nodes.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize) {
+        self.edges.push(Edge {
+            from,
+            port: None,
+            to,
+            dashed: false,
+            complemented: false,
+        });
+    }
+
+    fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.dot(),
+            GraphFormat::Mermaid => self.mermaid(),
+        }
+    }
+
+    fn dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut out = String::from("digraph {\n");
+        for (i, (label, shape)) in self.nodes.iter().enumerate() {
+            let attrs = match shape {
+                Shape::Circle => format!("label=\"{}\" shape=circle", escape(label)),
+                Shape::Box => format!("label=\"{}\" shape=box", escape(label)),
+                Shape::Plain => format!("label=\"{}\" shape=plaintext", escape(label)),
+                Shape::Record(fields) => {
+                    let fields: Vec<String> = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(p, f)| {
+                            let f = f
+                                .chars()
+                                .map(|c| match c {
+                                    '|' | '{' | '}' | '<' | '>' | '"' | '\\' => format!("\\{}", c),
+                                    c => c.to_string(),
+                                })
+                                .collect::<String>();
+                            format!("<p{}> {}", p, f)
+                        })
+                        .collect();
+                    format!("label=\"{}\" shape=record", fields.join("|"))
+                }
+            };
+            out.push_str(&format!("  n{} [{}];\n", i, attrs));
+        }
+        for e in self.edges.iter() {
+            let from = match e.port {
+                Some(p) => format!("n{}:p{}:c", e.from, p),
+                None => format!("n{}", e.from),
+            };
+            let mut attrs = Vec::new();
+            if e.dashed {
+                attrs.push("style=dashed");
+            }
+            if e.complemented {
+                attrs.push("arrowhead=odot");
+            }
+            if e.port.is_some() {
+                attrs.push("tailclip=false");
+            }
+            if attrs.is_empty() {
+                out.push_str(&format!("  {} -> n{};\n", from, e.to));
+            } else {
+                out.push_str(&format!("  {} -> n{} [{}];\n", from, e.to, attrs.join(" ")));
+            }
+        }
+        for row in self.rows.iter().filter(|r| r.len() > 1) {
+            let ids: Vec<String> = row.iter().map(|i| format!("n{}", i)).collect();
+            out.push_str(&format!("  {{ rank=same; {} }}\n", ids.join(" ")));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let escape = |s: &str| s.replace('"', "#quot;");
+        let mut out = String::from("flowchart TD\n");
+        for (i, (label, shape)) in self.nodes.iter().enumerate() {
+            let node = match shape {
+                Shape::Circle => format!("n{}((\"{}\"))", i, escape(label)),
+                Shape::Box => format!("n{}[\"{}\"]", i, escape(label)),
+                Shape::Plain => format!("n{}([\"{}\"])", i, escape(label)),
+                Shape::Record(fields) => format!("n{}[\"{}\"]", i, escape(&fields.join(" | "))),
+            };
+            out.push_str(&format!("  {}\n", node));
+        }
+        // records have no ports, so edges from them are labeled by their field
+        for e in self.edges.iter() {
+            let arrow = if e.dashed { "-.->" } else { "-->" };
+            let label = match (e.port, e.complemented) {
+                (Some(0), _) => "|prime|",
+                (Some(_), _) => "|sub|",
+                (None, true) => "|¬|",
+                (None, false) => "",
+            };
+            out.push_str(&format!("  n{} {}{} n{}\n", e.from, arrow, label, e.to));
+        }
+        out
+    }
+}
+
+fn name(names: &HashMap<VarLabel, String>, v: VarLabel) -> String {
+    names
+        .get(&v)
+        .cloned()
+        .unwrap_or_else(|| v.value().to_string())
+}
+
+fn varset(names: &HashMap<VarLabel, String>, vars: &VarSet) -> String {
+    let vars: Vec<String> = vars.iter().map(|v| name(names, v)).collect();
+    format!("{{{}}}", vars.join(", "))
+}
+
+/// Draws `roots`, BDDs from the same builder, sharing their common nodes
+/// ```
+/// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+/// use rsdd::repr::{BddPtr, VarLabel};
+/// use rsdd::serialize::{bdd_to_graph, GraphFormat};
+/// use std::collections::HashMap;
+///
+/// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+/// let f = builder.or(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), true));
+/// let names = HashMap::from([(VarLabel::new(0), String::from("rain"))]);
+/// let dot = bdd_to_graph(&[f], &names, GraphFormat::Dot);
+/// assert!(dot.contains("label=\"rain\" shape=circle"));
+/// assert!(dot.contains("style=dashed"));
+/// ```
+pub fn bdd_to_graph(
+    roots: &[BddPtr],
+    names: &HashMap<VarLabel, String>,
+    format: GraphFormat,
+) -> String {
+    #[allow(clippy::mutable_key_type)]
+    fn helper<'a>(
+        bdd: BddPtr<'a>,
+        names: &HashMap<VarLabel, String>,
+        graph: &mut Graph,
+        ids: &mut HashMap<BddPtr<'a>, usize>,
+    ) -> usize {
+        // complemented edges point to the regular node
+        let reg = match bdd {
+            BddPtr::Compl(node) => BddPtr::Reg(node),
+            _ => bdd,
+        };
+        if let Some(id) = ids.get(&reg) {
+            return *id;
+        }
+        let id = match reg {
+            BddPtr::PtrTrue => graph.node(String::from("T"), Shape::Box),
+            BddPtr::PtrFalse => graph.node(String::from("F"), Shape::Box),
+            BddPtr::Reg(node) | BddPtr::Compl(node) => {
+                let id = graph.node(name(names, node.var), Shape::Circle);
+                for (child, dashed) in [(reg.high_raw(), false), (reg.low_raw(), true)] {
+                    let to = helper(child, names, graph, ids);
+                    graph.edges.push(Edge {
+                        from: id,
+                        port: None,
+                        to,
+                        dashed,
+                        complemented: child.is_neg(),
+                    });
+                }
+                id
+            }
+        };
+        ids.insert(reg, id);
+        id
+    }
+
+    let mut graph = Graph::default();
+    let mut ids = HashMap::new();
+    for (i, root) in roots.iter().enumerate() {
+        let entry = graph.node(format!("f{}", i), Shape::Plain);
+        let to = helper(*root, names, &mut graph, &mut ids);
+        graph.edges.push(Edge {
+            from: entry,
+            port: None,
+            to,
+            dashed: false,
+            complemented: root.is_neg(),
+        });
+    }
+    graph.render(format)
+}
+
+/// Draws `roots`, SDDs normalized for `vtree`, sharing their common nodes.
+/// Negations are pushed into the subs, so no edge is complemented.
+/// ```
+/// use rsdd::builder::{sdd::CompressionSddBuilder, BottomUpBuilder};
+/// use rsdd::repr::{VarLabel, VTree};
+/// use rsdd::serialize::{sdd_to_graph, GraphFormat};
+/// use std::collections::HashMap;
+///
+/// let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1), VarLabel::new(2)]);
+/// let builder = CompressionSddBuilder::new(vtree.clone());
+/// let f = builder.and(
+///     builder.or(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), true)),
+///     builder.var(VarLabel::new(2), false),
+/// );
+/// let mermaid = sdd_to_graph(&[f], &vtree, &HashMap::new(), GraphFormat::Mermaid);
+/// assert!(mermaid.starts_with("flowchart TD\n"));
+/// assert!(mermaid.contains("n3[\"¬0 | •\"]"));
+/// assert!(mermaid.contains("n3 -->|sub| n4"));
+/// ```
+#[allow(clippy::mutable_key_type)]
+pub fn sdd_to_graph(
+    roots: &[SddPtr],
+    vtree: &VTree,
+    names: &HashMap<VarLabel, String>,
+    format: GraphFormat,
+) -> String {
+    // the depth of each vtree node, by in-order index
+    fn depths(t: &VTree, depth: usize, out: &mut Vec<usize>) {
+        match t {
+            BTree::Leaf(_) => out.push(depth),
+            BTree::Node(_, l, r) => {
+                depths(l, depth + 1, out);
+                out.push(depth);
+                depths(r, depth + 1, out);
+            }
+        }
+    }
+
+    /// the inline text of a constant or literal
+    fn terminal(sdd: SddPtr, names: &HashMap<VarLabel, String>) -> Option<String> {
+        match sdd {
+            SddPtr::PtrTrue => Some(String::from("T")),
+            SddPtr::PtrFalse => Some(String::from("F")),
+            SddPtr::Var(v, true) => Some(name(names, v)),
+            SddPtr::Var(v, false) => Some(format!("¬{}", name(names, v))),
+            _ => None,
+        }
+    }
+
+    fn helper<'a>(
+        sdd: SddPtr<'a>,
+        names: &HashMap<VarLabel, String>,
+        depth: &[usize],
+        graph: &mut Graph,
+        ids: &mut HashMap<SddPtr<'a>, usize>,
+    ) -> usize {
+        if let Some(id) = ids.get(&sdd) {
+            return *id;
+        }
+        let id = match terminal(sdd, names) {
+            Some(text) => graph.node(text, Shape::Box),
+            None => {
+                let index = sdd.vtree().value();
+                let id = graph.node(index.to_string(), Shape::Circle);
+                if graph.rows.len() <= depth[index] {
+                    graph.rows.resize(depth[index] + 1, Vec::new());
+                }
+                graph.rows[depth[index]].push(id);
+                for (prime, sub) in sdd_elements(sdd) {
+                    let fields = [prime, sub]
+                        .map(|c| terminal(c, names).unwrap_or_else(|| String::from("•")));
+                    let element = graph.node(String::new(), Shape::Record(fields.to_vec()));
+                    graph.edge(id, element);
+                    for (port, child) in [prime, sub].into_iter().enumerate() {
+                        if terminal(child, names).is_none() {
+                            let to = helper(child, names, depth, graph, ids);
+                            graph.edges.push(Edge {
+                                from: element,
+                                port: Some(port),
+                                to,
+                                dashed: false,
+                                complemented: false,
+                            });
+                        }
+                    }
+                }
+                id
+            }
+        };
+        ids.insert(sdd, id);
+        id
+    }
+
+    let mut depth = Vec::new();
+    depths(vtree, 0, &mut depth);
+    let mut graph = Graph::default();
+    let mut ids = HashMap::new();
+    for (i, root) in roots.iter().enumerate() {
+        let entry = graph.node(format!("f{}", i), Shape::Plain);
+        let to = helper(*root, names, &depth, &mut graph, &mut ids);
+        graph.edge(entry, to);
+    }
+    graph.render(format)
+}
+
+/// Draws `vtree`, labeling internal nodes by their in-order index
+/// ```
+/// use rsdd::repr::{VarLabel, VTree};
+/// use rsdd::serialize::{vtree_to_graph, GraphFormat};
+/// use std::collections::HashMap;
+///
+/// let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1)]);
+/// assert_eq!(
+///     vtree_to_graph(&vtree, &HashMap::new(), GraphFormat::Mermaid),
+///     "flowchart TD\n  n0((\"1\"))\n  n1[\"0\"]\n  n2[\"1\"]\n  n0 --> n1\n  n0 --> n2\n"
+/// );
+/// ```
+pub fn vtree_to_graph(
+    vtree: &VTree,
+    names: &HashMap<VarLabel, String>,
+    format: GraphFormat,
+) -> String {
+    // returns the drawn node of `t`, whose leftmost leaf has in-order index
+    // `*next`
+    fn helper(
+        t: &VTree,
+        names: &HashMap<VarLabel, String>,
+        next: &mut usize,
+        graph: &mut Graph,
+    ) -> usize {
+        match t {
+            BTree::Leaf(v) => {
+                *next += 1;
+                graph.node(name(names, *v), Shape::Box)
+            }
+            BTree::Node(_, l, r) => {
+                let id = graph.node(String::new(), Shape::Circle);
+                let l = helper(l, names, next, graph);
+                graph.nodes[id].0 = next.to_string();
+                *next += 1;
+                let r = helper(r, names, next, graph);
+                graph.edge(id, l);
+                graph.edge(id, r);
+                id
+            }
+        }
+    }
+    let mut graph = Graph::default();
+    helper(vtree, names, &mut 0, &mut graph);
+    graph.render(format)
+}
+
+/// Draws `dtree`, labeling internal nodes by their cutsets and leaves by
+/// their clauses and cutsets
+pub fn dtree_to_graph(
+    dtree: &DTree,
+    names: &HashMap<VarLabel, String>,
+    format: GraphFormat,
+) -> String {
+    fn helper(t: &DTree, names: &HashMap<VarLabel, String>, graph: &mut Graph) -> usize {
+        match t {
+            DTree::Leaf { clause, cutset, .. } => {
+                let lits: Vec<String> = clause
+                    .iter()
+                    .map(|l| {
+                        let n = name(names, l.label());
+                        if l.polarity() {
+                            n
+                        } else {
+                            format!("¬{}", n)
+                        }
+                    })
+                    .collect();
+                let label = format!("({}) {}", lits.join(" ∨ "), varset(names, cutset));
+                graph.node(label, Shape::Box)
+            }
+            DTree::Node { l, r, cutset, .. } => {
+                let id = graph.node(varset(names, cutset), Shape::Circle);
+                let l = helper(l, names, graph);
+                let r = helper(r, names, graph);
+                graph.edge(id, l);
+                graph.edge(id, r);
+                id
+            }
+        }
+    }
+    let mut graph = Graph::default();
+    helper(dtree, names, &mut graph);
+    graph.render(format)
+}
+
+#[test]
+fn graphs_draw_every_node_once() {
+    use crate::builder::{
+        bdd::RobddBuilder, cache::AllIteTable, sdd::CompressionSddBuilder, BottomUpBuilder,
+    };
+    use crate::repr::{Cnf, VarOrder};
+
+    let cnf = Cnf::from_string("(1 || -2) && (2 || 3 || -4) && (-1 || 4)").unwrap();
+    let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(5);
+    let bdd = builder.compile_cnf(&cnf);
+    let dot = bdd_to_graph(&[bdd, bdd.neg()], &HashMap::new(), GraphFormat::Dot);
+    let circles = dot.matches("shape=circle").count();
+    assert_eq!(circles, bdd.count_nodes());
+    // both roots share the nodes, and exactly one of them is complemented
+    assert_eq!(dot.matches("shape=plaintext").count(), 2);
+
+    let order: Vec<VarLabel> = (0..5).map(VarLabel::new).collect();
+    let vtree = VTree::even_split(&order, 2);
+    let sdd_builder = CompressionSddBuilder::new(vtree.clone());
+    let sdd = sdd_builder.compile_cnf(&cnf);
+    let dot = sdd_to_graph(&[sdd], &vtree, &HashMap::new(), GraphFormat::Dot);
+    assert!(dot.starts_with("digraph {\n") && dot.ends_with("}\n"));
+    assert!(dot.contains("rank=same") && dot.contains("tailclip=false"));
+
+    let dtree = DTree::from_cnf(&cnf, &VarOrder::linear_order(5));
+    let mermaid = dtree_to_graph(&dtree, &HashMap::new(), GraphFormat::Mermaid);
+    assert_eq!(mermaid.matches(" ∨ ").count(), 4);
+}
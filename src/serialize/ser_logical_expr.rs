@@ -1,3 +1,4 @@
+use crate::repr::ParseError;
 use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -14,6 +15,23 @@ pub enum LogicalSExpr {
 }
 
 impl LogicalSExpr {
+    /// Parses an s-expression such as `(And (Var X) (Not (Var Y)))`.
+    /// Unbalanced parentheses are reported with their location; other
+    /// malformed input is reported without one.
+    /// ```
+    /// use rsdd::serialize::LogicalSExpr;
+    ///
+    /// let expr = LogicalSExpr::parse("(Not (Var X))").unwrap();
+    /// assert_eq!(expr.unique_variables().len(), 1);
+    ///
+    /// let err = LogicalSExpr::parse("(And (Var X)\n  (Var Y)))").unwrap_err();
+    /// assert_eq!((err.line(), err.column()), (2, 11));
+    /// ```
+    pub fn parse(input: &str) -> Result<LogicalSExpr, ParseError> {
+        ParseError::check_parens(input)?;
+        Ok(serde_sexpr::from_str::<LogicalSExpr>(input)?)
+    }
+
     /// ```
     /// use rsdd::serialize::LogicalSExpr;
     ///
@@ -68,6 +86,15 @@ impl LogicalSExpr {
     }
 }
 
+#[test]
+fn test_parse_errors() {
+    let err = LogicalSExpr::parse("(Or (Var X)\n(Var Y)").unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 1));
+
+    let err = LogicalSExpr::parse("(Maybe (Var X))").unwrap_err();
+    assert_eq!(err.line(), 0);
+}
+
 #[test]
 fn logical_expression_deserialization_base_cases() {
     assert_eq!(
@@ -0,0 +1,356 @@
+//! d-DNNFs in the `.nnf` formats of the c2d and d4 compilers
+//!
+//! The c2d format lists nodes bottom-up after a header `nnf <nodes> <edges>
+//! <vars>`: literal nodes `L <lit>`, conjunctions `A <count> <children>*`
+//! and disjunctions `O <decision var> <count> <children>*`, where the root is
+//! the last node. The d4 format lists the nodes `o <id> 0`, `a <id> 0`,
+//! `t <id> 0` and `f <id> 0`, followed by edges `<parent> <child> <lits>* 0`
+//! that conjoin literals onto their child; node 1 is the root. In both,
+//! variables are numbered from 1.
+//!
+//! BDDs and SDDs are written as the d-DNNF they represent: every decision
+//! node becomes a disjunction of conjunctions, with complemented edges
+//! pushed down to the literals.
+
+use std::collections::HashMap;
+
+use crate::repr::{BddPtr, NnfNode, SddPtr, VarLabel};
+
+use super::ser_ucla::sdd_elements;
+
+/// Which compiler's `.nnf` dialect to write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NnfFormat {
+    C2d,
+    D4,
+}
+
+/// A hash-consed NNF under construction; children are always added before
+/// their parents
+#[derive(Default)]
+struct NnfWriter {
+    nodes: Vec<NnfNode>,
+    table: HashMap<NnfNode, usize>,
+}
+
+impl NnfWriter {
+    fn add(&mut self, node: NnfNode) -> usize {
+        if let Some(id) = self.table.get(&node) {
+            return *id;
+        }
+        self.nodes.push(node.clone());
+        self.table.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn constant(&mut self, value: bool) -> usize {
+        if value {
+            self.add(NnfNode::And(vec![]))
+        } else {
+            self.add(NnfNode::Or(None, vec![]))
+        }
+    }
+
+    fn is_constant(&self, id: usize, value: bool) -> bool {
+        self.nodes[id]
+            == if value {
+                NnfNode::And(vec![])
+            } else {
+                NnfNode::Or(None, vec![])
+            }
+    }
+
+    fn and(&mut self, a: usize, b: usize) -> usize {
+        if self.is_constant(a, false) || self.is_constant(b, false) {
+            self.constant(false)
+        } else if self.is_constant(a, true) {
+            b
+        } else if self.is_constant(b, true) {
+            a
+        } else {
+            self.add(NnfNode::And(vec![a, b]))
+        }
+    }
+
+    fn or(&mut self, decision: Option<VarLabel>, children: Vec<usize>) -> usize {
+        let mut children: Vec<usize> = children
+            .into_iter()
+            .filter(|c| !self.is_constant(*c, false))
+            .collect();
+        if children.len() == 1 {
+            children.pop().unwrap()
+        } else {
+            self.add(NnfNode::Or(decision, children))
+        }
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn bdd<'a>(&mut self, bdd: BddPtr<'a>, memo: &mut HashMap<BddPtr<'a>, usize>) -> usize {
+        match bdd {
+            BddPtr::PtrTrue => return self.constant(true),
+            BddPtr::PtrFalse => return self.constant(false),
+            _ => (),
+        }
+        if let Some(id) = memo.get(&bdd) {
+            return *id;
+        }
+        let v = bdd.var_safe().unwrap();
+        let (hi, lo) = (self.bdd(bdd.high(), memo), self.bdd(bdd.low(), memo));
+        let (pos, neg) = (
+            self.add(NnfNode::Lit(v, true)),
+            self.add(NnfNode::Lit(v, false)),
+        );
+        let (hi, lo) = (self.and(pos, hi), self.and(neg, lo));
+        let id = self.or(Some(v), vec![hi, lo]);
+        memo.insert(bdd, id);
+        id
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn sdd<'a>(&mut self, sdd: SddPtr<'a>, memo: &mut HashMap<SddPtr<'a>, usize>) -> usize {
+        match sdd {
+            SddPtr::PtrTrue => return self.constant(true),
+            SddPtr::PtrFalse => return self.constant(false),
+            SddPtr::Var(v, polarity) => return self.add(NnfNode::Lit(v, polarity)),
+            _ => (),
+        }
+        if let Some(id) = memo.get(&sdd) {
+            return *id;
+        }
+        let decision = match sdd {
+            SddPtr::BDD(bdd) | SddPtr::ComplBDD(bdd) => Some(bdd.label()),
+            _ => None,
+        };
+        let elements: Vec<usize> = sdd_elements(sdd)
+            .into_iter()
+            .map(|(p, s)| {
+                let (p, s) = (self.sdd(p, memo), self.sdd(s, memo));
+                self.and(p, s)
+            })
+            .collect();
+        let id = self.or(decision, elements);
+        memo.insert(sdd, id);
+        id
+    }
+
+    /// writes the nodes reachable from `root`, which must be the last of
+    /// them
+    fn write(&self, root: usize, num_vars: usize, format: NnfFormat) -> String {
+        let mut reachable = vec![false; self.nodes.len()];
+        reachable[root] = true;
+        for id in (0..=root).rev() {
+            if reachable[id] {
+                if let NnfNode::And(c) | NnfNode::Or(_, c) = &self.nodes[id] {
+                    c.iter().for_each(|c| reachable[*c] = true);
+                }
+            }
+        }
+        // renumber the reachable nodes, keeping their order
+        let mut new_id = vec![0; self.nodes.len()];
+        let mut order = Vec::new();
+        for id in (0..=root).filter(|id| reachable[*id]) {
+            new_id[id] = order.len();
+            order.push(&self.nodes[id]);
+        }
+        let lit = |v: &VarLabel, polarity: &bool| {
+            let l = v.value() as i64 + 1;
+            if *polarity {
+                l
+            } else {
+                -l
+            }
+        };
+        let children = |c: &[usize]| {
+            c.iter()
+                .map(|c| new_id[*c].to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        match format {
+            NnfFormat::C2d => {
+                let mut edges = 0;
+                let mut lines = Vec::with_capacity(order.len());
+                for node in order.iter() {
+                    lines.push(match node {
+                        NnfNode::Lit(v, p) => format!("L {}", lit(v, p)),
+                        NnfNode::And(c) => {
+                            edges += c.len();
+                            format!("A {} {}", c.len(), children(c))
+                                .trim_end()
+                                .to_string()
+                        }
+                        NnfNode::Or(d, c) => {
+                            edges += c.len();
+                            let d = d.map_or(0, |d| d.value() + 1);
+                            format!("O {} {} {}", d, c.len(), children(c))
+                                .trim_end()
+                                .to_string()
+                        }
+                    });
+                }
+                format!(
+                    "nnf {} {} {}\n{}\n",
+                    order.len(),
+                    edges,
+                    num_vars,
+                    lines.join("\n")
+                )
+            }
+            NnfFormat::D4 => {
+                // the root is node 1; a literal becomes a conjunction with an
+                // edge to a true node labeled by the literal
+                let d4_id = |i: usize| order.len() - i;
+                let true_id = order
+                    .iter()
+                    .position(|n| **n == NnfNode::And(vec![]))
+                    .map_or(order.len() + 1, d4_id);
+                let mut node_lines = Vec::new();
+                let mut edge_lines = Vec::new();
+                for (i, node) in order.iter().enumerate().rev() {
+                    let id = d4_id(i);
+                    match node {
+                        NnfNode::Lit(v, p) => {
+                            node_lines.push(format!("a {} 0", id));
+                            edge_lines.push(format!("{} {} {} 0", id, true_id, lit(v, p)));
+                        }
+                        NnfNode::And(c) if c.is_empty() => node_lines.push(format!("t {} 0", id)),
+                        NnfNode::Or(_, c) if c.is_empty() => node_lines.push(format!("f {} 0", id)),
+                        NnfNode::And(c) | NnfNode::Or(_, c) => {
+                            let kind = if matches!(node, NnfNode::And(_)) {
+                                'a'
+                            } else {
+                                'o'
+                            };
+                            node_lines.push(format!("{} {} 0", kind, id));
+                            for c in c.iter() {
+                                edge_lines.push(format!("{} {} 0", id, d4_id(new_id[*c])));
+                            }
+                        }
+                    }
+                }
+                if true_id > order.len() {
+                    node_lines.push(format!("t {} 0", true_id));
+                }
+                node_lines.extend(edge_lines);
+                format!("{}\n", node_lines.join("\n"))
+            }
+        }
+    }
+}
+
+/// Writes `bdd` as a d-DNNF over variables `0..num_vars`
+/// ```
+/// use rsdd::builder::{bdd::RobddBuilder, cache::AllIteTable, BottomUpBuilder};
+/// use rsdd::repr::{BddPtr, VarLabel};
+/// use rsdd::serialize::{bdd_to_nnf, NnfFormat};
+///
+/// let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(2);
+/// let f = builder.or(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), false));
+/// // x1 \/ (-x1 /\ -x2)
+/// assert_eq!(
+///     bdd_to_nnf(f, 2, NnfFormat::C2d),
+///     "nnf 5 4 2\nL -2\nL 1\nL -1\nA 2 2 0\nO 1 2 1 3\n"
+/// );
+/// ```
+pub fn bdd_to_nnf(bdd: BddPtr, num_vars: usize, format: NnfFormat) -> String {
+    let mut writer = NnfWriter::default();
+    let root = writer.bdd(bdd, &mut HashMap::new());
+    writer.write(root, num_vars, format)
+}
+
+/// Writes `sdd` as a d-DNNF over variables `0..num_vars`
+pub fn sdd_to_nnf(sdd: SddPtr, num_vars: usize, format: NnfFormat) -> String {
+    let mut writer = NnfWriter::default();
+    let root = writer.sdd(sdd, &mut HashMap::new());
+    writer.write(root, num_vars, format)
+}
+
+#[test]
+fn nnf_formats_evaluate_correctly() {
+    use crate::builder::{
+        bdd::RobddBuilder, cache::AllIteTable, sdd::CompressionSddBuilder, BottomUpBuilder,
+    };
+    use crate::repr::{Cnf, DDNNFPtr, VTree};
+
+    // evaluates a written d-DNNF under `assignment`
+    fn eval(text: &str, format: NnfFormat, assignment: &[bool]) -> bool {
+        let holds = |l: i64| assignment[l.unsigned_abs() as usize - 1] == (l > 0);
+        let lines: Vec<Vec<&str>> = text
+            .lines()
+            .map(|l| l.split_whitespace().collect())
+            .collect();
+        let num = |s: &str| s.parse::<i64>().unwrap();
+        match format {
+            NnfFormat::C2d => {
+                let mut values: Vec<bool> = Vec::new();
+                for l in lines[1..].iter() {
+                    let (skip, all) = match l[0] {
+                        "L" => {
+                            values.push(holds(num(l[1])));
+                            continue;
+                        }
+                        "A" => (2, true),
+                        _ => (3, false),
+                    };
+                    let mut children = l[skip..].iter().map(|c| values[num(c) as usize]);
+                    let v = if all {
+                        children.all(|c| c)
+                    } else {
+                        children.any(|c| c)
+                    };
+                    values.push(v);
+                }
+                *values.last().unwrap()
+            }
+            NnfFormat::D4 => {
+                let kinds: Vec<&str> = lines
+                    .iter()
+                    .take_while(|l| l[0].parse::<i64>().is_err())
+                    .map(|l| l[0])
+                    .collect();
+                let edges = &lines[kinds.len()..];
+                fn go(
+                    n: usize,
+                    kinds: &[&str],
+                    edges: &[Vec<&str>],
+                    holds: &dyn Fn(i64) -> bool,
+                ) -> bool {
+                    let mut children = edges.iter().filter(|e| e[0] == n.to_string()).map(|e| {
+                        let child = e[1].parse::<usize>().unwrap();
+                        e[2..e.len() - 1].iter().all(|l| holds(l.parse().unwrap()))
+                            && go(child, kinds, edges, holds)
+                    });
+                    match kinds[n - 1] {
+                        "t" => true,
+                        "f" => false,
+                        "a" => children.all(|c| c),
+                        _ => children.any(|c| c),
+                    }
+                }
+                go(1, &kinds, edges, &holds)
+            }
+        }
+    }
+
+    let cnf = Cnf::from_string("(1 || -2) && (2 || 3 || -4) && (-1 || 4) && (3 || -5)").unwrap();
+    let bdd_builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(6);
+    let bdd = bdd_builder.negate(bdd_builder.compile_cnf(&cnf));
+    let order: Vec<VarLabel> = (0..6).map(VarLabel::new).collect();
+    let sdd_builder = CompressionSddBuilder::new(VTree::even_split(&order, 2));
+    let sdd = sdd_builder.compile_cnf(&cnf);
+    for format in [NnfFormat::C2d, NnfFormat::D4] {
+        let (bdd_text, sdd_text) = (bdd_to_nnf(bdd, 6, format), sdd_to_nnf(sdd, 6, format));
+        for m in 0..64u32 {
+            let assignment: Vec<bool> = (0..6).map(|v| m & (1 << v) != 0).collect();
+            assert_eq!(
+                eval(&bdd_text, format, &assignment),
+                bdd.evaluate(&assignment)
+            );
+            assert_eq!(
+                eval(&sdd_text, format, &assignment),
+                sdd.evaluate(&assignment)
+            );
+        }
+    }
+}
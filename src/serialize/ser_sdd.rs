@@ -2,7 +2,10 @@
 
 use std::collections::HashMap;
 
-use crate::repr::SddPtr;
+use crate::{
+    builder::sdd::SddBuilder,
+    repr::{ParseError, SddPtr, VarLabel},
+};
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum SerSDDPtr {
@@ -23,8 +26,7 @@ pub struct SDDOr(Vec<SDDAnd>);
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SDDSerializer {
-    /// list of nodes allocated in the BDD
-    /// `nodes[0]` is true, `nodes[1]` is false
+    /// list of nodes allocated in the SDD, children before parents
     nodes: Vec<SDDOr>,
     roots: Vec<SerSDDPtr>,
 }
@@ -106,12 +108,134 @@ impl SDDSerializer {
     }
 
     pub fn from_sdd(sdd: SddPtr) -> SDDSerializer {
+        SDDSerializer::from_sdds(&[sdd])
+    }
+
+    /// serializes several SDDs from the same builder, sharing their common
+    /// nodes
+    pub fn from_sdds(sdds: &[SddPtr]) -> SDDSerializer {
         let mut nodes = Vec::new();
         let mut table = HashMap::new();
-        let r = SDDSerializer::serialize_helper(sdd, &mut table, &mut nodes);
-        SDDSerializer {
-            nodes,
-            roots: vec![r],
+        let roots = sdds
+            .iter()
+            .map(|sdd| SDDSerializer::serialize_helper(*sdd, &mut table, &mut nodes))
+            .collect();
+        SDDSerializer { nodes, roots }
+    }
+
+    /// Rebuilds the serialized roots in `builder`, in the order they were
+    /// serialized. Each node is rebuilt as the disjunction of its elements,
+    /// so the builder's vtree need not be the one the SDDs were compiled
+    /// for; usually it is, restored with [`VTreeSerializer::to_vtree`].
+    /// ```
+    /// use rsdd::builder::{sdd::CompressionSddBuilder, BottomUpBuilder};
+    /// use rsdd::repr::{VarLabel, VTree};
+    /// use rsdd::serialize::{SDDSerializer, VTreeSerializer};
+    ///
+    /// let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1)]);
+    /// let builder = CompressionSddBuilder::new(vtree.clone());
+    /// let f = builder.and(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), false));
+    /// let sdd_json = serde_json::to_string(&SDDSerializer::from_sdd(f)).unwrap();
+    /// let vtree_json = serde_json::to_string(&VTreeSerializer::from_vtree(&vtree)).unwrap();
+    ///
+    /// let vtree: VTreeSerializer = serde_json::from_str(&vtree_json).unwrap();
+    /// let other = CompressionSddBuilder::new(vtree.to_vtree());
+    /// let ser: SDDSerializer = serde_json::from_str(&sdd_json).unwrap();
+    /// let g = other.and(other.var(VarLabel::new(0), true), other.var(VarLabel::new(1), false));
+    /// assert_eq!(ser.into_builder(&other), vec![g]);
+    /// ```
+    ///
+    /// Panics if the serialized SDDs are malformed; see
+    /// [`SDDSerializer::try_into_builder`].
+    ///
+    /// [`VTreeSerializer::to_vtree`]: super::VTreeSerializer::to_vtree
+    pub fn into_builder<'a, B: SddBuilder<'a>>(&self, builder: &'a B) -> Vec<SddPtr<'a>> {
+        self.try_into_builder(builder)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`SDDSerializer::into_builder`], but returns an error instead of
+    /// panicking if a node refers to itself or a node after it, or if a
+    /// literal's variable is not in `builder`'s vtree. Nothing is added to
+    /// `builder` when an error is returned.
+    pub fn try_into_builder<'a, B: SddBuilder<'a>>(
+        &self,
+        builder: &'a B,
+    ) -> Result<Vec<SddPtr<'a>>, ParseError> {
+        let vars = builder.vtree_manager().vtree_root().all_vars();
+        let check = |p: &SerSDDPtr, bound: usize, what: &str| match *p {
+            SerSDDPtr::Ptr { index, .. } if index >= bound => Err(ParseError::unlocated(format!(
+                "{} refers to node {}, which is not before it",
+                what, index
+            ))),
+            SerSDDPtr::Literal { label, .. } if !vars.contains(&label) => {
+                Err(ParseError::unlocated(format!(
+                    "{} has variable {}, which is not in the builder's vtree",
+                    what, label
+                )))
+            }
+            _ => Ok(()),
+        };
+        for (i, SDDOr(elements)) in self.nodes.iter().enumerate() {
+            let what = format!("node {}", i);
+            for and in elements.iter() {
+                check(&and.prime, i, &what)?;
+                check(&and.sub, i, &what)?;
+            }
+        }
+        for r in self.roots.iter() {
+            check(r, self.nodes.len(), "a root")?;
         }
+
+        let mut built: Vec<SddPtr<'a>> = Vec::with_capacity(self.nodes.len());
+        let ptr = |p: &SerSDDPtr, built: &[SddPtr<'a>]| match *p {
+            SerSDDPtr::True => builder.true_ptr(),
+            SerSDDPtr::False => builder.false_ptr(),
+            SerSDDPtr::Literal { label, polarity } => {
+                builder.var(VarLabel::new_usize(label), polarity)
+            }
+            SerSDDPtr::Ptr { index, compl } => {
+                if compl {
+                    builder.negate(built[index])
+                } else {
+                    built[index]
+                }
+            }
+        };
+        for SDDOr(elements) in self.nodes.iter() {
+            let node = elements.iter().fold(builder.false_ptr(), |acc, and| {
+                let (p, s) = (ptr(&and.prime, &built), ptr(&and.sub, &built));
+                builder.or(acc, builder.and(p, s))
+            });
+            built.push(node);
+        }
+        Ok(self.roots.iter().map(|r| ptr(r, &built)).collect())
     }
 }
+
+#[test]
+fn malformed_sdds_are_rejected() {
+    use crate::{builder::sdd::CompressionSddBuilder, builder::BottomUpBuilder, repr::VTree};
+
+    let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1)]);
+    let builder = CompressionSddBuilder::new(vtree.clone());
+    let f = builder.or(
+        builder.var(VarLabel::new(0), true),
+        builder.var(VarLabel::new(1), false),
+    );
+    let ser = SDDSerializer::from_sdd(f);
+    let other = CompressionSddBuilder::new(vtree);
+    assert!(ser.try_into_builder(&other).is_ok());
+
+    // roots may only refer to serialized nodes
+    let mut dangling = ser.clone();
+    dangling.roots[0] = SerSDDPtr::Ptr {
+        index: ser.nodes.len(),
+        compl: false,
+    };
+    assert!(dangling.try_into_builder(&other).is_err());
+
+    // every literal must be in the builder's vtree
+    let small = CompressionSddBuilder::new(VTree::new_leaf(VarLabel::new(0)));
+    assert!(ser.try_into_builder(&small).is_err());
+}
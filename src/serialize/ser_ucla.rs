@@ -0,0 +1,408 @@
+//! SDDs and vtrees in the text formats of the UCLA SDD library (sdd-2.0)
+//!
+//! Both formats list nodes bottom-up, children before parents, one per
+//! line. Vtree nodes are identified by their in-order position (the same as
+//! [`VTreeIndex`](crate::repr::VTreeIndex)) and variables are numbered from
+//! 1, so variable `v` of this crate is variable `v + 1` in these files. SDD
+//! nodes are numbered from 0 and the root is the last node.
+//!
+//! The library has no complemented edges, so negated nodes are written out
+//! as separate decompositions.
+//!
+//! When reading, the lines may appear in any order consistent with the
+//! children-first rule, and ids need not be consecutive.
+
+use std::collections::HashMap;
+
+use crate::{
+    builder::sdd::SddBuilder,
+    repr::{DDNNFPtr, ParseError, SddPtr, VTree, VTreeManager, VarLabel},
+    util::btree::BTree,
+};
+
+const VTREE_HEADER: &str = "c ids of vtree nodes start at 0
+c ids of variables start at 1
+c vtree nodes appear bottom-up, children before parents
+c
+c file syntax:
+c vtree number-of-nodes-in-vtree
+c L id-of-leaf-vtree-node id-of-variable
+c I id-of-internal-vtree-node id-of-left-child id-of-right-child
+c
+";
+
+const SDD_HEADER: &str = "c ids of sdd nodes start at 0
+c sdd nodes appear bottom-up, children before parents
+c
+c file syntax:
+c sdd count-of-sdd-nodes
+c F id-of-false-sdd-node
+c T id-of-true-sdd-node
+c L id-of-literal-sdd-node id-of-vtree literal
+c D id-of-decomposition-sdd-node id-of-vtree number-of-elements {id-of-prime id-of-sub}*
+c
+";
+
+/// Writes `vtree` in the `.vtree` format
+/// ```
+/// use rsdd::repr::{VarLabel, VTree};
+/// use rsdd::serialize::vtree_to_ucla;
+///
+/// let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1)]);
+/// assert!(vtree_to_ucla(&vtree).ends_with("vtree 3\nL 0 1\nL 2 2\nI 1 0 2\n"));
+/// ```
+pub fn vtree_to_ucla(vtree: &VTree) -> String {
+    // returns the in-order id of `t`, after writing its subtree
+    fn helper(t: &VTree, next_id: &mut usize, lines: &mut Vec<String>) -> usize {
+        match t {
+            BTree::Leaf(v) => {
+                let id = *next_id;
+                *next_id += 1;
+                lines.push(format!("L {} {}", id, v.value() + 1));
+                id
+            }
+            BTree::Node(_, l, r) => {
+                let l = helper(l, next_id, lines);
+                let id = *next_id;
+                *next_id += 1;
+                let r = helper(r, next_id, lines);
+                lines.push(format!("I {} {} {}", id, l, r));
+                id
+            }
+        }
+    }
+    let mut lines = Vec::new();
+    helper(vtree, &mut 0, &mut lines);
+    format!(
+        "{}vtree {}\n{}\n",
+        VTREE_HEADER,
+        lines.len(),
+        lines.join("\n")
+    )
+}
+
+/// Writes `sdd` in the `.sdd` format. `vtree` must be the vtree that `sdd`
+/// is normalized for; after a builder's vtree has changed, resolve the
+/// pointer first (see
+/// [`SddBuilder::resolve`](crate::builder::sdd::SddBuilder::resolve)).
+#[allow(clippy::mutable_key_type)]
+pub fn sdd_to_ucla(sdd: SddPtr, vtree: &VTree) -> String {
+    let manager = VTreeManager::new(vtree.clone());
+    let mut ids = HashMap::new();
+    let mut lines = Vec::new();
+    sdd_helper(sdd, &manager, &mut ids, &mut lines);
+    format!("{}sdd {}\n{}\n", SDD_HEADER, lines.len(), lines.join("\n"))
+}
+
+/// Reads a vtree in the `.vtree` format; the root is the last node
+/// ```
+/// use rsdd::repr::{VarLabel, VTree};
+/// use rsdd::serialize::{vtree_from_ucla, vtree_to_ucla};
+///
+/// let vtree = VTree::even_split(&[VarLabel::new(0), VarLabel::new(1), VarLabel::new(2)], 1);
+/// assert_eq!(vtree_from_ucla(&vtree_to_ucla(&vtree)).unwrap(), vtree);
+/// ```
+pub fn vtree_from_ucla(input: &str) -> Result<VTree, ParseError> {
+    // subtrees that do not have a parent yet
+    let mut roots: HashMap<usize, VTree> = HashMap::new();
+    let mut last = None;
+    let mut count = None;
+    for line in ucla_lines(input) {
+        let number = |token: &str| ParseError::number_at::<usize>(input, token);
+        let (id, tree) = match (line[0], line.len()) {
+            ("vtree", 2) if count.is_none() => {
+                count = Some(number(line[1])?);
+                continue;
+            }
+            ("L", 3) => {
+                let var = number(line[2])?;
+                if var == 0 {
+                    return Err(ParseError::at(input, line[2], "variables start at 1"));
+                }
+                (
+                    number(line[1])?,
+                    VTree::new_leaf(VarLabel::new_usize(var - 1)),
+                )
+            }
+            ("I", 4) => {
+                let mut child = |token: &str| {
+                    roots.remove(&number(token)?).ok_or_else(|| {
+                        ParseError::at(input, token, "child is not an earlier, unused node")
+                    })
+                };
+                let (l, r) = (child(line[2])?, child(line[3])?);
+                (number(line[1])?, VTree::new_node(Box::new(l), Box::new(r)))
+            }
+            _ => {
+                return Err(ParseError::at(
+                    input,
+                    line[0],
+                    "expected `vtree <count>`, `L <id> <var>` or `I <id> <left> <right>`",
+                ))
+            }
+        };
+        if roots.insert(id, tree).is_some() {
+            return Err(ParseError::at(input, line[1], "duplicate vtree node id"));
+        }
+        last = Some((id, line[0]));
+    }
+    let (root, token) = last.ok_or_else(|| ParseError::unlocated("empty vtree"))?;
+    if roots.len() != 1 {
+        return Err(ParseError::at(
+            input,
+            token,
+            format!("{} vtree nodes are not below the root", roots.len() - 1),
+        ));
+    }
+    let vtree = roots.remove(&root).unwrap();
+    if count.is_some_and(|c| c != vtree.num_nodes()) {
+        return Err(ParseError::unlocated(format!(
+            "header declares {} vtree nodes, found {}",
+            count.unwrap(),
+            vtree.num_nodes()
+        )));
+    }
+    if !VTree::is_valid_vtree(&vtree) {
+        return Err(ParseError::unlocated(
+            "a variable appears twice in the vtree",
+        ));
+    }
+    Ok(vtree)
+}
+
+/// Reads an SDD in the `.sdd` format into `builder`, whose vtree must
+/// contain every variable of the SDD but need not be the vtree it was
+/// written for. Each decomposition is checked to be an X-partition (its
+/// primes are consistent, mutually exclusive and exhaustive) and then
+/// rebuilt with the builder's operations, so the result is canonical for the
+/// builder's vtree even if the file was not compressed or trimmed.
+/// ```
+/// use rsdd::builder::{sdd::CompressionSddBuilder, BottomUpBuilder};
+/// use rsdd::repr::{VarLabel, VTree};
+/// use rsdd::serialize::{sdd_from_ucla, sdd_to_ucla};
+///
+/// let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1)]);
+/// let builder = CompressionSddBuilder::new(vtree.clone());
+/// let f = builder.or(builder.var(VarLabel::new(0), true), builder.var(VarLabel::new(1), false));
+/// assert_eq!(sdd_from_ucla(&sdd_to_ucla(f, &vtree), &builder).unwrap(), f);
+/// ```
+pub fn sdd_from_ucla<'a, B: SddBuilder<'a>>(
+    input: &str,
+    builder: &'a B,
+) -> Result<SddPtr<'a>, ParseError> {
+    let vars = builder.vtree_manager().vtree_root().all_vars();
+    let mut nodes: HashMap<usize, SddPtr<'a>> = HashMap::new();
+    let mut last = None;
+    let mut count = None;
+    for line in ucla_lines(input) {
+        let number = |token: &str| ParseError::number_at::<usize>(input, token);
+        let node = |token: &str| {
+            nodes
+                .get(&number(token)?)
+                .copied()
+                .ok_or_else(|| ParseError::at(input, token, "child is not an earlier node"))
+        };
+        let ptr = match (line[0], line.len()) {
+            ("sdd", 2) if count.is_none() => {
+                count = Some(number(line[1])?);
+                continue;
+            }
+            ("T", 2) => builder.true_ptr(),
+            ("F", 2) => builder.false_ptr(),
+            ("L", 4) => {
+                let lit: i64 = ParseError::number_at(input, line[3])?;
+                let var = lit.unsigned_abs() as usize;
+                if var == 0 || !vars.contains(&(var - 1)) {
+                    return Err(ParseError::at(
+                        input,
+                        line[3],
+                        "variable is not in the builder's vtree",
+                    ));
+                }
+                builder.var(VarLabel::new_usize(var - 1), lit > 0)
+            }
+            ("D", n) if n >= 4 => {
+                let size = number(line[3])?;
+                if n != 4 + 2 * size || size == 0 {
+                    return Err(ParseError::at(
+                        input,
+                        line[0],
+                        format!("expected {} elements", size),
+                    ));
+                }
+                let elements = line[4..]
+                    .chunks(2)
+                    .map(|e| Ok((node(e[0])?, node(e[1])?)))
+                    .collect::<Result<Vec<_>, ParseError>>()?;
+                let primes: Vec<SddPtr<'a>> = elements.iter().map(|(p, _)| *p).collect();
+                if !is_partition(builder, &primes) {
+                    return Err(ParseError::at(input, line[0], "primes are not a partition"));
+                }
+                elements
+                    .into_iter()
+                    .fold(builder.false_ptr(), |acc, (p, s)| {
+                        builder.or(acc, builder.and(p, s))
+                    })
+            }
+            _ => {
+                return Err(ParseError::at(
+                    input,
+                    line[0],
+                    "expected `sdd <count>`, `T <id>`, `F <id>`, `L <id> <vtree> <lit>` or \
+                     `D <id> <vtree> <count> {<prime> <sub>}*`",
+                ))
+            }
+        };
+        if nodes.insert(number(line[1])?, ptr).is_some() {
+            return Err(ParseError::at(input, line[1], "duplicate sdd node id"));
+        }
+        last = Some(ptr);
+    }
+    if count.is_some_and(|c| c != nodes.len()) {
+        return Err(ParseError::unlocated(format!(
+            "header declares {} sdd nodes, found {}",
+            count.unwrap(),
+            nodes.len()
+        )));
+    }
+    last.ok_or_else(|| ParseError::unlocated("empty sdd"))
+}
+
+/// true if `primes` are consistent, pairwise disjoint and cover every
+/// assignment
+fn is_partition<'a, B: SddBuilder<'a>>(builder: &'a B, primes: &[SddPtr<'a>]) -> bool {
+    let mut covered = builder.false_ptr();
+    for p in primes.iter() {
+        if p.is_false() || !builder.and(covered, *p).is_false() {
+            return false;
+        }
+        covered = builder.or(covered, *p);
+    }
+    covered.is_true()
+}
+
+/// the tokens of each non-empty, non-comment line of `input`
+fn ucla_lines(input: &str) -> impl Iterator<Item = Vec<&str>> {
+    input
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<&str>>())
+        .filter(|l| !l.is_empty() && l[0] != "c")
+}
+
+/// the (prime, sub) elements of a decision node, with a negation of the node
+/// pushed into its subs
+pub(crate) fn sdd_elements(sdd: SddPtr) -> Vec<(SddPtr, SddPtr)> {
+    match sdd {
+        SddPtr::BDD(bdd) | SddPtr::ComplBDD(bdd) => vec![
+            (SddPtr::Var(bdd.label(), true), sdd.high()),
+            (SddPtr::Var(bdd.label(), false), sdd.low()),
+        ],
+        SddPtr::Reg(or) => or.iter().map(|and| (and.prime(), and.sub())).collect(),
+        SddPtr::Compl(or) => or
+            .iter()
+            .map(|and| (and.prime(), and.sub().neg()))
+            .collect(),
+        SddPtr::PtrTrue | SddPtr::PtrFalse | SddPtr::Var(_, _) => {
+            panic!("called sdd_elements on a terminal")
+        }
+    }
+}
+
+#[allow(clippy::mutable_key_type)]
+fn sdd_helper<'a>(
+    sdd: SddPtr<'a>,
+    manager: &VTreeManager,
+    ids: &mut HashMap<SddPtr<'a>, usize>,
+    lines: &mut Vec<String>,
+) -> usize {
+    if let Some(id) = ids.get(&sdd) {
+        return *id;
+    }
+    let line = match sdd {
+        SddPtr::PtrTrue => format!("T {}", lines.len()),
+        SddPtr::PtrFalse => format!("F {}", lines.len()),
+        SddPtr::Var(label, polarity) => {
+            let lit = label.value() as i64 + 1;
+            format!(
+                "L {} {} {}",
+                lines.len(),
+                manager.var_index(label).value(),
+                if polarity { lit } else { -lit }
+            )
+        }
+        _ => {
+            let elements = sdd_elements(sdd);
+            let children: Vec<String> = elements
+                .iter()
+                .flat_map(|(p, s)| [*p, *s])
+                .map(|c| sdd_helper(c, manager, ids, lines).to_string())
+                .collect();
+            format!(
+                "D {} {} {} {}",
+                lines.len(),
+                sdd.vtree().value(),
+                elements.len(),
+                children.join(" ")
+            )
+        }
+    };
+    let id = lines.len();
+    lines.push(line);
+    ids.insert(sdd, id);
+    id
+}
+
+#[test]
+fn sdd_to_ucla_writes_every_node_once() {
+    use crate::builder::{sdd::CompressionSddBuilder, BottomUpBuilder};
+    use crate::repr::{Cnf, VarLabel};
+
+    let vtree = VTree::even_split(&(0..5).map(VarLabel::new).collect::<Vec<_>>(), 2);
+    let builder = CompressionSddBuilder::new(vtree.clone());
+    let sdd = builder.compile_cnf(&Cnf::from_string("(1 || 2) && (-3 || 4) && (1 || -4)").unwrap());
+    let text = sdd_to_ucla(sdd, &vtree);
+
+    let body: Vec<&str> = text.lines().filter(|l| !l.starts_with('c')).collect();
+    let count: usize = body[0].strip_prefix("sdd ").unwrap().parse().unwrap();
+    assert_eq!(count, body.len() - 1);
+    // ids are consecutive and every child is defined before its parent
+    for (id, line) in body[1..].iter().enumerate() {
+        let fields: Vec<usize> = line
+            .split_whitespace()
+            .skip(1)
+            .map(|f| f.parse::<i64>().unwrap().unsigned_abs() as usize)
+            .collect();
+        assert_eq!(fields[0], id);
+        if line.starts_with('D') {
+            assert_eq!(fields.len(), 3 + 2 * fields[2]);
+            assert!(fields[3..].iter().all(|c| *c < id));
+        }
+    }
+}
+
+#[test]
+fn ucla_loaders_check_their_input() {
+    use crate::builder::sdd::CompressionSddBuilder;
+
+    let err = vtree_from_ucla("vtree 3\nL 0 1\nL 2 2\nI 1 0 3\n").unwrap_err();
+    assert_eq!((err.line(), err.column()), (4, 7));
+    assert!(vtree_from_ucla("vtree 3\nL 0 1\nL 2 1\nI 1 0 2\n").is_err());
+    assert!(vtree_from_ucla("vtree 3\nL 0 1\nL 2 2\nL 1 3\n").is_err());
+
+    let vtree = VTree::right_linear(&[VarLabel::new(0), VarLabel::new(1)]);
+    let builder = CompressionSddBuilder::new(vtree);
+    // primes x1 and x1 overlap
+    let err = sdd_from_ucla(
+        "sdd 4\nL 0 0 1\nL 1 2 2\nL 2 2 -2\nD 3 1 2 0 1 0 2\n",
+        &builder,
+    )
+    .unwrap_err();
+    assert_eq!((err.line(), err.column()), (5, 1));
+    // an uncompressed, untrimmed x2 is read back canonically
+    let sdd = sdd_from_ucla(
+        "sdd 4\nL 0 0 1\nL 1 0 -1\nL 2 2 2\nD 3 1 2 0 2 1 2\n",
+        &builder,
+    )
+    .unwrap();
+    assert_eq!(sdd, SddPtr::Var(VarLabel::new(1), true));
+}
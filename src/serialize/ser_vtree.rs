@@ -41,7 +41,7 @@
 //! // }
 //! ```
 
-use crate::repr::VTree;
+use crate::repr::{VTree, VarLabel};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerVTree {
@@ -75,4 +75,17 @@ impl VTreeSerializer {
         let root = helper(vtree);
         VTreeSerializer { root: *root }
     }
+
+    /// the vtree this was serialized from
+    pub fn to_vtree(&self) -> VTree {
+        fn helper(t: &SerVTree) -> VTree {
+            match t {
+                SerVTree::Leaf(v) => VTree::new_leaf(VarLabel::new_usize(*v)),
+                SerVTree::Node { left, right } => {
+                    VTree::new_node(Box::new(helper(left)), Box::new(helper(right)))
+                }
+            }
+        }
+        helper(&self.root)
+    }
 }
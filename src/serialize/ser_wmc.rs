@@ -0,0 +1,28 @@
+//! Weights and weighted model counts, as read and written by the
+//! command-line tools
+
+use std::collections::HashMap;
+
+/// The weights of a variable's negative and positive literals
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VariableWeight<T> {
+    pub low: T,
+    pub high: T,
+}
+
+/// The weighted and unweighted model counts of a formula conjoined with a
+/// partial model, whose variables are given by name. `M` is the type of the
+/// model count, for formulas with more than `2^128` models.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialWmcResult<T, M = u128> {
+    pub partial_model: HashMap<String, bool>,
+    pub wmc: T,
+    pub mc: M,
+}
+
+/// The counts of a formula under many partial models
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialWmcOutput<T> {
+    pub bdd_size: usize,
+    pub results: Vec<PartialWmcResult<T>>,
+}
@@ -0,0 +1,165 @@
+//! A table of integer handles to copyable values, for bindings whose callers
+//! cannot hold the values (e.g. pointers that borrow from a builder) directly
+
+use std::{collections::HashMap, hash::Hash};
+
+/// Hands out integer handles to values. Equal values share a handle, which
+/// counts how many times it was handed out; once it is released as many
+/// times, its slot is reused for the next new value.
+///
+/// A handle is a slot number in its low 32 bits and the slot's generation in
+/// its high 32 bits. Freeing a slot bumps its generation, so a stale handle
+/// to a reused slot is unknown rather than referring to the new value.
+pub struct Handles<P> {
+    /// each slot's generation, and its value and reference count or `None`
+    /// if it is free
+    slots: Vec<(u32, Option<(P, usize)>)>,
+    index: HashMap<P, u64>,
+    free: Vec<usize>,
+}
+
+impl<P: Copy + Eq + Hash> Handles<P> {
+    pub fn new() -> Handles<P> {
+        Handles {
+            slots: Vec::new(),
+            index: HashMap::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// the slot of `handle`, if its generation is current
+    fn slot(&self, handle: u64) -> Option<usize> {
+        let slot = (handle & u32::MAX as u64) as usize;
+        match self.slots.get(slot) {
+            Some((generation, _)) if *generation as u64 == handle >> 32 => Some(slot),
+            _ => None,
+        }
+    }
+
+    /// a handle to `value`, which must be released once it is no longer used
+    pub fn insert(&mut self, value: P) -> u64 {
+        if let Some(handle) = self.index.get(&value) {
+            if let Some(slot) = self.slot(*handle) {
+                if let (_, Some((_, count))) = &mut self.slots[slot] {
+                    *count += 1;
+                }
+            }
+            return *handle;
+        }
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot].1 = Some((value, 1));
+                slot
+            }
+            None => {
+                self.slots.push((0, Some((value, 1))));
+                self.slots.len() - 1
+            }
+        };
+        let handle = slot as u64 | (self.slots[slot].0 as u64) << 32;
+        self.index.insert(value, handle);
+        handle
+    }
+
+    /// the value `handle` refers to, if it is live
+    pub fn get(&self, handle: u64) -> Option<P> {
+        self.slot(handle)
+            .and_then(|slot| self.slots[slot].1)
+            .map(|(value, _)| value)
+    }
+
+    /// gives up one reference to `handle`, freeing its slot when none are
+    /// left; false if `handle` is not live
+    pub fn release(&mut self, handle: u64) -> bool {
+        let slot = match self.slot(handle) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        let (generation, entry) = &mut self.slots[slot];
+        match entry {
+            Some((_, count)) if *count > 1 => *count -= 1,
+            Some((value, _)) => {
+                self.index.remove(value);
+                *entry = None;
+                *generation = generation.wrapping_add(1);
+                self.free.push(slot);
+            }
+            None => return false,
+        }
+        true
+    }
+
+    /// the number of live handles
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// the number of slots, live or free
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl<P: Copy + Eq + Hash> Default for Handles<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn equal_values_share_a_counted_handle() {
+    let mut handles = Handles::new();
+    let a = handles.insert('a');
+    assert_eq!(handles.insert('a'), a);
+    let b = handles.insert('b');
+    assert_ne!(a, b);
+
+    assert!(handles.release(a));
+    assert_eq!(handles.get(a), Some('a'));
+    assert!(handles.release(a));
+    assert_eq!(handles.get(a), None);
+    assert!(!handles.release(a));
+    assert_eq!(handles.get(b), Some('b'));
+    assert_eq!(handles.len(), 1);
+}
+
+#[test]
+fn released_slots_are_reused() {
+    let mut handles = Handles::new();
+    for round in 0..100 {
+        let h = handles.insert(round);
+        assert_eq!(handles.get(h), Some(round));
+        assert!(handles.release(h));
+    }
+    assert!(handles.is_empty());
+    assert_eq!(handles.capacity(), 1);
+
+    let a = handles.insert(1);
+    let b = handles.insert(2);
+    handles.release(a);
+    // the freed slot is reused under a new generation
+    let c = handles.insert(3);
+    assert_ne!(c, a);
+    assert_eq!(c & u32::MAX as u64, a & u32::MAX as u64);
+    assert_eq!(handles.get(c), Some(3));
+    assert_eq!(handles.get(b), Some(2));
+    assert_eq!(handles.insert(1), 2);
+    assert!(!handles.release(7));
+}
+
+#[test]
+fn stale_handles_are_unknown() {
+    let mut handles = Handles::new();
+    let a = handles.insert('a');
+    assert!(handles.release(a));
+    let b = handles.insert('b');
+    // `b` took `a`'s slot, but `a` does not refer to it
+    assert_eq!(handles.get(a), None);
+    assert!(!handles.release(a));
+    assert_eq!(handles.get(b), Some('b'));
+    assert!(handles.release(b));
+}
@@ -1,6 +1,8 @@
 use crate::repr::{Cnf, VarLabel};
+use crate::util::btree::BTree;
 use core::{fmt::Debug, hash::Hash};
-use std::collections::{HashMap, HashSet};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
 #[derive(Clone, Debug)]
 pub struct Hypergraph<T: Clone + Debug + PartialEq + Eq + Hash> {
@@ -171,10 +173,24 @@ impl<T: Clone + Debug + PartialEq + Eq + Hash> Hypergraph<T> {
         }
         r
     }
-    // pub fn get_dtree(&self) -> BTree<(), T> {
-    // follow Kernighan-Lin algorithm for finding a minimum-cut 2-partition
-    // todo!()
-    // }
+
+    /// splits the vertices into two balanced halves with few cut edges (see
+    /// [`min_cut_bisection`])
+    pub fn bisect(&self, config: &PartitionConfig) -> (Vec<T>, Vec<T>) {
+        let vertices: Vec<&T> = self.vertices.iter().collect();
+        let index: HashMap<&T, usize> = vertices.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+        let edges: Vec<Vec<usize>> = self
+            .hyperedges
+            .iter()
+            .map(|e| e.iter().map(|v| index[v]).collect())
+            .collect();
+        let part = min_cut_bisection(vertices.len(), &edges, config);
+        let (l, r): (Vec<_>, Vec<_>) = vertices.into_iter().zip(part).partition(|(_, p)| !*p);
+        (
+            l.into_iter().map(|(v, _)| v.clone()).collect(),
+            r.into_iter().map(|(v, _)| v.clone()).collect(),
+        )
+    }
 }
 
 fn dedupe_hashsets<T: Hash + Eq>(hss: Vec<HashSet<T>>) -> Vec<HashSet<T>> {
@@ -246,6 +262,427 @@ pub fn from_cnf(cnf: &Cnf) -> Hypergraph<VarLabel> {
     Hypergraph::new(vars, dedupe_hashsets(hedges))
 }
 
+/// Parameters for the multilevel min-cut partitioner ([`min_cut_bisection`]
+/// and [`recursive_bisection`])
+#[derive(Clone, Debug)]
+pub struct PartitionConfig {
+    /// allowed imbalance: each side of a bisection may weigh at most
+    /// `(1 + imbalance) * total / 2`
+    pub imbalance: f64,
+    /// stop coarsening once the hypergraph has at most this many vertices
+    pub coarsest_size: usize,
+    /// number of randomized initial partitions tried on the coarsest level
+    pub initial_tries: usize,
+    /// maximum number of Fiduccia-Mattheyses passes per level
+    pub refinement_passes: usize,
+    /// seed for tie-breaking and initial partitions
+    pub seed: u64,
+}
+
+impl Default for PartitionConfig {
+    fn default() -> Self {
+        PartitionConfig {
+            imbalance: 0.1,
+            coarsest_size: 64,
+            initial_tries: 8,
+            refinement_passes: 8,
+            seed: 0,
+        }
+    }
+}
+
+/// Computes a balanced 2-partition of a hypergraph over vertices
+/// `0..num_vertices` that approximately minimizes the number of cut
+/// hyperedges (edges with pins on both sides).
+///
+/// Follows the multilevel scheme of hMETIS/KaHyPar: the hypergraph is
+/// repeatedly coarsened by contracting matched vertex pairs, partitioned at the
+/// coarsest level, then projected back down with Fiduccia-Mattheyses
+/// refinement at every level.
+///
+/// Returns, for each vertex, whether it is on the right side.
+/// ```
+/// use rsdd::util::hypergraph::{min_cut_bisection, PartitionConfig};
+/// // two triangles joined by a single edge
+/// let edges = vec![vec![0, 1], vec![1, 2], vec![0, 2], vec![2, 3], vec![3, 4], vec![4, 5], vec![3, 5]];
+/// let part = min_cut_bisection(6, &edges, &PartitionConfig::default());
+/// assert!(part[0] == part[1] && part[1] == part[2]);
+/// assert!(part[3] == part[4] && part[4] == part[5]);
+/// assert!(part[0] != part[3]);
+/// ```
+pub fn min_cut_bisection(
+    num_vertices: usize,
+    edges: &[Vec<usize>],
+    config: &PartitionConfig,
+) -> Vec<bool> {
+    let mut rng = SmallRng::seed_from_u64(config.seed);
+    let edges: Vec<Vec<usize>> = edges
+        .iter()
+        .map(|e| {
+            let mut e = e.clone();
+            e.sort_unstable();
+            e.dedup();
+            e
+        })
+        .filter(|e| e.len() > 1)
+        .collect();
+
+    // coarsening phase
+    let mut levels: Vec<(Vec<usize>, Vec<Vec<usize>>)> = vec![(vec![1; num_vertices], edges)];
+    let mut maps: Vec<Vec<usize>> = Vec::new();
+    loop {
+        let (weights, edges) = levels.last().unwrap();
+        if weights.len() <= config.coarsest_size.max(2) {
+            break;
+        }
+        let total: usize = weights.iter().sum();
+        let max_cluster = usize::max(1, total / config.coarsest_size.max(2));
+        let (coarse_weights, coarse_edges, map) = coarsen(weights, edges, max_cluster, &mut rng);
+        // stop once contraction no longer makes meaningful progress
+        if coarse_weights.len() * 10 > weights.len() * 9 {
+            break;
+        }
+        levels.push((coarse_weights, coarse_edges));
+        maps.push(map);
+    }
+
+    // initial partitioning phase
+    let (weights, edges) = levels.last().unwrap();
+    let max_weight = max_part_weight(weights, config.imbalance);
+    let mut best: Option<(usize, Vec<bool>)> = None;
+    for _ in 0..config.initial_tries.max(1) {
+        let mut part = grow_partition(weights, edges, &mut rng);
+        fm_refine(
+            weights,
+            edges,
+            &mut part,
+            max_weight,
+            config.refinement_passes,
+            &mut rng,
+        );
+        let cut = cut_size(edges, &part);
+        if best.as_ref().is_none_or(|(c, _)| cut < *c) {
+            best = Some((cut, part));
+        }
+    }
+    let mut part = best.unwrap().1;
+
+    // uncoarsening phase
+    for (level, map) in levels[..levels.len() - 1].iter().zip(maps.iter()).rev() {
+        let (weights, edges) = level;
+        part = map.iter().map(|c| part[*c]).collect();
+        let max_weight = max_part_weight(weights, config.imbalance);
+        fm_refine(
+            weights,
+            edges,
+            &mut part,
+            max_weight,
+            config.refinement_passes,
+            &mut rng,
+        );
+    }
+    part
+}
+
+/// Recursively bisects a hypergraph with [`min_cut_bisection`] until every
+/// part is a single vertex. Hyperedges cut at some bisection are dropped from
+/// the sub-problems below it, so each bisection only pays for edges that are
+/// not already cut by an ancestor.
+///
+/// Panics if `num_vertices` is 0.
+pub fn recursive_bisection(
+    num_vertices: usize,
+    edges: &[Vec<usize>],
+    config: &PartitionConfig,
+) -> BTree<(), usize> {
+    fn recurse(
+        ids: Vec<usize>,
+        edges: Vec<Vec<usize>>,
+        config: &PartitionConfig,
+    ) -> BTree<(), usize> {
+        match ids.len() {
+            0 => panic!("cannot bisect an empty hypergraph"),
+            1 => BTree::Leaf(ids[0]),
+            2 => BTree::Node(
+                (),
+                Box::new(BTree::Leaf(ids[0])),
+                Box::new(BTree::Leaf(ids[1])),
+            ),
+            n => {
+                let mut part = min_cut_bisection(n, &edges, config);
+                // guarantee progress if refinement emptied one side
+                if part.iter().all(|p| *p) || part.iter().all(|p| !*p) {
+                    part = (0..n).map(|i| i >= n / 2).collect();
+                }
+                // new local index of each vertex within its side
+                let mut local = vec![0; n];
+                let mut sides: [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+                for (v, side) in part.iter().enumerate() {
+                    let side = &mut sides[*side as usize];
+                    local[v] = side.len();
+                    side.push(ids[v]);
+                }
+                let mut side_edges: [Vec<Vec<usize>>; 2] = [Vec::new(), Vec::new()];
+                for e in edges {
+                    let side = part[e[0]];
+                    if e.iter().all(|v| part[*v] == side) {
+                        side_edges[side as usize].push(e.iter().map(|v| local[*v]).collect());
+                    }
+                }
+                let [l_ids, r_ids] = sides;
+                let [l_edges, r_edges] = side_edges;
+                BTree::Node(
+                    (),
+                    Box::new(recurse(l_ids, l_edges, config)),
+                    Box::new(recurse(r_ids, r_edges, config)),
+                )
+            }
+        }
+    }
+
+    let edges = edges.iter().filter(|e| e.len() > 1).cloned().collect();
+    recurse((0..num_vertices).collect(), edges, config)
+}
+
+fn max_part_weight(weights: &[usize], imbalance: f64) -> usize {
+    let total: usize = weights.iter().sum();
+    let bound = ((1.0 + imbalance) * total as f64 / 2.0).ceil() as usize;
+    // a single heavy vertex must always fit somewhere
+    bound.max(*weights.iter().max().unwrap_or(&0))
+}
+
+fn cut_size(edges: &[Vec<usize>], part: &[bool]) -> usize {
+    edges
+        .iter()
+        .filter(|e| e.iter().any(|v| part[*v] != part[e[0]]))
+        .count()
+}
+
+fn incidence(num_vertices: usize, edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut inc = vec![Vec::new(); num_vertices];
+    for (i, e) in edges.iter().enumerate() {
+        for v in e {
+            inc[*v].push(i);
+        }
+    }
+    inc
+}
+
+/// contracts a matching of vertex pairs chosen by the heavy-edge rating
+/// `sum over shared edges e of 1 / (|e| - 1)`; returns the coarse weights,
+/// coarse edges and the map from fine to coarse vertices
+fn coarsen(
+    weights: &[usize],
+    edges: &[Vec<usize>],
+    max_cluster: usize,
+    rng: &mut SmallRng,
+) -> (Vec<usize>, Vec<Vec<usize>>, Vec<usize>) {
+    let n = weights.len();
+    let inc = incidence(n, edges);
+    let mut map = vec![usize::MAX; n];
+    let mut coarse_weights = Vec::new();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+    let mut rating: HashMap<usize, f64> = HashMap::new();
+    for u in order {
+        if map[u] != usize::MAX {
+            continue;
+        }
+        rating.clear();
+        for e in inc[u].iter().map(|i| &edges[*i]) {
+            let score = 1.0 / (e.len() - 1) as f64;
+            for v in e {
+                if *v != u && map[*v] == usize::MAX && weights[u] + weights[*v] <= max_cluster {
+                    *rating.entry(*v).or_insert(0.0) += score;
+                }
+            }
+        }
+        let id = coarse_weights.len();
+        map[u] = id;
+        let best = rating
+            .iter()
+            .max_by(|(v1, s1), (v2, s2)| s1.partial_cmp(s2).unwrap().then(v2.cmp(v1)));
+        match best {
+            Some((v, _)) => {
+                map[*v] = id;
+                coarse_weights.push(weights[u] + weights[*v]);
+            }
+            None => coarse_weights.push(weights[u]),
+        }
+    }
+    let coarse_edges = edges
+        .iter()
+        .filter_map(|e| {
+            let mut c: Vec<usize> = e.iter().map(|v| map[*v]).collect();
+            c.sort_unstable();
+            c.dedup();
+            if c.len() > 1 {
+                Some(c)
+            } else {
+                None
+            }
+        })
+        .collect();
+    (coarse_weights, coarse_edges, map)
+}
+
+/// greedily grows the left side breadth-first from a random vertex until it
+/// holds half of the total weight
+fn grow_partition(weights: &[usize], edges: &[Vec<usize>], rng: &mut SmallRng) -> Vec<bool> {
+    let n = weights.len();
+    let inc = incidence(n, edges);
+    let total: usize = weights.iter().sum();
+    let mut part = vec![true; n];
+    let mut left = 0;
+    let mut queue = VecDeque::new();
+    let mut unvisited: Vec<usize> = (0..n).collect();
+    unvisited.shuffle(rng);
+    while left * 2 < total {
+        let u = match queue.pop_front() {
+            Some(u) => u,
+            None => match unvisited.pop() {
+                Some(u) => u,
+                None => break,
+            },
+        };
+        if !part[u] {
+            continue;
+        }
+        part[u] = false;
+        left += weights[u];
+        for e in inc[u].iter() {
+            for v in edges[*e].iter() {
+                if part[*v] {
+                    queue.push_back(*v);
+                }
+            }
+        }
+    }
+    part
+}
+
+/// Fiduccia-Mattheyses refinement: each pass moves every vertex at most once,
+/// highest gain first, then rolls back to the best prefix of moves
+fn fm_refine(
+    weights: &[usize],
+    edges: &[Vec<usize>],
+    part: &mut [bool],
+    max_weight: usize,
+    passes: usize,
+    rng: &mut SmallRng,
+) {
+    let n = weights.len();
+    let inc = incidence(n, edges);
+    for _ in 0..passes {
+        // number of pins of each edge on each side
+        let mut pins: Vec<[usize; 2]> = edges
+            .iter()
+            .map(|e| {
+                let r = e.iter().filter(|v| part[**v]).count();
+                [e.len() - r, r]
+            })
+            .collect();
+        let mut side_weight = [0, 0];
+        for v in 0..n {
+            side_weight[part[v] as usize] += weights[v];
+        }
+        let mut gain: Vec<i64> = (0..n)
+            .map(|v| {
+                let from = part[v] as usize;
+                inc[v]
+                    .iter()
+                    .map(|e| {
+                        if pins[*e][from] == 1 {
+                            1
+                        } else if pins[*e][1 - from] == 0 {
+                            -1
+                        } else {
+                            0
+                        }
+                    })
+                    .sum()
+            })
+            .collect();
+        let mut heap: BinaryHeap<(i64, u32, usize)> =
+            (0..n).map(|v| (gain[v], rng.gen(), v)).collect();
+        let mut locked = vec![false; n];
+        let mut moves: Vec<usize> = Vec::new();
+        let (mut cur, mut best, mut best_len) = (0_i64, 0_i64, 0);
+        let imbalance = |w: &[usize; 2]| w[0].abs_diff(w[1]);
+        let mut best_imbalance = imbalance(&side_weight);
+
+        while let Some((g, _, v)) = heap.pop() {
+            if locked[v] || g != gain[v] {
+                continue;
+            }
+            let from = part[v] as usize;
+            let to = 1 - from;
+            if side_weight[to] + weights[v] > max_weight {
+                continue;
+            }
+            locked[v] = true;
+
+            let mut touched = Vec::new();
+            for e in inc[v].iter() {
+                let pin_list = &edges[*e];
+                if pins[*e][to] == 0 {
+                    for u in pin_list.iter().filter(|u| !locked[**u]) {
+                        gain[*u] += 1;
+                        touched.push(*u);
+                    }
+                } else if pins[*e][to] == 1 {
+                    if let Some(u) = pin_list.iter().find(|u| part[**u] as usize == to) {
+                        if !locked[*u] {
+                            gain[*u] -= 1;
+                            touched.push(*u);
+                        }
+                    }
+                }
+                pins[*e][from] -= 1;
+                pins[*e][to] += 1;
+                if pins[*e][from] == 0 {
+                    for u in pin_list.iter().filter(|u| !locked[**u]) {
+                        gain[*u] -= 1;
+                        touched.push(*u);
+                    }
+                } else if pins[*e][from] == 1 {
+                    if let Some(u) = pin_list
+                        .iter()
+                        .find(|u| **u != v && part[**u] as usize == from)
+                    {
+                        if !locked[*u] {
+                            gain[*u] += 1;
+                            touched.push(*u);
+                        }
+                    }
+                }
+            }
+            part[v] = !part[v];
+            side_weight[from] -= weights[v];
+            side_weight[to] += weights[v];
+            moves.push(v);
+            for u in touched {
+                heap.push((gain[u], rng.gen(), u));
+            }
+
+            cur += g;
+            let cur_imbalance = imbalance(&side_weight);
+            if cur > best || (cur == best && cur_imbalance < best_imbalance) {
+                best = cur;
+                best_len = moves.len();
+                best_imbalance = cur_imbalance;
+            }
+        }
+
+        for v in moves.drain(best_len..) {
+            part[v] = !part[v];
+        }
+        if best <= 0 {
+            break;
+        }
+    }
+}
+
 mod test {
     #[test]
     fn cnf_to_hg() {
@@ -594,4 +1031,41 @@ mod test {
         let topvars: HashSet<u64> = topscore(&ordering).into_iter().map(|(v, _)| v).collect();
         assert!(!topvars.is_disjoint(&HashSet::from([21, 55, 12])));
     }
+
+    #[test]
+    fn bisection_finds_planted_cut() {
+        use crate::util::btree::BTree;
+        use crate::util::hypergraph::{min_cut_bisection, recursive_bisection, PartitionConfig};
+
+        // two dense clusters of 100 vertices joined by 3 edges
+        let mut edges = Vec::new();
+        for base in [0, 100] {
+            for i in 0..100 {
+                edges.push(vec![base + i, base + (i + 1) % 100, base + (i + 7) % 100]);
+                edges.push(vec![base + i, base + (i + 13) % 100]);
+            }
+        }
+        edges.extend([vec![0, 100], vec![50, 150], vec![99, 199]]);
+
+        let config = PartitionConfig::default();
+        let part = min_cut_bisection(200, &edges, &config);
+        let cut = edges
+            .iter()
+            .filter(|e| e.iter().any(|v| part[*v] != part[e[0]]))
+            .count();
+        assert_eq!(cut, 3);
+        assert_eq!(part.iter().filter(|p| **p).count(), 100);
+
+        // every vertex appears exactly once as a leaf
+        let tree = recursive_bisection(200, &edges, &config);
+        let mut leaves: Vec<usize> = tree
+            .inorder_dfs_iter()
+            .filter_map(|t| match t {
+                BTree::Leaf(v) => Some(*v),
+                _ => None,
+            })
+            .collect();
+        leaves.sort_unstable();
+        assert_eq!(leaves, (0..200).collect::<Vec<_>>());
+    }
 }
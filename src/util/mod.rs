@@ -1,6 +1,7 @@
 //! Useful utilities and generic data structures used throughout the library
 
 pub mod btree;
+pub mod handles;
 pub mod hypergraph;
 pub mod lru;
 pub mod semirings;
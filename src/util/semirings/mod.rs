@@ -6,6 +6,7 @@ mod rational;
 mod realsemiring;
 mod semiring_traits;
 mod tropical;
+mod vector;
 
 pub use self::boolean::*;
 pub use self::complex::*;
@@ -15,3 +16,4 @@ pub use self::rational::*;
 pub use self::realsemiring::*;
 pub use self::semiring_traits::*;
 pub use self::tropical::*;
+pub use self::vector::*;
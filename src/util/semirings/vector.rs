@@ -0,0 +1,71 @@
+use super::semiring_traits::*;
+use std::{fmt::Display, ops};
+
+/// `N` independent values of a semiring, combined lane by lane. Counting a
+/// diagram over `VecSemiring` weights evaluates `N` weightings in a single
+/// pass; with `f64`-backed lanes the element-wise loops compile to SIMD.
+/// ```
+/// use rsdd::util::semirings::{RealSemiring, Semiring, VecSemiring};
+///
+/// let a = VecSemiring([RealSemiring(0.5), RealSemiring(2.0)]);
+/// let b = VecSemiring([RealSemiring(0.25), RealSemiring(3.0)]);
+/// assert_eq!((a * b + VecSemiring::one()).0, [RealSemiring(1.125), RealSemiring(7.0)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VecSemiring<T, const N: usize>(pub [T; N]);
+
+impl<T: Semiring, const N: usize> VecSemiring<T, N> {
+    /// every lane set to `value`
+    pub fn splat(value: T) -> Self {
+        VecSemiring([value; N])
+    }
+}
+
+impl<T: Semiring, const N: usize> Display for VecSemiring<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, lane) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", lane)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T: Semiring, const N: usize> ops::Add<VecSemiring<T, N>> for VecSemiring<T, N> {
+    type Output = VecSemiring<T, N>;
+
+    fn add(self, rhs: VecSemiring<T, N>) -> Self::Output {
+        VecSemiring(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<T: Semiring, const N: usize> ops::Mul<VecSemiring<T, N>> for VecSemiring<T, N> {
+    type Output = VecSemiring<T, N>;
+
+    fn mul(self, rhs: VecSemiring<T, N>) -> Self::Output {
+        VecSemiring(std::array::from_fn(|i| self.0[i] * rhs.0[i]))
+    }
+}
+
+impl<T: Ring, const N: usize> ops::Sub<VecSemiring<T, N>> for VecSemiring<T, N> {
+    type Output = VecSemiring<T, N>;
+
+    fn sub(self, rhs: VecSemiring<T, N>) -> Self::Output {
+        VecSemiring(std::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<T: Semiring, const N: usize> Semiring for VecSemiring<T, N> {
+    fn one() -> Self {
+        VecSemiring::splat(T::one())
+    }
+
+    fn zero() -> Self {
+        VecSemiring::splat(T::zero())
+    }
+}
+
+impl<T: Ring, const N: usize> Ring for VecSemiring<T, N> {}
@@ -1,3 +1,9 @@
+mod session;
+
+pub use self::session::*;
+
+use std::collections::HashMap;
+
 use crate::{
     builder::{
         bdd::RobddBuilder,
@@ -7,8 +13,11 @@ use crate::{
     },
     constants::primes,
     repr::{BddPtr, Cnf, DDNNFPtr, DTree, VTree, VarLabel, VarOrder, WmcParams},
-    serialize::{BDDSerializer, SDDSerializer, VTreeSerializer},
-    util::semirings::FiniteField,
+    serialize::{
+        bdd_to_graph, dtree_to_graph, sdd_to_graph, vtree_to_graph, BDDSerializer, GraphFormat,
+        SDDSerializer, VTreeSerializer,
+    },
+    util::{hypergraph::PartitionConfig, semirings::FiniteField},
 };
 use wasm_bindgen::prelude::*;
 
@@ -19,6 +28,7 @@ pub enum VTreeType {
     EvenSplit(usize),
     FromDTreeLinear,
     FromDTreeMinFill,
+    FromHypergraph,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -31,7 +41,7 @@ pub struct SddModelCountResult {
 // used in: https://github.com/mattxwang/indecision
 #[wasm_bindgen]
 pub fn vtree(cnf_input: String, vtree_type_input: JsValue) -> Result<JsValue, JsValue> {
-    let cnf = Cnf::from_dimacs(&cnf_input);
+    let cnf = parse_cnf(&cnf_input)?;
 
     let vtree_type: VTreeType = serde_wasm_bindgen::from_value(vtree_type_input)?;
 
@@ -44,21 +54,21 @@ pub fn vtree(cnf_input: String, vtree_type_input: JsValue) -> Result<JsValue, Js
 
 // used in: https://github.com/mattxwang/indecision
 #[wasm_bindgen]
-pub fn bdd(cnf_input: String) -> String {
-    let cnf = Cnf::from_dimacs(&cnf_input);
+pub fn bdd(cnf_input: String) -> Result<String, JsValue> {
+    let cnf = parse_cnf(&cnf_input)?;
 
     let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
     let bdd = builder.compile_cnf(&cnf);
 
     let json = BDDSerializer::from_bdd(bdd);
 
-    serde_json::to_string(&json).unwrap()
+    Ok(serde_json::to_string(&json).unwrap())
 }
 
 // used in: https://github.com/mattxwang/indecision
 #[wasm_bindgen]
-pub fn bdd_with_var_order(cnf_input: String, order: &[u64]) -> String {
-    let cnf = Cnf::from_dimacs(&cnf_input);
+pub fn bdd_with_var_order(cnf_input: String, order: &[u64]) -> Result<String, JsValue> {
+    let cnf = parse_cnf(&cnf_input)?;
 
     let var_order = VarOrder::new(&order.iter().map(|v| VarLabel::new(*v)).collect::<Vec<_>>());
 
@@ -67,13 +77,13 @@ pub fn bdd_with_var_order(cnf_input: String, order: &[u64]) -> String {
 
     let json = BDDSerializer::from_bdd(bdd);
 
-    serde_json::to_string(&json).unwrap()
+    Ok(serde_json::to_string(&json).unwrap())
 }
 
 // used in: https://github.com/mattxwang/indecision
 #[wasm_bindgen]
 pub fn sdd(cnf_input: String, vtree_type_input: JsValue) -> Result<JsValue, JsValue> {
-    let cnf = Cnf::from_dimacs(&cnf_input);
+    let cnf = parse_cnf(&cnf_input)?;
 
     let vtree_type: VTreeType = serde_wasm_bindgen::from_value(vtree_type_input)?;
 
@@ -90,7 +100,7 @@ pub fn sdd(cnf_input: String, vtree_type_input: JsValue) -> Result<JsValue, JsVa
 // used in rsdd-docs
 #[wasm_bindgen]
 pub fn demo_model_count_sdd(cnf_input: String) -> Result<JsValue, JsValue> {
-    let cnf = Cnf::from_dimacs(&cnf_input);
+    let cnf = parse_cnf(&cnf_input)?;
 
     let vtree = build_vtree(&cnf, VTreeType::FromDTreeLinear);
 
@@ -118,6 +128,87 @@ pub fn demo_model_count_sdd(cnf_input: String) -> Result<JsValue, JsValue> {
     Ok(serde_wasm_bindgen::to_value(&res)?)
 }
 
+// used in: https://github.com/mattxwang/indecision
+// `format` is `"Dot"` or `"Mermaid"`; `names` is an optional array of display
+// names, indexed by variable label
+#[wasm_bindgen]
+pub fn bdd_graph(cnf_input: String, format: JsValue, names: JsValue) -> Result<String, JsValue> {
+    let cnf = parse_cnf(&cnf_input)?;
+    let format: GraphFormat = serde_wasm_bindgen::from_value(format)?;
+
+    let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
+    let bdd = builder.compile_cnf(&cnf);
+
+    Ok(bdd_to_graph(&[bdd], &parse_names(names)?, format))
+}
+
+// used in: https://github.com/mattxwang/indecision
+#[wasm_bindgen]
+pub fn sdd_graph(
+    cnf_input: String,
+    vtree_type_input: JsValue,
+    format: JsValue,
+    names: JsValue,
+) -> Result<String, JsValue> {
+    let cnf = parse_cnf(&cnf_input)?;
+    let vtree_type: VTreeType = serde_wasm_bindgen::from_value(vtree_type_input)?;
+    let format: GraphFormat = serde_wasm_bindgen::from_value(format)?;
+
+    let vtree = build_vtree(&cnf, vtree_type);
+    let builder = CompressionSddBuilder::new(vtree.clone());
+    let sdd = builder.compile_cnf(&cnf);
+
+    Ok(sdd_to_graph(&[sdd], &vtree, &parse_names(names)?, format))
+}
+
+// used in: https://github.com/mattxwang/indecision
+#[wasm_bindgen]
+pub fn vtree_graph(
+    cnf_input: String,
+    vtree_type_input: JsValue,
+    format: JsValue,
+    names: JsValue,
+) -> Result<String, JsValue> {
+    let cnf = parse_cnf(&cnf_input)?;
+    let vtree_type: VTreeType = serde_wasm_bindgen::from_value(vtree_type_input)?;
+    let format: GraphFormat = serde_wasm_bindgen::from_value(format)?;
+
+    let vtree = build_vtree(&cnf, vtree_type);
+
+    Ok(vtree_to_graph(&vtree, &parse_names(names)?, format))
+}
+
+// used in: https://github.com/mattxwang/indecision
+#[wasm_bindgen]
+pub fn dtree_graph(cnf_input: String, format: JsValue, names: JsValue) -> Result<String, JsValue> {
+    let cnf = parse_cnf(&cnf_input)?;
+    let format: GraphFormat = serde_wasm_bindgen::from_value(format)?;
+
+    let dtree = DTree::from_cnf(&cnf, &cnf.min_fill_order());
+
+    Ok(dtree_to_graph(&dtree, &parse_names(names)?, format))
+}
+
+// internal function -- a missing mapping means no names
+fn parse_names(names: JsValue) -> Result<HashMap<VarLabel, String>, JsValue> {
+    let names: Option<Vec<String>> = serde_wasm_bindgen::from_value(names)?;
+    Ok(names
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(v, name)| (VarLabel::new_usize(v), name))
+        .collect())
+}
+
+// internal function -- parse errors are surfaced to JS as
+// `{ line, column, message }` objects
+fn parse_cnf(cnf_input: &str) -> Result<Cnf, JsValue> {
+    Cnf::from_dimacs(cnf_input).map_err(|err| match serde_wasm_bindgen::to_value(&err) {
+        Ok(value) => value,
+        Err(e) => e.into(),
+    })
+}
+
 // internal function -- no intermediate types needed
 fn build_vtree(cnf: &Cnf, vtree_type: VTreeType) -> VTree {
     let range: Vec<usize> = (0..cnf.num_vars()).collect();
@@ -139,5 +230,6 @@ fn build_vtree(cnf: &Cnf, vtree_type: VTreeType) -> VTree {
             let dtree = DTree::from_cnf(cnf, &cnf.min_fill_order());
             VTree::from_dtree(&dtree).unwrap()
         }
+        VTreeType::FromHypergraph => VTree::from_cnf_hypergraph(cnf, &PartitionConfig::default()),
     }
 }
@@ -0,0 +1,645 @@
+use std::{cell::Ref, collections::HashMap, hash::Hash};
+
+use super::{build_vtree, parse_cnf, parse_names, VTreeType};
+use crate::{
+    builder::{
+        bdd::RobddBuilder,
+        cache::AllIteTable,
+        sdd::{CompressionSddBuilder, SddBuilder},
+        BottomUpBuilder,
+    },
+    repr::{BddPtr, DDNNFPtr, SddPtr, VTree, VarLabel, VarOrder, VarSet, WmcParams},
+    serialize::{bdd_to_graph, sdd_to_graph, BDDSerializer, GraphFormat, SDDSerializer},
+    util::{handles, semirings::RealSemiring},
+};
+use wasm_bindgen::prelude::*;
+
+type BddBuilder = RobddBuilder<'static, AllIteTable<BddPtr<'static>>>;
+
+// JS cannot hold pointers that borrow from a builder, so sessions hand out
+// indices into a table of the diagrams built so far. Equal pointers share a
+// handle, so in a canonical builder equivalent functions do too.
+struct Handles<P>(handles::Handles<P>);
+
+impl<P: Copy + Eq + Hash> Handles<P> {
+    fn new() -> Handles<P> {
+        Handles(handles::Handles::new())
+    }
+
+    fn insert(&mut self, ptr: P) -> u64 {
+        self.0.insert(ptr)
+    }
+
+    fn get(&self, handle: u64) -> Result<P, JsValue> {
+        self.0
+            .get(handle)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown handle {}", handle)))
+    }
+
+    fn release(&mut self, handle: u64) -> Result<(), JsValue> {
+        if self.0.release(handle) {
+            Ok(())
+        } else {
+            Err(JsValue::from_str(&format!("unknown handle {}", handle)))
+        }
+    }
+}
+
+fn check_label(label: u64, num_vars: usize) -> Result<VarLabel, JsValue> {
+    if (label as usize) < num_vars {
+        Ok(VarLabel::new(label))
+    } else {
+        Err(JsValue::from_str(&format!(
+            "variable {} is outside this session's {} variables",
+            label, num_vars
+        )))
+    }
+}
+
+// internal function -- `weights` is an optional array of `[low, high]`
+// pairs indexed by label; missing weights are `[1, 1]`
+fn parse_weights(weights: JsValue, num_vars: usize) -> Result<WmcParams<RealSemiring>, JsValue> {
+    let weights: Option<Vec<Option<(f64, f64)>>> = serde_wasm_bindgen::from_value(weights)?;
+    let weights = weights.unwrap_or_default();
+    let mut params = WmcParams::default();
+    for v in 0..usize::max(num_vars, weights.len()) {
+        let (low, high) = weights.get(v).copied().flatten().unwrap_or((1.0, 1.0));
+        params.set_weight(
+            VarLabel::new_usize(v),
+            RealSemiring(low),
+            RealSemiring(high),
+        );
+    }
+    Ok(params)
+}
+
+// internal function -- one node of a diagram, for `wmc`'s walk
+enum Node<P> {
+    True,
+    False,
+    Lit(VarLabel, bool),
+    /// a decision on a label, to its low and high children
+    Decision(VarLabel, P, P),
+    /// a disjunction of conjunctions of primes and subs
+    Elements(Vec<(P, P)>),
+}
+
+fn bdd_node(f: BddPtr) -> Node<BddPtr> {
+    if f.is_true() {
+        Node::True
+    } else if f.is_false() {
+        Node::False
+    } else {
+        Node::Decision(f.var_safe().unwrap(), f.low(), f.high())
+    }
+}
+
+fn sdd_node(f: SddPtr) -> Node<SddPtr> {
+    match f {
+        SddPtr::PtrTrue => Node::True,
+        SddPtr::PtrFalse => Node::False,
+        SddPtr::Var(v, polarity) => Node::Lit(v, polarity),
+        _ => Node::Elements(
+            f.node_iter()
+                .map(|and| {
+                    let sub = if f.is_neg() {
+                        and.sub().neg()
+                    } else {
+                        and.sub()
+                    };
+                    (and.prime(), sub)
+                })
+                .collect(),
+        ),
+    }
+}
+
+// internal function -- `value`, a count over the labels `vars`, extended to
+// the labels `scope` by weighing each missing label with `low + high`
+fn smooth(params: &WmcParams<RealSemiring>, (value, vars): &(f64, VarSet), scope: &VarSet) -> f64 {
+    scope.minus(vars).iter().fold(*value, |acc, v| {
+        let (low, high) = params.var_weight(v);
+        acc * (low.0 + high.0)
+    })
+}
+
+// internal function -- the weighted model count of `f` over `num_vars`
+// variables. Diagrams skip labels that a path never tests, so every node is
+// counted over the labels below it, and branches that test fewer labels than
+// their siblings are smoothed to the labels of the whole node
+fn wmc<P: Copy + Eq + Hash>(
+    f: P,
+    expand: fn(P) -> Node<P>,
+    params: &WmcParams<RealSemiring>,
+    num_vars: usize,
+) -> f64 {
+    fn go<P: Copy + Eq + Hash>(
+        f: P,
+        expand: fn(P) -> Node<P>,
+        params: &WmcParams<RealSemiring>,
+        memo: &mut HashMap<P, (f64, VarSet)>,
+    ) -> (f64, VarSet) {
+        if let Some(count) = memo.get(&f) {
+            return count.clone();
+        }
+        let weight = |v: VarLabel, polarity: bool| {
+            let (low, high) = params.var_weight(v);
+            if polarity {
+                high.0
+            } else {
+                low.0
+            }
+        };
+        let count = match expand(f) {
+            Node::True => (1.0, VarSet::new()),
+            Node::False => (0.0, VarSet::new()),
+            Node::Lit(v, polarity) => {
+                let mut vars = VarSet::new();
+                vars.insert(v);
+                (weight(v, polarity), vars)
+            }
+            Node::Decision(v, low, high) => {
+                let low = go(low, expand, params, memo);
+                let high = go(high, expand, params, memo);
+                let mut vars = low.1.union(&high.1);
+                let value = weight(v, false) * smooth(params, &low, &vars)
+                    + weight(v, true) * smooth(params, &high, &vars);
+                vars.insert(v);
+                (value, vars)
+            }
+            Node::Elements(elements) => {
+                let elements: Vec<(f64, VarSet)> = elements
+                    .into_iter()
+                    .map(|(prime, sub)| {
+                        let prime = go(prime, expand, params, memo);
+                        let sub = go(sub, expand, params, memo);
+                        (prime.0 * sub.0, prime.1.union(&sub.1))
+                    })
+                    .collect();
+                let vars = elements
+                    .iter()
+                    .fold(VarSet::new(), |vars, element| vars.union(&element.1));
+                let value = elements.iter().map(|e| smooth(params, e, &vars)).sum();
+                (value, vars)
+            }
+        };
+        memo.insert(f, count.clone());
+        count
+    }
+    let mut all = VarSet::new_with_num_vars(num_vars);
+    for v in 0..num_vars {
+        all.insert(VarLabel::new_usize(v));
+    }
+    smooth(params, &go(f, expand, params, &mut HashMap::new()), &all)
+}
+
+// internal function -- the first `limit` models of `f` over `num_vars`
+// variables, in lexicographic order, found by conditioning on each variable
+// in turn and pruning unsatisfiable branches
+fn models<'a, P: DDNNFPtr<'a>, B: BottomUpBuilder<'a, P>>(
+    builder: &'a B,
+    f: P,
+    num_vars: usize,
+    limit: usize,
+) -> Vec<Vec<bool>> {
+    fn go<'a, P: DDNNFPtr<'a>, B: BottomUpBuilder<'a, P>>(
+        builder: &'a B,
+        f: P,
+        model: &mut Vec<bool>,
+        num_vars: usize,
+        limit: usize,
+        found: &mut Vec<Vec<bool>>,
+    ) {
+        if f.is_false() || found.len() >= limit {
+            return;
+        }
+        if model.len() == num_vars {
+            found.push(model.clone());
+            return;
+        }
+        for value in [false, true] {
+            let label = VarLabel::new_usize(model.len());
+            model.push(value);
+            go(
+                builder,
+                builder.condition(f, label, value),
+                model,
+                num_vars,
+                limit,
+                found,
+            );
+            model.pop();
+        }
+    }
+    let mut found = Vec::new();
+    go(builder, f, &mut Vec::new(), num_vars, limit, &mut found);
+    found
+}
+
+/// A BDD builder that lives across calls, so that diagrams can be built up
+/// interactively. Diagrams are referred to by integer handles (`BigInt`s in
+/// JS); every handle a method returns should be given back to `release` once
+/// it is no longer needed, so that its slot can be reused. A released handle
+/// stays unknown even after its slot is reused.
+#[wasm_bindgen]
+pub struct BddSession {
+    // the pointers borrow from `builder`, so they must be dropped first
+    handles: Handles<BddPtr<'static>>,
+    builder: Box<BddBuilder>,
+}
+
+impl BddSession {
+    fn from_order(order: VarOrder) -> BddSession {
+        BddSession {
+            handles: Handles::new(),
+            builder: Box::new(RobddBuilder::new(order)),
+        }
+    }
+
+    // sound because the only pointers into the builder live in `handles`,
+    // which is dropped before it
+    fn builder(&self) -> &'static BddBuilder {
+        unsafe { &*(self.builder.as_ref() as *const BddBuilder) }
+    }
+
+    fn apply(
+        &mut self,
+        a: u64,
+        b: u64,
+        op: impl FnOnce(&'static BddBuilder, BddPtr<'static>, BddPtr<'static>) -> BddPtr<'static>,
+    ) -> Result<u64, JsValue> {
+        let (a, b) = (self.handles.get(a)?, self.handles.get(b)?);
+        let ptr = op(self.builder(), a, b);
+        Ok(self.handles.insert(ptr))
+    }
+}
+
+#[wasm_bindgen]
+impl BddSession {
+    /// a session over `num_vars` variables in their natural order
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_vars: usize) -> BddSession {
+        BddSession::from_order(VarOrder::linear_order(num_vars))
+    }
+
+    /// a session whose variable order is `order`, a permutation of the
+    /// labels `0..order.length`
+    pub fn with_order(order: &[u64]) -> Result<BddSession, JsValue> {
+        let mut seen = vec![false; order.len()];
+        for v in order {
+            match seen.get_mut(*v as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Err(JsValue::from_str("the order must be a permutation")),
+            }
+        }
+        let order: Vec<VarLabel> = order.iter().map(|v| VarLabel::new(*v)).collect();
+        Ok(BddSession::from_order(VarOrder::new(&order)))
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.builder.num_vars()
+    }
+
+    /// gives back a handle returned by this session. A handle returned `n`
+    /// times stays valid until it is released `n` times; the builder's
+    /// nodes themselves are only freed with the session.
+    pub fn release(&mut self, f: u64) -> Result<(), JsValue> {
+        self.handles.release(f)
+    }
+
+    pub fn var(&mut self, label: u64, polarity: bool) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().var(label, polarity);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn constant(&mut self, value: bool) -> u64 {
+        let ptr = if value {
+            self.builder.true_ptr()
+        } else {
+            self.builder.false_ptr()
+        };
+        self.handles.insert(ptr)
+    }
+
+    pub fn and(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.and(a, b))
+    }
+
+    pub fn or(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.or(a, b))
+    }
+
+    pub fn iff(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.iff(a, b))
+    }
+
+    pub fn xor(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.xor(a, b))
+    }
+
+    pub fn not(&mut self, f: u64) -> Result<u64, JsValue> {
+        let ptr = self.builder().negate(self.handles.get(f)?);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn ite(&mut self, f: u64, g: u64, h: u64) -> Result<u64, JsValue> {
+        let (f, g, h) = (
+            self.handles.get(f)?,
+            self.handles.get(g)?,
+            self.handles.get(h)?,
+        );
+        let ptr = self.builder().ite(f, g, h);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn condition(&mut self, f: u64, label: u64, value: bool) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().condition(self.handles.get(f)?, label, value);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn exists(&mut self, f: u64, label: u64) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().exists(self.handles.get(f)?, label);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn forall(&mut self, f: u64, label: u64) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().forall(self.handles.get(f)?, label);
+        Ok(self.handles.insert(ptr))
+    }
+
+    /// compiles a DIMACS CNF over (at most) this session's variables
+    pub fn compile_cnf(&mut self, cnf_input: String) -> Result<u64, JsValue> {
+        let cnf = parse_cnf(&cnf_input)?;
+        check_label(cnf.num_vars().saturating_sub(1) as u64, self.num_vars())?;
+        let ptr = self.builder().compile_cnf(&cnf);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn is_true(&self, f: u64) -> Result<bool, JsValue> {
+        Ok(self.handles.get(f)?.is_true())
+    }
+
+    pub fn is_false(&self, f: u64) -> Result<bool, JsValue> {
+        Ok(self.handles.get(f)?.is_false())
+    }
+
+    pub fn count_nodes(&self, f: u64) -> Result<usize, JsValue> {
+        Ok(self.handles.get(f)?.count_nodes())
+    }
+
+    /// the weighted model count of `f` over all of this session's
+    /// variables; `weights` is an optional array of `[low, high]` pairs
+    /// indexed by label, defaulting to `[1, 1]` (so that by default this is
+    /// the number of models)
+    pub fn wmc(&self, f: u64, weights: JsValue) -> Result<f64, JsValue> {
+        let params = parse_weights(weights, self.num_vars())?;
+        Ok(wmc(
+            self.handles.get(f)?,
+            bdd_node,
+            &params,
+            self.num_vars(),
+        ))
+    }
+
+    /// up to `limit` (by default, all) satisfying assignments of `f`, as
+    /// arrays of booleans indexed by label
+    pub fn models(&self, f: u64, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let f = self.handles.get(f)?;
+        let models = models(
+            self.builder(),
+            f,
+            self.num_vars(),
+            limit.unwrap_or(usize::MAX),
+        );
+        Ok(serde_wasm_bindgen::to_value(&models)?)
+    }
+
+    /// draws the diagrams `roots` (see `bdd_graph`)
+    pub fn graph(&self, roots: &[u64], format: JsValue, names: JsValue) -> Result<String, JsValue> {
+        let format: GraphFormat = serde_wasm_bindgen::from_value(format)?;
+        let roots = roots
+            .iter()
+            .map(|r| self.handles.get(*r))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(bdd_to_graph(&roots, &parse_names(names)?, format))
+    }
+
+    pub fn to_json(&self, f: u64) -> Result<String, JsValue> {
+        let ser = BDDSerializer::from_bdd(self.handles.get(f)?);
+        Ok(serde_json::to_string(&ser).unwrap())
+    }
+}
+
+type SddBuilderImpl = CompressionSddBuilder<'static>;
+
+/// An SDD builder that lives across calls; see [`BddSession`]
+#[wasm_bindgen]
+pub struct SddSession {
+    // the pointers borrow from `builder`, so they must be dropped first
+    handles: Handles<SddPtr<'static>>,
+    builder: Box<SddBuilderImpl>,
+}
+
+impl SddSession {
+    fn from_vtree(vtree: VTree) -> SddSession {
+        SddSession {
+            handles: Handles::new(),
+            builder: Box::new(CompressionSddBuilder::new(vtree)),
+        }
+    }
+
+    // sound because the only pointers into the builder live in `handles`,
+    // which is dropped before it
+    fn builder(&self) -> &'static SddBuilderImpl {
+        unsafe { &*(self.builder.as_ref() as *const SddBuilderImpl) }
+    }
+
+    fn vtree(&self) -> Ref<'_, VTree> {
+        Ref::map(self.builder.vtree_manager(), |m| m.vtree_root())
+    }
+
+    fn apply(
+        &mut self,
+        a: u64,
+        b: u64,
+        op: impl FnOnce(&'static SddBuilderImpl, SddPtr<'static>, SddPtr<'static>) -> SddPtr<'static>,
+    ) -> Result<u64, JsValue> {
+        let (a, b) = (self.handles.get(a)?, self.handles.get(b)?);
+        let ptr = op(self.builder(), a, b);
+        Ok(self.handles.insert(ptr))
+    }
+}
+
+#[wasm_bindgen]
+impl SddSession {
+    /// a session over `num_vars` variables whose vtree is `LeftLinear`,
+    /// `RightLinear` or `{ EvenSplit: n }`; the other vtree types are built
+    /// from a CNF, see `from_cnf`
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_vars: usize, vtree_type_input: JsValue) -> Result<SddSession, JsValue> {
+        if num_vars == 0 {
+            return Err(JsValue::from_str("a vtree needs at least one variable"));
+        }
+        let order: Vec<VarLabel> = (0..num_vars).map(VarLabel::new_usize).collect();
+        let vtree = match serde_wasm_bindgen::from_value(vtree_type_input)? {
+            VTreeType::LeftLinear => VTree::left_linear(&order),
+            VTreeType::RightLinear => VTree::right_linear(&order),
+            VTreeType::EvenSplit(num) => VTree::even_split(&order, num),
+            _ => return Err(JsValue::from_str("this vtree type needs a CNF")),
+        };
+        Ok(SddSession::from_vtree(vtree))
+    }
+
+    /// a session whose vtree is built for the DIMACS CNF `cnf_input`, which
+    /// can then be compiled with `compile_cnf`
+    pub fn from_cnf(cnf_input: String, vtree_type_input: JsValue) -> Result<SddSession, JsValue> {
+        let cnf = parse_cnf(&cnf_input)?;
+        if cnf.num_vars() == 0 {
+            return Err(JsValue::from_str("a vtree needs at least one variable"));
+        }
+        let vtree_type: VTreeType = serde_wasm_bindgen::from_value(vtree_type_input)?;
+        Ok(SddSession::from_vtree(build_vtree(&cnf, vtree_type)))
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.vtree().num_vars()
+    }
+
+    /// gives back a handle returned by this session (see
+    /// `BddSession.release`)
+    pub fn release(&mut self, f: u64) -> Result<(), JsValue> {
+        self.handles.release(f)
+    }
+
+    pub fn var(&mut self, label: u64, polarity: bool) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().var(label, polarity);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn constant(&mut self, value: bool) -> u64 {
+        let ptr = if value {
+            self.builder.true_ptr()
+        } else {
+            self.builder.false_ptr()
+        };
+        self.handles.insert(ptr)
+    }
+
+    pub fn and(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.and(a, b))
+    }
+
+    pub fn or(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.or(a, b))
+    }
+
+    pub fn iff(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.iff(a, b))
+    }
+
+    pub fn xor(&mut self, a: u64, b: u64) -> Result<u64, JsValue> {
+        self.apply(a, b, |builder, a, b| builder.xor(a, b))
+    }
+
+    pub fn not(&mut self, f: u64) -> Result<u64, JsValue> {
+        let ptr = self.builder().negate(self.handles.get(f)?);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn ite(&mut self, f: u64, g: u64, h: u64) -> Result<u64, JsValue> {
+        let (f, g, h) = (
+            self.handles.get(f)?,
+            self.handles.get(g)?,
+            self.handles.get(h)?,
+        );
+        let ptr = self.builder().ite(f, g, h);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn condition(&mut self, f: u64, label: u64, value: bool) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().condition(self.handles.get(f)?, label, value);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn exists(&mut self, f: u64, label: u64) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().exists(self.handles.get(f)?, label);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn forall(&mut self, f: u64, label: u64) -> Result<u64, JsValue> {
+        let label = check_label(label, self.num_vars())?;
+        let ptr = self.builder().forall(self.handles.get(f)?, label);
+        Ok(self.handles.insert(ptr))
+    }
+
+    /// compiles a DIMACS CNF over (at most) this session's variables
+    pub fn compile_cnf(&mut self, cnf_input: String) -> Result<u64, JsValue> {
+        let cnf = parse_cnf(&cnf_input)?;
+        check_label(cnf.num_vars().saturating_sub(1) as u64, self.num_vars())?;
+        let ptr = self.builder().compile_cnf(&cnf);
+        Ok(self.handles.insert(ptr))
+    }
+
+    pub fn is_true(&self, f: u64) -> Result<bool, JsValue> {
+        Ok(self.handles.get(f)?.is_true())
+    }
+
+    pub fn is_false(&self, f: u64) -> Result<bool, JsValue> {
+        Ok(self.handles.get(f)?.is_false())
+    }
+
+    pub fn count_nodes(&self, f: u64) -> Result<usize, JsValue> {
+        Ok(self.handles.get(f)?.count_nodes())
+    }
+
+    /// the weighted model count of `f` (see `BddSession.wmc`)
+    pub fn wmc(&self, f: u64, weights: JsValue) -> Result<f64, JsValue> {
+        let params = parse_weights(weights, self.num_vars())?;
+        Ok(wmc(
+            self.handles.get(f)?,
+            sdd_node,
+            &params,
+            self.num_vars(),
+        ))
+    }
+
+    /// up to `limit` (by default, all) satisfying assignments of `f` (see
+    /// `BddSession.models`)
+    pub fn models(&self, f: u64, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let f = self.handles.get(f)?;
+        let models = models(
+            self.builder(),
+            f,
+            self.num_vars(),
+            limit.unwrap_or(usize::MAX),
+        );
+        Ok(serde_wasm_bindgen::to_value(&models)?)
+    }
+
+    /// draws the diagrams `roots` (see `sdd_graph`)
+    pub fn graph(&self, roots: &[u64], format: JsValue, names: JsValue) -> Result<String, JsValue> {
+        let format: GraphFormat = serde_wasm_bindgen::from_value(format)?;
+        let roots = roots
+            .iter()
+            .map(|r| self.handles.get(*r))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sdd_to_graph(
+            &roots,
+            &self.vtree(),
+            &parse_names(names)?,
+            format,
+        ))
+    }
+
+    pub fn to_json(&self, f: u64) -> Result<String, JsValue> {
+        let ser = SDDSerializer::from_sdd(self.handles.get(f)?);
+        Ok(serde_json::to_string(&ser).unwrap())
+    }
+}
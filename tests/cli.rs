@@ -0,0 +1,500 @@
+//! Runs the `rsdd` command-line tool on small inputs
+
+use num_bigint::BigUint;
+use rsdd::serialize::{BinaryDiagram, DiagramKind};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+const DIAGRAMS: [&str; 3] = ["bdd", "sdd", "dnnf"];
+
+/// `(1 \/ 2) /\ (-1 \/ 3)`, whose models are `1 3`, `1 2 3`, `-1 2` and
+/// `-1 2 3`
+const CNF: &str = "p cnf 3 2\n1 2 0\n-1 3 0\n";
+
+/// writes `contents` to a file called `name` for this test run
+fn input(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rsdd-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+/// the standard output of `rsdd args`, which must succeed
+fn rsdd(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rsdd"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "rsdd {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn rsdd_json(args: &[&str]) -> Value {
+    serde_json::from_str(&rsdd(args)).unwrap()
+}
+
+/// the `count` that `rsdd count args` prints, in full
+fn count(args: &[&str]) -> String {
+    let output = rsdd(&[&["count"], args].concat());
+    let count = output
+        .trim()
+        .strip_prefix("{\n  \"count\": ")
+        .and_then(|rest| rest.strip_suffix("\n}"))
+        .unwrap_or_else(|| panic!("unexpected output {}", output));
+    count.to_string()
+}
+
+#[test]
+fn count_with_evidence() {
+    let cnf = input("count.cnf", CNF);
+    let cnf = cnf.to_str().unwrap();
+    for diagram in DIAGRAMS {
+        let count = |evidence: &[&str]| count(&[&[cnf, "--diagram", diagram], evidence].concat());
+        assert_eq!(count(&[]), "4");
+        assert_eq!(count(&["-e", "1=true"]), "2");
+        assert_eq!(count(&["-e", "2=false"]), "1");
+        assert_eq!(count(&["-e", "1=true", "-e", "1=false"]), "0");
+    }
+}
+
+#[test]
+fn count_projected() {
+    let cnf = input(
+        "projected.cnf",
+        "p cnf 3 2\nc p show 2 3 0\n1 2 0\n-1 3 0\n",
+    );
+    for diagram in DIAGRAMS {
+        let args = [cnf.to_str().unwrap(), "--input-format", "wdimacs"];
+        assert_eq!(count(&[&args[..], &["--diagram", diagram]].concat()), "3");
+    }
+}
+
+#[test]
+fn count_is_exact_beyond_floats() {
+    // 3 * 2^58 is not a multiple of the spacing of doubles near it
+    let cnf = input("sixty.cnf", "p cnf 60 1\n1 2 0\n");
+    let expected = (BigUint::from(3u8) << 58u32).to_string();
+    assert_eq!(count(&[cnf.to_str().unwrap()]), expected);
+
+    // 3 * 2^1098 overflows a double
+    let cnf = input("wide.cnf", "p cnf 1100 1\n1 2 0\n");
+    let expected = (BigUint::from(3u8) << 1098u32).to_string();
+    for diagram in DIAGRAMS {
+        assert_eq!(
+            count(&[cnf.to_str().unwrap(), "--diagram", diagram]),
+            expected
+        );
+    }
+}
+
+#[test]
+fn weighted_queries() {
+    let cnf = input("weighted.cnf", CNF);
+    let weights = input("weights.json", r#"{"1": {"low": 0.4, "high": 0.6}}"#);
+    for diagram in DIAGRAMS {
+        let args = [
+            cnf.to_str().unwrap(),
+            "--diagram",
+            diagram,
+            "-w",
+            weights.to_str().unwrap(),
+        ];
+        let wmc = rsdd_json(&[&["wmc"], &args[..]].concat());
+        assert!((wmc["wmc"].as_f64().unwrap() - 2.0).abs() < 1e-9);
+
+        let marginals = rsdd_json(&[&["marginals"], &args[..], &["-e", "2=false"]].concat());
+        assert_eq!(
+            marginals["marginals"]["1"],
+            json!({"false": 0.0, "true": 1.0})
+        );
+        assert_eq!(
+            marginals["marginals"]["3"],
+            json!({"false": 0.0, "true": 1.0})
+        );
+
+        let mpe = rsdd_json(&[&["mpe"], &args[..], &["-e", "3=false"]].concat());
+        assert_eq!(
+            mpe["assignment"],
+            json!({"1": "false", "2": "true", "3": "false"})
+        );
+
+        let map = rsdd_json(&[&["map"], &args[..], &["--vars", "1"]].concat());
+        assert_eq!(map["assignment"], json!({"1": "true"}));
+        assert!((map["wmc"].as_f64().unwrap() - 1.2).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn network_marginals() {
+    let network = concat!(env!("CARGO_MANIFEST_DIR"), "/bayesian_networks/cancer.json");
+    let marginals = rsdd_json(&["marginals", network, "-e", "Xray=positive"]);
+    let cancer = marginals["marginals"]["Cancer"]["True"].as_f64().unwrap();
+    assert!((cancer - 0.050288).abs() < 1e-6);
+    assert!((marginals["wmc"].as_f64().unwrap() - 0.208141).abs() < 1e-6);
+}
+
+#[test]
+fn compile_and_stats() {
+    let cnf = input("compile.cnf", CNF);
+    for diagram in DIAGRAMS {
+        let compiled = rsdd_json(&["compile", cnf.to_str().unwrap(), "--diagram", diagram]);
+        assert_eq!(compiled["diagram"], diagram);
+        assert_eq!(compiled["num_vars"], 3);
+        assert!(compiled["serialized"].is_object());
+    }
+
+    let stats = rsdd_json(&["stats", cnf.to_str().unwrap()]);
+    assert_eq!(stats["num_vars"], 3);
+    assert_eq!(stats["num_clauses"], 2);
+    assert_eq!(stats["induced_width"], 1);
+}
+
+#[test]
+fn bad_input_is_an_error() {
+    let cnf = input("bad.cnf", CNF);
+    for args in [
+        vec!["count", "does-not-exist.cnf"],
+        vec!["count", cnf.to_str().unwrap(), "-e", "4=true"],
+        vec!["count", cnf.to_str().unwrap(), "-e", "1=maybe"],
+        vec!["map", cnf.to_str().unwrap(), "--vars", "nope"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_rsdd"))
+            .args(&args)
+            .output()
+            .unwrap();
+        assert!(
+            !output.status.success(),
+            "rsdd {} succeeded",
+            args.join(" ")
+        );
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+    }
+}
+
+#[test]
+fn plan_labels_are_checked() {
+    let cnf = input("plan.cnf", "p cnf 2 1\n1 2 0\n");
+    for (name, plan) in [
+        ("literal.sexpr", "(And (Literal 0 true) (Literal 7 true))"),
+        ("exists.sexpr", "(Exists (0 5) (Literal 0 true))"),
+        ("condition.sexpr", "(Condition 2 true (Literal 0 true))"),
+        (
+            "compose.sexpr",
+            "(Compose 3 (Literal 0 true) (Literal 1 true))",
+        ),
+        ("literal.json", r#"{"Literal": [9, false]}"#),
+    ] {
+        let plan = input(name, plan);
+        let output = Command::new(env!("CARGO_BIN_EXE_bottomup_cnf_to_bdd"))
+            .args([
+                "-f",
+                cnf.to_str().unwrap(),
+                "--plan",
+                plan.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success(), "plan {} succeeded", name);
+        assert!(stderr.starts_with("Error parsing plan"), "{}", stderr);
+    }
+
+    let plan = input("ok.sexpr", "(And (Literal 0 true) (Literal 1 true))");
+    let output = Command::new(env!("CARGO_BIN_EXE_bottomup_cnf_to_bdd"))
+        .args([
+            "-f",
+            cnf.to_str().unwrap(),
+            "--plan",
+            plan.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+/// the output of `rsdd export` on `cnf`, or its error
+fn export(cnf: &Path, diagram: &str, format: &str, evidence: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_rsdd"))
+        .args(
+            [
+                &[
+                    "export",
+                    cnf.to_str().unwrap(),
+                    "--diagram",
+                    diagram,
+                    "--format",
+                    format,
+                ],
+                evidence,
+            ]
+            .concat(),
+        )
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(stderr)
+    }
+}
+
+/// whether the c2d d-DNNF `nnf` holds under `assignment`, indexed by DIMACS
+/// variable
+fn nnf_holds(nnf: &str, assignment: &[bool]) -> bool {
+    let mut values: Vec<bool> = Vec::new();
+    for line in nnf.lines().skip(1) {
+        let tokens: Vec<i64> = line[2..]
+            .split_whitespace()
+            .map(|t| t.parse().unwrap())
+            .collect();
+        let children = |skip: usize| tokens[skip..].iter().map(|c| values[*c as usize]);
+        let value = match &line[..1] {
+            "L" => assignment[tokens[0].unsigned_abs() as usize] == (tokens[0] > 0),
+            "A" => children(1).all(|v| v),
+            "O" => children(2).any(|v| v),
+            _ => panic!("unexpected line {}", line),
+        };
+        values.push(value);
+    }
+    *values.last().unwrap()
+}
+
+#[test]
+fn export_formats() {
+    let cnf = input("export.cnf", CNF);
+    let unsupported = [
+        ("bdd", "ucla"),
+        ("sdd", "dddmp"),
+        ("dnnf", "dddmp"),
+        ("dnnf", "binary"),
+        ("dnnf", "ucla"),
+    ];
+    for diagram in DIAGRAMS {
+        for format in [
+            "json", "dddmp", "nnf", "d4", "binary", "dot", "mermaid", "ucla",
+        ] {
+            let output = export(&cnf, diagram, format, &[]);
+            if unsupported.contains(&(diagram, format)) {
+                let error = output.unwrap_err();
+                assert!(error.contains("cannot export"), "{}", error);
+                continue;
+            }
+            let output = output.unwrap_or_else(|e| panic!("{} {}: {}", diagram, format, e));
+            if format == "binary" {
+                let binary = BinaryDiagram::new(&output).unwrap();
+                let kind = if diagram == "bdd" {
+                    DiagramKind::Bdd
+                } else {
+                    DiagramKind::Sdd
+                };
+                assert_eq!(binary.kind(), kind);
+                assert_eq!(binary.num_vars(), 3);
+                assert_eq!(binary.roots().len(), 1);
+                continue;
+            }
+            let text = String::from_utf8(output).unwrap();
+            let header = match format {
+                "json" => "{\"nodes\":",
+                "dddmp" => ".ver DDDMP-2.0",
+                "nnf" => "nnf ",
+                "d4" => "o 1 0",
+                "dot" => "digraph {",
+                "mermaid" => "flowchart TD",
+                _ => "c ids of sdd nodes",
+            };
+            assert!(text.starts_with(header), "{} {}: {}", diagram, format, text);
+        }
+    }
+}
+
+#[test]
+fn export_nnf_models() {
+    let cnf = input("models.cnf", CNF);
+    let models = |a: &[bool]| (a[1] || a[2]) && (!a[1] || a[3]);
+    for diagram in DIAGRAMS {
+        let nnf = |evidence: &[&str]| {
+            String::from_utf8(export(&cnf, diagram, "nnf", evidence).unwrap()).unwrap()
+        };
+        let whole = nnf(&[]);
+        let conditioned = nnf(&["-e", "1=false"]);
+        assert!(whole.lines().next().unwrap().ends_with(" 3"), "{}", whole);
+        for bits in 0..8 {
+            let assignment: Vec<bool> = (0..4)
+                .map(|v| v > 0 && bits & (1 << (v - 1)) != 0)
+                .collect();
+            assert_eq!(nnf_holds(&whole, &assignment), models(&assignment));
+            // the conditioned diagram no longer depends on variable 1
+            let mut fixed = assignment.clone();
+            fixed[1] = false;
+            assert_eq!(nnf_holds(&conditioned, &assignment), models(&fixed));
+        }
+    }
+}
+
+/// the responses of `rsdd serve args` to `requests`, one per line
+fn serve(args: &[&str], requests: &[Value]) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsdd"))
+        .args([&["serve"], args].concat())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    for request in requests {
+        writeln!(stdin, "{}", request).unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn request(id: usize, method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+}
+
+#[test]
+fn serve_network_queries() {
+    let network = concat!(env!("CARGO_MANIFEST_DIR"), "/bayesian_networks/cancer.json");
+    let responses = serve(
+        &[network],
+        &[
+            request(1, "marginals", json!({"evidence": {"Xray": "positive"}})),
+            request(2, "condition", json!({"evidence": {"Smoker": "True"}})),
+            request(3, "wmc", json!({})),
+            // weights are keyed by indicator, and replace the indicator's
+            // unit weight
+            request(
+                4,
+                "wmc",
+                json!({"weights": {"Smoker=True": {"low": 1.0, "high": 0.5}}}),
+            ),
+            request(5, "map", json!({"vars": ["Cancer"]})),
+            // a notification, which gets no response
+            json!({"jsonrpc": "2.0", "method": "wmc"}),
+        ],
+    );
+    assert_eq!(responses.len(), 5);
+    let results: Vec<&Value> = responses.iter().map(|r| &r["result"]).collect();
+
+    let cancer = results[0]["marginals"]["Cancer"]["True"].as_f64().unwrap();
+    assert!((cancer - 0.050288).abs() < 1e-6);
+    assert_eq!(results[1]["partial_model"], json!({"Smoker=True": true}));
+    assert!((results[1]["wmc"].as_f64().unwrap() - 0.3).abs() < 1e-9);
+    assert_eq!(results[2], results[1]);
+    assert!((results[3]["wmc"].as_f64().unwrap() - 0.15).abs() < 1e-9);
+    // every model with `Smoker=True`, whatever its weight
+    assert_eq!(results[3]["mc"], results[2]["mc"]);
+    assert_eq!(results[4]["assignment"], json!({"Cancer": "False"}));
+}
+
+#[test]
+fn serve_errors() {
+    let network = concat!(env!("CARGO_MANIFEST_DIR"), "/bayesian_networks/cancer.json");
+    let responses = serve(
+        &[network],
+        &[
+            request(
+                1,
+                "wmc",
+                json!({"weights": {"Smoker": {"low": 1.0, "high": 0.5}}}),
+            ),
+            request(2, "count", json!({})),
+            request(3, "wmc", json!({"evidence": {"Smoker": "Maybe"}})),
+            json!({"jsonrpc": "1.0", "id": 4, "method": "wmc"}),
+        ],
+    );
+    let codes: Vec<&Value> = responses.iter().map(|r| &r["error"]["code"]).collect();
+    assert_eq!(codes, [-32602, -32601, -32602, -32600]);
+    // a whole network variable cannot be weighed, but its indicators can
+    let message = responses[0]["error"]["message"].as_str().unwrap();
+    assert!(message.contains("`Smoker=True`"), "{}", message);
+}
+
+#[test]
+fn serve_counts_exactly() {
+    let cnf = input("serve-wide.cnf", "p cnf 1100 1\n1 2 0\n");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsdd"))
+        .args(["serve", cnf.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(
+        stdin,
+        "{}",
+        request(1, "wmc", json!({"evidence": {"1": "false"}}))
+    )
+    .unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    // the count is read as text, as parsing it would round it to a double
+    let response = String::from_utf8(output.stdout).unwrap();
+    let expected = (BigUint::from(1u8) << 1098u32).to_string();
+    assert!(
+        response.contains(&format!("\"mc\":{}}}", expected)),
+        "{}",
+        response
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn serve_on_a_socket() {
+    use std::{io::BufRead, io::BufReader, os::unix::net::UnixStream, thread, time::Duration};
+
+    let cnf = input("socket.cnf", CNF);
+    let socket = cnf.with_extension("sock");
+    let _ = fs::remove_file(&socket);
+    let mut server = Command::new(env!("CARGO_BIN_EXE_rsdd"))
+        .args(["serve", cnf.to_str().unwrap(), "--socket"])
+        .arg(&socket)
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // the server answers one connection at a time, so the probe that
+    // finds it listening must close straight away
+    let listening = (0..100).any(|_| {
+        thread::sleep(Duration::from_millis(50));
+        UnixStream::connect(&socket).is_ok()
+    });
+    assert!(listening, "the server never listened");
+
+    // connections are independent: evidence set by one does not carry over
+    for _ in 0..2 {
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut ask = |request: Value| {
+            writeln!(stream, "{}", request).unwrap();
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            serde_json::from_str::<Value>(&line).unwrap()
+        };
+        assert_eq!(ask(request(1, "wmc", json!({})))["result"]["mc"], 4);
+        let conditioned = ask(request(2, "condition", json!({"evidence": {"1": "true"}})));
+        assert_eq!(conditioned["result"]["mc"], 2);
+        assert_eq!(ask(request(3, "wmc", json!({})))["result"]["mc"], 2);
+    }
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+    let _ = fs::remove_file(&socket);
+}
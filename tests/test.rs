@@ -188,20 +188,62 @@ p cnf 2 1
 
 fn canonical_forms() -> Vec<(Cnf, Cnf)> {
     vec![
-        (Cnf::from_dimacs(C1_A), Cnf::from_dimacs(C1_B)),
-        (Cnf::from_dimacs(C2_A), Cnf::from_dimacs(C2_B)),
-        (Cnf::from_dimacs(C3_A), Cnf::from_dimacs(C3_B)),
-        (Cnf::from_dimacs(C4_A), Cnf::from_dimacs(C4_B)),
-        (Cnf::from_dimacs(C5_A), Cnf::from_dimacs(C5_B)),
-        (Cnf::from_dimacs(C6_A), Cnf::from_dimacs(C6_B)),
-        (Cnf::from_dimacs(C7_A), Cnf::from_dimacs(C7_B)),
-        (Cnf::from_dimacs(C8_A), Cnf::from_dimacs(C8_B)),
-        (Cnf::from_dimacs(C9_A), Cnf::from_dimacs(C9_B)),
-        (Cnf::from_dimacs(C10_A), Cnf::from_dimacs(C10_B)),
-        (Cnf::from_dimacs(C11_A), Cnf::from_dimacs(C11_B)),
-        (Cnf::from_dimacs(C12_A), Cnf::from_dimacs(C12_B)),
-        (Cnf::from_dimacs(C13_A), Cnf::from_dimacs(C13_B)),
-        (Cnf::from_dimacs(C14_A), Cnf::from_dimacs(C14_B)),
+        (
+            Cnf::from_dimacs(C1_A).unwrap(),
+            Cnf::from_dimacs(C1_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C2_A).unwrap(),
+            Cnf::from_dimacs(C2_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C3_A).unwrap(),
+            Cnf::from_dimacs(C3_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C4_A).unwrap(),
+            Cnf::from_dimacs(C4_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C5_A).unwrap(),
+            Cnf::from_dimacs(C5_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C6_A).unwrap(),
+            Cnf::from_dimacs(C6_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C7_A).unwrap(),
+            Cnf::from_dimacs(C7_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C8_A).unwrap(),
+            Cnf::from_dimacs(C8_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C9_A).unwrap(),
+            Cnf::from_dimacs(C9_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C10_A).unwrap(),
+            Cnf::from_dimacs(C10_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C11_A).unwrap(),
+            Cnf::from_dimacs(C11_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C12_A).unwrap(),
+            Cnf::from_dimacs(C12_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C13_A).unwrap(),
+            Cnf::from_dimacs(C13_B).unwrap(),
+        ),
+        (
+            Cnf::from_dimacs(C14_A).unwrap(),
+            Cnf::from_dimacs(C14_B).unwrap(),
+        ),
     ]
 }
 
@@ -280,19 +322,30 @@ mod test_bdd_builder {
     use rsdd::builder::decision_nnf::StandardDecisionNNFBuilder;
     use rsdd::builder::BottomUpBuilder;
     use rsdd::constants::primes;
+    use rsdd::plan::{BottomUpPlan, DagPlan};
     use rsdd::repr::BddPtr;
+    use rsdd::repr::Circuit;
     use rsdd::repr::Cnf;
     use rsdd::repr::DTree;
-    use rsdd::repr::PartialModel;
+    use rsdd::repr::Literal;
+    use rsdd::repr::NnfCircuit;
+    use rsdd::repr::OrderHeuristic;
+    use rsdd::repr::PreprocessConfig;
+    use rsdd::repr::SATSolver;
     use rsdd::repr::VTree;
     use rsdd::repr::VarLabel;
     use rsdd::repr::VarOrder;
+    use rsdd::repr::VarSet;
     use rsdd::repr::WmcParams;
     use rsdd::repr::{create_semantic_hash_map, DDNNFPtr};
+    use rsdd::repr::{PartialModel, BATCH_WIDTH};
+    use rsdd::serialize::{bdd_from_dddmp, bdd_to_dddmp, bdd_to_nnf, BDDSerializer, NnfFormat};
+    use rsdd::util::hypergraph::PartitionConfig;
     use rsdd::util::semirings::ExpectedUtility;
     use rsdd::util::semirings::FiniteField;
     use rsdd::util::semirings::RealSemiring;
     use rsdd::util::semirings::Semiring;
+    use rsdd::util::semirings::VecSemiring;
     use std::collections::HashMap;
     use std::collections::HashSet;
     use std::iter::FromIterator;
@@ -412,6 +465,108 @@ mod test_bdd_builder {
         }
     }
 
+    quickcheck! {
+        /// test that preprocessing preserves the WMC of the projected variables
+        fn preprocess_wmc_eq(c1: Cnf, num_projected: usize) -> TestResult {
+            if c1.num_vars() == 0 { return TestResult::discard() }
+            let n = c1.num_vars();
+            let mut projected = VarSet::new();
+            for v in 0..(num_projected % (n + 1)) {
+                projected.insert(VarLabel::new_usize(v));
+            }
+
+            let weight = create_semantic_hash_map::<{primes::U32_SMALL}>(n);
+            // compiles a CNF, existentially quantifies the unprojected
+            // variables, and computes the smoothed WMC by enumeration
+            let projected_wmc = |cnf: &Cnf, params: &WmcParams<FiniteField<{primes::U32_SMALL}>>| {
+                let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(n);
+                let bdd = (0..n)
+                    .map(VarLabel::new_usize)
+                    .filter(|v| !projected.contains(*v))
+                    .fold(builder.compile_cnf(cnf), |acc, v| builder.exists(acc, v));
+                if bdd.is_false() {
+                    return None;
+                }
+                let wmc = (0..(1 << n)).fold(FiniteField::zero(), |acc, i: usize| {
+                    let assgn: Vec<bool> = (0..n).map(|v| (i >> v) & 1 == 1).collect();
+                    if !bdd.evaluate(&assgn) {
+                        return acc;
+                    }
+                    let lits: Vec<Literal> = assgn
+                        .iter()
+                        .enumerate()
+                        .map(|(v, b)| Literal::new(VarLabel::new_usize(v), *b))
+                        .collect();
+                    acc + params.assignment_weight(&lits)
+                });
+                Some(wmc)
+            };
+
+            let expected = projected_wmc(&c1, &weight);
+            match c1.preprocess(&projected, &PreprocessConfig::default()) {
+                None => TestResult::from_bool(expected.is_none()),
+                Some(simplified) => {
+                    let (params, scale) = simplified.map().map_weights(&weight);
+                    let actual = projected_wmc(simplified.cnf(), &params);
+                    TestResult::from_bool(expected == actual.map(|wmc| scale * wmc))
+                }
+            }
+        }
+    }
+
+    quickcheck! {
+        /// test that models of a preprocessed CNF extend to models of the original
+        fn preprocess_extend_model(c1: Cnf) -> bool {
+            let sat = SATSolver::new(c1.clone()).is_some_and(|mut s| s.solve());
+            let simplified = match c1.simplify() {
+                None => return !sat,
+                Some(s) => s,
+            };
+            let mut solver = match SATSolver::new(simplified.cnf().clone()) {
+                None => return !sat,
+                Some(s) => s,
+            };
+            if !solver.solve() {
+                return !sat;
+            }
+            let model = simplified.map().extend_model(solver.model().unwrap());
+            c1.is_sat_partial(&model)
+        }
+    }
+
+    quickcheck! {
+        /// test that projected counts agree between the BDD, SDD, top-down and
+        /// exhaustive paths
+        fn projected_wmc_eq(c1: Cnf, vtree: VTree, num_unprojected: usize) -> TestResult {
+            if c1.num_vars() == 0 { return TestResult::discard() }
+            let n = c1.num_vars();
+            // project onto a suffix of the variables, so that the top-down
+            // order must be rearranged
+            let mut projection = VarSet::new();
+            for v in (num_unprojected % (n + 1))..n {
+                projection.insert(VarLabel::new_usize(v));
+            }
+            let weight = create_semantic_hash_map::<{primes::U32_SMALL}>(n);
+            let expected = c1.projected_wmc(&weight, &projection);
+
+            let bdd_builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(n);
+            let bdd = bdd_builder.compile_cnf_projected(&c1, &projection);
+
+            let sdd_builder = super::CompressionSddBuilder::new(vtree);
+            let sdd = sdd_builder.compile_cnf_projected(&c1, &projection);
+
+            let order = VarOrder::linear_order(n).project_first(&projection);
+            let dnnf_builder = StandardDecisionNNFBuilder::new(order);
+            let dnnf = dnnf_builder.compile_cnf_topdown_projected(&c1, &projection);
+
+            TestResult::from_bool(
+                bdd.unsmoothed_wmc(&weight) == expected
+                    && sdd.unsmoothed_wmc(&weight) == expected
+                    && dnnf.unsmoothed_wmc(&weight) == expected,
+            )
+        }
+    }
+
     quickcheck! {
         /// test that an SDD and BDD both have the same semantic hash
         fn sdd_semantic_eq_bdd(c1: Cnf, vtree: VTree) -> bool {
@@ -440,6 +595,252 @@ mod test_bdd_builder {
         }
     }
 
+    quickcheck! {
+        /// test that an SDD compiled with a hypergraph-partitioned vtree, and a
+        /// BDD compiled along a hypergraph-partitioned dtree, agree with a
+        /// linear-order BDD
+        fn sdd_semantic_eq_bdd_hypergraph(c1: Cnf) -> bool {
+            let bdd_builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(c1.num_vars());
+            let vtree = VTree::from_cnf_hypergraph(&c1, &PartitionConfig::default());
+            let dtree = DTree::from_cnf_hypergraph(&c1, &PartitionConfig::default());
+
+            let sdd_builder = super::CompressionSddBuilder::new(vtree);
+            let map : WmcParams<rsdd::util::semirings::FiniteField<{primes::U32_SMALL}>>= create_semantic_hash_map(c1.num_vars());
+            let bdd = bdd_builder.compile_cnf(&c1);
+            let sdd = sdd_builder.compile_cnf(&c1);
+            let plan_bdd = bdd_builder.compile_plan(&BottomUpPlan::from_dtree(&dtree));
+            bdd.semantic_hash(&map) == sdd.semantic_hash(&map) && bdd == plan_bdd
+        }
+    }
+
+    quickcheck! {
+        /// test that BDDs compiled under every ordering heuristic denote the
+        /// same function
+        fn bdd_order_heuristics_eq(c1: Cnf) -> bool {
+            let map : WmcParams<FiniteField<{primes::U32_SMALL}>> = create_semantic_hash_map(c1.num_vars());
+            let linear = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(c1.num_vars());
+            let expected = linear.compile_cnf(&c1).semantic_hash(&map);
+            OrderHeuristic::ALL.iter().all(|(_, heuristic)| {
+                let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new(c1.order_by(*heuristic));
+                builder.compile_cnf(&c1).semantic_hash(&map) == expected
+            })
+        }
+    }
+
+    quickcheck! {
+        /// test that greedy elimination orders are never narrower than the
+        /// treewidth, found by trying every order on small CNFs
+        fn greedy_orders_bound_treewidth(c1: Cnf) -> TestResult {
+            if c1.num_vars() > 6 {
+                return TestResult::discard();
+            }
+            fn orders(prefix: &mut Vec<VarLabel>, n: usize, all: &mut Vec<VarOrder>) {
+                if prefix.len() == n {
+                    all.push(VarOrder::new(prefix));
+                    return;
+                }
+                for v in (0..n).map(VarLabel::new_usize) {
+                    if !prefix.contains(&v) {
+                        prefix.push(v);
+                        orders(prefix, n, all);
+                        prefix.pop();
+                    }
+                }
+            }
+            let mut all = Vec::new();
+            orders(&mut Vec::new(), c1.num_vars(), &mut all);
+            let treewidth = all.iter().map(|o| c1.induced_width(o)).min().unwrap();
+            // every clause is a clique in the interaction graph
+            let max_clause = c1.clauses().iter().map(|c| {
+                c.iter().map(|l| l.label()).collect::<HashSet<_>>().len()
+            }).max().unwrap_or(0);
+
+            let upper = c1.treewidth_upper_bound();
+            TestResult::from_bool(
+                treewidth + 1 >= max_clause
+                    && upper >= treewidth
+                    && c1.induced_width(&c1.min_degree_order()) >= upper
+                    && c1.induced_width(&c1.weighted_min_fill_order()) >= treewidth,
+            )
+        }
+    }
+
+    quickcheck! {
+        /// test that every plan generator compiles to the same BDD as compile_cnf
+        fn bdd_plan_generators_eq(c1: Cnf, projected: Vec<bool>) -> bool {
+            let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(c1.num_vars());
+            let expected = builder.compile_cnf(&c1);
+            let order = c1.min_fill_order();
+
+            let mut projection = VarSet::new();
+            let mut unprojected = VarSet::new();
+            for v in 0..c1.num_vars() {
+                if projected.get(v).copied().unwrap_or(true) {
+                    projection.insert(VarLabel::new_usize(v));
+                } else {
+                    unprojected.insert(VarLabel::new_usize(v));
+                }
+            }
+            let expected_projected = builder.exists_set(expected, &unprojected);
+
+            let plans = [
+                BottomUpPlan::from_cnf_balanced(&c1),
+                BottomUpPlan::from_clusters(&c1, &order, 4),
+                BottomUpPlan::from_bucket_elimination(&c1, &order, None),
+            ];
+            let projected_plan = BottomUpPlan::from_bucket_elimination(&c1, &order, Some(&projection));
+
+            plans.iter().all(|p| builder.compile_plan(p) == expected)
+                && builder.compile_plan(&projected_plan) == expected_projected
+        }
+    }
+
+    quickcheck! {
+        /// test that pushing quantifiers preserves the compiled function, and
+        /// that forall, condition and compose plans compile as specified
+        fn bdd_plan_push_quantifiers_eq(c1: Cnf, c2: Cnf, exists: Vec<bool>, forall: Vec<bool>) -> TestResult {
+            let num_vars = usize::max(c1.num_vars(), c2.num_vars());
+            if num_vars == 0 { return TestResult::discard() }
+            let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(num_vars);
+            let to_set = |bits: &Vec<bool>| {
+                let mut s = VarSet::new();
+                for v in (0..num_vars).filter(|v| bits.get(*v).copied().unwrap_or(false)) {
+                    s.insert(VarLabel::new_usize(v));
+                }
+                s
+            };
+            let (e, a) = (to_set(&exists), to_set(&forall));
+            let v0 = VarLabel::new(0);
+
+            let p1 = BottomUpPlan::from_cnf_balanced(&c1);
+            let p2 = BottomUpPlan::from_dtree(&DTree::from_cnf(&c2, &c2.min_fill_order()));
+            let plan = BottomUpPlan::exists(
+                e.clone(),
+                BottomUpPlan::and(
+                    BottomUpPlan::not(BottomUpPlan::forall(a.clone(), BottomUpPlan::or(p1.clone(), p2.clone()))),
+                    BottomUpPlan::condition(v0, true, BottomUpPlan::compose(v0, p1.clone(), p2.clone())),
+                ),
+            );
+            let compiled = builder.compile_plan(&plan);
+            if builder.compile_plan(&plan.push_quantifiers()) != compiled {
+                return TestResult::failed();
+            }
+
+            let (f1, f2) = (builder.compile_plan(&p1), builder.compile_plan(&p2));
+            let forall_v0 = builder.and(builder.condition(f1, v0, true), builder.condition(f1, v0, false));
+            let mut just_v0 = VarSet::new();
+            just_v0.insert(v0);
+            let composed = builder.compose(f1, v0, f2);
+            TestResult::from_bool(
+                builder.compile_plan(&BottomUpPlan::forall(just_v0, p1.clone())) == forall_v0
+                    && builder.compile_plan(&BottomUpPlan::compose(v0, p1, p2)) == composed,
+            )
+        }
+    }
+
+    quickcheck! {
+        /// test that compiling a DAG plan agrees with compiling its tree form
+        fn bdd_dag_plan_eq(c1: Cnf, c2: Cnf) -> bool {
+            let num_vars = usize::max(c1.num_vars(), c2.num_vars());
+            let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(num_vars);
+            let p1 = BottomUpPlan::from_cnf_balanced(&c1);
+            let p2 = BottomUpPlan::from_dtree(&DTree::from_cnf(&c2, &c2.min_fill_order()));
+            // p1 and p2 occur twice each
+            let plan = BottomUpPlan::and(
+                BottomUpPlan::or(p1.clone(), p2.clone()),
+                BottomUpPlan::iff(p1.clone(), BottomUpPlan::not(p2)),
+            );
+
+            let mut dag = DagPlan::new();
+            let root = dag.add_plan(&plan);
+            let shared = dag.add_plan(&p1);
+            let compiled = builder.compile_dag_plan(&dag, &[root, shared]);
+            compiled == vec![builder.compile_plan(&plan), builder.compile_plan(&p1)]
+                && builder.compile_plan(&dag.to_plan(root)) == compiled[0]
+        }
+    }
+
+    /// encodes a CNF as a BLIF netlist with one gate per clause, each given
+    /// by the off-set of its falsifying assignment
+    fn cnf_to_blif(cnf: &Cnf) -> String {
+        let names = |vars: &mut dyn Iterator<Item = String>| vars.collect::<Vec<_>>().join(" ");
+        let mut blif = format!(
+            ".model cnf\n.inputs {}\n.outputs f\n",
+            names(&mut (0..cnf.num_vars()).map(|v| format!("x{}", v)))
+        );
+        for (i, clause) in cnf.clauses().iter().enumerate() {
+            let vars = names(&mut clause.iter().map(|l| format!("x{}", l.label().value())));
+            blif.push_str(&format!(".names {} c{}\n", vars, i));
+            if !clause.is_empty() {
+                let falsifying: String = clause
+                    .iter()
+                    .map(|l| if l.polarity() { '0' } else { '1' })
+                    .collect();
+                blif.push_str(&format!("{} 0\n", falsifying));
+            }
+        }
+        let clauses = names(&mut (0..cnf.clauses().len()).map(|i| format!("c{}", i)));
+        blif.push_str(&format!(
+            ".names {} f\n{} 1\n.end\n",
+            clauses,
+            "1".repeat(cnf.clauses().len())
+        ));
+        blif
+    }
+
+    quickcheck! {
+        /// test that a circuit parsed from BLIF compiles to the same BDD and
+        /// SDD as the CNF it encodes
+        fn circuit_blif_eq_cnf(c1: Cnf) -> bool {
+            let circuit = Circuit::from_blif(&cnf_to_blif(&c1)).unwrap();
+            let order = circuit.fanin_order();
+            let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new(order.clone());
+            let bdd = builder.compile_dag_plan(circuit.plan(), &circuit.output_nodes())[0];
+
+            let vars: Vec<VarLabel> = order.in_order_iter().collect();
+            let sdd_builder = super::CompressionSddBuilder::new(VTree::right_linear(&vars));
+            let sdd = sdd_builder.compile_dag_plan(circuit.plan(), &circuit.output_nodes())[0];
+            bdd == builder.compile_cnf(&c1) && sdd == sdd_builder.compile_cnf(&c1)
+        }
+    }
+
+    quickcheck! {
+        /// test that BDDs read back from DDDMP into a builder with another
+        /// order are canonical there, and that their c2d d-DNNFs have the
+        /// same weighted model count
+        fn bdd_dddmp_nnf_round_trip(c1: Cnf) -> bool {
+            let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(c1.num_vars());
+            let bdd = builder.compile_cnf(&c1);
+            let text = bdd_to_dddmp(&[bdd, builder.negate(bdd)], builder.order());
+            let reversed_order: Vec<VarLabel> = (0..c1.num_vars()).rev().map(VarLabel::new_usize).collect();
+            let reversed = super::RobddBuilder::<AllIteTable<BddPtr>>::new(VarOrder::new(&reversed_order));
+            let expected = reversed.compile_cnf(&c1);
+            let roots = bdd_from_dddmp(&text, &reversed).unwrap();
+
+            let weights = WmcParams::new(HashMap::from_iter(
+                (0..c1.num_vars()).map(|x| (VarLabel::new_usize(x), (RealSemiring(0.3), RealSemiring(0.7))))));
+            let circuit = NnfCircuit::from_c2d(&bdd_to_nnf(bdd, c1.num_vars(), NnfFormat::C2d)).unwrap();
+            let nnf_wmc = circuit.root().unsmoothed_wmc(&weights);
+            roots == vec![expected, reversed.negate(expected)]
+                && (nnf_wmc.0 - bdd.unsmoothed_wmc(&weights).0).abs() < 0.0001
+        }
+    }
+
+    quickcheck! {
+        /// test that BDDs serialized to JSON are rebuilt in a fresh builder
+        /// as the same functions
+        fn bdd_serializer_round_trip(c1: Cnf, c2: Cnf) -> bool {
+            let builder = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(16);
+            let roots = vec![builder.compile_cnf(&c1), builder.negate(builder.compile_cnf(&c2))];
+            let json = serde_json::to_string(&BDDSerializer::from_bdds(&roots)).unwrap();
+
+            let other = super::RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(16);
+            let ser: BDDSerializer = serde_json::from_str(&json).unwrap();
+            ser.into_builder(&other)
+                == vec![other.compile_cnf(&c1), other.negate(other.compile_cnf(&c2))]
+        }
+    }
+
     quickcheck! {
         fn wmc_bdd_dnnf_eq(c1: Cnf) -> TestResult {
             // constrain the size
@@ -697,6 +1098,50 @@ mod test_bdd_builder {
             bdd.semantic_hash( &map) == smoothed.semantic_hash( &map)
         }
     }
+
+    quickcheck! {
+        /// test that batched partial-model counts agree with conditioning on
+        /// each model and counting
+        fn batch_wmc_eq_condition_model(cnf: Cnf, assignments: Vec<Vec<Option<bool>>>) -> TestResult {
+            if cnf.num_vars() == 0 || cnf.num_vars() > 12 { return TestResult::discard() }
+            let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
+            let bdd = builder.compile_cnf(&cnf);
+            let map = create_semantic_hash_map::<{primes::U32_SMALL}>(cnf.num_vars());
+            let models: Vec<PartialModel> = assignments
+                .iter()
+                .map(|a| {
+                    let a: Vec<Option<bool>> = (0..cnf.num_vars()).map(|i| a.get(i).copied().flatten()).collect();
+                    PartialModel::from_assignments(&a)
+                })
+                .collect();
+
+            let expected: Vec<_> = models
+                .iter()
+                .map(|m| builder.condition_model(bdd, m).unsmoothed_wmc(&map))
+                .collect();
+            TestResult::from_bool(
+                bdd.batch_wmc::<_, BATCH_WIDTH>(&map, &models) == expected
+                    && bdd.batch_wmc::<_, 3>(&map, &models) == expected,
+            )
+        }
+    }
+
+    quickcheck! {
+        /// test that counting over stacked weightings matches counting under
+        /// each one
+        fn vec_semiring_wmc_eq_lanes(cnf: Cnf) -> bool {
+            let builder = RobddBuilder::<AllIteTable<BddPtr>>::new_with_linear_order(cnf.num_vars());
+            let bdd = builder.compile_cnf(&cnf);
+            let lanes: [WmcParams<FiniteField<{primes::U32_SMALL}>>; 3] = std::array::from_fn(|lane| {
+                WmcParams::new(HashMap::from_iter((0..cnf.num_vars()).map(|v| {
+                    let (v, lane) = (v as u128, lane as u128);
+                    (VarLabel::new_usize(v as usize), (FiniteField::new(v + lane + 2), FiniteField::new(3 * v + 5 * lane + 1)))
+                })))
+            });
+            let VecSemiring(counts) = bdd.unsmoothed_wmc(&WmcParams::from_lanes(&lanes));
+            counts.iter().zip(lanes.iter()).all(|(count, params)| *count == bdd.unsmoothed_wmc(params))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -704,10 +1149,12 @@ mod test_sdd_builder {
     use quickcheck::{Arbitrary, TestResult};
     use rand::rngs::SmallRng;
     use rand::seq::SliceRandom;
-    use rand::SeedableRng;
+    use rand::{Rng, SeedableRng};
     use rsdd::builder::bdd::RobddBuilder;
     use rsdd::builder::cache::AllIteTable;
-    use rsdd::builder::sdd::{CompressionSddBuilder, SddBuilder, SemanticSddBuilder};
+    use rsdd::builder::sdd::{
+        num_shared_nodes, CompressionSddBuilder, SddBuilder, SemanticSddBuilder,
+    };
     use rsdd::builder::BottomUpBuilder;
     use rsdd::constants::primes;
     use rsdd::repr::BddPtr;
@@ -719,7 +1166,11 @@ mod test_sdd_builder {
     use rsdd::repr::WmcParams;
     use rsdd::repr::{create_semantic_hash_map, DDNNFPtr};
     use rsdd::repr::{Literal, VarLabel};
-    use rsdd::util::semirings::FiniteField;
+    use rsdd::serialize::{
+        sdd_from_ucla, sdd_to_binary, sdd_to_ucla, vtree_from_ucla, vtree_to_ucla, BinaryDiagram,
+        SDDSerializer, VTreeSerializer,
+    };
+    use rsdd::util::semirings::{FiniteField, RealSemiring};
     use std::collections::HashMap;
 
     quickcheck! {
@@ -888,6 +1339,55 @@ mod test_sdd_builder {
         }
     }
 
+    quickcheck! {
+        /// test that vtrees and SDDs survive the UCLA formats, and that an
+        /// SDD read into a builder with another vtree is canonical there
+        fn sdd_ucla_round_trip(c: Cnf, vtree: VTree) -> bool {
+            let builder = super::CompressionSddBuilder::new(vtree.clone());
+            let sdd = builder.compile_cnf(&c);
+            let text = sdd_to_ucla(sdd, &vtree);
+            let vars: Vec<VarLabel> = (0..vtree.num_vars()).map(VarLabel::new_usize).collect();
+            let other = super::CompressionSddBuilder::new(VTree::right_linear(&vars));
+            vtree_from_ucla(&vtree_to_ucla(&vtree)).unwrap() == vtree
+                && sdd_from_ucla(&text, &builder).unwrap() == sdd
+                && sdd_from_ucla(&text, &other).unwrap() == other.compile_cnf(&c)
+        }
+    }
+
+    quickcheck! {
+        /// test that SDDs and their vtree serialized to JSON are rebuilt in a
+        /// fresh builder as the same functions
+        fn sdd_serializer_round_trip(c1: Cnf, c2: Cnf, vtree: VTree) -> bool {
+            let builder = super::CompressionSddBuilder::new(vtree.clone());
+            let roots = vec![builder.compile_cnf(&c1), builder.negate(builder.compile_cnf(&c2))];
+            let sdd_json = serde_json::to_string(&SDDSerializer::from_sdds(&roots)).unwrap();
+            let vtree_json = serde_json::to_string(&VTreeSerializer::from_vtree(&vtree)).unwrap();
+
+            let vtree: VTreeSerializer = serde_json::from_str(&vtree_json).unwrap();
+            let other = super::CompressionSddBuilder::new(vtree.to_vtree());
+            let ser: SDDSerializer = serde_json::from_str(&sdd_json).unwrap();
+            ser.into_builder(&other)
+                == vec![other.compile_cnf(&c1), other.negate(other.compile_cnf(&c2))]
+        }
+    }
+
+    quickcheck! {
+        /// test that binary SDDs evaluated in place have the same weighted
+        /// model counts as the SDDs they were written from
+        fn sdd_binary_wmc_eq(c: Cnf, vtree: VTree) -> bool {
+            let weights = WmcParams::new(HashMap::from_iter(
+                (0..16).map(|x| (VarLabel::new(x), (RealSemiring(0.3), RealSemiring(0.7))))));
+            let builder = super::CompressionSddBuilder::new(vtree.clone());
+            let sdd = builder.compile_cnf(&c);
+            let bytes = sdd_to_binary(&[sdd, sdd.neg()], &vtree);
+            let diagram = BinaryDiagram::new(&bytes).unwrap();
+            diagram.vtree().unwrap() == vtree
+                && diagram.roots().iter().zip([sdd, sdd.neg()]).all(|(root, sdd)| {
+                    (root.unsmoothed_wmc(&weights).0 - sdd.unsmoothed_wmc(&weights).0).abs() < 0.0001
+                })
+        }
+    }
+
     quickcheck! {
         fn sdd_trimmed_arbitrary_vtree(c: Cnf, vtree: VTree) -> bool {
             let builder = super::CompressionSddBuilder::new(vtree);
@@ -896,6 +1396,69 @@ mod test_sdd_builder {
         }
     }
 
+    /// `minimize` runs a full vtree search per pass, so this covers a fixed set
+    /// of seeded inputs with two passes rather than quickcheck's random sizes
+    #[test]
+    fn sdd_minimize_preserves_pointers() {
+        let weights = WmcParams::new(HashMap::from_iter(
+            (0..16).map(|x| (VarLabel::new(x), (RealSemiring(0.3), RealSemiring(0.7)))),
+        ));
+
+        for seed in 0..6 {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let num_vars = rng.gen_range(1..=8);
+            let clauses: Vec<Vec<Literal>> = (0..rng.gen_range(1..=16))
+                .map(|_| {
+                    (0..rng.gen_range(1..=3))
+                        .map(|_| Literal::new(VarLabel::new(rng.gen_range(0..num_vars)), rng.gen()))
+                        .collect()
+                })
+                .collect();
+            let c = Cnf::new(&clauses);
+
+            let mut order: Vec<VarLabel> = (0..16).map(VarLabel::new).collect();
+            order.shuffle(&mut rng);
+            let vtree = match seed % 3 {
+                0 => VTree::right_linear(&order),
+                1 => VTree::left_linear(&order),
+                _ => VTree::even_split(&order, 3),
+            };
+
+            let builder = CompressionSddBuilder::new(vtree.clone());
+            let f = builder.compile_cnf(&c);
+            let before_size = num_shared_nodes(&[f]);
+            let before_wmc = f.unsmoothed_wmc(&weights);
+
+            let after_size = builder.minimize(&[f], 2);
+            let resolved = builder.resolve(f);
+            let g = builder.compile_cnf(&c);
+            let after_wmc = resolved.unsmoothed_wmc(&weights);
+
+            assert!(after_size <= before_size, "seed {seed}: {c}");
+            assert_eq!(
+                after_size,
+                num_shared_nodes(&[resolved]),
+                "seed {seed}: {c}"
+            );
+            assert_eq!(resolved, g, "seed {seed}: {c}");
+            assert!(resolved.is_canonical(), "seed {seed}: {c}");
+            assert!(
+                (before_wmc.0 - after_wmc.0).abs() < 0.0001,
+                "seed {seed}: {c}"
+            );
+            assert!(builder.eq(f, g), "seed {seed}: {c}");
+
+            let sem_builder = SemanticSddBuilder::<{ primes::U32_SMALL }>::new(vtree);
+            let sem_f = sem_builder.compile_cnf(&c);
+            let sem_before_size = num_shared_nodes(&[sem_f]);
+            let sem_size = sem_builder.minimize(&[sem_f], 2);
+            let sem_g = sem_builder.compile_cnf(&c);
+
+            assert!(sem_size <= sem_before_size, "seed {seed}: {c}");
+            assert!(sem_builder.eq(sem_f, sem_g), "seed {seed}: {c}");
+        }
+    }
+
     quickcheck! {
         fn prob_equiv_identical(c: Cnf, vtree:VTree) -> bool {
             let builder1 = CompressionSddBuilder::new(vtree.clone());